@@ -0,0 +1,296 @@
+//! Runs a query repeatedly with different settings, and prints how fast the result set is written
+//! and how much memory it takes. Helps tuning the options of the `query` subcommand.
+
+use std::{
+    env, fs,
+    fs::File,
+    io::Read,
+    path::Path,
+    process::{Child, Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Error};
+use bytesize::ByteSize;
+use clap::ValueEnum;
+use log::{error, info};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+
+use crate::{enum_args::EncodingArgument, query_opt_from_args, BenchOpt};
+
+/// Settings of a single configuration the query is run with.
+#[derive(Clone, Copy)]
+struct Configuration {
+    batch_size_row: usize,
+    encoding: EncodingArgument,
+    fetch_buffers: u16,
+    encoding_threads: u16,
+}
+
+impl Configuration {
+    /// Arguments of the `query` subcommand applying this configuration.
+    fn args(&self) -> Vec<String> {
+        vec![
+            "--batch-size-row".to_owned(),
+            self.batch_size_row.to_string(),
+            "--encoding".to_owned(),
+            encoding_name(self.encoding),
+            "--fetch-buffers".to_owned(),
+            self.fetch_buffers.to_string(),
+            "--encoding-threads".to_owned(),
+            self.encoding_threads.to_string(),
+        ]
+    }
+}
+
+/// Outcome of running the query once.
+struct Run {
+    duration: Duration,
+    rows: i64,
+    /// Largest resident set size of the process. `None` if the platform does not report it.
+    peak_memory: Option<ByteSize>,
+}
+
+pub fn bench(opt: BenchOpt) -> Result<(), Error> {
+    let configurations = configurations(
+        &opt.batch_size_row,
+        &opt.encoding,
+        &opt.fetch_buffers,
+        &opt.encoding_threads,
+    );
+    println!(
+        "{:>10} {:>8} {:>13} {:>16} {:>10} {:>9} {:>10} {:>11}",
+        "batch size",
+        "encoding",
+        "fetch buffers",
+        "encoding threads",
+        "rows",
+        "seconds",
+        "rows/sec",
+        "peak memory"
+    );
+    for configuration in &configurations {
+        let settings = format!(
+            "{:>10} {:>8} {:>13} {:>16}",
+            configuration.batch_size_row,
+            encoding_name(configuration.encoding),
+            configuration.fetch_buffers,
+            configuration.encoding_threads
+        );
+        let mut runs = Vec::new();
+        for repetition in 1..=opt.repetitions {
+            info!(
+                "Running the query with {} ({repetition}/{}).",
+                configuration.args().join(" "),
+                opt.repetitions
+            );
+            match run(&opt, configuration) {
+                Ok(run) => runs.push(run),
+                Err(e) => {
+                    error!("Query failed with {}: {e}", configuration.args().join(" "));
+                    break;
+                }
+            }
+        }
+        if runs.len() != opt.repetitions {
+            println!("{settings} {:>10}", "failed");
+            continue;
+        }
+        // The median is robust against single runs slowed down by other load on the system.
+        runs.sort_by_key(|run| run.duration);
+        let median = &runs[runs.len() / 2];
+        let seconds = median.duration.as_secs_f64();
+        let peak_memory = runs
+            .iter()
+            .filter_map(|run| run.peak_memory)
+            .max()
+            .map_or_else(|| "n/a".to_owned(), |peak| peak.to_string_as(true));
+        println!(
+            "{settings} {:>10} {:>9.3} {:>10.0} {:>11}",
+            median.rows,
+            seconds,
+            median.rows as f64 / seconds,
+            peak_memory
+        );
+    }
+    Ok(())
+}
+
+/// Fails if the options passed on to the `query` subcommand are invalid for any configuration, so
+/// this does not only turn out after running the others.
+pub fn validate(opt: &BenchOpt) -> Result<(), Error> {
+    if opt.connect_opts.prompt {
+        bail!("bench does not support prompting for the connection string.")
+    }
+    if opt.repetitions == 0 {
+        bail!("repetitions must be at least 1.")
+    }
+    for configuration in configurations(
+        &opt.batch_size_row,
+        &opt.encoding,
+        &opt.fetch_buffers,
+        &opt.encoding_threads,
+    ) {
+        let mut args = configuration.args();
+        args.extend(["out.par".to_owned(), opt.query.clone()]);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        query_opt_from_args(&opt.connect_opts, &args, &opt.query_args)?;
+    }
+    Ok(())
+}
+
+/// Each combination of the given values.
+fn configurations(
+    batch_sizes: &[usize],
+    encodings: &[EncodingArgument],
+    fetch_buffers: &[u16],
+    encoding_threads: &[u16],
+) -> Vec<Configuration> {
+    let mut configurations = Vec::new();
+    for &batch_size_row in batch_sizes {
+        for &encoding in encodings {
+            for &fetch_buffers in fetch_buffers {
+                for &encoding_threads in encoding_threads {
+                    configurations.push(Configuration {
+                        batch_size_row,
+                        encoding,
+                        fetch_buffers,
+                        encoding_threads,
+                    });
+                }
+            }
+        }
+    }
+    configurations
+}
+
+fn encoding_name(encoding: EncodingArgument) -> String {
+    encoding.to_possible_value().unwrap().get_name().to_owned()
+}
+
+/// Runs the `query` subcommand in a process of its own, so its peak memory usage can be measured
+/// in isolation. The output is written into a temporary directory and discarded afterwards.
+fn run(opt: &BenchOpt, configuration: &Configuration) -> Result<Run, Error> {
+    let dir = tempfile::tempdir()?;
+    let mut command = Command::new(env::current_exe()?);
+    command
+        .arg("--quiet")
+        .arg("query")
+        .args(configuration.args());
+    if let Some(dsn) = &opt.connect_opts.dsn {
+        command.args(["--dsn", dsn]);
+    }
+    // Passed via the environment, so credentials do not show up in the list of processes.
+    let connect_opts = &opt.connect_opts;
+    for (name, value) in [
+        ("ODBC_CONNECTION_STRING", &connect_opts.connection_string),
+        ("ODBC_USER", &connect_opts.user),
+        ("ODBC_PASSWORD", &connect_opts.password),
+    ] {
+        match value {
+            Some(value) => command.env(name, value),
+            None => command.env_remove(name),
+        };
+    }
+    command
+        .arg(dir.path().join("out.par"))
+        .arg(&opt.query)
+        .args(&opt.query_args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+
+    let start = Instant::now();
+    let mut child = command.spawn()?;
+    let mut stderr = String::new();
+    child.stderr.take().unwrap().read_to_string(&mut stderr)?;
+    let (success, peak_memory) = wait(child)?;
+    let duration = start.elapsed();
+    if !success {
+        bail!("{}", stderr.trim_end())
+    }
+    Ok(Run {
+        duration,
+        rows: count_rows(dir.path())?,
+        peak_memory,
+    })
+}
+
+/// Waits for the child process to exit. Returns whether it succeeded, together with its peak memory
+/// usage.
+#[cfg(unix)]
+fn wait(child: Child) -> Result<(bool, Option<ByteSize>), Error> {
+    let mut status = 0;
+    // Safety: `rusage` is plain old data, for which all zeros is a valid value.
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    loop {
+        // Safety: The child has not been waited for yet, so its process id is still valid.
+        let pid = unsafe { libc::wait4(child.id() as libc::pid_t, &mut status, 0, &mut usage) };
+        if pid != -1 {
+            break;
+        }
+        let error = std::io::Error::last_os_error();
+        if error.kind() != std::io::ErrorKind::Interrupted {
+            return Err(error.into());
+        }
+    }
+    // Linux reports the maximum resident set size in KiB, macOS in bytes.
+    let unit = if cfg!(target_os = "macos") { 1 } else { 1024 };
+    let peak_memory = ByteSize::b(usage.ru_maxrss as u64 * unit);
+    let success = libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0;
+    Ok((success, Some(peak_memory)))
+}
+
+/// Waits for the child process to exit. Returns whether it succeeded. The peak memory usage is not
+/// reported on this platform.
+#[cfg(not(unix))]
+fn wait(mut child: Child) -> Result<(bool, Option<ByteSize>), Error> {
+    Ok((child.wait()?.success(), None))
+}
+
+/// Total number of rows of all parquet files within the directory, including the ones of
+/// partitions in subdirectories.
+fn count_rows(dir: &Path) -> Result<i64, Error> {
+    let mut rows = 0;
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            rows += count_rows(&path)?;
+        } else if let Ok(reader) = SerializedFileReader::new(File::open(&path)?) {
+            // Other files, like success markers, are skipped.
+            rows += reader.metadata().file_metadata().num_rows();
+        }
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::enum_args::EncodingArgument;
+
+    use super::configurations;
+
+    #[test]
+    fn combine_all_settings() {
+        let configurations =
+            configurations(&[1024, 65535], &[EncodingArgument::Utf16], &[1, 2], &[4]);
+        let settings: Vec<_> = configurations
+            .iter()
+            .map(|c| (c.batch_size_row, c.fetch_buffers))
+            .collect();
+        assert_eq!(vec![(1024, 1), (1024, 2), (65535, 1), (65535, 2)], settings);
+        assert_eq!(
+            vec![
+                "--batch-size-row",
+                "1024",
+                "--encoding",
+                "utf16",
+                "--fetch-buffers",
+                "2",
+                "--encoding-threads",
+                "4"
+            ],
+            configurations[1].args()
+        );
+    }
+}
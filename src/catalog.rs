@@ -0,0 +1,106 @@
+//! Metadata describing the structure of the data source, as returned by the catalog functions of
+//! ODBC. Written into parquet files by the `catalog` subcommand, so data catalogs can ingest it.
+
+use std::fmt;
+
+use anyhow::Error;
+use log::info;
+use odbc_api::{handles::StatementImpl, Connection, CursorImpl, Environment};
+
+use crate::{query, query_opt_from_args, CatalogOpt};
+
+/// Catalog function executed instead of a query. The result set is written like the result set of
+/// a query.
+#[derive(Clone, Debug)]
+pub enum CatalogFunction {
+    /// `SQLTables`: One row per table.
+    Tables {
+        catalog: String,
+        schema: String,
+        table: String,
+        table_type: String,
+    },
+    /// `SQLColumns`: One row per column of each table.
+    Columns {
+        catalog: String,
+        schema: String,
+        table: String,
+    },
+}
+
+impl CatalogFunction {
+    pub fn execute<'c>(
+        &self,
+        conn: &'c Connection<'_>,
+    ) -> Result<CursorImpl<StatementImpl<'c>>, Error> {
+        let cursor = match self {
+            CatalogFunction::Tables {
+                catalog,
+                schema,
+                table,
+                table_type,
+            } => conn.tables(catalog, schema, table, table_type)?,
+            CatalogFunction::Columns {
+                catalog,
+                schema,
+                table,
+            } => conn.columns(catalog, schema, table, "")?,
+        };
+        Ok(cursor)
+    }
+}
+
+/// Passed as the query text, e.g. for the `{query_hash}` placeholder.
+impl fmt::Display for CatalogFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CatalogFunction::Tables {
+                catalog,
+                schema,
+                table,
+                table_type,
+            } => write!(
+                f,
+                "SQLTables('{catalog}', '{schema}', '{table}', '{table_type}')"
+            ),
+            CatalogFunction::Columns {
+                catalog,
+                schema,
+                table,
+            } => write!(f, "SQLColumns('{catalog}', '{schema}', '{table}', '')"),
+        }
+    }
+}
+
+/// Writes `tables.par` and `columns.par` into the output directory.
+pub fn catalog(environment: &Environment, opt: CatalogOpt) -> Result<(), Error> {
+    let functions = [
+        (
+            "tables.par",
+            CatalogFunction::Tables {
+                catalog: opt.catalog.clone(),
+                schema: opt.schema.clone(),
+                table: opt.table.clone(),
+                table_type: opt.table_type.clone(),
+            },
+        ),
+        (
+            "columns.par",
+            CatalogFunction::Columns {
+                catalog: opt.catalog.clone(),
+                schema: opt.schema.clone(),
+                table: opt.table.clone(),
+            },
+        ),
+    ];
+    for (file_name, function) in functions {
+        let output = format!("{}/{file_name}", opt.output.trim_end_matches('/'));
+        let description = function.to_string();
+        info!("Writing {description} to '{output}'.");
+        let mut query_opt =
+            query_opt_from_args(&opt.connect_opts, &[&output, &description], &opt.query_args)?;
+        query_opt.catalog_function = Some(function);
+        query::query(environment, query_opt)?;
+    }
+    Ok(())
+}
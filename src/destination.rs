@@ -0,0 +1,1117 @@
+use std::{
+    env,
+    ffi::OsString,
+    fmt,
+    fs::{create_dir_all, remove_file, rename, File},
+    io::{self, stdout, Write},
+    net::TcpStream,
+    path::{Path, PathBuf},
+    process,
+    str::FromStr,
+};
+
+use anyhow::{bail, format_err, Error};
+use object_store::{parse_url_opts, ObjectStoreExt, WriteMultipart};
+use percent_encoding::percent_decode_str;
+use reqwest::{
+    blocking::Client,
+    header::{CONTENT_TYPE, LOCATION},
+    redirect::Policy,
+    Method, StatusCode,
+};
+use ssh2::{CheckResult, KnownHostFileKind, Session, Sftp};
+use tokio::runtime::Runtime;
+use url::Url;
+
+/// Maximum number of parts of a multipart upload, which are in flight at the same time. Writing to
+/// the sink blocks, until one of the pending parts finished uploading. This way we do not buffer
+/// the entire output in memory, if the upload is slower than fetching from the database.
+const MAX_CONCURRENT_UPLOADS: usize = 4;
+
+/// Size of the chunks appended to a file in HDFS. Each chunk requires two HTTP round trips, so it
+/// should not be too small. It also is the amount of memory we need to buffer the output.
+const WEB_HDFS_CHUNK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Port used by the WebHDFS REST API of the name node, if none is specified in the URL (Hadoop 3
+/// default).
+const WEB_HDFS_DEFAULT_PORT: u16 = 9870;
+
+/// Placeholder in the output file name, which is replaced with the number of the file, if the
+/// output is split into several files.
+const SEQUENCE_PLACEHOLDER: &str = "{seq}";
+
+/// Where the output of a query is written to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Destination {
+    /// Standard output. Indicated by passing `-` at the command line.
+    StdOut,
+    /// A path in the local file system.
+    File(PathBuf),
+    /// A location in an object store, e.g. `s3://bucket/prefix/out.par`,
+    /// `abfss://container@account.dfs.core.windows.net/prefix/out.par` or
+    /// `gs://bucket/prefix/out.par`.
+    ObjectStore(Url),
+    /// A file in HDFS, accessed via the WebHDFS (or HttpFS) REST API. E.g.
+    /// `webhdfs://namenode:9870/prefix/out.par`.
+    WebHdfs(Url),
+    /// A file on a remote host, accessed via SFTP. E.g. `sftp://user@host/drop/out.par`.
+    Sftp(Url),
+    /// Identical files are written to several destinations at once, e.g. to a local path and an
+    /// S3 bucket. This way the result set only needs to be fetched once.
+    Tee(Vec<Destination>),
+}
+
+impl Destination {
+    /// `true` if the destination is standard out. Standard out does not allow for splitting the
+    /// output into several files.
+    pub fn is_std_out(&self) -> bool {
+        match self {
+            Destination::StdOut => true,
+            Destination::Tee(destinations) => destinations.iter().any(Destination::is_std_out),
+            _ => false,
+        }
+    }
+
+    /// Combines several destinations into one, which writes identical files to all of them.
+    pub fn tee(destinations: Vec<Destination>) -> Self {
+        if destinations.len() == 1 {
+            destinations.into_iter().next().unwrap()
+        } else {
+            Destination::Tee(destinations)
+        }
+    }
+
+    /// Replaces placeholders like `{date}` in the file name with their values. The `{seq}`
+    /// placeholder is left untouched, since it is replaced by [`Self::with_suffix`].
+    pub fn expand_placeholders(&self, placeholders: &[(&str, String)]) -> Self {
+        self.map_path(&|path| {
+            placeholders
+                .iter()
+                .fold(path.to_owned(), |path, (name, value)| {
+                    path.replace(&format!("{{{name}}}"), value)
+                })
+        })
+    }
+
+    /// `true` if the file name contains a `{seq}` placeholder for the number of the file.
+    pub fn has_sequence_placeholder(&self) -> bool {
+        match self {
+            Destination::StdOut => false,
+            Destination::File(path) => path.to_string_lossy().contains(SEQUENCE_PLACEHOLDER),
+            Destination::ObjectStore(url) | Destination::WebHdfs(url) | Destination::Sftp(url) => {
+                url_path_with_braces(url).contains(SEQUENCE_PLACEHOLDER)
+            }
+            Destination::Tee(destinations) => destinations
+                .iter()
+                .any(Destination::has_sequence_placeholder),
+        }
+    }
+
+    /// Derives the destination for the n-th file, in case the output is split into several files.
+    /// E.g. `out.par` becomes `out_03.par`. If the file name contains a `{seq}` placeholder, it is
+    /// replaced with the number instead. E.g. `out_{seq}.par` becomes `out_03.par`, too.
+    pub fn with_suffix(&self, num_file: u32, suffix_length: usize) -> Result<Self, Error> {
+        if let Destination::Tee(destinations) = self {
+            let destinations = destinations
+                .iter()
+                .map(|destination| destination.with_suffix(num_file, suffix_length))
+                .collect::<Result<_, _>>()?;
+            return Ok(Destination::Tee(destinations));
+        }
+        if self.has_sequence_placeholder() {
+            let number = pad_number(num_file, suffix_length);
+            return Ok(self.map_path(&|path| path.replace(SEQUENCE_PLACEHOLDER, &number)));
+        }
+        let destination = match self {
+            Destination::StdOut => panic!("Standard out must not be split into several files."),
+            Destination::File(path) => {
+                Destination::File(path_with_suffix(path, num_file, suffix_length)?)
+            }
+            Destination::ObjectStore(url) => {
+                Destination::ObjectStore(url_with_suffix(url, num_file, suffix_length)?)
+            }
+            Destination::WebHdfs(url) => {
+                Destination::WebHdfs(url_with_suffix(url, num_file, suffix_length)?)
+            }
+            Destination::Sftp(url) => {
+                Destination::Sftp(url_with_suffix(url, num_file, suffix_length)?)
+            }
+            Destination::Tee(_) => unreachable!("Tee is handled above."),
+        };
+        Ok(destination)
+    }
+
+    /// Applies a transformation to the path of the destination.
+    fn map_path(&self, f: &dyn Fn(&str) -> String) -> Self {
+        match self {
+            Destination::StdOut => Destination::StdOut,
+            Destination::File(path) => Destination::File(f(&path.to_string_lossy()).into()),
+            Destination::ObjectStore(url) => Destination::ObjectStore(url_map_path(url, f)),
+            Destination::WebHdfs(url) => Destination::WebHdfs(url_map_path(url, f)),
+            Destination::Sftp(url) => Destination::Sftp(url_map_path(url, f)),
+            Destination::Tee(destinations) => Destination::Tee(
+                destinations
+                    .iter()
+                    .map(|destination| destination.map_path(f))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Interprets the destination as a directory and appends a relative path to it. E.g. `out` and
+    /// `year=2022/part.par` become `out/year=2022/part.par`.
+    pub fn join(&self, relative: &str) -> Self {
+        match self {
+            Destination::StdOut => panic!("Standard out must not be treated as a directory."),
+            Destination::File(path) => Destination::File(path.join(relative)),
+            Destination::ObjectStore(url) => Destination::ObjectStore(url_join(url, relative)),
+            Destination::WebHdfs(url) => Destination::WebHdfs(url_join(url, relative)),
+            Destination::Sftp(url) => Destination::Sftp(url_join(url, relative)),
+            Destination::Tee(destinations) => Destination::Tee(
+                destinations
+                    .iter()
+                    .map(|destination| destination.join(relative))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Directory containing the destination. E.g. `out/data.par` becomes `out`.
+    pub fn parent(&self) -> Self {
+        self.map_path(&|path| {
+            Path::new(path)
+                .parent()
+                .map(|parent| parent.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        })
+    }
+
+    /// Path of the destination relative to `directory`, or `None` if it is not located within it.
+    /// For tees the first destination is used, since all of them share the same layout.
+    pub fn relative_to(&self, directory: &Destination) -> Option<String> {
+        if let (Destination::Tee(destinations), Destination::Tee(directories)) = (self, directory) {
+            return destinations.first()?.relative_to(directories.first()?);
+        }
+        let path = self.path()?;
+        let directory = directory.path()?;
+        let relative = Path::new(&path).strip_prefix(directory).ok()?;
+        Some(relative.to_string_lossy().into_owned())
+    }
+
+    /// Path of a single destination, decoded in case of URLs.
+    fn path(&self) -> Option<String> {
+        match self {
+            Destination::File(path) => Some(path.to_string_lossy().into_owned()),
+            Destination::ObjectStore(url) | Destination::WebHdfs(url) | Destination::Sftp(url) => {
+                Some(
+                    percent_decode_str(url.path())
+                        .decode_utf8_lossy()
+                        .into_owned(),
+                )
+            }
+            Destination::StdOut | Destination::Tee(_) => None,
+        }
+    }
+
+    /// `true` if a file or directory exists at the destination. Only files in the local file system
+    /// are checked, other destinations are reported as not existing.
+    pub fn exists(&self) -> bool {
+        match self {
+            Destination::File(path) => path.exists(),
+            Destination::Tee(destinations) => destinations.iter().any(Destination::exists),
+            _ => false,
+        }
+    }
+
+    /// Removes a file written before. Only files in the local file system can be removed.
+    pub fn remove(&self) -> Result<(), Error> {
+        match self {
+            Destination::File(path) => remove_file(path)?,
+            Destination::Tee(destinations) => {
+                for destination in destinations {
+                    destination.remove()?;
+                }
+            }
+            Destination::StdOut => bail!("Output written to stdout can not be removed."),
+            Destination::ObjectStore(url) | Destination::WebHdfs(url) | Destination::Sftp(url) => {
+                bail!("Removing '{url}' is not supported.")
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates a new (empty) file at the destination and returns a sink to write into it. Missing
+    /// parent directories are created.
+    pub fn create(&self) -> Result<Box<dyn Sink>, Error> {
+        let sink: Box<dyn Sink> = match self {
+            Destination::StdOut => Box::new(stdout()),
+            Destination::File(path) => {
+                if let Some(parent) = path.parent() {
+                    create_dir_all(parent)?;
+                }
+                // Special files, like named pipes or `/dev/null`, can not be replaced by renaming.
+                let is_special_file = path.metadata().is_ok_and(|metadata| !metadata.is_file());
+                if is_special_file {
+                    Box::new(File::create(path)?)
+                } else {
+                    Box::new(LocalFileSink::new(path.clone())?)
+                }
+            }
+            Destination::ObjectStore(url) => Box::new(ObjectStoreSink::new(url)?),
+            Destination::WebHdfs(url) => Box::new(WebHdfsSink::new(url)?),
+            Destination::Sftp(url) => Box::new(SftpSink::new(url)?),
+            Destination::Tee(destinations) => Box::new(TeeSink {
+                sinks: destinations
+                    .iter()
+                    .map(Destination::create)
+                    .collect::<Result<_, _>>()?,
+            }),
+        };
+        Ok(sink)
+    }
+}
+
+impl FromStr for Destination {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let destination = if s == "-" {
+            Destination::StdOut
+        } else if let Some(url) = object_store_url(s)? {
+            Destination::ObjectStore(url)
+        } else if s.starts_with("webhdfs://") || s.starts_with("swebhdfs://") {
+            Destination::WebHdfs(Url::parse(s)?)
+        } else if s.starts_with("sftp://") {
+            Destination::Sftp(Url::parse(s)?)
+        } else {
+            Destination::File(s.into())
+        };
+        Ok(destination)
+    }
+}
+
+/// Formats the destination the way it is passed at the command line.
+impl fmt::Display for Destination {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Destination::StdOut => write!(f, "-"),
+            Destination::File(path) => write!(f, "{}", path.display()),
+            Destination::ObjectStore(url) | Destination::WebHdfs(url) | Destination::Sftp(url) => {
+                write!(f, "{url}")
+            }
+            Destination::Tee(destinations) => {
+                let destinations: Vec<_> = destinations.iter().map(ToString::to_string).collect();
+                write!(f, "{}", destinations.join(", "))
+            }
+        }
+    }
+}
+
+/// Parses the argument as an URL, if it starts with the scheme of one of the supported object
+/// stores.
+fn object_store_url(s: &str) -> Result<Option<Url>, Error> {
+    let scheme = s.split_once("://").map(|(scheme, _)| scheme);
+    let is_object_store = matches!(
+        scheme,
+        // Amazon S3
+        Some("s3" | "s3a")
+        // Azure Blob Storage and Azure Data Lake Storage Gen2
+        | Some("abfs" | "abfss" | "az" | "adl" | "azure")
+        // Google Cloud Storage
+        | Some("gs")
+    );
+    if is_object_store {
+        Ok(Some(Url::parse(s)?))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Braces are percent encoded in URL paths. We decode them, so we can find the placeholders.
+fn url_path_with_braces(url: &Url) -> String {
+    url.path()
+        .replace("%7B", "{")
+        .replace("%7b", "{")
+        .replace("%7D", "}")
+        .replace("%7d", "}")
+}
+
+fn url_map_path(url: &Url, f: impl Fn(&str) -> String) -> Url {
+    let path = f(&url_path_with_braces(url));
+    let mut url = url.clone();
+    url.set_path(&path);
+    url
+}
+
+fn url_join(url: &Url, relative: &str) -> Url {
+    // `%` is used to escape characters in partition names and must not be mistaken for percent
+    // encoding of the URL.
+    let relative = relative.replace('%', "%25");
+    let mut url = url.clone();
+    let path = format!("{}/{relative}", url.path().trim_end_matches('/'));
+    url.set_path(&path);
+    url
+}
+
+fn url_with_suffix(url: &Url, num_file: u32, suffix_length: usize) -> Result<Url, Error> {
+    let path = path_with_suffix(&url_decoded_path(url)?, num_file, suffix_length)?;
+    let mut url = url.clone();
+    // Escape `%`, so it is not mistaken for percent encoding once the path is set.
+    url.set_path(&path.to_str().unwrap().replace('%', "%25"));
+    Ok(url)
+}
+
+/// Path of the URL without percent encoding, e.g. `/drop zone/out.par` for
+/// `sftp://host/drop%20zone/out.par`.
+fn url_decoded_path(url: &Url) -> Result<PathBuf, Error> {
+    let path = percent_decode_str(url.path()).decode_utf8()?;
+    Ok(PathBuf::from(path.into_owned()))
+}
+
+fn path_with_suffix(path: &Path, num_file: u32, suffix_length: usize) -> Result<PathBuf, Error> {
+    let suffix = format!("_{}", pad_number(num_file, suffix_length));
+    let mut stem = path
+        .file_stem()
+        .ok_or_else(|| format_err!("Output needs To have a file stem."))?
+        .to_owned();
+    stem.push(suffix);
+    let mut path_with_suffix = path.with_file_name(stem);
+    path_with_suffix = path_with_suffix.with_extension("par");
+    Ok(path_with_suffix)
+}
+
+fn pad_number(num_file: u32, suffix_length: usize) -> String {
+    let num_file = num_file.to_string();
+    let num_leading_zeroes = if suffix_length > num_file.len() {
+        suffix_length - num_file.len()
+    } else {
+        // Suffix is already large enough (if not too large) without leading zeroes
+        0
+    };
+    let padding = "0".repeat(num_leading_zeroes);
+    format!("{padding}{num_file}")
+}
+
+/// A `Write` which needs to be explicitly finished, once the parquet writer is done with it. E.g.
+/// to complete a multipart upload.
+pub trait Sink: Write + Send {
+    fn finish(self: Box<Self>) -> Result<(), Error>;
+}
+
+impl Sink for File {
+    fn finish(mut self: Box<Self>) -> Result<(), Error> {
+        self.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes into a hidden temporary file next to the output, which is renamed to the output once it
+/// is complete. This way processes polling for the output never pick up a partially written file.
+struct LocalFileSink {
+    file: File,
+    path: PathBuf,
+    tmp_path: PathBuf,
+    /// `true` once the temporary file has been renamed.
+    finished: bool,
+}
+
+impl LocalFileSink {
+    fn new(path: PathBuf) -> Result<Self, Error> {
+        let tmp_path = tmp_path(&path)?;
+        let file = File::create(&tmp_path)?;
+        Ok(Self {
+            file,
+            path,
+            tmp_path,
+            finished: false,
+        })
+    }
+}
+
+impl Write for LocalFileSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Sink for LocalFileSink {
+    fn finish(mut self: Box<Self>) -> Result<(), Error> {
+        self.file.flush()?;
+        self.file.sync_all()?;
+        rename(&self.tmp_path, &self.path)?;
+        self.finished = true;
+        Ok(())
+    }
+}
+
+impl Drop for LocalFileSink {
+    fn drop(&mut self) {
+        // The export failed, do not leave the incomplete file behind.
+        if !self.finished {
+            let _ = remove_file(&self.tmp_path);
+        }
+    }
+}
+
+/// Hidden temporary file in the same directory as `path`, e.g. `.out.par.1234.tmp` for `out.par`.
+/// Tools like Spark or Hive ignore hidden files, when reading a directory as a dataset. The id of
+/// the process prevents concurrent exports from writing into the same temporary file.
+fn tmp_path(path: &Path) -> Result<PathBuf, Error> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| format_err!("Output needs to have a file name."))?;
+    let mut tmp_name = OsString::from(".");
+    tmp_name.push(file_name);
+    tmp_name.push(format!(".{}.tmp", process::id()));
+    Ok(path.with_file_name(tmp_name))
+}
+
+impl Sink for io::Stdout {
+    fn finish(mut self: Box<Self>) -> Result<(), Error> {
+        self.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes the same output into several sinks.
+struct TeeSink {
+    sinks: Vec<Box<dyn Sink>>,
+}
+
+impl Write for TeeSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for sink in &mut self.sinks {
+            sink.write_all(buf)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        for sink in &mut self.sinks {
+            sink.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl Sink for TeeSink {
+    fn finish(self: Box<Self>) -> Result<(), Error> {
+        for sink in self.sinks {
+            sink.finish()?;
+        }
+        Ok(())
+    }
+}
+
+/// Streams the output into an object store using a multipart upload. This way the output never
+/// needs to be materialized on the local disk.
+struct ObjectStoreSink {
+    /// The object store client is asynchronous. We use a dedicated runtime to block on it, and to
+    /// upload parts in the background while we are fetching the next batch.
+    runtime: Runtime,
+    /// `None` once the upload has been finished.
+    upload: Option<WriteMultipart>,
+}
+
+impl ObjectStoreSink {
+    fn new(url: &Url) -> Result<Self, Error> {
+        let (store, path) = parse_url_opts(url, object_store_options())?;
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()?;
+        let upload = runtime.block_on(store.put_multipart(&path))?;
+        let upload = Some(WriteMultipart::new(upload));
+        Ok(Self { runtime, upload })
+    }
+}
+
+/// Credentials and other configuration (e.g. `AWS_REGION`, `AZURE_STORAGE_ACCOUNT_NAME` or
+/// `GOOGLE_SERVICE_ACCOUNT`) of the object store client are picked up from the environment.
+fn object_store_options() -> Vec<(String, String)> {
+    let mut options: Vec<_> = env::vars().collect();
+    // Azure users are likely to have a connection string at hand, rather than the individual
+    // settings. So we support it, even though the object store client does not.
+    if let Ok(connection_string) = env::var("AZURE_STORAGE_CONNECTION_STRING") {
+        options.extend(azure_options_from_connection_string(&connection_string));
+    }
+    options
+}
+
+/// Translates an Azure storage connection string (e.g.
+/// `DefaultEndpointsProtocol=https;AccountName=my_account;AccountKey=my_key`) into the
+/// configuration options understood by the object store client.
+fn azure_options_from_connection_string(connection_string: &str) -> Vec<(String, String)> {
+    connection_string
+        .split(';')
+        // Account keys are base64 encoded and may end in `=`, so we only split at the first one.
+        .filter_map(|attribute| attribute.split_once('='))
+        .filter_map(|(key, value)| {
+            let key = match key.trim() {
+                "AccountName" => "azure_storage_account_name",
+                "AccountKey" => "azure_storage_account_key",
+                "SharedAccessSignature" => "azure_storage_sas_token",
+                "BlobEndpoint" => "azure_storage_endpoint",
+                // Other attributes like `DefaultEndpointsProtocol` or `EndpointSuffix` are implied
+                // by the URL of the destination.
+                _ => return None,
+            };
+            Some((key.to_owned(), value.trim().to_owned()))
+        })
+        .collect()
+}
+
+impl Write for ObjectStoreSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let upload = self
+            .upload
+            .as_mut()
+            .expect("Upload must not be finished yet");
+        self.runtime
+            .block_on(upload.wait_for_capacity(MAX_CONCURRENT_UPLOADS))
+            .map_err(io::Error::other)?;
+        // Uploading a part spawns a new task, so we need to be within the context of the runtime.
+        let _guard = self.runtime.enter();
+        upload.write(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // Parts are uploaded once they reach their minimum size. Only `finish` uploads the
+        // remainder.
+        Ok(())
+    }
+}
+
+impl Sink for ObjectStoreSink {
+    fn finish(mut self: Box<Self>) -> Result<(), Error> {
+        // Finishing aborts the upload itself, should uploading one of the parts fail.
+        let upload = self.upload.take().expect("Upload must not be finished yet");
+        self.runtime.block_on(upload.finish())?;
+        Ok(())
+    }
+}
+
+impl Drop for ObjectStoreSink {
+    fn drop(&mut self) {
+        // The export failed. Abort the upload, so the parts uploaded so far do not linger (and are
+        // billed) in the bucket.
+        if let Some(upload) = self.upload.take() {
+            let _ = self.runtime.block_on(upload.abort());
+        }
+    }
+}
+
+/// Streams the output into a file in HDFS, using the WebHDFS REST API. The output is buffered and
+/// appended to the file in chunks. This way the output never needs to be materialized on the local
+/// disk. Like for local files, the output is written into a hidden temporary file first, which is
+/// renamed once it is complete.
+struct WebHdfsSink {
+    client: Client,
+    /// Endpoint of the temporary file in the REST API of the name node. E.g.
+    /// `http://namenode:9870/webhdfs/v1/prefix/.out.par.1234.tmp`
+    endpoint: Url,
+    /// Endpoint of the output in the REST API of the name node.
+    output_endpoint: Url,
+    /// Path of the output in HDFS, e.g. `/prefix/out.par`.
+    path: PathBuf,
+    buffer: Vec<u8>,
+    /// `true` after the first chunk has been uploaded. Subsequent chunks are appended to the file.
+    created: bool,
+    /// `true` once the temporary file has been renamed.
+    finished: bool,
+}
+
+impl WebHdfsSink {
+    fn new(url: &Url) -> Result<Self, Error> {
+        let scheme = if url.scheme() == "swebhdfs" {
+            "https"
+        } else {
+            "http"
+        };
+        let host = url
+            .host_str()
+            .ok_or_else(|| format_err!("WebHDFS URL must specify the name node: {url}"))?;
+        let port = url.port().unwrap_or(WEB_HDFS_DEFAULT_PORT);
+        let name_node = Url::parse(&format!("{scheme}://{host}:{port}"))?;
+        let endpoint_of = |path: &Path| {
+            let mut endpoint = name_node.clone();
+            // Escape `%`, so it is not mistaken for percent encoding once the path is set.
+            endpoint.set_path(&format!(
+                "/webhdfs/v1{}",
+                path.to_str().unwrap().replace('%', "%25")
+            ));
+            endpoint
+        };
+        let path = url_decoded_path(url)?;
+        let endpoint = endpoint_of(&tmp_path(&path)?);
+        let output_endpoint = endpoint_of(&path);
+        // The name node redirects us to the data node. We need to follow the redirect ourselves,
+        // since only the second request may carry the data.
+        let client = Client::builder().redirect(Policy::none()).build()?;
+        Ok(Self {
+            client,
+            endpoint,
+            output_endpoint,
+            path,
+            buffer: Vec::with_capacity(WEB_HDFS_CHUNK_SIZE),
+            created: false,
+            finished: false,
+        })
+    }
+
+    /// Creates the file with the current contents of the buffer, or appends them to the file if it
+    /// has already been created.
+    fn upload_chunk(&mut self) -> Result<(), Error> {
+        let (method, operation) = if self.created {
+            (Method::POST, "APPEND")
+        } else {
+            (Method::PUT, "CREATE")
+        };
+        let mut url = web_hdfs_operation(&self.endpoint, operation);
+        if !self.created {
+            url.query_pairs_mut().append_pair("overwrite", "true");
+        }
+
+        let response = self.client.request(method.clone(), url).send()?;
+        if response.status() != StatusCode::TEMPORARY_REDIRECT {
+            bail!(
+                "Expected WebHDFS to redirect {operation} request to data node. Got status {} \
+                instead: {}",
+                response.status(),
+                response.text().unwrap_or_default()
+            )
+        }
+        let location = response
+            .headers()
+            .get(LOCATION)
+            .ok_or_else(|| format_err!("WebHDFS redirect is missing a location."))?
+            .to_str()?
+            .to_owned();
+
+        let chunk = std::mem::replace(&mut self.buffer, Vec::with_capacity(WEB_HDFS_CHUNK_SIZE));
+        self.client
+            .request(method, location)
+            .header(CONTENT_TYPE, "application/octet-stream")
+            .body(chunk)
+            .send()?
+            .error_for_status()?;
+        self.created = true;
+        Ok(())
+    }
+
+    /// Renames the temporary file to the output. Most file systems, HDFS included, do not rename
+    /// onto an existing file, so an existing output is deleted first.
+    fn rename(&self) -> Result<(), Error> {
+        self.client
+            .delete(web_hdfs_operation(&self.output_endpoint, "DELETE"))
+            .send()?
+            .error_for_status()?;
+        let mut url = web_hdfs_operation(&self.endpoint, "RENAME");
+        url.query_pairs_mut()
+            .append_pair("destination", self.path.to_str().unwrap());
+        let response = self.client.put(url).send()?.error_for_status()?;
+        // The name node answers with `{"boolean": false}` if the file could not be renamed.
+        let renamed: serde_json::Value = serde_json::from_str(&response.text()?)?;
+        if renamed["boolean"] != true {
+            bail!(
+                "WebHDFS failed to rename temporary file to '{}'.",
+                self.path.display()
+            )
+        }
+        Ok(())
+    }
+}
+
+/// URL of a WebHDFS operation, e.g. `CREATE` or `RENAME`, on the file at the endpoint.
+fn web_hdfs_operation(endpoint: &Url, operation: &str) -> Url {
+    let mut url = endpoint.clone();
+    url.query_pairs_mut().append_pair("op", operation);
+    // Same environment variable the hadoop client tools use for simple authentication.
+    if let Ok(user) = env::var("HADOOP_USER_NAME") {
+        url.query_pairs_mut().append_pair("user.name", &user);
+    }
+    url
+}
+
+impl Write for WebHdfsSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if self.buffer.len() >= WEB_HDFS_CHUNK_SIZE {
+            self.upload_chunk().map_err(io::Error::other)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // Appending tiny chunks would be inefficient. Only `finish` uploads the remainder.
+        Ok(())
+    }
+}
+
+impl Sink for WebHdfsSink {
+    fn finish(mut self: Box<Self>) -> Result<(), Error> {
+        if !self.buffer.is_empty() || !self.created {
+            self.upload_chunk()?;
+        }
+        self.rename()?;
+        self.finished = true;
+        Ok(())
+    }
+}
+
+impl Drop for WebHdfsSink {
+    fn drop(&mut self) {
+        // The export failed, do not leave the incomplete file behind for readers to pick up.
+        if self.created && !self.finished {
+            let _ = self
+                .client
+                .delete(web_hdfs_operation(&self.endpoint, "DELETE"))
+                .send();
+        }
+    }
+}
+
+/// Writes the output into a file on a remote host using SFTP. Like for local files, the output is
+/// written into a hidden temporary file first, which is renamed once it is complete.
+struct SftpSink {
+    file: ssh2::File,
+    path: PathBuf,
+    tmp_path: PathBuf,
+    /// `true` once the temporary file has been renamed.
+    finished: bool,
+    sftp: Sftp,
+    // Keep the session alive until we are done with the file.
+    _session: Session,
+}
+
+impl SftpSink {
+    fn new(url: &Url) -> Result<Self, Error> {
+        let host = url
+            .host_str()
+            .ok_or_else(|| format_err!("SFTP URL must specify a host: {url}"))?;
+        let port = url.port().unwrap_or(22);
+        let user = if url.username().is_empty() {
+            env::var("USER").or_else(|_| env::var("USERNAME"))?
+        } else {
+            url.username().to_owned()
+        };
+
+        let mut session = Session::new()?;
+        session.set_tcp_stream(TcpStream::connect((host, port))?);
+        session.handshake()?;
+        verify_host_key(&session, host, port)?;
+
+        if let Some(password) = url.password() {
+            let password = percent_decode_str(password).decode_utf8()?;
+            session.userauth_password(&user, &password)?;
+        } else {
+            authenticate_with_key(&session, &user)?;
+        }
+
+        let sftp = session.sftp()?;
+        let path = url_decoded_path(url)?;
+        if let Some(parent) = path.parent() {
+            sftp_create_dir_all(&sftp, parent)?;
+        }
+        let tmp_path = tmp_path(&path)?;
+        let file = sftp.create(&tmp_path)?;
+        Ok(Self {
+            file,
+            path,
+            tmp_path,
+            finished: false,
+            sftp,
+            _session: session,
+        })
+    }
+}
+
+/// Creates a directory on the remote host, together with all its missing parents.
+fn sftp_create_dir_all(sftp: &Sftp, dir: &Path) -> Result<(), Error> {
+    if dir.as_os_str().is_empty() || sftp.stat(dir).is_ok() {
+        return Ok(());
+    }
+    if let Some(parent) = dir.parent() {
+        sftp_create_dir_all(sftp, parent)?;
+    }
+    sftp.mkdir(dir, 0o755)?;
+    Ok(())
+}
+
+/// Checks the key of the remote host against the `known_hosts` file of the user. We do not want to
+/// send sensitive data to a host we can not trust.
+fn verify_host_key(session: &Session, host: &str, port: u16) -> Result<(), Error> {
+    let (key, _key_type) = session
+        .host_key()
+        .ok_or_else(|| format_err!("SFTP server did not present a host key."))?;
+    let mut known_hosts = session.known_hosts()?;
+    let known_hosts_path = ssh_dir()?.join("known_hosts");
+    // A missing file is fine, it just means that the host is not known.
+    if known_hosts_path.exists() {
+        known_hosts.read_file(&known_hosts_path, KnownHostFileKind::OpenSSH)?;
+    }
+    match known_hosts.check_port(host, port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::NotFound => bail!(
+            "Host '{host}' is not contained in '{}'. Connect to it once using `ssh` or \
+            `ssh-keyscan` to add its key.",
+            known_hosts_path.display()
+        ),
+        CheckResult::Mismatch => bail!(
+            "Host key of '{host}' does not match the one in '{}'.",
+            known_hosts_path.display()
+        ),
+        CheckResult::Failure => bail!("Failed to verify the host key of '{host}'."),
+    }
+}
+
+/// Authenticate using the SSH agent, falling back to the default private key files of the user.
+fn authenticate_with_key(session: &Session, user: &str) -> Result<(), Error> {
+    if session.userauth_agent(user).is_ok() {
+        return Ok(());
+    }
+    let ssh_dir = ssh_dir()?;
+    for key_file in ["id_ed25519", "id_ecdsa", "id_rsa"] {
+        let private_key = ssh_dir.join(key_file);
+        if private_key.exists()
+            && session
+                .userauth_pubkey_file(user, None, &private_key, None)
+                .is_ok()
+        {
+            return Ok(());
+        }
+    }
+    bail!(
+        "Failed to authenticate as '{user}' via SFTP. Either specify a password in the URL, or \
+        provide a key via the SSH agent or in '{}'.",
+        ssh_dir.display()
+    )
+}
+
+/// `.ssh` directory in the home directory of the current user.
+fn ssh_dir() -> Result<PathBuf, Error> {
+    let home = env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .ok_or_else(|| format_err!("Could not determine home directory of current user."))?;
+    Ok(PathBuf::from(home).join(".ssh"))
+}
+
+impl Write for SftpSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Sink for SftpSink {
+    fn finish(mut self: Box<Self>) -> Result<(), Error> {
+        self.file.flush()?;
+        self.file.close()?;
+        // Most servers only implement version 3 of the protocol, which does not allow renaming
+        // onto an existing file.
+        if self.sftp.stat(&self.path).is_ok() {
+            self.sftp.unlink(&self.path)?;
+        }
+        self.sftp.rename(&self.tmp_path, &self.path, None)?;
+        self.finished = true;
+        Ok(())
+    }
+}
+
+impl Drop for SftpSink {
+    fn drop(&mut self) {
+        // The export failed, do not leave the incomplete file behind in the drop zone.
+        if !self.finished {
+            let _ = self.sftp.unlink(&self.tmp_path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{fs, io::Write};
+
+    use tempfile::tempdir;
+
+    use super::{azure_options_from_connection_string, Destination};
+
+    #[test]
+    fn file_appears_once_complete() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("out.par");
+        let mut sink = Destination::File(path.clone()).create().unwrap();
+        sink.write_all(b"PAR1").unwrap();
+        assert!(!path.exists());
+
+        sink.finish().unwrap();
+
+        assert_eq!(b"PAR1".as_slice(), fs::read(&path).unwrap());
+        // The temporary file has been renamed, nothing else is left in the directory.
+        assert_eq!(1, fs::read_dir(dir.path()).unwrap().count());
+    }
+
+    #[test]
+    fn failed_output_leaves_no_file_behind() {
+        let dir = tempdir().unwrap();
+        let mut sink = Destination::File(dir.path().join("out.par"))
+            .create()
+            .unwrap();
+        sink.write_all(b"PAR1").unwrap();
+        drop(sink);
+        assert_eq!(0, fs::read_dir(dir.path()).unwrap().count());
+    }
+
+    #[test]
+    fn parse_destinations() {
+        assert_eq!(Destination::StdOut, "-".parse().unwrap());
+        assert_eq!(
+            Destination::File("out.par".into()),
+            "out.par".parse().unwrap()
+        );
+        assert!(matches!(
+            "s3://bucket/prefix/out.par".parse().unwrap(),
+            Destination::ObjectStore(_)
+        ));
+        assert!(matches!(
+            "abfss://container@account.dfs.core.windows.net/out.par"
+                .parse()
+                .unwrap(),
+            Destination::ObjectStore(_)
+        ));
+        assert!(matches!(
+            "webhdfs://namenode:9870/out.par".parse().unwrap(),
+            Destination::WebHdfs(_)
+        ));
+        assert!(matches!(
+            "sftp://user@host/drop/out.par".parse().unwrap(),
+            Destination::Sftp(_)
+        ));
+    }
+
+    #[test]
+    fn join_partition_path() {
+        let file: Destination = "out".parse().unwrap();
+        assert_eq!(
+            Destination::File("out/year=2022/part.par".into()),
+            file.join("year=2022/part.par")
+        );
+        let s3: Destination = "s3://bucket/prefix/".parse().unwrap();
+        assert_eq!(
+            "s3://bucket/prefix/month=2022%252F01/part.par"
+                .parse::<Destination>()
+                .unwrap(),
+            s3.join("month=2022%2F01/part.par")
+        );
+    }
+
+    #[test]
+    fn derive_tee_destinations() {
+        let tee = Destination::tee(vec![
+            "out.par".parse().unwrap(),
+            "s3://bucket/prefix/out.par".parse().unwrap(),
+        ]);
+        assert_eq!(
+            Destination::Tee(vec![
+                Destination::File("out_03.par".into()),
+                "s3://bucket/prefix/out_03.par".parse().unwrap(),
+            ]),
+            tee.with_suffix(3, 2).unwrap()
+        );
+        assert!(!tee.is_std_out());
+        let single = Destination::tee(vec!["out.par".parse().unwrap()]);
+        assert_eq!(Destination::File("out.par".into()), single);
+    }
+
+    #[test]
+    fn expand_placeholders_in_file_name() {
+        let file: Destination = "export_{date}_{seq}.par".parse().unwrap();
+        let file = file.expand_placeholders(&[("date", "2022-10-15".to_owned())]);
+        assert_eq!(
+            Destination::File("export_2022-10-15_03.par".into()),
+            file.with_suffix(3, 2).unwrap()
+        );
+        let s3: Destination = "s3://bucket/{date}/out.par".parse().unwrap();
+        assert_eq!(
+            "s3://bucket/2022-10-15/out.par"
+                .parse::<Destination>()
+                .unwrap(),
+            s3.expand_placeholders(&[("date", "2022-10-15".to_owned())])
+        );
+    }
+
+    #[test]
+    fn relative_paths_within_directory() {
+        let file: Destination = "exports/out.par".parse().unwrap();
+        assert_eq!(
+            Some("out_01.par".to_owned()),
+            file.with_suffix(1, 2).unwrap().relative_to(&file.parent())
+        );
+        let current_dir: Destination = "out.par".parse().unwrap();
+        assert_eq!(
+            Some("out.par".to_owned()),
+            current_dir.relative_to(&current_dir.parent())
+        );
+        let s3: Destination = "s3://bucket/prefix".parse().unwrap();
+        assert_eq!(
+            Some("month=2022%2F01/part.par".to_owned()),
+            s3.join("month=2022%2F01/part.par").relative_to(&s3)
+        );
+        let tee = Destination::tee(vec![
+            "out.par".parse().unwrap(),
+            "s3://bucket/prefix/out.par".parse().unwrap(),
+        ]);
+        assert_eq!(Some("out.par".to_owned()), tee.relative_to(&tee.parent()));
+        assert_eq!(None, file.relative_to(&"elsewhere".parse().unwrap()));
+    }
+
+    #[test]
+    fn azure_connection_string() {
+        let connection_string = "DefaultEndpointsProtocol=https;AccountName=my_account;\
+            AccountKey=c2VjcmV0==;EndpointSuffix=core.windows.net";
+
+        let actual = azure_options_from_connection_string(connection_string);
+
+        let expected = vec![
+            (
+                "azure_storage_account_name".to_owned(),
+                "my_account".to_owned(),
+            ),
+            (
+                "azure_storage_account_key".to_owned(),
+                "c2VjcmV0==".to_owned(),
+            ),
+        ];
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn suffix_for_object_store_destination() {
+        let destination: Destination = "s3://bucket/prefix/out.par".parse().unwrap();
+
+        let actual = destination.with_suffix(3, 2).unwrap();
+
+        let expected: Destination = "s3://bucket/prefix/out_03.par".parse().unwrap();
+        assert_eq!(expected, actual);
+
+        let destination: Destination = "gs://bucket/out.par".parse().unwrap();
+
+        let actual = destination.with_suffix(12, 2).unwrap();
+
+        let expected: Destination = "gs://bucket/out_12.par".parse().unwrap();
+        assert_eq!(expected, actual);
+
+        let destination: Destination = "sftp://host/drop%20zone/out%25.par".parse().unwrap();
+
+        let actual = destination.with_suffix(1, 2).unwrap();
+
+        let expected: Destination = "sftp://host/drop%20zone/out%25_01.par".parse().unwrap();
+        assert_eq!(expected, actual);
+    }
+}
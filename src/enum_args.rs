@@ -1,6 +1,12 @@
-use anyhow::{anyhow, bail, Error};
+use std::{path::PathBuf, time::Duration};
+
+use anyhow::{anyhow, bail, Context, Error};
+use chrono::FixedOffset;
 use clap::ValueEnum;
-use parquet::basic::{Compression, Encoding};
+use parquet::{
+    basic::{BrotliLevel, Compression, Encoding, GzipLevel, TimeUnit, ZstdLevel},
+    file::properties::{EnabledStatistics, WriterVersion},
+};
 
 #[derive(Debug, Clone, Copy, ValueEnum)]
 pub enum EncodingArgument {
@@ -35,19 +41,393 @@ pub enum CompressionVariants {
     Lz0,
     Zstd,
     Snappy,
-    Brotli
+    Brotli,
 }
 
 impl CompressionVariants {
     pub fn as_compression(self) -> Compression {
         match self {
             CompressionVariants::Uncompressed => Compression::UNCOMPRESSED,
-            CompressionVariants::Gzip => Compression::GZIP,
+            CompressionVariants::Gzip => Compression::GZIP(Default::default()),
             CompressionVariants::Lz4 => Compression::LZ4,
             CompressionVariants::Lz0 => Compression::LZO,
-            CompressionVariants::Zstd => Compression::ZSTD,
-            CompressionVariants::Snappy => Compression::ZSTD,
-            CompressionVariants::Brotli => Compression::BROTLI,
+            CompressionVariants::Zstd => Compression::ZSTD(Default::default()),
+            CompressionVariants::Snappy => Compression::SNAPPY,
+            CompressionVariants::Brotli => Compression::BROTLI(Default::default()),
+        }
+    }
+
+    /// Like [`Self::as_compression`], but with an explicit compression level. Only `gzip` (0-9),
+    /// `brotli` (0-11) and `zstd` (up to 22) support levels.
+    pub fn as_compression_with_level(self, level: i32) -> Result<Compression, Error> {
+        let unsigned = |level: i32| {
+            u32::try_from(level)
+                .map_err(|_| anyhow!("Compression level of {self:?} must not be negative."))
+        };
+        let compression = match self {
+            CompressionVariants::Gzip => Compression::GZIP(GzipLevel::try_new(unsigned(level)?)?),
+            CompressionVariants::Zstd => Compression::ZSTD(ZstdLevel::try_new(level)?),
+            CompressionVariants::Brotli => {
+                Compression::BROTLI(BrotliLevel::try_new(unsigned(level)?)?)
+            }
+            _ => bail!("Compression {self:?} does not support a compression level."),
+        };
+        Ok(compression)
+    }
+}
+
+/// Compression parsed from the command line, optionally with a level. E.g. `zstd` or `zstd:9`.
+#[derive(Debug, Clone, Copy)]
+pub struct CompressionArgument {
+    pub variant: CompressionVariants,
+    pub level: Option<i32>,
+}
+
+impl CompressionArgument {
+    /// Applies `level`, unless a level has already been specified explicitly.
+    pub fn or_level(self, level: Option<i32>) -> Self {
+        Self {
+            variant: self.variant,
+            level: self.level.or(level),
+        }
+    }
+
+    pub fn as_compression(self) -> Result<Compression, Error> {
+        match self.level {
+            None => Ok(self.variant.as_compression()),
+            Some(level) => self.variant.as_compression_with_level(level),
+        }
+    }
+}
+
+/// Parses a compression in format `COMPRESSION[:LEVEL]`, e.g. `gzip` or `zstd:9`.
+pub fn compression_from_str(source: &str) -> Result<CompressionArgument, Error> {
+    let (variant, level) = match source.split_once(':') {
+        Some((variant, level)) => {
+            let level = level
+                .parse()
+                .map_err(|_| anyhow!("Compression level must be an integer, but got '{level}'."))?;
+            (variant, Some(level))
+        }
+        None => (source, None),
+    };
+    let variant = CompressionVariants::from_str(variant, true)
+        .map_err(|message| anyhow!("Invalid compression: {message}"))?;
+    let compression = CompressionArgument { variant, level };
+    // Validate the level early, so invalid arguments are reported before connecting.
+    compression.as_compression()?;
+    Ok(compression)
+}
+
+/// Parses the compression of a single column in format `COLUMN:COMPRESSION[:LEVEL]`, e.g.
+/// `payload:uncompressed` or `comment:zstd:9`.
+pub fn column_compression_from_str(source: &str) -> Result<(String, Compression), Error> {
+    let (mut name, mut compression) = source.rsplit_once(':').ok_or_else(|| {
+        anyhow!("Column compression must be passed in format: 'COLUMN_NAME:COMPRESSION'")
+    })?;
+    // The last part may be the level, rather than the compression.
+    if compression.parse::<i32>().is_ok() {
+        if let Some((name_without_compression, _)) = name.rsplit_once(':') {
+            compression = &source[name_without_compression.len() + 1..];
+            name = name_without_compression;
+        }
+    }
+    let compression = compression_from_str(compression)?;
+    Ok((name.to_owned(), compression.as_compression()?))
+}
+
+/// Enables or disables a feature, e.g. dictionary encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Switch {
+    On,
+    Off,
+}
+
+impl Switch {
+    pub fn is_on(self) -> bool {
+        self == Switch::On
+    }
+}
+
+/// Parses whether a column is dictionary encoded in format `COLUMN:on` or `COLUMN:off`.
+pub fn column_dictionary_from_str(source: &str) -> Result<(String, bool), Error> {
+    let (name, switch) = source.rsplit_once(':').ok_or_else(|| {
+        anyhow!("Column dictionary must be passed in format: 'COLUMN_NAME:on|off'")
+    })?;
+    let switch = Switch::from_str(switch, true)
+        .map_err(|message| anyhow!("Invalid column dictionary: {message}"))?;
+    Ok((name.to_owned(), switch.is_on()))
+}
+
+/// Mirrors parquets `EnabledStatistics` enum in order to parse it from the command line
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum StatisticsLevel {
+    /// No statistics at all.
+    None,
+    /// Min/max values and null count for each column chunk, stored in the footer.
+    Chunk,
+    /// In addition to the column chunk statistics, min/max values for each page, stored in the
+    /// column index. Allows readers to skip individual pages.
+    Page,
+}
+
+impl StatisticsLevel {
+    pub fn as_enabled_statistics(self) -> EnabledStatistics {
+        match self {
+            StatisticsLevel::None => EnabledStatistics::None,
+            StatisticsLevel::Chunk => EnabledStatistics::Chunk,
+            StatisticsLevel::Page => EnabledStatistics::Page,
+        }
+    }
+}
+
+/// Parses the statistics level of a column in format `COLUMN:none|chunk|page`.
+pub fn column_statistics_from_str(source: &str) -> Result<(String, EnabledStatistics), Error> {
+    let (name, level) = source.rsplit_once(':').ok_or_else(|| {
+        anyhow!("Column statistics must be passed in format: 'COLUMN_NAME:none|chunk|page'")
+    })?;
+    let level = StatisticsLevel::from_str(level, true)
+        .map_err(|message| anyhow!("Invalid column statistics: {message}"))?;
+    Ok((name.to_owned(), level.as_enabled_statistics()))
+}
+
+/// Physical type used to store timestamps in parquet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TimestampsAs {
+    /// 64 Bit integer, annotated with the `TIMESTAMP` logical type.
+    Int64,
+    /// Legacy 12 byte representation of nanoseconds within a julian day. Deprecated, yet the only
+    /// one understood by old versions of e.g. Hive and Impala.
+    Int96,
+}
+
+/// How to interpret timestamps without time zone, e.g. `DATETIME` in Microsoft SQL Server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TimestampTzMode {
+    /// Write them as is, without claiming any time zone (`isAdjustedToUTC=false`).
+    Naive,
+    /// Write them as is, marking them as UTC (`isAdjustedToUTC=true`).
+    Utc,
+    /// Interpret them as local time of `--session-time-zone` and convert them to UTC.
+    Session,
+}
+
+impl TimestampTzMode {
+    /// Offset of the time zone the timestamps are local to. `None` if they are written naive.
+    pub fn utc_offset(
+        self,
+        session_time_zone: Option<FixedOffset>,
+    ) -> Result<Option<FixedOffset>, Error> {
+        match (self, session_time_zone) {
+            (TimestampTzMode::Session, Some(offset)) => Ok(Some(offset)),
+            (TimestampTzMode::Session, None) => {
+                bail!("`--timestamp-tz session` requires `--session-time-zone`.")
+            }
+            (_, Some(_)) => bail!("session-time-zone requires `--timestamp-tz session`."),
+            (TimestampTzMode::Naive, None) => Ok(None),
+            (TimestampTzMode::Utc, None) => Ok(Some(FixedOffset::east_opt(0).unwrap())),
+        }
+    }
+}
+
+/// Parses a fixed offset from UTC, e.g. `+02:00`. `UTC` and `Z` are accepted as a zero offset.
+pub fn time_zone_offset_from_str(source: &str) -> Result<FixedOffset, Error> {
+    if source.eq_ignore_ascii_case("utc") || source.eq_ignore_ascii_case("z") {
+        return Ok(FixedOffset::east_opt(0).unwrap());
+    }
+    source.parse().map_err(|_| {
+        anyhow!("Session time zone must be an offset from UTC like '+02:00', but got '{source}'.")
+    })
+}
+
+/// Mirrors parquets `TimeUnit` in order to parse it from the command line.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum TimeUnitArgument {
+    Millis,
+    Micros,
+    Nanos,
+}
+
+/// Parses the unit of the timestamps in a column in format `COLUMN:millis|micros|nanos`.
+pub fn column_time_unit_from_str(source: &str) -> Result<(String, TimeUnit), Error> {
+    let (name, unit) = source.rsplit_once(':').ok_or_else(|| {
+        anyhow!("Timestamp precision must be passed in format: 'COLUMN_NAME:millis|micros|nanos'")
+    })?;
+    let unit = TimeUnitArgument::from_str(unit, true)
+        .map_err(|message| anyhow!("Invalid timestamp precision: {message}"))?;
+    Ok((name.to_owned(), unit.as_time_unit()))
+}
+
+impl TimeUnitArgument {
+    pub fn as_time_unit(self) -> TimeUnit {
+        match self {
+            TimeUnitArgument::Millis => TimeUnit::MILLIS,
+            TimeUnitArgument::Micros => TimeUnit::MICROS,
+            TimeUnitArgument::Nanos => TimeUnit::NANOS,
+        }
+    }
+}
+
+/// Representation of SQL intervals in parquet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum IntervalsAs {
+    /// 12 byte `INTERVAL` of months, days and milliseconds. Can not represent negative intervals.
+    Interval,
+    /// UTF8 string holding an ISO 8601 duration, e.g. `P1Y2M` or `-P3DT4H5M6.5S`.
+    Iso8601,
+}
+
+/// What happens to the files written so far, if the export is interrupted with Ctrl+C.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OnInterrupt {
+    /// Keep the files. They are valid parquet files containing the rows fetched so far.
+    Keep,
+    /// Remove the files.
+    Discard,
+}
+
+/// What happens if the driver truncates values, because they are longer than the fetch buffer of
+/// their column, see `--on-truncation`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OnTruncation {
+    /// Grow the buffers of the affected columns and fetch the result set again.
+    Refetch,
+    /// Fail the export.
+    Error,
+    /// Write the truncated values, with a warning naming the affected columns.
+    Warn,
+}
+
+/// What happens to values larger than `--max-value-size`, see `--oversized`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OversizedPolicy {
+    /// Fail the export.
+    Error,
+    /// Write the value as NULL.
+    Null,
+    /// Write the first bytes of the value, up to `--max-value-size`.
+    Truncate,
+    /// Write the value into a side file, and its path into the column `<column>_file`.
+    File,
+}
+
+/// How side files are named, see `--side-file-names`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SideFileNames {
+    /// SHA-256 of the value, as lower case hex digits.
+    Hash,
+    /// Value of the first `--sorted-by` column in the row.
+    Key,
+}
+
+/// Parses the policy for oversized values in format `[COLUMN:]POLICY`. Without a column, the policy
+/// applies to all columns without one of their own.
+pub fn oversized_from_str(source: &str) -> Result<(Option<String>, OversizedPolicy), Error> {
+    let (column, policy) = match source.rsplit_once(':') {
+        Some((column, policy)) if !column.is_empty() => (Some(column.to_owned()), policy),
+        Some(_) => {
+            bail!("Oversized values must be handled in format: '[COLUMN:]null|truncate|error|file'")
+        }
+        None => (None, source),
+    };
+    let policy = OversizedPolicy::from_str(policy, true)
+        .map_err(|message| anyhow!("Invalid policy for oversized values: {message}"))?;
+    Ok((column, policy))
+}
+
+/// What happens if the output already exists, see `--if-exists`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum IfExists {
+    /// Replace the existing output.
+    Overwrite,
+    /// Fail without executing the query.
+    Error,
+    /// Do not execute the query and leave the existing output as it is.
+    Skip,
+    /// Continue the existing sequence of files, like `--append`.
+    Append,
+}
+
+/// Isolation level of the transaction the export is wrapped in, see `--isolation-level`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum IsolationLevel {
+    /// Rows changed by transactions which did not commit yet are visible.
+    ReadUncommitted,
+    /// Only committed changes are visible, but they may become visible while the export runs.
+    ReadCommitted,
+    /// Rows read once are not changed by other transactions until the export completes.
+    RepeatableRead,
+    /// The export behaves as if no other transaction ran concurrently.
+    Serializable,
+    /// The export sees the data as committed when its transaction started, without blocking
+    /// writers. Microsoft SQL Server only. Snapshot isolation must be allowed for the database.
+    Snapshot,
+}
+
+impl IsolationLevel {
+    /// Name of the isolation level in a `SET TRANSACTION ISOLATION LEVEL` statement.
+    pub fn as_sql(self) -> &'static str {
+        match self {
+            IsolationLevel::ReadUncommitted => "READ UNCOMMITTED",
+            IsolationLevel::ReadCommitted => "READ COMMITTED",
+            IsolationLevel::RepeatableRead => "REPEATABLE READ",
+            IsolationLevel::Serializable => "SERIALIZABLE",
+            IsolationLevel::Snapshot => "SNAPSHOT",
+        }
+    }
+}
+
+/// Mirrors parquets `WriterVersion` enum in order to parse it from the command line
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum WriterVersionArgument {
+    /// Parquet format version 1.0, readable by virtually every parquet reader.
+    #[value(name = "1")]
+    V1,
+    /// Parquet format version 2.0. Uses V2 data pages and the newer encodings, e.g.
+    /// `DELTA_BINARY_PACKED` for integers, by default.
+    #[value(name = "2")]
+    V2,
+}
+
+impl WriterVersionArgument {
+    pub fn as_writer_version(self) -> WriterVersion {
+        match self {
+            WriterVersionArgument::V1 => WriterVersion::PARQUET_1_0,
+            WriterVersionArgument::V2 => WriterVersion::PARQUET_2_0,
+        }
+    }
+}
+
+/// Format of the file(s) written by the `query` subcommand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Parquet,
+    Csv,
+    Duckdb,
+    Sqlite,
+    Xlsx,
+}
+
+/// Mirrors the `QuoteStyle` of the csv crate in order to parse it from the command line
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CsvQuoteStyle {
+    /// Only quote fields containing the delimiter, quotes or line breaks.
+    Necessary,
+    /// Quote every field.
+    Always,
+    /// Quote every field which is not a number.
+    NonNumeric,
+    /// Never quote any field, even if this results in invalid CSV.
+    Never,
+}
+
+impl CsvQuoteStyle {
+    pub fn as_quote_style(self) -> csv::QuoteStyle {
+        match self {
+            CsvQuoteStyle::Necessary => csv::QuoteStyle::Necessary,
+            CsvQuoteStyle::Always => csv::QuoteStyle::Always,
+            CsvQuoteStyle::NonNumeric => csv::QuoteStyle::NonNumeric,
+            CsvQuoteStyle::Never => csv::QuoteStyle::Never,
         }
     }
 }
@@ -78,3 +458,347 @@ pub fn column_encoding_from_str(source: &str) -> Result<(String, Encoding), Erro
     let (name, encoding) = source.split_at(pos);
     Ok((name.to_owned(), encoding_from_str(&encoding[1..])?))
 }
+
+/// Parses a column name, optionally followed by the false positive probability of its bloom filter,
+/// in format `COLUMN[:FPP]`.
+pub fn bloom_filter_from_str(source: &str) -> Result<(String, Option<f64>), Error> {
+    let Some((name, fpp)) = source
+        .rsplit_once(':')
+        .and_then(|(name, fpp)| Some((name, fpp.parse::<f64>().ok()?)))
+    else {
+        return Ok((source.to_owned(), None));
+    };
+    if !(fpp > 0. && fpp < 1.) {
+        bail!(
+            "False positive probability of bloom filter must be between 0 and 1 (exclusive), but \
+            got {fpp}."
+        )
+    }
+    Ok((name.to_owned(), Some(fpp)))
+}
+
+/// Column the result set is sorted by, see `--sorted-by`.
+#[derive(Debug, Clone)]
+pub struct SortColumn {
+    pub name: String,
+    pub descending: bool,
+    pub nulls_first: bool,
+}
+
+/// Parses a column in format `COLUMN [ASC|DESC] [NULLS FIRST|NULLS LAST]`, like in an `ORDER BY`
+/// clause. Unless specified otherwise, NULL is considered smaller than any other value. I.e. NULLs
+/// come first in ascending and last in descending order.
+pub fn sort_column_from_str(source: &str) -> Result<SortColumn, Error> {
+    let mut words: Vec<_> = source.split_whitespace().collect();
+    let mut take_keyword = |keyword: &str| {
+        let is_match = words.len() > 1 && words[words.len() - 1].eq_ignore_ascii_case(keyword);
+        if is_match {
+            words.pop();
+        }
+        is_match
+    };
+    let nulls_first = if take_keyword("first") {
+        Some(true)
+    } else if take_keyword("last") {
+        Some(false)
+    } else {
+        None
+    };
+    if nulls_first.is_some() && !take_keyword("nulls") {
+        bail!("Expected 'NULLS FIRST' or 'NULLS LAST' in sort column '{source}'.")
+    }
+    let descending = if take_keyword("desc") {
+        true
+    } else {
+        take_keyword("asc");
+        false
+    };
+    if words.is_empty() {
+        bail!("Sort column must not be empty.")
+    }
+    Ok(SortColumn {
+        name: words.join(" "),
+        descending,
+        nulls_first: nulls_first.unwrap_or(!descending),
+    })
+}
+
+/// How parquet field IDs are assigned to the columns, see `--field-ids`.
+#[derive(Debug, Clone)]
+pub enum FieldIdsArgument {
+    /// Number the columns of the result set, starting with 1.
+    Sequential,
+    /// Look up the IDs by column name in an Iceberg name mapping.
+    NameMapping(PathBuf),
+}
+
+/// Parses either `sequential` or the path to a file containing an Iceberg name mapping.
+pub fn field_ids_from_str(source: &str) -> Result<FieldIdsArgument, Error> {
+    if source.is_empty() {
+        bail!("Field IDs must either be 'sequential' or the path to a name mapping file.")
+    }
+    if source.eq_ignore_ascii_case("sequential") {
+        Ok(FieldIdsArgument::Sequential)
+    } else {
+        Ok(FieldIdsArgument::NameMapping(source.into()))
+    }
+}
+
+/// How column names are sanitized, see `--sanitize-column-names`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SanitizeColumnNames {
+    /// Lower case words separated by underscores, e.g. `Order Id` becomes `order_id`.
+    #[value(name = "snake_case")]
+    SnakeCase,
+    /// Only lower case the names.
+    Lower,
+    /// Like `snake_case`, but only ASCII letters, digits and underscores are kept and names do not
+    /// start with a digit.
+    Strict,
+}
+
+/// Parses a renaming of a column in format `SOURCE=DESTINATION`.
+pub fn rename_from_str(source: &str) -> Result<(String, String), Error> {
+    let (from, to) = source
+        .split_once('=')
+        .filter(|(from, to)| !from.is_empty() && !to.is_empty())
+        .ok_or_else(|| anyhow!("Renaming must be passed in format: 'SOURCE=DESTINATION'"))?;
+    Ok((from.to_owned(), to.to_owned()))
+}
+
+/// Parses the definition of a variable in format `NAME=VALUE`, see `--var`. The value may be empty.
+pub fn variable_from_str(source: &str) -> Result<(String, String), Error> {
+    let (name, value) = source
+        .split_once('=')
+        .filter(|(name, _value)| !name.is_empty())
+        .ok_or_else(|| anyhow!("Variables must be passed in format: 'NAME=VALUE'"))?;
+    Ok((name.to_owned(), value.to_owned()))
+}
+
+/// Parses an output parameter in format `POSITION=NAME`, see `--output-parameter`. The position of
+/// the first placeholder is `1`.
+pub fn output_parameter_from_str(source: &str) -> Result<(u16, String), Error> {
+    source
+        .split_once('=')
+        .and_then(|(position, name)| {
+            let position: u16 = position
+                .trim()
+                .parse()
+                .ok()
+                .filter(|&position| position != 0)?;
+            (!name.is_empty()).then(|| (position, name.to_owned()))
+        })
+        .ok_or_else(|| {
+            anyhow!("Output parameters must be passed in format: 'POSITION=NAME', e.g. '1=total'")
+        })
+}
+
+/// Parses a value of a column in format `COLUMN=VALUE`, e.g. for `--null-value`. The value may be
+/// empty.
+pub fn column_value_from_str(source: &str) -> Result<(String, String), Error> {
+    let (column, value) = source
+        .split_once('=')
+        .filter(|(column, _value)| !column.is_empty())
+        .ok_or_else(|| anyhow!("Values must be passed in format: 'COLUMN=VALUE'"))?;
+    Ok((column.to_owned(), value.to_owned()))
+}
+
+/// Column appended to every row, see `--add-column`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AddedColumn {
+    /// Text column with the same value in every row.
+    Constant { name: String, value: String },
+    /// `_extracted_at`: Start of the export as timestamp in UTC.
+    ExtractedAt,
+    /// `_source`: Data source name or server the result set is fetched from.
+    Source,
+}
+
+/// Parses an added column in format `NAME=VALUE`, or the name of a built-in column.
+pub fn added_column_from_str(source: &str) -> Result<AddedColumn, Error> {
+    let column = match source.split_once('=') {
+        Some((name, value)) if !name.is_empty() => AddedColumn::Constant {
+            name: name.to_owned(),
+            value: value.to_owned(),
+        },
+        None if source == "_extracted_at" => AddedColumn::ExtractedAt,
+        None if source == "_source" => AddedColumn::Source,
+        _ => bail!(
+            "Columns must be added in format: 'NAME=VALUE', or be one of the built-in columns \
+            '_extracted_at' and '_source'"
+        ),
+    };
+    Ok(column)
+}
+
+/// How the values of a column are masked, see `--mask`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mask {
+    /// Write the SHA-256 of the salt followed by the value, as hex digits.
+    Sha256 { salt: String },
+    /// Write every value as NULL.
+    Redact,
+}
+
+/// Parses the mask of a column in format `COLUMN:sha256[:SALT]` or `COLUMN:redact`.
+pub fn mask_from_str(source: &str) -> Result<(String, Mask), Error> {
+    let mut parts = source.splitn(3, ':');
+    let column = parts.next().filter(|column| !column.is_empty());
+    let method = parts.next();
+    let salt = parts.next();
+    let mask = match (column, method, salt) {
+        (Some(_), Some(method), salt) if method.eq_ignore_ascii_case("sha256") => Mask::Sha256 {
+            salt: salt.unwrap_or_default().to_owned(),
+        },
+        (Some(_), Some(method), None) if method.eq_ignore_ascii_case("redact") => Mask::Redact,
+        _ => bail!("Masks must be passed in format: 'COLUMN:sha256[:SALT]' or 'COLUMN:redact'"),
+    };
+    Ok((column.unwrap().to_owned(), mask))
+}
+
+/// Parquet type of numbers which can not be derived from the column description, see
+/// `--default-number-as` and `--decfloat-as`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberAs {
+    Decimal { precision: u8, scale: i32 },
+    Double,
+    Text,
+}
+
+/// Parses `decimal(PRECISION,SCALE)`, `double` or `text`.
+pub fn number_as_from_str(source: &str) -> Result<NumberAs, Error> {
+    let source = source.trim();
+    if source.eq_ignore_ascii_case("double") {
+        return Ok(NumberAs::Double);
+    }
+    if source.eq_ignore_ascii_case("text") {
+        return Ok(NumberAs::Text);
+    }
+    let invalid = || {
+        anyhow!(
+            "Number type must be either 'decimal(PRECISION,SCALE)', 'double' or 'text', \
+            not '{source}'."
+        )
+    };
+    let arguments = source
+        .get(.."decimal(".len())
+        .filter(|prefix| prefix.eq_ignore_ascii_case("decimal("))
+        .and_then(|_| source["decimal(".len()..].strip_suffix(')'))
+        .ok_or_else(invalid)?;
+    let (precision, scale) = arguments.split_once(',').ok_or_else(invalid)?;
+    let precision: u8 = precision.trim().parse().map_err(|_| invalid())?;
+    let scale: i32 = scale.trim().parse().map_err(|_| invalid())?;
+    if precision == 0 || scale < 0 || scale > precision as i32 {
+        bail!(
+            "Number type '{source}' must have a precision of at least 1 and a scale \
+            between 0 and its precision."
+        )
+    }
+    Ok(NumberAs::Decimal { precision, scale })
+}
+
+/// Key used for parquet modular encryption.
+#[derive(Clone)]
+pub struct EncryptionKey(pub Vec<u8>);
+
+/// Parses a hex encoded AES key with a length of 16, 24 or 32 bytes.
+pub fn encryption_key_from_str(source: &str) -> Result<EncryptionKey, Error> {
+    let source = source.trim();
+    let key = (0..source.len())
+        .step_by(2)
+        .map(|pos| {
+            source
+                .get(pos..pos + 2)
+                .and_then(|byte| u8::from_str_radix(byte, 16).ok())
+        })
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| anyhow!("Encryption key must be hex encoded."))?;
+    if ![16, 24, 32].contains(&key.len()) {
+        bail!(
+            "Encryption key must be 16, 24 or 32 bytes long, but got {} bytes.",
+            key.len()
+        )
+    }
+    Ok(EncryptionKey(key))
+}
+
+pub fn column_encryption_key_from_str(source: &str) -> Result<(String, EncryptionKey), Error> {
+    let pos = source.rfind(':').ok_or_else(|| {
+        anyhow!("Column encryption key must be passed in format: 'COLUMN_NAME:KEY'")
+    })?;
+    let (name, key) = source.split_at(pos);
+    Ok((name.to_owned(), encryption_key_from_str(&key[1..])?))
+}
+
+/// Parses a single ASCII character used as delimiter in CSV output. `\t` is accepted for a tab.
+pub fn csv_delimiter_from_str(source: &str) -> Result<u8, Error> {
+    match source.as_bytes() {
+        [b'\\', b't'] => Ok(b'\t'),
+        [delimiter] if delimiter.is_ascii() => Ok(*delimiter),
+        _ => bail!("CSV delimiter must be a single ASCII character, but got '{source}'."),
+    }
+}
+
+/// Parses an interval like `30s`, `15m`, `2h` or `1d`, see `--every`.
+pub fn interval_from_str(source: &str) -> Result<Duration, Error> {
+    let error = || anyhow!("Interval must be a number followed by s, m, h or d, e.g. '15m'.");
+    let unit_start = source
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(source.len());
+    let (number, unit) = source.split_at(unit_start);
+    let number: u64 = number.parse().map_err(|_| error())?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(error()),
+    };
+    if number == 0 {
+        bail!("Interval must not be zero.")
+    }
+    let seconds = number
+        .checked_mul(seconds)
+        .context("Interval is too large.")?;
+    Ok(Duration::from_secs(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use parquet::basic::{Compression, GzipLevel};
+
+    use std::time::Duration;
+
+    use super::{column_compression_from_str, compression_from_str, interval_from_str};
+
+    #[test]
+    fn parse_compression() {
+        let snappy = compression_from_str("snappy").unwrap();
+        assert_eq!(Compression::SNAPPY, snappy.as_compression().unwrap());
+        let gzip = compression_from_str("gzip:4").unwrap();
+        assert_eq!(
+            Compression::GZIP(GzipLevel::try_new(4).unwrap()),
+            gzip.as_compression().unwrap()
+        );
+        assert!(compression_from_str("snappy:4").is_err());
+        assert_eq!(
+            ("payload".to_owned(), Compression::SNAPPY),
+            column_compression_from_str("payload:snappy").unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_interval() {
+        assert_eq!(
+            Duration::from_secs(15 * 60),
+            interval_from_str("15m").unwrap()
+        );
+        assert!(interval_from_str("0s").is_err());
+        assert_eq!(
+            "Interval is too large.",
+            interval_from_str("99999999999999999d")
+                .unwrap_err()
+                .to_string()
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! Exports all tables of a schema, each into its own output. Every table is exported like the
+//! `query` subcommand would export it with `--source-table`.
+
+use std::{iter::once, sync::Mutex, thread};
+
+use anyhow::{bail, Error};
+use log::{error, info, warn};
+use odbc_api::{buffers::TextRowSet, Cursor, Environment};
+
+use crate::{open_connection, query, query_opt_from_args, ExportSchemaOpt, QueryOpt};
+
+/// Number of rows fetched at once while listing the tables.
+const TABLE_BATCH_SIZE: usize = 100;
+
+/// Upper bound for the length of catalog, schema and table names.
+const MAX_NAME_LEN: usize = 4096;
+
+/// A table returned by `SQLTables`.
+struct Table {
+    catalog: String,
+    schema: String,
+    name: String,
+}
+
+impl Table {
+    /// Name of the table qualified with schema and catalog, as understood by `--source-table`.
+    fn qualified_name(&self) -> String {
+        [&self.catalog, &self.schema, &self.name]
+            .into_iter()
+            .filter(|part| !part.is_empty())
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join(".")
+    }
+
+    /// Name matched against `--include` and `--exclude`.
+    fn pattern_subject(&self) -> String {
+        if self.schema.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}.{}", self.schema, self.name)
+        }
+    }
+}
+
+pub fn export_schema(environment: &Environment, opt: ExportSchemaOpt) -> Result<(), Error> {
+    let tables = list_tables(environment, &opt)?;
+    info!("Exporting {} tables.", tables.len());
+
+    let pending = Mutex::new(tables.iter());
+    let failed = Mutex::new(Vec::new());
+    thread::scope(|scope| {
+        for _ in 0..opt.parallel.max(1) {
+            scope.spawn(|| {
+                // Each worker takes the next table, until all tables are exported.
+                while let Some(table) = pending.lock().unwrap().next() {
+                    if let Err(e) = export_table(environment, &opt, table) {
+                        error!("Failed to export table '{}': {e:?}", table.qualified_name());
+                        failed.lock().unwrap().push(table.qualified_name());
+                    }
+                }
+            });
+        }
+    });
+
+    let failed = failed.into_inner().unwrap();
+    if !failed.is_empty() {
+        bail!(
+            "Failed to export {} of {} tables: {}",
+            failed.len(),
+            tables.len(),
+            failed.join(", ")
+        )
+    }
+    Ok(())
+}
+
+/// Parses the options of the `query` subcommand used to export a single table. Fails if they are
+/// invalid, independent of the table, so this can be used to validate them up front.
+pub fn query_opt(opt: &ExportSchemaOpt, table: &str, output: &str) -> Result<QueryOpt, Error> {
+    query_opt_from_args(
+        &opt.connect_opts,
+        &["--source-table", table, output],
+        &opt.query_args,
+    )
+}
+
+fn export_table(
+    environment: &Environment,
+    opt: &ExportSchemaOpt,
+    table: &Table,
+) -> Result<(), Error> {
+    let output = opt
+        .output
+        .replace("{catalog}", &table.catalog)
+        .replace("{schema}", &table.schema)
+        .replace("{table}", &table.name);
+    info!(
+        "Exporting table '{}' to '{output}'.",
+        table.qualified_name()
+    );
+    query::query(
+        environment,
+        query_opt(opt, &table.qualified_name(), &output)?,
+    )
+}
+
+/// Tables reported by the data source, which match the patterns of `--include` and `--exclude`.
+fn list_tables(environment: &Environment, opt: &ExportSchemaOpt) -> Result<Vec<Table>, Error> {
+    let conn = open_connection(environment, &opt.connect_opts)?;
+    let mut cursor = conn.tables(&opt.catalog, &opt.schema, "", &opt.table_type)?;
+    let buffer = TextRowSet::for_cursor(TABLE_BATCH_SIZE, &mut cursor, Some(MAX_NAME_LEN))?;
+    let mut row_set_cursor = cursor.bind_buffer(buffer)?;
+    let mut tables = Vec::new();
+    while let Some(batch) = row_set_cursor.fetch()? {
+        for row_index in 0..batch.num_rows() {
+            // Columns are TABLE_CAT, TABLE_SCHEM, TABLE_NAME, TABLE_TYPE and REMARKS.
+            let text = |col_index| -> Result<String, Error> {
+                Ok(batch
+                    .at_as_str(col_index, row_index)?
+                    .unwrap_or_default()
+                    .to_owned())
+            };
+            let table = Table {
+                catalog: text(0)?,
+                schema: text(1)?,
+                name: text(2)?,
+            };
+            if [&table.catalog, &table.schema, &table.name]
+                .iter()
+                .any(|part| part.contains('.'))
+            {
+                warn!(
+                    "Skipping table '{}', since its qualified name contains a dot.",
+                    table.name
+                );
+                continue;
+            }
+            let subject = table.pattern_subject();
+            let included = opt.include.is_empty()
+                || opt.include.iter().any(|pattern| matches(pattern, &subject));
+            let excluded = opt.exclude.iter().any(|pattern| matches(pattern, &subject));
+            if included && !excluded {
+                tables.push(table);
+            }
+        }
+    }
+    Ok(tables)
+}
+
+/// `true` if `text` matches the wildcard `pattern`. `*` matches any number of characters, `?`
+/// exactly one.
+fn matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    // `reachable[i]` is `true` if the first `i` characters of the pattern match the text so far.
+    let mut reachable: Vec<bool> = once(true).chain(pattern.iter().map(|_| false)).collect();
+    for (i, &p) in pattern.iter().enumerate() {
+        if p == '*' {
+            reachable[i + 1] = reachable[i];
+        }
+    }
+    for &c in &text {
+        let mut next = vec![false; pattern.len() + 1];
+        for (i, &p) in pattern.iter().enumerate() {
+            next[i + 1] = match p {
+                '*' => next[i] || reachable[i + 1],
+                '?' => reachable[i],
+                p => reachable[i] && p == c,
+            };
+        }
+        reachable = next;
+    }
+    reachable[pattern.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::matches;
+
+    #[test]
+    fn match_wildcard_patterns() {
+        assert!(matches("sales.*", "sales.orders"));
+        assert!(matches("*.orders", "sales.orders"));
+        assert!(matches("sales.order?", "sales.orders"));
+        assert!(matches("*", ""));
+        assert!(!matches("sales.*", "hr.employees"));
+        assert!(!matches("sales.order?", "sales.order"));
+        assert!(!matches("*_tmp", "sales.orders_tmp_old"));
+    }
+}
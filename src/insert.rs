@@ -7,7 +7,7 @@ use std::{
 };
 
 use anyhow::{bail, Error};
-use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, Timelike};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Timelike};
 use log::info;
 use num_traits::{FromPrimitive, PrimInt, Signed, ToPrimitive};
 use odbc_api::{
@@ -290,13 +290,15 @@ trait InserterBuilderStart: DataType + Sized {
         } else {
             Box::new(
                 |num_rows: usize,
-                 _: &mut ParquetBuffer,
+                 pb: &mut ParquetBuffer,
                  column_reader: ColumnReader,
                  column_writer: AnyColumnSliceMut| {
                     let mut cr = Self::get_column_reader(column_reader).expect(BUG);
                     let values = Self::unwrap_writer_required(column_writer);
-                    // Do not utilize parquet buffer. just pass the values through.
-                    cr.read_batch(num_rows, None, None, values)?;
+                    let it = pb.read_required(&mut cr, num_rows)?;
+                    for (target, value) in values.iter_mut().zip(it) {
+                        *target = *value;
+                    }
                     Ok(())
                 },
             )
@@ -470,10 +472,12 @@ fn parquet_type_to_odbc_buffer_desc(
                 BufferKind::Timestamp,
                 Int64Type::map_to::<Timestamp>().with(
                     |&microseconds_since_epoch| {
-                        let dt = NaiveDateTime::from_timestamp(
+                        let dt = DateTime::from_timestamp(
                             microseconds_since_epoch / 1_000_000,
                             ((microseconds_since_epoch % 1_000_000) * 1_000) as u32,
-                        );
+                        )
+                        .unwrap()
+                        .naive_utc();
                         Timestamp {
                             year: dt.year().try_into().unwrap(),
                             month: dt.month() as u16,
@@ -491,10 +495,12 @@ fn parquet_type_to_odbc_buffer_desc(
                 BufferKind::Timestamp,
                 Int64Type::map_to::<Timestamp>().with(
                     |&milliseconds_since_epoch| {
-                        let dt = NaiveDateTime::from_timestamp(
+                        let dt = DateTime::from_timestamp(
                             milliseconds_since_epoch / 1000,
                             ((milliseconds_since_epoch % 1000) * 1_000_000) as u32,
-                        );
+                        )
+                        .unwrap()
+                        .naive_utc();
                         Timestamp {
                             year: dt.year().try_into().unwrap(),
                             month: dt.month() as u16,
@@ -699,7 +705,7 @@ fn i128_from_be_slice(bytes: &[u8]) -> i128 {
 }
 
 fn days_since_epoch_to_odbc_date(days_since_epoch: i32) -> odbc_api::sys::Date {
-    let unix_epoch = NaiveDate::from_ymd(1970, 1, 1);
+    let unix_epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
     let naive_date = unix_epoch.add(Duration::days(days_since_epoch as i64));
     odbc_api::sys::Date {
         year: naive_date.year().try_into().unwrap(),
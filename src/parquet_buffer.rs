@@ -1,7 +1,8 @@
-use anyhow::Error;
+use anyhow::{bail, Error};
+use bytes::Bytes;
 use parquet::{
     column::{reader::ColumnReaderImpl, writer::ColumnWriterImpl},
-    data_type::{ByteArray, DataType, FixedLenByteArray, FixedLenByteArrayType},
+    data_type::{ByteArray, DataType, FixedLenByteArray, FixedLenByteArrayType, Int96},
 };
 use std::mem::size_of;
 
@@ -10,12 +11,34 @@ use std::mem::size_of;
 pub struct ParquetBuffer {
     pub values_i32: Vec<i32>,
     pub values_i64: Vec<i64>,
+    pub values_i96: Vec<Int96>,
     pub values_f32: Vec<f32>,
     pub values_f64: Vec<f64>,
     pub values_bytes_array: Vec<ByteArray>,
     pub values_fixed_bytes_array: Vec<FixedLenByteArray>,
     pub values_bool: Vec<bool>,
     pub def_levels: Vec<i16>,
+    /// Ascending indices of the rows in the current batch, which are written into the column
+    /// writers. `None` if all rows are written. Used to route the rows of a batch to different
+    /// files, e.g. if writing a partitioned dataset.
+    selected_rows: Option<Vec<usize>>,
+    /// Rows of the current batch which are written as NULL, regardless of their value. Values of
+    /// these rows are not converted, so they can not cause errors either.
+    null_rows: Option<Vec<bool>>,
+    /// Value written instead of NULL, if any.
+    null_default: Option<NullDefault>,
+}
+
+/// Physical value written instead of NULL, see `--null-default`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NullDefault {
+    Boolean(bool),
+    Int32(i32),
+    Int64(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(ByteArray),
+    FixedLenByteArray(FixedLenByteArray),
 }
 
 impl ParquetBuffer {
@@ -23,6 +46,7 @@ impl ParquetBuffer {
     /// estimate good batch sizes.
     pub const MEMORY_USAGE_BYTES_PER_ROW: usize = size_of::<i32>()
         + size_of::<i64>()
+        + size_of::<Int96>()
         + size_of::<f32>()
         + size_of::<f64>()
         + size_of::<ByteArray>()
@@ -34,19 +58,58 @@ impl ParquetBuffer {
         ParquetBuffer {
             values_i32: Vec::with_capacity(batch_size),
             values_i64: Vec::with_capacity(batch_size),
+            values_i96: Vec::with_capacity(batch_size),
             values_f32: Vec::with_capacity(batch_size),
             values_f64: Vec::with_capacity(batch_size),
             values_bytes_array: Vec::with_capacity(batch_size),
             values_fixed_bytes_array: Vec::with_capacity(batch_size),
             values_bool: Vec::with_capacity(batch_size),
             def_levels: Vec::with_capacity(batch_size),
+            selected_rows: None,
+            null_rows: None,
+            null_default: None,
         }
     }
 
+    /// Only write the rows with the specified (ascending) indices into the column writers. Pass
+    /// `None` to write all rows of the batch.
+    pub fn select_rows(&mut self, rows: Option<Vec<usize>>) {
+        self.selected_rows = rows;
+    }
+
+    /// Indices of the rows written into the column writers, or `None` if all rows are written.
+    pub fn selected_rows(&self) -> Option<&[usize]> {
+        self.selected_rows.as_deref()
+    }
+
+    /// Write the rows marked `true` as NULL. Pass `None` to write all values as they are.
+    pub fn set_null_rows(&mut self, null_rows: Option<Vec<bool>>) {
+        self.null_rows = null_rows;
+    }
+
+    /// Rows written as NULL, regardless of their value. `None` if all values are written as they
+    /// are.
+    pub fn null_rows(&self) -> Option<&[bool]> {
+        self.null_rows.as_deref()
+    }
+
+    /// Write `null_default` instead of NULL. Pass `None` to write NULLs as they are.
+    pub fn set_null_default(&mut self, null_default: Option<NullDefault>) {
+        self.null_default = null_default;
+    }
+
+    /// `true` if the values of all rows of the batch are written as they are. Neither are rows
+    /// skipped, nor are values replaced with NULL or a default. Strategies may then write the
+    /// values directly, without going through this buffer.
+    pub fn writes_values_as_is(&self) -> bool {
+        self.selected_rows.is_none() && self.null_rows.is_none() && self.null_default.is_none()
+    }
+
     pub fn set_num_rows_fetched(&mut self, num_rows: usize) {
         self.def_levels.resize(num_rows, 0);
         self.values_i32.resize(num_rows, 0);
         self.values_i64.resize(num_rows, 0);
+        self.values_i96.resize(num_rows, Int96::new());
         self.values_f32.resize(num_rows, 0.);
         self.values_f64.resize(num_rows, 0.);
         self.values_bytes_array.resize(num_rows, ByteArray::new());
@@ -70,29 +133,48 @@ impl ParquetBuffer {
         })
     }
 
+    /// Writes twos complement representations, which already have the length of the fixed sized
+    /// byte array.
+    pub fn write_twos_complement(
+        &mut self,
+        cw: &mut ColumnWriterImpl<FixedLenByteArrayType>,
+        source: impl Iterator<Item = Option<Vec<u8>>>,
+    ) -> Result<(), Error> {
+        self.write_optional_any_falliable(cw, source.map(Ok), |bytes| {
+            // Vec<u8> -> ByteArray -> FixedLenByteArray
+            let out: ByteArray = bytes.into();
+            out.into()
+        })
+    }
+
     fn write_optional_any_falliable<T, S>(
         &mut self,
         cw: &mut ColumnWriterImpl<T>,
         source: impl Iterator<Item = Result<Option<S>, Error>>,
-        mut into_physical: impl FnMut(S) -> T::T,
+        into_physical: impl FnMut(S) -> T::T,
     ) -> Result<(), Error>
     where
         T: DataType,
         T::T: BufferedDataType,
     {
+        // Take the selection out of self, so we can borrow the buffers mutably.
+        let selected_rows = self.selected_rows.take();
+        let null_rows = self.null_rows.take();
+        let null_default = self.null_default.as_ref().and_then(T::T::from_null_default);
         let (values, def_levels) = T::T::mut_buf(self);
-        let mut values_index = 0;
-        for (item, definition_level) in source.zip(&mut def_levels.iter_mut()) {
-            *definition_level = if let Some(value) = item? {
-                values[values_index] = into_physical(value);
-                values_index += 1;
-                1
-            } else {
-                0
-            }
-        }
-        cw.write_batch(values, Some(def_levels), None)?;
-        Ok(())
+        let result = write_selected_rows(
+            cw,
+            values,
+            def_levels,
+            selected_rows.as_deref(),
+            null_rows.as_deref(),
+            null_default,
+            source,
+            into_physical,
+        );
+        self.selected_rows = selected_rows;
+        self.null_rows = null_rows;
+        result
     }
 
     /// Write to a parquet buffer using an iterator over optional source items. A default
@@ -139,10 +221,13 @@ impl ParquetBuffer {
         T::T: BufferedDataType,
     {
         let (values, def_levels) = T::T::mut_buf(self);
-        let (_num_val, _num_lvl) = cr.read_batch(batch_size, Some(def_levels), None, values)?;
+        values.clear();
+        def_levels.clear();
+        let (_num_rec, _num_val, _num_lvl) =
+            cr.read_records(batch_size, Some(def_levels), None, values)?;
         // Strip mutability form the element of values, so we can use it in scan, there we only want
         // to mutate which part of values we see, not the elements of values themselfes.
-        let values: &_ = values;
+        let values: &[T::T] = values;
         let it = def_levels.iter().scan(values, |values, def| match def {
             0 => Some(None),
             1 => {
@@ -167,88 +252,206 @@ impl ParquetBuffer {
         T::T: BufferedDataType,
     {
         let (values, _def_levels) = T::T::mut_buf(self);
-        let (_num_val, _num_lvl) = cr.read_batch(batch_size, None, None, values)?;
+        values.clear();
+        let (_num_rec, _num_val, _num_lvl) = cr.read_records(batch_size, None, None, values)?;
         let it = values.iter();
 
         Ok(it)
     }
 }
 
+#[allow(clippy::too_many_arguments)]
+fn write_selected_rows<T, S>(
+    cw: &mut ColumnWriterImpl<T>,
+    values: &mut [T::T],
+    def_levels: &mut [i16],
+    selected_rows: Option<&[usize]>,
+    null_rows: Option<&[bool]>,
+    null_default: Option<T::T>,
+    source: impl Iterator<Item = Result<Option<S>, Error>>,
+    mut into_physical: impl FnMut(S) -> T::T,
+) -> Result<(), Error>
+where
+    T: DataType,
+{
+    let mut values_index = 0;
+    let mut num_levels = 0;
+    let mut selected_rows = selected_rows.map(|rows| rows.iter().peekable());
+    for (row_index, item) in source.enumerate() {
+        if let Some(selected_rows) = &mut selected_rows {
+            if selected_rows.next_if_eq(&&row_index).is_none() {
+                continue;
+            }
+        }
+        let is_null = null_rows.is_some_and(|null_rows| null_rows[row_index]);
+        // Ignore errors converting values which are written as NULL anyway.
+        let item = if is_null { None } else { item? };
+        let value = match item {
+            Some(value) => Some(into_physical(value)),
+            None => null_default.clone(),
+        };
+        def_levels[num_levels] = if let Some(value) = value {
+            values[values_index] = value;
+            values_index += 1;
+            1
+        } else if cw.get_descriptor().max_def_level() == 0 {
+            bail!("Found NULL in a column written as REQUIRED.")
+        } else {
+            0
+        };
+        num_levels += 1;
+    }
+    // Required columns ignore the definition levels, so we must not pass more values than rows.
+    cw.write_batch(
+        &values[..values_index],
+        Some(&def_levels[..num_levels]),
+        None,
+    )?;
+    Ok(())
+}
+
+/// Copies `values` into a single allocation and returns byte arrays referencing slices of it. This
+/// is a lot faster than allocating each value separately, which dominates the time spent on writing
+/// text columns otherwise. We can not reference the ODBC buffer directly, because the column
+/// writers keep some of the values (e.g. in the dictionary or the statistics) beyond the batch,
+/// while the ODBC buffer is overwritten by the next one.
+pub fn shared_byte_arrays(values: &[&[u8]]) -> Vec<ByteArray> {
+    let mut data = Vec::with_capacity(values.iter().map(|value| value.len()).sum());
+    for value in values {
+        data.extend_from_slice(value);
+    }
+    let data = Bytes::from(data);
+    let mut start = 0;
+    values
+        .iter()
+        .map(|value| {
+            let end = start + value.len();
+            let byte_array = ByteArray::from(data.slice(start..end));
+            start = end;
+            byte_array
+        })
+        .collect()
+}
+
 pub trait BufferedDataType: Sized {
-    fn mut_buf(buffer: &mut ParquetBuffer) -> (&mut [Self], &mut [i16]);
+    fn mut_buf(buffer: &mut ParquetBuffer) -> (&mut Vec<Self>, &mut Vec<i16>);
+
+    /// The default value, if it has the physical type of the buffer.
+    fn from_null_default(null_default: &NullDefault) -> Option<Self>;
 }
 
 impl BufferedDataType for i32 {
-    fn mut_buf(buffer: &mut ParquetBuffer) -> (&mut [Self], &mut [i16]) {
-        (
-            buffer.values_i32.as_mut_slice(),
-            buffer.def_levels.as_mut_slice(),
-        )
+    fn mut_buf(buffer: &mut ParquetBuffer) -> (&mut Vec<Self>, &mut Vec<i16>) {
+        (&mut buffer.values_i32, &mut buffer.def_levels)
+    }
+    fn from_null_default(null_default: &NullDefault) -> Option<Self> {
+        match null_default {
+            NullDefault::Int32(value) => Some(*value),
+            _ => None,
+        }
     }
 }
 
 impl BufferedDataType for i64 {
-    fn mut_buf(buffer: &mut ParquetBuffer) -> (&mut [Self], &mut [i16]) {
-        (
-            buffer.values_i64.as_mut_slice(),
-            buffer.def_levels.as_mut_slice(),
-        )
+    fn mut_buf(buffer: &mut ParquetBuffer) -> (&mut Vec<Self>, &mut Vec<i16>) {
+        (&mut buffer.values_i64, &mut buffer.def_levels)
+    }
+    fn from_null_default(null_default: &NullDefault) -> Option<Self> {
+        match null_default {
+            NullDefault::Int64(value) => Some(*value),
+            _ => None,
+        }
+    }
+}
+
+impl BufferedDataType for Int96 {
+    fn mut_buf(buffer: &mut ParquetBuffer) -> (&mut Vec<Self>, &mut Vec<i16>) {
+        (&mut buffer.values_i96, &mut buffer.def_levels)
+    }
+    fn from_null_default(_null_default: &NullDefault) -> Option<Self> {
+        // Only timestamps are INT96, and there are no defaults for them.
+        None
     }
 }
 
 impl BufferedDataType for f32 {
-    fn mut_buf(buffer: &mut ParquetBuffer) -> (&mut [Self], &mut [i16]) {
-        (
-            buffer.values_f32.as_mut_slice(),
-            buffer.def_levels.as_mut_slice(),
-        )
+    fn mut_buf(buffer: &mut ParquetBuffer) -> (&mut Vec<Self>, &mut Vec<i16>) {
+        (&mut buffer.values_f32, &mut buffer.def_levels)
+    }
+    fn from_null_default(null_default: &NullDefault) -> Option<Self> {
+        match null_default {
+            NullDefault::Float(value) => Some(*value),
+            _ => None,
+        }
     }
 }
 
 impl BufferedDataType for f64 {
-    fn mut_buf(buffer: &mut ParquetBuffer) -> (&mut [Self], &mut [i16]) {
-        (
-            buffer.values_f64.as_mut_slice(),
-            buffer.def_levels.as_mut_slice(),
-        )
+    fn mut_buf(buffer: &mut ParquetBuffer) -> (&mut Vec<Self>, &mut Vec<i16>) {
+        (&mut buffer.values_f64, &mut buffer.def_levels)
+    }
+    fn from_null_default(null_default: &NullDefault) -> Option<Self> {
+        match null_default {
+            NullDefault::Double(value) => Some(*value),
+            _ => None,
+        }
     }
 }
 
 impl BufferedDataType for bool {
-    fn mut_buf(buffer: &mut ParquetBuffer) -> (&mut [Self], &mut [i16]) {
-        (
-            buffer.values_bool.as_mut_slice(),
-            buffer.def_levels.as_mut_slice(),
-        )
+    fn mut_buf(buffer: &mut ParquetBuffer) -> (&mut Vec<Self>, &mut Vec<i16>) {
+        (&mut buffer.values_bool, &mut buffer.def_levels)
+    }
+    fn from_null_default(null_default: &NullDefault) -> Option<Self> {
+        match null_default {
+            NullDefault::Boolean(value) => Some(*value),
+            _ => None,
+        }
     }
 }
 
 impl BufferedDataType for ByteArray {
-    fn mut_buf(buffer: &mut ParquetBuffer) -> (&mut [Self], &mut [i16]) {
-        (
-            buffer.values_bytes_array.as_mut_slice(),
-            buffer.def_levels.as_mut_slice(),
-        )
+    fn mut_buf(buffer: &mut ParquetBuffer) -> (&mut Vec<Self>, &mut Vec<i16>) {
+        (&mut buffer.values_bytes_array, &mut buffer.def_levels)
+    }
+    fn from_null_default(null_default: &NullDefault) -> Option<Self> {
+        match null_default {
+            NullDefault::ByteArray(value) => Some(value.clone()),
+            _ => None,
+        }
     }
 }
 
 impl BufferedDataType for FixedLenByteArray {
-    fn mut_buf(buffer: &mut ParquetBuffer) -> (&mut [Self], &mut [i16]) {
-        (
-            buffer.values_fixed_bytes_array.as_mut_slice(),
-            buffer.def_levels.as_mut_slice(),
-        )
+    fn mut_buf(buffer: &mut ParquetBuffer) -> (&mut Vec<Self>, &mut Vec<i16>) {
+        (&mut buffer.values_fixed_bytes_array, &mut buffer.def_levels)
+    }
+    fn from_null_default(null_default: &NullDefault) -> Option<Self> {
+        match null_default {
+            NullDefault::FixedLenByteArray(value) => Some(value.clone()),
+            _ => None,
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
 
-    use super::ParquetBuffer;
+    use parquet::data_type::ByteArray;
+
+    use super::{shared_byte_arrays, ParquetBuffer};
 
     #[test]
     #[cfg(target_pointer_width = "64")] // Memory usage is platform dependent
     fn memory_usage() {
-        assert_eq!(59, ParquetBuffer::MEMORY_USAGE_BYTES_PER_ROW);
+        assert_eq!(71, ParquetBuffer::MEMORY_USAGE_BYTES_PER_ROW);
+    }
+
+    #[test]
+    fn byte_arrays_sharing_one_allocation() {
+        let values: [&[u8]; 4] = [b"Hello", b"", b",", b"World"];
+        let expected: Vec<ByteArray> = values.iter().map(|v| ByteArray::from(v.to_vec())).collect();
+        assert_eq!(expected, shared_byte_arrays(&values));
+        assert!(shared_byte_arrays(&[]).is_empty());
     }
 }
@@ -1,72 +1,397 @@
+mod added_column;
+mod append;
+mod arrow_schema;
+mod available_memory;
+mod batch_growth;
 mod batch_size_limit;
 mod binary;
 mod boolean;
+mod change_tracking;
+mod checkpoint;
+mod column_cast;
+mod column_filter;
+mod column_mask;
+mod column_name;
+mod column_order;
+mod column_size;
+mod concurrent_fetch;
+mod converted_type;
+mod csv_writer;
 mod date;
+mod decfloat;
 mod decimal;
+mod delta;
+#[cfg(feature = "duckdb")]
+mod duckdb_writer;
+mod encryption;
+mod field_id;
+mod float16;
+mod geo_parquet;
+mod guid;
 mod identical;
+mod interrupt;
+mod interval;
+mod json;
+mod manifest;
+mod not_null;
+mod null_default;
+mod null_sentinel;
+mod output_parameter;
+mod oversized;
+mod parallel;
+mod parameter_file;
 mod parquet_writer;
+mod partition;
+mod polling;
+#[cfg(feature = "flight")]
+pub mod record_batch;
+mod result_set;
+mod retry;
+mod sample;
+mod side_file;
+mod sort_order;
+mod source_table;
+mod spill;
+mod sql_table;
+mod sqlite_writer;
 mod strategy;
+mod template;
 mod text;
+mod time;
 mod timestamp;
 mod timestamp_tz;
+mod transaction;
+mod truncation;
+mod unsigned;
+mod value_text;
+mod wkb;
+mod xlsx_writer;
+mod xml;
 
 use self::{
+    added_column::{added_columns, source_name, Constant, ADDED_COLUMN_NUMBER},
+    append::{last_existing_file, verify_schema},
+    arrow_schema::ArrowSchemaOptions,
+    batch_growth::{GrowingFetch, INITIAL_BATCH_SIZE_ROWS},
     batch_size_limit::{BatchSizeLimit, FileSizeLimit},
+    change_tracking::ChangeTracking,
+    checkpoint::{Checkpoint, Checkpointing},
+    column_cast::read_cast_file,
+    column_filter::is_column_selected,
+    column_mask::MaskedColumn,
+    column_name::ColumnNames,
+    column_order::{read_column_order_file, reorder_columns},
+    concurrent_fetch::{ConcurrentFetch, Fetch},
+    converted_type::without_logical_type,
+    csv_writer::{cursor_to_csv, CsvFormatOptions},
+    delta::DeltaTable,
+    encryption::file_encryption_properties,
+    field_id::{field_ids, with_field_id},
+    geo_parquet::GeoParquetOptions,
+    interrupt::{handle_interrupts, is_interrupted, sleep_unless_interrupted, CancelOnInterrupt},
+    json::with_json_logical_type,
+    manifest::Completion,
+    not_null::NotNull,
+    null_default::WithNullDefault,
+    null_sentinel::NullSentinels,
+    output_parameter::ProcedureParameters,
+    oversized::{limit_value_size, oversized_policy, OversizedCheck},
+    parallel::partition_queries,
+    parameter_file::{read_parameter_file, ParameterSet},
     parquet_writer::ParquetFormatOptions,
     parquet_writer::ParquetWriter,
+    partition::{remove_stale_files, Partitioning},
+    result_set::next_result_set,
+    retry::with_retries,
+    sample::sample_column_lengths,
+    side_file::{side_file_columns, SideFiles},
+    sort_order::SortOrder,
+    source_table::select_from_table,
+    spill::{
+        columns_to_spill, is_spillable, is_unbounded, placeholder, SpilledColumn, SpillingFetch,
+    },
+    sqlite_writer::cursor_to_sqlite,
     strategy::{strategy_from_column_description, ColumnFetchStrategy, MappingOptions},
+    template::substitute_variables,
+    text::Utf8,
+    timestamp_tz::{datetimeoffset_precision, OffsetMinutes},
+    transaction::in_transaction,
+    truncation::{with_refetches, TruncationCheck},
+    wkb::query_with_geometries_as_wkb,
+    xlsx_writer::cursor_to_xlsx,
+    xml::{is_xml, xml_columns_metadata},
 };
 
+pub use self::xlsx_writer::XLSX_MAX_DATA_ROWS;
+
 use std::{
+    collections::{btree_map::Entry, BTreeMap},
+    fs,
     io::{stdin, Read},
+    iter::once,
+    path::{Path, PathBuf},
     sync::Arc,
+    thread,
+    time::Instant,
 };
 
-use anyhow::{bail, Error};
-use io_arg::IoArg;
-use log::{debug, info};
-use odbc_api::{buffers::ColumnarAnyBuffer, ColumnDescription, Cursor, Environment, IntoParameter};
-use parquet::schema::types::{Type, TypePtr};
+use anyhow::{bail, Context, Error};
+use chrono::Utc;
+use log::{debug, error, info, warn};
+use odbc_api::{
+    buffers::{AnyColumnView, ColumnarAnyBuffer},
+    handles::{Statement, StatementImpl},
+    ColumnDescription, Connection, Cursor, CursorImpl, Environment, IntoParameter, Nullability,
+    ResultSetMetadata,
+};
+use parquet::{
+    basic::{Repetition, Type as PhysicalType},
+    column::writer::ColumnWriter,
+    schema::types::{Type, TypePtr},
+};
+use sha2::{Digest, Sha256};
 
-use crate::{open_connection, parquet_buffer::ParquetBuffer, QueryOpt};
+use crate::{
+    destination::Destination,
+    enum_args::{
+        FieldIdsArgument, IfExists, IntervalsAs, Mask, OnInterrupt, OnTruncation, OutputFormat,
+        SideFileNames, SortColumn, TimeUnitArgument, TimestampsAs,
+    },
+    open_connection,
+    parquet_buffer::ParquetBuffer,
+    QueryOpt,
+};
 
-/// Execute a query and writes the result to parquet.
+/// Execute a query and writes the result to parquet (or one of the other output formats).
 pub fn query(environment: &Environment, opt: QueryOpt) -> Result<(), Error> {
     let QueryOpt {
         connect_opts,
         output,
-        parameters,
+        mut parameters,
         query,
+        query_file,
+        source_table,
+        change_tracking,
+        state_file,
+        var,
+        parameter_file,
+        output_parameter,
+        inout_parameter,
+        output_parameters_file,
+        catalog_function,
         batch_size_row,
         batch_size_memory,
+        fetch_buffers,
+        adaptive_batch_size,
+        partition_on,
+        concurrency,
         row_groups_per_file,
         file_size_threshold,
+        row_group_size_bytes,
+        page_checksums,
+        encoding_threads,
+        field_ids,
+        arrow_schema,
+        arrow_dictionary,
+        arrow_time_zone,
+        geometry_column,
+        geometry_crs,
+        cast_geometry_to_wkb,
+        wkb_max_length,
+        json_column,
         encoding,
         prefer_varbinary,
         column_compression_default,
+        compression_level,
+        column_compression,
+        writer_version,
         parquet_column_encoding,
+        dictionary,
+        column_dictionary,
+        statistics,
+        column_statistics,
+        statistics_truncate_length,
+        column_index,
+        offset_index,
+        data_page_size,
+        dictionary_page_size,
+        bloom_filter,
+        sorted_by,
+        verify_sort_order,
+        encryption_footer_key,
+        encryption_column_key,
+        encryption_key_command,
         driver_does_not_support_64bit_integers,
         prefer_int_over_decimal,
+        plain_narrow_integers,
+        real_as_float16,
+        decimal_as_double,
+        decimal_as_text,
+        default_number_as,
+        decfloat_as,
+        unknown_types_as_text,
+        trim_char,
+        trim_char_column,
+        cast_file,
+        column_order,
+        column_order_file,
+        rename,
+        columns,
+        exclude_columns,
+        sanitize_column_names,
+        null_value,
+        null_default,
+        mask,
+        add_column,
+        assert_not_null,
+        force_nullable,
+        timestamps_as,
+        timestamp_tz,
+        session_time_zone,
+        time_unit,
+        time_as_text,
+        timestamp_precision,
+        converted_types_only,
+        guid_as_text,
+        xml_max_length,
+        xml_metadata,
+        max_column_size,
+        datetimeoffset_offset_minutes,
+        intervals_as,
         suffix_length,
+        partition_by,
+        overwrite_partitions,
+        delta_table,
+        success_marker,
+        manifest,
+        on_interrupt,
+        on_truncation,
+        sample_lengths,
+        max_value_size,
+        oversized,
+        side_file_dir,
+        side_file_names,
+        checkpoint,
+        resume,
+        resume_query,
+        every,
+        isolation_level,
+        async_execution,
+        retries,
+        retry_backoff,
+        if_exists,
+        append,
+        tee,
+        format,
+        csv_delimiter,
+        csv_quote_style,
+        csv_no_header,
+        table,
+        xlsx_max_rows,
     } = opt;
 
+    let if_exists = if append { IfExists::Append } else { if_exists };
     let batch_size = BatchSizeLimit::new(batch_size_row, batch_size_memory);
     let file_size = FileSizeLimit::new(row_groups_per_file, file_size_threshold);
-    let query = query_statement_text(query)?;
-
-    // Convert the input strings into parameters suitable for use with ODBC.
-    let params: Vec<_> = parameters
-        .iter()
-        .map(|param| param.as_str().into_parameter())
-        .collect();
+    let timestamps_utc_offset = timestamp_tz.utc_offset(session_time_zone)?;
 
-    let odbc_conn = open_connection(environment, &connect_opts)?;
+    let mut odbc_conn = open_connection(environment, &connect_opts)?;
     let db_name = odbc_conn.database_management_system_name()?;
     info!("Database Managment System Name: {db_name}");
+    let source = source_name(
+        connect_opts.dsn.as_deref(),
+        connect_opts.connection_string.as_deref(),
+        &db_name,
+    );
+
+    let change_tracking = match (change_tracking, state_file) {
+        (Some(table), Some(state_file)) => Some(ChangeTracking::new(
+            &odbc_conn, &db_name, &table, state_file,
+        )?),
+        _ => None,
+    };
+
+    let query = match (query_file, query, source_table, &change_tracking) {
+        (None, None, None, Some(change_tracking)) => change_tracking.query.clone(),
+        // The name of the table must be quoted the way the data source expects it.
+        (None, None, Some(table), _) => {
+            let query = select_from_table(&table, &db_name)?;
+            info!("Exporting table with query: {query}");
+            query
+        }
+        (Some(path), query, _, _) => {
+            // Without a query at the command line, the first positional argument after the output
+            // is taken for the query, rather than a parameter.
+            parameters.splice(0..0, query);
+            fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read query file '{}'.", path.display()))?
+        }
+        (None, Some(query), _, _) => query_statement_text(query)?,
+        (None, None, None, None) => unreachable!(
+            "Command line parser requires either query, query file, source table or change \
+            tracking."
+        ),
+    };
+    let query = if var.is_empty() {
+        query
+    } else {
+        substitute_variables(&query, &var)?
+    };
+    let output = Destination::tee(once(output).chain(tee).collect());
+    if output_parameters_file.is_some() && output_parameter.is_empty() && inout_parameter.is_empty()
+    {
+        bail!("output-parameters-file requires at least one output or input/output parameter.")
+    }
+    let parameter_sets = match &parameter_file {
+        Some(path) => {
+            if !parameters.is_empty() {
+                bail!("Positional parameters can not be combined with a parameter file.")
+            }
+            if output_parameters_file.is_some() {
+                bail!("Output parameters can not be combined with a parameter file.")
+            }
+            Some(read_parameter_file(path)?)
+        }
+        None => None,
+    };
 
     let parquet_format_options = ParquetFormatOptions {
-        column_compression_default: column_compression_default.as_compression(),
+        column_compression_default: column_compression_default
+            .or_level(compression_level)
+            .as_compression()?,
+        column_compressions: column_compression,
+        writer_version: writer_version.as_writer_version(),
         column_encodings: parquet_column_encoding,
+        dictionary: dictionary.is_on(),
+        column_dictionaries: column_dictionary,
+        statistics: statistics.as_enabled_statistics(),
+        column_statistics,
+        statistics_truncate_length: (statistics_truncate_length != 0)
+            .then_some(statistics_truncate_length),
+        column_index: column_index.is_on(),
+        offset_index: offset_index.is_on(),
+        data_page_size,
+        dictionary_page_size,
+        bloom_filters: bloom_filter,
+        sorting_columns: None,
+        row_group_size: row_group_size_bytes,
+        page_checksums,
+        encoding_threads: encoding_threads.into(),
+        key_value_metadata: Vec::new(),
+        encryption: file_encryption_properties(
+            encryption_footer_key,
+            encryption_column_key,
+            encryption_key_command.as_deref(),
+        )?,
+    };
+
+    let column_casts = match &cast_file {
+        Some(path) => read_cast_file(path)?,
+        None => Vec::new(),
+    };
+    let column_order = match &column_order_file {
+        Some(path) => read_column_order_file(path)?,
+        None => column_order,
     };
 
     let mapping_options = MappingOptions {
@@ -75,24 +400,485 @@ pub fn query(environment: &Environment, opt: QueryOpt) -> Result<(), Error> {
         prefer_varbinary,
         driver_does_support_i64: !driver_does_not_support_64bit_integers,
         prefer_int_over_decimal: prefer_int_over_decimal,
+        plain_narrow_integers,
+        real_as_float16,
+        decimal_as_double,
+        decimal_as_text,
+        default_number_as,
+        decfloat_as,
+        timestamps_as_int96: timestamps_as == TimestampsAs::Int96,
+        timestamps_utc_offset,
+        timestamp_units: &timestamp_precision,
+        time_unit: time_unit.map(TimeUnitArgument::as_time_unit),
+        time_as_text,
+        // Other output formats are not aware of UUIDs and write GUIDs as text.
+        guid_as_uuid: format == OutputFormat::Parquet && !guid_as_text,
+        xml_max_length,
+        // Other output formats write intervals as text.
+        intervals_as_iso8601: format != OutputFormat::Parquet
+            || intervals_as == IntervalsAs::Iso8601,
+        geometry_columns: &geometry_column,
+        wkb_max_length,
+        max_column_size: max_column_size.map(|size| size.as_u64().try_into().unwrap()),
+        column_lengths: &[],
+        unknown_types_as_text,
+        trim_char,
+        trim_char_columns: &trim_char_column,
+        column_casts: &column_casts,
+        column_order: &column_order,
+        renames: &rename,
+        columns: &columns,
+        exclude_columns: &exclude_columns,
+        sanitize_column_names,
+        null_values: &null_value,
+        null_defaults: &null_default,
+        masks: &mask,
+        max_value_size: max_value_size.map(|size| size.as_u64().try_into().unwrap()),
+        oversized: &oversized,
+        not_null_columns: &assert_not_null,
+        nullable_columns: &force_nullable,
     };
 
-    if let Some(cursor) = odbc_conn.execute(&query, params.as_slice())? {
-        cursor_to_parquet(
-            cursor,
-            output,
+    let csv_format_options = CsvFormatOptions {
+        delimiter: csv_delimiter,
+        quote_style: csv_quote_style.as_quote_style(),
+        header: !csv_no_header,
+    };
+
+    let query = if cast_geometry_to_wkb {
+        let mut prepared = odbc_conn.prepare(&query)?;
+        let column_names = (1..=prepared.num_result_cols()?)
+            .map(|column_number| prepared.col_name(column_number as u16))
+            .collect::<Result<Vec<_>, _>>()?;
+        // The query knows the geometry columns by their names in the result set.
+        let mut sanitized_names = ColumnNames::default();
+        let output_names: Vec<String> = column_names
+            .iter()
+            .map(|source| output_column_name(source, &mapping_options, &mut sanitized_names))
+            .collect();
+        let geometry_sources: Vec<String> = geometry_column
+            .iter()
+            .map(|name| {
+                output_names
+                    .iter()
+                    .position(|output_name| output_name == name)
+                    .map_or(name, |index| &column_names[index])
+                    .clone()
+            })
+            .collect();
+        let query =
+            query_with_geometries_as_wkb(&query, &column_names, &geometry_sources, &db_name)?;
+        info!("Converting geometries to WKB with query: {query}");
+        query
+    } else {
+        query
+    };
+
+    let write_parquet = |cursor: &mut CursorImpl<StatementImpl<'_>>,
+                         output: Destination,
+                         delta_table: Option<DeltaTable>,
+                         checkpoint: Option<(PathBuf, Checkpoint)>,
+                         skip_rows: u64,
+                         column_lengths: &[(String, usize)]| {
+        let export_options = ParquetExportOptions {
             batch_size,
+            fetch_buffers: fetch_buffers.into(),
+            adaptive_batch_size,
             file_size,
-            mapping_options,
-            parquet_format_options,
             suffix_length,
+            partition_by: partition_by.clone(),
+            overwrite_partitions,
+            delta_table,
+            sorted_by: &sorted_by,
+            verify_sort_order,
+            field_ids_argument: field_ids.as_ref(),
+            arrow_schema: arrow_schema.then(|| ArrowSchemaOptions {
+                dictionary_columns: arrow_dictionary.clone(),
+                time_zone: arrow_time_zone.clone(),
+            }),
+            converted_types_only,
+            geo_parquet: (!geometry_column.is_empty()).then(|| GeoParquetOptions {
+                columns: geometry_column.clone(),
+                crs: geometry_crs.clone(),
+            }),
+            json_columns: &json_column,
+            xml_metadata,
+            offset_minutes: datetimeoffset_offset_minutes,
+            success_marker,
+            manifest,
+            on_interrupt,
+            on_truncation,
+            append: if_exists == IfExists::Append,
+            added_columns: added_columns(&add_column, &source, Utc::now()),
+            side_file_dir: &side_file_dir,
+            side_file_names,
+        };
+        cursor_to_parquet(
+            cursor,
+            output,
+            MappingOptions {
+                column_lengths,
+                ..mapping_options
+            },
+            parquet_format_options.clone(),
+            export_options,
+            checkpoint,
+            skip_rows,
+        )
+    };
+
+    // Each partition of the result set is fetched with a connection of its own and written into a
+    // file of its own. See `--partition-on`.
+    let export_in_parallel = |queries: &[String],
+                              parameters: &[String],
+                              output: &Destination,
+                              column_lengths: &[(String, usize)]|
+     -> Result<(), Error> {
+        thread::scope(|scope| {
+            let mut threads = Vec::new();
+            for (index, partition_query) in queries.iter().enumerate() {
+                let output = output.with_suffix(index as u32 + 1, suffix_length)?;
+                let write_parquet = &write_parquet;
+                let connect_opts = &connect_opts;
+                threads.push(scope.spawn(move || -> Result<(), Error> {
+                    info!("Fetching partition {} with: {partition_query}", index + 1);
+                    let conn = open_connection(environment, connect_opts)?;
+                    let params: Vec<_> = parameters
+                        .iter()
+                        .map(|param| param.as_str().into_parameter())
+                        .collect();
+                    if let Some(mut cursor) = polling::execute(
+                        &conn,
+                        async_execution,
+                        partition_query,
+                        params.as_slice(),
+                    )? {
+                        write_parquet(&mut cursor, output, None, None, 0, column_lengths)?;
+                    }
+                    Ok(())
+                }));
+            }
+            // Wait for all partitions, even if one of them failed, and report the first error.
+            let mut result = Ok(());
+            for thread in threads {
+                let thread_result = thread.join().expect("Export thread must not panic.");
+                if result.is_ok() {
+                    result = thread_result;
+                }
+            }
+            result
+        })
+    };
+
+    // Executes the query and writes the result. Invoked once, or repeatedly with `--every`. Also
+    // invoked again, to retry a failed export, or to fetch truncated values with larger buffers.
+    let export = |odbc_conn: &Connection<'_>,
+                  retry: bool,
+                  column_lengths: &[(String, usize)]|
+     -> Result<(), Error> {
+        // A retry continues from the checkpoint recorded by the failed attempt, if any.
+        let resume = resume || retry;
+        // Placeholders like `{timestamp}` are expanded for each export.
+        let output = output.expand_placeholders(&file_name_placeholders(&query));
+        // Each execution of the query is written into its own output.
+        let executions = match &parameter_sets {
+            Some(parameter_sets) => {
+                executions_from_parameter_sets(&output, parameter_sets.clone())?
+            }
+            None => vec![(output, parameters.clone())],
+        };
+        for (output, parameters) in executions {
+            // Outputs may exist, because they have been written by the failed attempt.
+            if !retry
+                && matches!(if_exists, IfExists::Error | IfExists::Skip)
+                && output_exists(&output, file_size, suffix_length)?
+            {
+                if if_exists == IfExists::Error {
+                    bail!("Output '{output}' already exists.")
+                }
+                info!("Skipping execution, since output '{output}' already exists.");
+                continue;
+            }
+            let checkpoint = match &output {
+                Destination::File(path) if checkpoint => {
+                    let path = Checkpoint::path(path);
+                    let resumed = if resume {
+                        resume_from(&path, &query)?
+                    } else {
+                        None
+                    };
+                    let resumed = resumed.unwrap_or_else(|| Checkpoint::start(query.clone()));
+                    Some((path, resumed))
+                }
+                _ => None,
+            };
+            // With a resume query, rows written before are not fetched again.
+            let resume_with = match (&checkpoint, &resume_query) {
+                (Some((_, resumed)), Some(resume_query)) if resumed.files != 0 => {
+                    if resumed.key.is_none() {
+                        warn!("No key recorded in checkpoint. Skipping the rows written instead.");
+                    }
+                    resumed.key.as_ref().map(|key| (resume_query, key))
+                }
+                _ => None,
+            };
+            let skip_rows = match (&checkpoint, &resume_with) {
+                (Some((_, resumed)), None) => resumed.rows,
+                _ => 0,
+            };
+            let delta_table = if delta_table {
+                Some(DeltaTable::new(&output)?)
+            } else {
+                None
+            };
+            // Output parameters are bound to the statement in addition to the input strings.
+            let mut procedure_parameters = match &output_parameters_file {
+                Some(_) => Some(ProcedureParameters::new(
+                    parameters.clone(),
+                    &output_parameter,
+                    &inout_parameter,
+                )?),
+                None => None,
+            };
+            // Convert the input strings into parameters suitable for use with ODBC.
+            let params: Vec<_> = parameters
+                .iter()
+                .map(|param| param.as_str().into_parameter())
+                .collect();
+            if let Some(column) = &partition_on {
+                let queries = partition_queries(
+                    odbc_conn,
+                    &db_name,
+                    &query,
+                    &parameters,
+                    column,
+                    concurrency.into(),
+                )?;
+                export_in_parallel(&queries, &parameters, &output, column_lengths)?;
+                continue;
+            }
+            let cursor = match (&catalog_function, &mut procedure_parameters, &resume_with) {
+                (Some(function), _, _) => Some(function.execute(odbc_conn)?),
+                (None, Some(procedure_parameters), _) => {
+                    polling::execute(odbc_conn, async_execution, &query, procedure_parameters)?
+                }
+                (None, None, Some((resume_query, key))) => {
+                    let resume_query = substitute_variables(resume_query, &var)?;
+                    info!("Resuming after key '{key}' with query: {resume_query}");
+                    polling::execute(
+                        odbc_conn,
+                        async_execution,
+                        &resume_query,
+                        &key.as_str().into_parameter(),
+                    )?
+                }
+                (None, None, None) => {
+                    polling::execute(odbc_conn, async_execution, &query, params.as_slice())?
+                }
+            };
+
+            if let Some(cursor) = cursor {
+                match format {
+                    OutputFormat::Parquet => {
+                        let mut cursor = cursor;
+                        let is_delta_table = delta_table.is_some();
+                        write_parquet(
+                            &mut cursor,
+                            output.clone(),
+                            delta_table,
+                            checkpoint,
+                            skip_rows,
+                            column_lengths,
+                        )?;
+                        // Each further result set, e.g. of a stored procedure, is written into an
+                        // output of its own. Only the first one is recorded by the checkpoint.
+                        let mut result_set = 1;
+                        while let Some(next) = next_result_set(cursor)? {
+                            cursor = next;
+                            result_set += 1;
+                            if output.is_std_out() {
+                                warn!(
+                                    "Skipping further result sets, since the output is standard \
+                                    out."
+                                );
+                                break;
+                            }
+                            let output = output.with_suffix(result_set, suffix_length)?;
+                            info!("Writing result set {result_set} into '{output}'.");
+                            let delta_table = if is_delta_table {
+                                Some(DeltaTable::new(&output)?)
+                            } else {
+                                None
+                            };
+                            write_parquet(
+                                &mut cursor,
+                                output,
+                                delta_table,
+                                None,
+                                0,
+                                column_lengths,
+                            )?;
+                        }
+                    }
+                    OutputFormat::Csv => cursor_to_csv(
+                        cursor,
+                        output,
+                        batch_size,
+                        mapping_options,
+                        csv_format_options.clone(),
+                    )?,
+                    #[cfg(feature = "duckdb")]
+                    OutputFormat::Duckdb => duckdb_writer::cursor_to_duckdb(
+                        cursor,
+                        output,
+                        batch_size,
+                        mapping_options,
+                        table
+                            .as_deref()
+                            .expect("Table must be specified for DuckDB output."),
+                    )?,
+                    #[cfg(not(feature = "duckdb"))]
+                    OutputFormat::Duckdb => {
+                        unreachable!(
+                            "DuckDB output is rejected during validation without the feature."
+                        )
+                    }
+                    OutputFormat::Sqlite => cursor_to_sqlite(
+                        cursor,
+                        output,
+                        batch_size,
+                        mapping_options,
+                        table
+                            .as_deref()
+                            .expect("Table must be specified for SQLite output."),
+                    )?,
+                    OutputFormat::Xlsx => {
+                        cursor_to_xlsx(cursor, output, batch_size, mapping_options, xlsx_max_rows)?
+                    }
+                }
+            } else {
+                eprintln!(
+                    "Query came back empty (not even a schema has been returned). No file has been created"
+                );
+            }
+            // The cursor has been consumed and closed by now, so the driver has written the values of
+            // the output parameters.
+            if let (Some(procedure_parameters), Some(path)) =
+                (&procedure_parameters, &output_parameters_file)
+            {
+                procedure_parameters.write(path)?;
+            }
+        }
+        Ok(())
+    };
+
+    // Executes the query once more, to size the buffers from a sample of its rows. See
+    // `--sample-lengths`.
+    let sample = |odbc_conn: &Connection<'_>, rows: usize| -> Result<_, Error> {
+        let params: Vec<_> = parameters
+            .iter()
+            .map(|param| param.as_str().into_parameter())
+            .collect();
+        info!("Sampling up to {rows} rows.");
+        match polling::execute(odbc_conn, async_execution, &query, params.as_slice())? {
+            Some(cursor) => sample_column_lengths(cursor, mapping_options, rows),
+            None => Ok(Vec::new()),
+        }
+    };
+
+    let run = |odbc_conn: &Connection<'_>, retry: bool| {
+        in_transaction(odbc_conn, &db_name, isolation_level, || {
+            let column_lengths = match sample_lengths {
+                Some(rows) => sample(odbc_conn, rows)?,
+                None => Vec::new(),
+            };
+            with_refetches(column_lengths, |column_lengths, refetch| {
+                export(odbc_conn, retry || refetch, column_lengths)
+            })
+        })
+    };
+
+    let Some(every) = every else {
+        with_retries(
+            environment,
+            &connect_opts,
+            &mut odbc_conn,
+            retries,
+            retry_backoff,
+            run,
         )?;
+        if let Some(change_tracking) = &change_tracking {
+            change_tracking.commit()?;
+        }
+        return Ok(());
+    };
+    // Ctrl+C stops waiting for the next execution.
+    handle_interrupts();
+    loop {
+        let started = Instant::now();
+        let result = with_retries(
+            environment,
+            &connect_opts,
+            &mut odbc_conn,
+            retries,
+            retry_backoff,
+            run,
+        );
+        if let Err(error) = result {
+            if is_interrupted() {
+                return Err(error);
+            }
+            error!("Execution failed: {error:?}");
+            // The connection may have been lost, so a new one is used for the next execution.
+            match open_connection(environment, &connect_opts) {
+                Ok(new_conn) => odbc_conn = new_conn,
+                Err(error) => error!("Failed to reconnect: {error:?}"),
+            }
+        }
+        let next = started
+            .checked_add(every)
+            .context("Interval of --every is too large.")?;
+        let until_next = next.saturating_duration_since(Instant::now());
+        info!("Next execution in {}s.", until_next.as_secs());
+        if !sleep_unless_interrupted(until_next) {
+            return Ok(());
+        }
+    }
+}
+
+/// `true` if the output already exists. A split output exists if its first file does.
+fn output_exists(
+    output: &Destination,
+    file_size: FileSizeLimit,
+    suffix_length: usize,
+) -> Result<bool, Error> {
+    if file_size.output_is_splitted() || output.has_sequence_placeholder() {
+        Ok(output.with_suffix(1, suffix_length)?.exists())
     } else {
-        eprintln!(
-            "Query came back empty (not even a schema has been returned). No file has been created"
+        Ok(output.exists())
+    }
+}
+
+/// Checkpoint to resume the export from. `None` if there is none, yet.
+fn resume_from(path: &Path, query: &str) -> Result<Option<Checkpoint>, Error> {
+    let Some(checkpoint) = Checkpoint::read(path)? else {
+        warn!(
+            "No checkpoint found at '{}'. Starting from the beginning.",
+            path.display()
         );
+        return Ok(None);
+    };
+    if checkpoint.query != query {
+        bail!(
+            "Checkpoint '{}' has been recorded for a different query.",
+            path.display()
+        )
     }
-    Ok(())
+    info!(
+        "Resuming after {} files with {} rows.",
+        checkpoint.files, checkpoint.rows
+    );
+    Ok(Some(checkpoint))
 }
 
 /// The query statement is either passed verbatim at the command line, or via stdin. The latter is
@@ -108,29 +894,619 @@ fn query_statement_text(query: String) -> Result<String, Error> {
     })
 }
 
-fn cursor_to_parquet(
-    mut cursor: impl Cursor,
-    path: IoArg,
+/// Values for the placeholders, which may be used in the name of the output file. `{seq}` is not
+/// part of this list, since it is replaced by the writer once it knows the number of the file.
+fn file_name_placeholders(query: &str) -> Vec<(&'static str, String)> {
+    let now = Utc::now();
+    let query_hash: String = Sha256::digest(query.as_bytes())[..8]
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    vec![
+        ("timestamp", now.format("%Y%m%dT%H%M%SZ").to_string()),
+        ("date", now.format("%Y-%m-%d").to_string()),
+        ("query_hash", query_hash),
+    ]
+}
+
+/// Pairs each set of parameters with its own output, by replacing the placeholders for the names of
+/// the parameters with their values. E.g. `out_{tenant}.par` becomes `out_acme.par`.
+fn executions_from_parameter_sets(
+    output: &Destination,
+    parameter_sets: Vec<ParameterSet>,
+) -> Result<Vec<(Destination, Vec<String>)>, Error> {
+    let mut executions: Vec<(Destination, Vec<String>)> = Vec::new();
+    for parameter_set in parameter_sets {
+        let placeholders: Vec<(&str, String)> = parameter_set
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.clone()))
+            .collect();
+        let execution_output = output.expand_placeholders(&placeholders);
+        if execution_output == *output {
+            bail!(
+                "The output must contain a placeholder for at least one parameter of the parameter \
+                file, e.g. 'out_{{{}}}.par'.",
+                placeholders.first().map_or("name", |(name, _)| name)
+            )
+        }
+        if executions
+            .iter()
+            .any(|(other, _)| *other == execution_output)
+        {
+            bail!(
+                "Two sets of parameters would be written to the same output. Use placeholders for \
+                more parameters in the output to tell them apart."
+            )
+        }
+        let values = parameter_set.into_iter().map(|(_, value)| value).collect();
+        executions.push((execution_output, values));
+    }
+    Ok(executions)
+}
+
+/// How a result set is fetched and written into parquet files, apart from the mapping of its
+/// columns ([`MappingOptions`]) and the format of the files ([`ParquetFormatOptions`]).
+struct ParquetExportOptions<'a> {
     batch_size: BatchSizeLimit,
+    fetch_buffers: usize,
+    adaptive_batch_size: bool,
     file_size: FileSizeLimit,
-    mapping_options: MappingOptions,
-    parquet_format_options: ParquetFormatOptions,
     suffix_length: usize,
+    partition_by: Vec<String>,
+    overwrite_partitions: bool,
+    /// Delta Lake table the written files are committed to.
+    delta_table: Option<DeltaTable>,
+    sorted_by: &'a [SortColumn],
+    verify_sort_order: bool,
+    field_ids_argument: Option<&'a FieldIdsArgument>,
+    arrow_schema: Option<ArrowSchemaOptions>,
+    converted_types_only: bool,
+    geo_parquet: Option<GeoParquetOptions>,
+    json_columns: &'a [String],
+    xml_metadata: bool,
+    /// Write the offsets of `DATETIMEOFFSET` columns into sibling columns.
+    offset_minutes: bool,
+    success_marker: bool,
+    manifest: bool,
+    on_interrupt: OnInterrupt,
+    on_truncation: OnTruncation,
+    /// Continue the sequence of files written by a previous export.
+    append: bool,
+    added_columns: Vec<(String, Constant)>,
+    side_file_dir: &'a str,
+    side_file_names: SideFileNames,
+}
+
+/// Writes the result set into parquet files at `path`.
+///
+/// # Parameters
+///
+/// * `checkpoint`: Records the progress of the export, so it can be resumed.
+/// * `skip_rows`: Rows at the start of the result set, which have been written by a previous
+///   attempt.
+fn cursor_to_parquet(
+    cursor: &mut impl Cursor,
+    path: Destination,
+    mapping_options: MappingOptions,
+    mut parquet_format_options: ParquetFormatOptions,
+    export_options: ParquetExportOptions,
+    checkpoint: Option<(PathBuf, Checkpoint)>,
+    mut skip_rows: u64,
 ) -> Result<(), Error> {
-    let strategies = make_schema(&mut cursor, mapping_options)?;
+    let ParquetExportOptions {
+        batch_size,
+        fetch_buffers,
+        adaptive_batch_size,
+        file_size,
+        suffix_length,
+        partition_by,
+        overwrite_partitions,
+        delta_table,
+        sorted_by,
+        verify_sort_order,
+        field_ids_argument,
+        arrow_schema,
+        converted_types_only,
+        geo_parquet,
+        json_columns,
+        xml_metadata,
+        offset_minutes,
+        success_marker,
+        manifest,
+        on_interrupt,
+        on_truncation,
+        append,
+        added_columns,
+        side_file_dir,
+        side_file_names,
+    } = export_options;
+    handle_interrupts();
+    let strategies = make_schema(cursor, mapping_options, true)?;
+    let offset_columns = if offset_minutes {
+        offset_minutes_columns(cursor, &strategies, mapping_options.db_name)?
+    } else {
+        Vec::new()
+    };
+    let column_names: Vec<_> = strategies
+        .iter()
+        .map(|(_index, name, _strategy)| name.as_str())
+        .collect();
+
+    let partitioning = if partition_by.is_empty() {
+        None
+    } else {
+        Some(Partitioning::new(&partition_by, &column_names)?)
+    };
+
+    let added_columns: Vec<ColumnInfo> = added_columns
+        .into_iter()
+        .map(|(name, constant)| {
+            let strategy: Box<dyn ColumnFetchStrategy> = Box::new(constant);
+            (ADDED_COLUMN_NUMBER, name, strategy)
+        })
+        .collect();
+
+    // Columns holding the paths of the side files. Their values are appended to the fetch buffer.
+    let side_file_columns = side_file_columns(
+        &strategies,
+        mapping_options.max_value_size,
+        mapping_options.oversized,
+    );
+    let path_columns: Vec<ColumnInfo> = side_file_columns
+        .iter()
+        .map(|&buffer_index| {
+            let strategy: Box<dyn ColumnFetchStrategy> =
+                Box::new(Utf8::with_bytes_length(Repetition::OPTIONAL, 1));
+            let name = format!("{}_file", strategies[buffer_index].1);
+            (ADDED_COLUMN_NUMBER, name, strategy)
+        })
+        .collect();
+
+    // Offset columns are numbered after the fetched columns, followed by the columns holding the
+    // paths of side files and the added columns.
+    let mut field_id_names: Vec<_> = column_names
+        .iter()
+        .copied()
+        .chain(
+            offset_columns
+                .iter()
+                .map(|(_buffer_index, (_, name, _))| name.as_str()),
+        )
+        .collect();
+    for (_, name, _) in &path_columns {
+        if field_id_names.contains(&name.as_str()) {
+            bail!("Column '{name}' for the paths of side files is already part of the result set.")
+        }
+        field_id_names.push(name);
+    }
+    for (_, name, _) in &added_columns {
+        if field_id_names.contains(&name.as_str()) {
+            bail!("Added column '{name}' is already part of the parquet files.")
+        }
+        field_id_names.push(name);
+    }
+    let field_ids = field_ids_argument
+        .map(|argument| field_ids(argument, &field_id_names))
+        .transpose()?;
+    let field_id = |index: usize| field_ids.as_ref().map(|ids| ids[index]);
+
+    // Columns of the fetch buffer, which are written into the parquet files, each followed by the
+    // offsets derived from it. Partition columns are part of the path instead.
+    let mut parquet_columns = Vec::new();
+    for (buffer_index, info) in strategies.iter().enumerate() {
+        if partitioning
+            .as_ref()
+            .is_some_and(|p| p.is_partition_column(buffer_index))
+        {
+            continue;
+        }
+        parquet_columns.push(ParquetColumn {
+            buffer_index,
+            info,
+            field_id: field_id(buffer_index),
+        });
+        for (offset_index, (_, offset_info)) in offset_columns
+            .iter()
+            .enumerate()
+            .filter(|(_, (index, _))| *index == buffer_index)
+        {
+            parquet_columns.push(ParquetColumn {
+                buffer_index,
+                info: offset_info,
+                field_id: field_id(strategies.len() + offset_index),
+            });
+        }
+        if let Some(path_index) = side_file_columns.iter().position(|&i| i == buffer_index) {
+            parquet_columns.push(ParquetColumn {
+                buffer_index: strategies.len() + path_index,
+                info: &path_columns[path_index],
+                field_id: field_id(strategies.len() + offset_columns.len() + path_index),
+            });
+        }
+    }
 
-    let parquet_schema = parquet_schema_from_strategies(&strategies);
+    // Added columns are not fetched, yet their values are written for each row of the fetch
+    // buffer. So they borrow the first column of the buffer to know the number of rows.
+    if !strategies.is_empty() {
+        for (added_index, info) in added_columns.iter().enumerate() {
+            parquet_columns.push(ParquetColumn {
+                buffer_index: 0,
+                info,
+                field_id: field_id(
+                    strategies.len() + offset_columns.len() + path_columns.len() + added_index,
+                ),
+            });
+        }
+    }
 
-    if strategies.is_empty() {
+    if parquet_columns.is_empty() {
         bail!("Resulting parquet file would not have any columns!")
     }
 
+    for name in json_columns {
+        if !parquet_columns.iter().any(|column| column.info.1 == *name) {
+            bail!("JSON column '{name}' is not part of the parquet files.")
+        }
+    }
+
+    for (name, _) in &mut parquet_format_options.column_compressions {
+        *name = parquet_column_name(cursor, &strategies, &parquet_columns, name, "Compression")?;
+    }
+    for (name, _) in &mut parquet_format_options.column_dictionaries {
+        *name = parquet_column_name(cursor, &strategies, &parquet_columns, name, "Dictionary")?;
+    }
+    for (name, _) in &mut parquet_format_options.column_statistics {
+        *name = parquet_column_name(cursor, &strategies, &parquet_columns, name, "Statistics")?;
+    }
+    for (name, _fpp) in &mut parquet_format_options.bloom_filters {
+        *name = parquet_column_name(cursor, &strategies, &parquet_columns, name, "Bloom filter")?;
+    }
+
+    let parquet_schema = parquet_schema_from_strategies(
+        parquet_columns
+            .iter()
+            .map(|column| (column.info, column.field_id)),
+        json_columns,
+        converted_types_only,
+    )?;
+
+    if let Some(arrow_schema) = &arrow_schema {
+        let metadata = arrow_schema.metadata(&parquet_schema)?;
+        parquet_format_options.key_value_metadata.push(metadata);
+    }
+    if let Some(geo_parquet) = &geo_parquet {
+        let metadata = geo_parquet.metadata(&parquet_schema)?;
+        parquet_format_options.key_value_metadata.push(metadata);
+    }
+    if xml_metadata {
+        let mut xml_columns = Vec::new();
+        for column in &parquet_columns {
+            let (column_number, name, _strategy) = column.info;
+            if *column_number != ADDED_COLUMN_NUMBER
+                && is_xml(&cursor.col_data_type(*column_number)?)
+            {
+                xml_columns.push(name.as_str());
+            }
+        }
+        parquet_format_options
+            .key_value_metadata
+            .push(xml_columns_metadata(&xml_columns));
+    }
+
+    let mut sort_order_verifier = None;
+    if !sorted_by.is_empty() {
+        let sort_order = SortOrder::new(sorted_by, &strategies)?;
+        let buffer_indices: Vec<usize> = parquet_columns
+            .iter()
+            .map(|column| column.buffer_index)
+            .collect();
+        parquet_format_options.sorting_columns = Some(sort_order.sorting_columns(&buffer_indices));
+        if verify_sort_order {
+            sort_order_verifier = Some(sort_order.verifier());
+        }
+    }
+
+    let streamed = unbounded_columns(&strategies, &parquet_columns, sorted_by)?;
+    let spilled = oversized_columns(&strategies, &parquet_columns, sorted_by, batch_size);
+    let fetch_in_parts = !spilled.is_empty() || !streamed.is_empty();
+    let batch_size_row = batch_size_in_rows(
+        &strategies,
+        &spilled,
+        batch_size,
+        ParquetBuffer::MEMORY_USAGE_BYTES_PER_ROW,
+    )?;
+    if fetch_in_parts && (fetch_buffers > 1 || adaptive_batch_size) {
+        warn!(
+            "Rows are fetched one by one, since the values of some columns are fetched in parts. \
+            Ignoring --fetch-buffers and --adaptive-batch-size."
+        );
+    }
+    let adaptive_batch_size = adaptive_batch_size && !fetch_in_parts;
+
+    // Partitions and Delta Lake tables are written into the output directory. Otherwise the output
+    // is a file.
+    let directory = if partitioning.is_some() || delta_table.is_some() {
+        path.clone()
+    } else {
+        path.parent()
+    };
+    let side_files = if side_file_columns.is_empty() {
+        None
+    } else {
+        Some(SideFiles::new(
+            directory.clone(),
+            side_file_dir,
+            side_file_names,
+        )?)
+    };
+
+    // The checkpoint records the first column of the sort order as key. So do the reports of
+    // oversized values, and side files named by key.
+    let key_column = sorted_by.first().and_then(|sort_column| {
+        column_names
+            .iter()
+            .position(|name| *name == sort_column.name)
+    });
+
+    // Ctrl+C cancels a fetch blocked in the data source.
+    let statement = cursor.as_stmt_ref().as_sys();
+    // Only allocated, if all batches are fetched into the same buffer.
+    let mut odbc_buffer;
+    let mut row_set_cursor = if fetch_in_parts {
+        Fetch::Spilling(SpillingFetch::new(
+            cursor,
+            &strategies,
+            &spilled,
+            &streamed,
+            batch_size_row,
+            batch_size.memory(),
+            mapping_options.max_value_size,
+            mapping_options.oversized,
+            side_files.as_ref(),
+            key_column,
+        )?)
+    } else if fetch_buffers > 1 {
+        let buffers = (0..fetch_buffers)
+            .map(|_| odbc_buffer_for(&strategies, batch_size_row))
+            .collect();
+        Fetch::Concurrent(ConcurrentFetch::new(cursor, buffers))
+    } else if adaptive_batch_size {
+        Fetch::Growing(GrowingFetch::new(cursor, batch_size_row, |rows| {
+            odbc_buffer_for(&strategies, rows)
+        }))
+    } else {
+        odbc_buffer = odbc_buffer_for(&strategies, batch_size_row);
+        Fetch::sequential(cursor, &mut odbc_buffer)
+    };
+    let mut truncation_check = TruncationCheck::new(
+        &strategies,
+        on_truncation,
+        mapping_options.max_column_size,
+        mapping_options.max_value_size,
+    );
+
+    let completion = Completion {
+        directory,
+        success_marker,
+        manifest,
+    };
+
+    // Data files of a Delta Lake table get unique names within the table directory.
+    let path = match &delta_table {
+        Some(delta_table) => delta_table.data_files(),
+        None => path,
+    };
+
+    let mut checkpointing =
+        checkpoint.map(|(path, resumed)| Checkpointing::new(path, resumed, key_column));
+    let mut oversized_check = OversizedCheck::new(
+        &strategies,
+        mapping_options.max_value_size,
+        mapping_options.oversized,
+        key_column,
+    );
+    let first_file = match &checkpointing {
+        Some(checkpointing) => checkpointing.first_file(),
+        None if append => {
+            let last_file = last_existing_file(&path, suffix_length)?;
+            if last_file != 0 {
+                let last_existing = path.with_suffix(last_file, suffix_length)?;
+                verify_schema(&last_existing, &parquet_schema)?;
+                info!("Appending to {last_file} existing files.");
+            }
+            last_file + 1
+        }
+        None => 1,
+    };
+
+    // Grows together with the batches, if their size is adaptive.
+    let mut pb = ParquetBuffer::new(if adaptive_batch_size {
+        INITIAL_BATCH_SIZE_ROWS.min(batch_size_row)
+    } else {
+        batch_size_row
+    });
+    let mut num_batch = 0;
+
+    let new_writer = |path: Destination| {
+        ParquetWriter::new(
+            path,
+            parquet_schema.clone(),
+            file_size,
+            parquet_format_options.clone(),
+            suffix_length,
+            first_file,
+        )
+    };
+
+    // Without partitioning all rows are written into the same writer. Otherwise we create one
+    // writer for each partition, the first time we encounter a row belonging to it.
+    let mut writer = if partitioning.is_none() {
+        Some(new_writer(path.clone())?)
+    } else {
+        None
+    };
+    let mut partition_writers: BTreeMap<String, ParquetWriter> = BTreeMap::new();
+
+    // Safety: The cursor owning the statement handle outlives the guard.
+    let _cancel = unsafe { CancelOnInterrupt::new(statement) };
+    loop {
+        let row_set = match row_set_cursor.fetch() {
+            // The fetch has been cancelled, since Ctrl+C has been pressed.
+            Err(_) if is_interrupted() => break,
+            row_set => row_set?,
+        };
+        let Some((buffer, spilled, truncated)) = row_set else {
+            break;
+        };
+        num_batch += 1;
+        let num_rows = buffer.num_rows();
+        info!("Fetched batch {} with {} rows.", num_batch, num_rows);
+        pb.set_num_rows_fetched(num_rows);
+        if is_interrupted() {
+            // The batch fetched last is not written.
+            break;
+        }
+        if truncated {
+            truncation_check.check(buffer, num_batch)?;
+        }
+        if let Some(verifier) = &mut sort_order_verifier {
+            verifier.verify(buffer)?;
+        }
+        // Rows written before resuming are skipped.
+        let num_skipped = skip_rows.min(num_rows as u64) as usize;
+        skip_rows -= num_skipped as u64;
+        oversized_check.check(buffer, spilled, num_skipped)?;
+        if num_skipped == num_rows {
+            continue;
+        }
+        if num_skipped != 0 {
+            pb.select_rows(Some((num_skipped..num_rows).collect()));
+        }
+        if let Some(checkpointing) = &mut checkpointing {
+            checkpointing.batch(buffer, num_rows - num_skipped);
+        }
+        if let Some(partitioning) = &partitioning {
+            for (partition, rows) in partitioning.split_batch(buffer) {
+                let writer = match partition_writers.entry(partition) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => {
+                        info!("Creating new partition '{}'.", entry.key());
+                        let path = path.join(&format!("{}/part.par", entry.key()));
+                        entry.insert(new_writer(path)?)
+                    }
+                };
+                pb.select_rows(Some(rows));
+                write_batch(
+                    writer,
+                    &mut pb,
+                    buffer,
+                    spilled,
+                    &parquet_columns,
+                    &parquet_schema,
+                )?;
+            }
+            pb.select_rows(None);
+        } else {
+            write_batch(
+                writer.as_mut().unwrap(),
+                &mut pb,
+                buffer,
+                spilled,
+                &parquet_columns,
+                &parquet_schema,
+            )?;
+            pb.select_rows(None);
+        }
+        if let Some(checkpointing) = &mut checkpointing {
+            checkpointing.update(writer.as_ref().unwrap())?;
+        }
+    }
+
+    let mut written_files = Vec::new();
+    if let Some(writer) = writer {
+        written_files.extend(writer.close()?);
+    }
+    let partitions: Vec<_> = partition_writers.keys().cloned().collect();
+    for writer in partition_writers.into_values() {
+        written_files.extend(writer.close()?);
+    }
+
+    if is_interrupted() {
+        match on_interrupt {
+            OnInterrupt::Keep => {
+                warn!("Keeping {} incomplete files.", written_files.len());
+            }
+            OnInterrupt::Discard => {
+                for file in &written_files {
+                    if let Err(e) = file.destination.remove() {
+                        warn!("Failed to remove incomplete output: {e}");
+                    }
+                }
+                if let Some(checkpointing) = &checkpointing {
+                    checkpointing.reset()?;
+                }
+            }
+        }
+        bail!("Export interrupted after {num_batch} batches.")
+    }
+
+    if overwrite_partitions {
+        for partition in &partitions {
+            remove_stale_files(&path.join(partition), &written_files)?;
+        }
+    }
+
+    if let Some(delta_table) = delta_table {
+        delta_table.commit(&parquet_schema, &written_files)?;
+    }
+    completion.write(&parquet_schema, &written_files)?;
+    if let Some(checkpointing) = &checkpointing {
+        checkpointing.remove()?;
+    }
+
+    Ok(())
+}
+
+/// Allocates the buffer bound to the cursor, to fetch the result set in batches.
+///
+/// # Parameters
+///
+/// * `strategies`: Fetch strategies for each column of the result set.
+/// * `batch_size`: Limits the number of rows in a batch, and therefore the size of the buffer.
+/// * `mem_usage_per_row_writer`: Memory the writer needs to buffer one row of the batch, in
+///   addition to the ODBC buffer.
+///
+/// Returns the buffer together with its capacity in rows.
+fn fetch_buffer(
+    strategies: &[ColumnInfo],
+    batch_size: BatchSizeLimit,
+    mem_usage_per_row_writer: usize,
+) -> Result<(ColumnarAnyBuffer, usize), Error> {
+    let batch_size_row = batch_size_in_rows(strategies, &[], batch_size, mem_usage_per_row_writer)?;
+    Ok((odbc_buffer_for(strategies, batch_size_row), batch_size_row))
+}
+
+/// Maximum number of rows in a batch. Parameters are the same as for [`fetch_buffer`], with
+/// `spilled` holding the buffer indices of the columns fetched into temporary files.
+fn batch_size_in_rows(
+    strategies: &[ColumnInfo],
+    spilled: &[usize],
+    batch_size: BatchSizeLimit,
+    mem_usage_per_row_writer: usize,
+) -> Result<usize, Error> {
     let mem_usage_odbc_buffer_per_row: usize = strategies
         .iter()
-        .map(|(_index, _name, strategy)| strategy.buffer_description().bytes_per_row())
+        .enumerate()
+        .map(|(buffer_index, (_index, _name, strategy))| {
+            let description = strategy.buffer_description();
+            if spilled.contains(&buffer_index) {
+                placeholder(description).bytes_per_row()
+            } else {
+                description.bytes_per_row()
+            }
+        })
         .sum();
-    let total_mem_usage_per_row =
-        mem_usage_odbc_buffer_per_row + ParquetBuffer::MEMORY_USAGE_BYTES_PER_ROW;
+    let total_mem_usage_per_row = mem_usage_odbc_buffer_per_row + mem_usage_per_row_writer;
     info!(
         "Memory usage per row is {} bytes. This excludes memory directly allocated by the ODBC \
         driver.",
@@ -141,73 +1517,391 @@ fn cursor_to_parquet(
 
     info!("Batch size set to {} rows.", batch_size_row);
 
-    let mut odbc_buffer = ColumnarAnyBuffer::from_description_and_indices(
+    Ok(batch_size_row)
+}
+
+/// Buffer indices of the columns without a maximum length, e.g. `VARCHAR(max)`. Their values are
+/// fetched in parts, since no buffer can be bound for them. Fails if one of them is not written
+/// into the parquet files as it is, since its values are not held in the fetch buffer.
+fn unbounded_columns(
+    strategies: &[ColumnInfo],
+    parquet_columns: &[ParquetColumn],
+    sorted_by: &[SortColumn],
+) -> Result<Vec<usize>, Error> {
+    let mut unbounded = Vec::new();
+    for (buffer_index, (_index, name, strategy)) in strategies.iter().enumerate() {
+        if !is_unbounded(&strategy.buffer_description()) {
+            continue;
+        }
+        if !is_written_as_is(buffer_index, name, parquet_columns, sorted_by) {
+            bail!(
+                "Column '{name}' has no maximum length. Its values are fetched in parts, so it can \
+                not be used for partitioning, sorting, or offsets."
+            )
+        }
+        unbounded.push(buffer_index);
+    }
+    Ok(unbounded)
+}
+
+/// `true` if the values of the column are only copied into a single parquet column, i.e. not used
+/// for partitioning, sorting, or offsets. Only these can be fetched in parts.
+fn is_written_as_is(
+    buffer_index: usize,
+    name: &str,
+    parquet_columns: &[ParquetColumn],
+    sorted_by: &[SortColumn],
+) -> bool {
+    let num_parquet_columns = parquet_columns
+        .iter()
+        .filter(|column| {
+            column.buffer_index == buffer_index && column.info.0 != ADDED_COLUMN_NUMBER
+        })
+        .count();
+    num_parquet_columns == 1 && !sorted_by.iter().any(|column| column.name == *name)
+}
+
+/// Buffer indices of the columns to fetch into temporary files, because not even a single row
+/// would fit into the memory limit of a batch otherwise. Empty if a row fits, or if spilling can not
+/// make it fit. Only columns of variable length are spilled, which are written into the parquet
+/// files as they are, i.e. not used for partitioning, sorting, or offsets. Columns without a
+/// maximum length are streamed instead.
+fn oversized_columns(
+    strategies: &[ColumnInfo],
+    parquet_columns: &[ParquetColumn],
+    sorted_by: &[SortColumn],
+    batch_size: BatchSizeLimit,
+) -> Vec<usize> {
+    let Some(memory) = batch_size.memory() else {
+        return Vec::new();
+    };
+    let descriptions: Vec<_> = strategies
+        .iter()
+        .map(|(_index, _name, strategy)| strategy.buffer_description())
+        .collect();
+    let bytes_per_row = descriptions
+        .iter()
+        .map(|description| description.bytes_per_row())
+        .sum::<usize>()
+        + ParquetBuffer::MEMORY_USAGE_BYTES_PER_ROW;
+    let candidates: Vec<_> = descriptions
+        .iter()
+        .enumerate()
+        .filter(|&(buffer_index, description)| {
+            let (_index, name, _strategy) = &strategies[buffer_index];
+            is_spillable(description)
+                && !is_unbounded(description)
+                && is_written_as_is(buffer_index, name, parquet_columns, sorted_by)
+        })
+        .map(|(buffer_index, description)| {
+            let saved = description.bytes_per_row() - placeholder(*description).bytes_per_row();
+            (buffer_index, saved)
+        })
+        .collect();
+    columns_to_spill(bytes_per_row, &candidates, memory).unwrap_or_default()
+}
+
+/// Allocates a buffer holding `batch_size_row` rows of the result set.
+fn odbc_buffer_for(strategies: &[ColumnInfo], batch_size_row: usize) -> ColumnarAnyBuffer {
+    ColumnarAnyBuffer::from_description_and_indices(
         batch_size_row,
         strategies
             .iter()
             .map(|(index, _name, strategy)| (*index, strategy.buffer_description())),
-    );
-
-    let mut row_set_cursor = cursor.bind_buffer(&mut odbc_buffer)?;
+    )
+}
 
-    let mut pb = ParquetBuffer::new(batch_size_row as usize);
-    let mut num_batch = 0;
+/// Writes the (selected) rows of the fetched batch. Unless the size of the row groups is limited in
+/// bytes, each batch is written as a row group of its own.
+fn write_batch(
+    writer: &mut ParquetWriter,
+    pb: &mut ParquetBuffer,
+    buffer: &ColumnarAnyBuffer,
+    spilled: &[SpilledColumn],
+    parquet_columns: &[ParquetColumn],
+    parquet_schema: &Type,
+) -> Result<(), Error> {
+    if !writer.buffers_row_groups() {
+        return write_row_group(writer, pb, buffer, spilled, parquet_columns, parquet_schema);
+    }
+    let Some(row_group_size) = writer.row_group_size() else {
+        // Each batch is still a row group of its own, yet it is written by the buffered writers.
+        buffer_rows(writer, pb, buffer, spilled, parquet_columns, 0)?;
+        return writer.flush_row_group();
+    };
+    let limit = row_group_size.as_u64();
+    let selection = pb.selected_rows().map(<[usize]>::to_vec);
+    let rows = selection
+        .clone()
+        .unwrap_or_else(|| (0..buffer.num_rows()).collect());
+    let row_sizes = row_sizes(buffer, spilled, parquet_columns, parquet_schema);
 
-    let mut writer = ParquetWriter::new(
-        path,
-        parquet_schema.clone(),
-        file_size,
-        parquet_format_options,
-        suffix_length,
-    )?;
+    // Rows are added to the buffered row group, until it reaches the limit. The remaining rows are
+    // the start of the next one.
+    let mut start = 0;
+    let mut size = 0;
+    for (position, &row) in rows.iter().enumerate() {
+        size += row_sizes[row];
+        if writer.buffered_row_group_size() + size >= limit {
+            pb.select_rows(Some(rows[start..=position].to_vec()));
+            buffer_rows(writer, pb, buffer, spilled, parquet_columns, size)?;
+            writer.flush_row_group()?;
+            start = position + 1;
+            size = 0;
+        }
+    }
+    if start < rows.len() {
+        pb.select_rows(Some(rows[start..].to_vec()));
+        buffer_rows(writer, pb, buffer, spilled, parquet_columns, size)?;
+    }
+    pb.select_rows(selection);
+    Ok(())
+}
 
-    while let Some(buffer) = row_set_cursor
-        .fetch()
-        .map_err(give_hint_about_flag_for_oracle_users)?
-    {
-        let mut row_group_writer = writer.next_row_group(num_batch)?;
-        let mut col_index = 0;
-        num_batch += 1;
+/// Adds the selected rows of the fetched batch to the row group buffered by the writer.
+fn buffer_rows(
+    writer: &mut ParquetWriter,
+    pb: &mut ParquetBuffer,
+    buffer: &ColumnarAnyBuffer,
+    spilled: &[SpilledColumn],
+    parquet_columns: &[ParquetColumn],
+    size: u64,
+) -> Result<(), Error> {
+    if writer.encoding_threads() > 1 {
+        // Each thread needs buffers of its own to convert the values into.
         let num_rows = buffer.num_rows();
-        info!("Fetched batch {} with {} rows.", num_batch, num_rows);
-        pb.set_num_rows_fetched(num_rows);
-        while let Some(mut column_writer) = row_group_writer.next_column()? {
-            let col_name = parquet_schema.get_fields()[col_index]
-                .get_basic_info()
-                .name();
-            debug!(
-                "Writing column with index {} and name '{}'.",
-                col_index, col_name
-            );
+        let selection = pb.selected_rows().map(<[usize]>::to_vec);
+        let init = || {
+            let mut pb = ParquetBuffer::new(num_rows);
+            pb.set_num_rows_fetched(num_rows);
+            pb.select_rows(selection.clone());
+            pb
+        };
+        return writer.buffer_rows_concurrently(size, init, |pb, col_index, column_writer| {
+            copy_column(
+                pb,
+                column_writer,
+                buffer,
+                spilled,
+                &parquet_columns[col_index],
+            )
+        });
+    }
+    writer.buffer_rows(size, |col_index, column_writer| {
+        copy_column(
+            pb,
+            column_writer,
+            buffer,
+            spilled,
+            &parquet_columns[col_index],
+        )
+    })
+}
 
-            let odbc_column = buffer.column(col_index);
+/// Copies the (selected) values of the parquet column from the fetched batch, or from the
+/// temporary file, if the column is spilled.
+fn copy_column(
+    pb: &mut ParquetBuffer,
+    column_writer: &mut ColumnWriter,
+    buffer: &ColumnarAnyBuffer,
+    spilled: &[SpilledColumn],
+    column: &ParquetColumn,
+) -> Result<(), Error> {
+    let (column_number, _name, strategy) = column.info;
+    match spilled
+        .iter()
+        .find(|spilled| spilled.column_number() == *column_number)
+    {
+        Some(spilled) => spilled.copy_to_parquet(strategy.as_ref(), pb, column_writer),
+        None => {
+            strategy.copy_odbc_to_parquet(pb, column_writer, buffer.column(column.buffer_index))
+        }
+    }
+}
 
-            strategies[col_index].2.copy_odbc_to_parquet(
-                &mut pb,
-                column_writer.untyped(),
-                odbc_column,
-            )?;
-            column_writer.close()?;
-            col_index += 1;
+/// Approximate uncompressed size of each row of the fetched batch within the parquet file.
+fn row_sizes(
+    buffer: &ColumnarAnyBuffer,
+    spilled: &[SpilledColumn],
+    parquet_columns: &[ParquetColumn],
+    parquet_schema: &Type,
+) -> Vec<u64> {
+    let mut sizes = vec![0u64; buffer.num_rows()];
+    for (field, column) in parquet_schema.get_fields().iter().zip(parquet_columns) {
+        let fixed_size = match field.get_physical_type() {
+            PhysicalType::BOOLEAN => 1,
+            PhysicalType::INT32 | PhysicalType::FLOAT => 4,
+            PhysicalType::INT64 | PhysicalType::DOUBLE => 8,
+            PhysicalType::INT96 => 12,
+            PhysicalType::FIXED_LEN_BYTE_ARRAY => match field.as_ref() {
+                Type::PrimitiveType { type_length, .. } => (*type_length).try_into().unwrap_or(0),
+                Type::GroupType { .. } => 0,
+            },
+            // Values of variable length are prefixed with their length. We neglect the length of
+            // added columns, as they do not have a fetch buffer of their own.
+            PhysicalType::BYTE_ARRAY if column.info.0 == ADDED_COLUMN_NUMBER => 4,
+            PhysicalType::BYTE_ARRAY => {
+                let spilled = spilled
+                    .iter()
+                    .find(|spilled| spilled.column_number() == column.info.0);
+                let lengths: Vec<usize> = if let Some(spilled) = spilled {
+                    // The fetch buffer holds no values of spilled columns.
+                    spilled.lengths().iter().map(|v| v.unwrap_or(0)).collect()
+                } else {
+                    match buffer.column(column.buffer_index) {
+                        AnyColumnView::Text(view) => {
+                            view.iter().map(|v| v.map_or(0, <[u8]>::len)).collect()
+                        }
+                        AnyColumnView::Binary(view) => {
+                            view.iter().map(|v| v.map_or(0, <[u8]>::len)).collect()
+                        }
+                        AnyColumnView::WText(view) => {
+                            view.iter().map(|v| v.map_or(0, |v| v.len() * 2)).collect()
+                        }
+                        _ => vec![0; buffer.num_rows()],
+                    }
+                };
+                for (size, length) in sizes.iter_mut().zip(lengths) {
+                    *size += 4 + length as u64;
+                }
+                continue;
+            }
+        };
+        for size in &mut sizes {
+            *size += fixed_size;
         }
-        let metadata = row_group_writer.close()?;
-        writer.update_current_file_size(metadata.compressed_size());
     }
+    sizes
+}
 
-    writer.close()?;
+/// Writes the (selected) rows of the fetched batch as a new row group.
+fn write_row_group(
+    writer: &mut ParquetWriter,
+    pb: &mut ParquetBuffer,
+    buffer: &ColumnarAnyBuffer,
+    spilled: &[SpilledColumn],
+    parquet_columns: &[ParquetColumn],
+    parquet_schema: &Type,
+) -> Result<(), Error> {
+    let mut row_group_writer = writer.next_row_group()?;
+    let mut col_index = 0;
+    while let Some(mut column_writer) = row_group_writer.next_column()? {
+        let col_name = parquet_schema.get_fields()[col_index]
+            .get_basic_info()
+            .name();
+        debug!(
+            "Writing column with index {} and name '{}'.",
+            col_index, col_name
+        );
 
+        let column = &parquet_columns[col_index];
+        copy_column(pb, column_writer.untyped(), buffer, spilled, column)?;
+        column_writer.close()?;
+        col_index += 1;
+    }
+    let metadata = row_group_writer.close()?;
+    writer.finish_row_group(metadata);
     Ok(())
 }
 
 type ColumnInfo = (u16, String, Box<dyn ColumnFetchStrategy>);
 
+/// A column of the parquet files.
+struct ParquetColumn<'a> {
+    /// Position within the fetch buffer of the column the values are copied from.
+    buffer_index: usize,
+    /// Name and strategy of the parquet column. Derived columns, like the offsets of
+    /// `DATETIMEOFFSET` values, share the fetch buffer of another column.
+    info: &'a ColumnInfo,
+    field_id: Option<i32>,
+}
+
+/// Columns holding the offsets of the `DATETIMEOFFSET` columns in minutes, named
+/// `<column>_offset_minutes`, together with the position of the timestamp in the fetch buffer.
+fn offset_minutes_columns(
+    cursor: &mut impl Cursor,
+    strategies: &[ColumnInfo],
+    db_name: &str,
+) -> Result<Vec<(usize, ColumnInfo)>, Error> {
+    let mut offset_columns = Vec::new();
+    for (buffer_index, (column_number, name, strategy)) in strategies.iter().enumerate() {
+        let Some(precision) =
+            datetimeoffset_precision(&cursor.col_data_type(*column_number)?, db_name)
+        else {
+            continue;
+        };
+        // An offset is present, whenever the timestamp is.
+        let repetition = strategy.parquet_type(name).get_basic_info().repetition();
+        let strategy: Box<dyn ColumnFetchStrategy> =
+            Box::new(OffsetMinutes::new(repetition, precision));
+        offset_columns.push((
+            buffer_index,
+            (*column_number, format!("{name}_offset_minutes"), strategy),
+        ));
+    }
+    Ok(offset_columns)
+}
+
+/// Name of the parquet column a per-column option of the parquet files, like `--bloom-filter`,
+/// refers to. Columns are named either as in the parquet files or as in the result set, in which
+/// case the name is mapped through `--rename` and `--sanitize-column-names`. `option` names the
+/// option in the error message.
+fn parquet_column_name(
+    cursor: &mut impl Cursor,
+    strategies: &[ColumnInfo],
+    parquet_columns: &[ParquetColumn],
+    name: &str,
+    option: &str,
+) -> Result<String, Error> {
+    let is_parquet_column = |name: &str| parquet_columns.iter().any(|column| column.info.1 == name);
+    if is_parquet_column(name) {
+        return Ok(name.to_owned());
+    }
+    for (column_number, output_name, _strategy) in strategies {
+        if cursor.col_name(*column_number)? == name && is_parquet_column(output_name) {
+            return Ok(output_name.clone());
+        }
+    }
+    bail!("{option} column '{name}' is not part of the result set.")
+}
+
+/// Name a column of the result set is written under, see `--rename` and `--sanitize-column-names`.
+/// `sanitized_names` holds the names of the previous columns.
+fn output_column_name(
+    source: &str,
+    mapping_options: &MappingOptions,
+    sanitized_names: &mut ColumnNames,
+) -> String {
+    // The last one wins, should a column be renamed multiple times.
+    let renamed = mapping_options
+        .renames
+        .iter()
+        .rev()
+        .find(|(from, _)| from == source)
+        .map_or(source, |(_, to)| to.as_str());
+    match mapping_options.sanitize_column_names {
+        Some(mode) => sanitized_names.sanitize(renamed, mode),
+        None => renamed.to_owned(),
+    }
+}
+
+/// Fetch strategy of each selected column of the result set.
+///
+/// # Parameters
+///
+/// * `fetch_unbounded_in_parts`: `true` if the caller fetches columns without a maximum length in
+///   parts, see [`is_unbounded`]. Otherwise these columns are ignored, since no buffer can be bound
+///   for them.
 fn make_schema(
     cursor: &mut impl Cursor,
     mapping_options: MappingOptions,
+    fetch_unbounded_in_parts: bool,
 ) -> Result<Vec<ColumnInfo>, Error> {
     let num_cols = cursor.num_result_cols()?;
 
     let mut odbc_buffer_desc = Vec::new();
+    let mut source_names = Vec::new();
+    let mut sanitized_names = ColumnNames::default();
 
     for index in 1..(num_cols + 1) {
         let mut cd = ColumnDescription::default();
@@ -224,30 +1918,233 @@ fn make_schema(
         } else {
             name
         };
+        let output_name = output_column_name(&name, &mapping_options, &mut sanitized_names);
+        source_names.push(name);
+        let name = output_name;
+
+        if !is_column_selected(
+            &name,
+            mapping_options.columns,
+            mapping_options.exclude_columns,
+        ) {
+            debug!("Skipping column '{name}', since it is not selected.");
+            continue;
+        }
+
+        let sentinels: Vec<String> = mapping_options
+            .null_values
+            .iter()
+            .filter(|(column, _value)| *column == name)
+            .map(|(_column, value)| value.clone())
+            .collect();
+        // The last one wins, should a column be masked multiple times.
+        let mask = mapping_options
+            .masks
+            .iter()
+            .rev()
+            .find(|(column, _mask)| *column == name)
+            .map(|(_column, mask)| mask.clone());
+        // The last one wins, should a default be specified multiple times.
+        let null_default = mapping_options
+            .null_defaults
+            .iter()
+            .rev()
+            .find(|(column, _value)| *column == name)
+            .map(|(_column, value)| value.as_str());
+        if null_default.is_some() && mapping_options.nullable_columns.contains(&name) {
+            bail!("Column '{name}' can not have a default for NULLs and be forced nullable.")
+        }
+        let is_not_null = mapping_options.not_null_columns.contains(&name);
+        if is_not_null {
+            // Redacted values and sentinels are written as NULL.
+            if !sentinels.is_empty() || mask == Some(Mask::Redact) {
+                bail!("Column '{name}' asserted not to contain NULL would be written as NULL.")
+            }
+            if mapping_options.nullable_columns.contains(&name) {
+                bail!("Column '{name}' can not be asserted not null and forced nullable.")
+            }
+        }
+        // Sentinels and redacted values are written as NULL, even if the database claims the
+        // column has none. Columns asserted not to contain NULL are fetched as nullable, too, so we
+        // can detect NULLs, but are written as REQUIRED. So are columns with a default for NULLs.
+        if !sentinels.is_empty()
+            || null_default.is_some()
+            || mask == Some(Mask::Redact)
+            || mapping_options.nullable_columns.contains(&name)
+            || is_not_null
+        {
+            cd.nullability = Nullability::Nullable;
+        }
+
+        let column_fetch_strategy =
+            strategy_from_column_description(&cd, &name, mapping_options, cursor, index)?;
+        if !fetch_unbounded_in_parts && is_unbounded(&column_fetch_strategy.buffer_description()) {
+            warn!(
+                "Ignoring column '{}' with index {}. Driver reported a length of 0. \
+                This can happen for types without a fixed size limit. Only parquet output fetches \
+                the values of these columns in parts.",
+                name, index
+            );
+            continue;
+        }
+        let column_fetch_strategy = match mask {
+            Some(mask) => Box::new(MaskedColumn::new(column_fetch_strategy, mask)),
+            None => column_fetch_strategy,
+        };
+        // Wraps the mask, so oversized values written as NULL are not hashed either.
+        let column_fetch_strategy = match mapping_options.max_value_size {
+            Some(max_value_size) => limit_value_size(
+                column_fetch_strategy,
+                &name,
+                max_value_size,
+                oversized_policy(&name, mapping_options.oversized),
+            ),
+            None => column_fetch_strategy,
+        };
+        // Sentinels are compared with the values before they are masked.
+        let column_fetch_strategy = if sentinels.is_empty() {
+            column_fetch_strategy
+        } else {
+            Box::new(NullSentinels::new(column_fetch_strategy, sentinels))
+        };
+        let column_fetch_strategy = match null_default {
+            Some(text) => Box::new(WithNullDefault::new(column_fetch_strategy, &name, text)?),
+            None => column_fetch_strategy,
+        };
+        let column_fetch_strategy = if is_not_null {
+            Box::new(NotNull::new(column_fetch_strategy, name.clone()))
+        } else {
+            column_fetch_strategy
+        };
+        odbc_buffer_desc.push((index as u16, name, column_fetch_strategy));
+    }
 
-        if let Some(column_fetch_strategy) =
-            strategy_from_column_description(&cd, &name, mapping_options, cursor, index)?
+    for (name, _unit) in mapping_options.timestamp_units {
+        if !odbc_buffer_desc
+            .iter()
+            .any(|(_index, column, _strategy)| column == name)
         {
-            odbc_buffer_desc.push((index as u16, name, column_fetch_strategy));
+            bail!("Timestamp precision column '{name}' is not part of the result set.")
         }
     }
 
-    Ok(odbc_buffer_desc)
-}
+    for (source, _destination) in mapping_options.renames {
+        if !source_names.contains(source) {
+            bail!("Renamed column '{source}' is not part of the result set.")
+        }
+    }
 
-fn parquet_schema_from_strategies(
-    strategies: &[(u16, String, Box<dyn ColumnFetchStrategy>)],
-) -> TypePtr {
-    let mut fields = strategies
+    if odbc_buffer_desc.is_empty() && !source_names.is_empty() {
+        bail!("None of the columns of the result set is selected to be written.")
+    }
+
+    for name in mapping_options.trim_char_columns {
+        if !odbc_buffer_desc
+            .iter()
+            .any(|(_index, column, _strategy)| column == name)
+        {
+            bail!("Trim char column '{name}' is not part of the result set.")
+        }
+    }
+
+    for (name, _cast) in mapping_options.column_casts {
+        if !odbc_buffer_desc
+            .iter()
+            .any(|(_index, column, _strategy)| column == name)
+        {
+            bail!("Cast column '{name}' is not part of the result set.")
+        }
+    }
+
+    for (name, _value) in mapping_options.null_values {
+        if !odbc_buffer_desc
+            .iter()
+            .any(|(_index, column, _strategy)| column == name)
+        {
+            bail!("Null value column '{name}' is not part of the result set.")
+        }
+    }
+
+    for (name, _value) in mapping_options.null_defaults {
+        if !odbc_buffer_desc
+            .iter()
+            .any(|(_index, column, _strategy)| column == name)
+        {
+            bail!("Null default column '{name}' is not part of the result set.")
+        }
+    }
+
+    for (name, _mask) in mapping_options.masks {
+        if !odbc_buffer_desc
+            .iter()
+            .any(|(_index, column, _strategy)| column == name)
+        {
+            bail!("Masked column '{name}' is not part of the result set.")
+        }
+    }
+
+    for name in mapping_options
+        .oversized
         .iter()
-        .map(|(_index, name, s)| Arc::new(s.parquet_type(name)))
-        .collect();
-    Arc::new(
+        .filter_map(|(column, _policy)| column.as_ref())
+    {
+        if !odbc_buffer_desc
+            .iter()
+            .any(|(_index, column, _strategy)| column == name)
+        {
+            bail!("Oversized column '{name}' is not part of the result set.")
+        }
+    }
+
+    for name in mapping_options
+        .not_null_columns
+        .iter()
+        .chain(mapping_options.nullable_columns)
+    {
+        if !odbc_buffer_desc
+            .iter()
+            .any(|(_index, column, _strategy)| column == name)
+        {
+            bail!("Nullability override column '{name}' is not part of the result set.")
+        }
+    }
+
+    reorder_columns(
+        odbc_buffer_desc,
+        |(_index, name, _strategy)| name,
+        mapping_options.column_order,
+    )
+}
+
+/// Schema of the parquet files, with an optional field ID for each column. Columns named in
+/// `json_columns` are annotated as JSON. If `converted_types_only` is set, columns are annotated
+/// with the legacy converted types only.
+fn parquet_schema_from_strategies<'a>(
+    strategies: impl Iterator<Item = (&'a ColumnInfo, Option<i32>)>,
+    json_columns: &[String],
+    converted_types_only: bool,
+) -> Result<TypePtr, Error> {
+    let fields = strategies
+        .map(|((_index, name, s), field_id)| {
+            let mut parquet_type = s.parquet_type(name);
+            if json_columns.contains(name) {
+                parquet_type = with_json_logical_type(parquet_type)?;
+            }
+            if converted_types_only {
+                parquet_type = without_logical_type(parquet_type);
+            }
+            Ok(Arc::new(match field_id {
+                Some(id) => with_field_id(parquet_type, id),
+                None => parquet_type,
+            }))
+        })
+        .collect::<Result<_, Error>>()?;
+    Ok(Arc::new(
         Type::group_type_builder("schema")
-            .with_fields(&mut fields)
+            .with_fields(fields)
             .build()
             .unwrap(),
-    )
+    ))
 }
 
 /// If we hit the issue with oracle not supporting 64Bit, let's tell our users that we have
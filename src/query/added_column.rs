@@ -0,0 +1,150 @@
+//! Columns which are not part of the result set, but appended to every row, e.g. to record the
+//! lineage of the data. See `--add-column`.
+
+use anyhow::Error;
+use chrono::{DateTime, Utc};
+use odbc_api::buffers::{AnyColumnView, BufferDescription, BufferKind};
+use parquet::{
+    basic::{LogicalType, Repetition, TimeUnit, Type as PhysicalType},
+    column::writer::{get_typed_column_writer_mut, ColumnWriter},
+    data_type::{ByteArray, ByteArrayType, Int64Type},
+    schema::types::Type,
+};
+
+use crate::{enum_args::AddedColumn, parquet_buffer::ParquetBuffer};
+
+use super::strategy::ColumnFetchStrategy;
+
+/// Column number of columns which are not fetched from the data source. ODBC column numbers start
+/// at 1 (0 is reserved for bookmarks), so it can not clash with a column of the result set.
+pub const ADDED_COLUMN_NUMBER: u16 = 0;
+
+/// Writes the same value into every row.
+pub enum Constant {
+    Text(String),
+    /// Microseconds since epoch in UTC
+    Timestamp(i64),
+}
+
+impl ColumnFetchStrategy for Constant {
+    fn parquet_type(&self, name: &str) -> Type {
+        let (physical_type, logical_type) = match self {
+            Constant::Text(_) => (PhysicalType::BYTE_ARRAY, LogicalType::String),
+            Constant::Timestamp(_) => (
+                PhysicalType::INT64,
+                LogicalType::timestamp(true, TimeUnit::MICROS),
+            ),
+        };
+        Type::primitive_type_builder(name, physical_type)
+            .with_logical_type(Some(logical_type))
+            .with_repetition(Repetition::REQUIRED)
+            .build()
+            .unwrap()
+    }
+
+    fn buffer_description(&self) -> BufferDescription {
+        // Never bound, since the values are not fetched.
+        BufferDescription {
+            kind: BufferKind::I64,
+            nullable: false,
+        }
+    }
+
+    fn copy_odbc_to_parquet(
+        &self,
+        parquet_buffer: &mut ParquetBuffer,
+        column_writer: &mut ColumnWriter,
+        _column_view: AnyColumnView,
+    ) -> Result<(), Error> {
+        // The parquet buffer is sized to the number of rows of the batch.
+        let num_rows = parquet_buffer.def_levels.len();
+        match self {
+            Constant::Text(text) => {
+                let cw = get_typed_column_writer_mut::<ByteArrayType>(column_writer);
+                let value = ByteArray::from(text.as_str());
+                parquet_buffer.write_optional(cw, (0..num_rows).map(|_| Some(value.clone())))
+            }
+            Constant::Timestamp(micros) => {
+                let cw = get_typed_column_writer_mut::<Int64Type>(column_writer);
+                parquet_buffer.write_optional(cw, (0..num_rows).map(|_| Some(*micros)))
+            }
+        }
+    }
+}
+
+/// Name and value of the columns added to every row. `source` identifies the data source the
+/// result set is fetched from and `extracted_at` is the start of the export.
+pub fn added_columns(
+    columns: &[AddedColumn],
+    source: &str,
+    extracted_at: DateTime<Utc>,
+) -> Vec<(String, Constant)> {
+    columns
+        .iter()
+        .map(|column| match column {
+            AddedColumn::Constant { name, value } => (name.clone(), Constant::Text(value.clone())),
+            AddedColumn::ExtractedAt => (
+                "_extracted_at".to_owned(),
+                Constant::Timestamp(extracted_at.timestamp_micros()),
+            ),
+            AddedColumn::Source => ("_source".to_owned(), Constant::Text(source.to_owned())),
+        })
+        .collect()
+}
+
+/// Identifies the data source in `_source`, without revealing credentials. The data source name if
+/// one is used, otherwise the server (or host) specified in the connection string. Falls back to
+/// the name of the database management system.
+pub fn source_name(dsn: Option<&str>, connection_string: Option<&str>, db_name: &str) -> String {
+    if let Some(dsn) = dsn {
+        return dsn.to_owned();
+    }
+    connection_string
+        .into_iter()
+        .flat_map(|connection_string| connection_string.split(';'))
+        .filter_map(|attribute| attribute.split_once('='))
+        .find(|(key, _value)| {
+            ["server", "host", "hostname", "dbq", "data source"]
+                .iter()
+                .any(|candidate| key.trim().eq_ignore_ascii_case(candidate))
+        })
+        .map(|(_key, value)| {
+            let value = value.trim();
+            value
+                .strip_prefix('{')
+                .and_then(|value| value.strip_suffix('}'))
+                .unwrap_or(value)
+                .to_owned()
+        })
+        .unwrap_or_else(|| db_name.to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::source_name;
+
+    #[test]
+    fn name_the_source() {
+        assert_eq!("my_dsn", source_name(Some("my_dsn"), None, "Oracle"));
+        assert_eq!(
+            "localhost",
+            source_name(
+                None,
+                Some("Driver={ODBC Driver 17 for SQL Server};Server=localhost;PWD=secret;"),
+                "Microsoft SQL Server"
+            )
+        );
+        assert_eq!(
+            "db.example.com",
+            source_name(
+                None,
+                Some("DRIVER={PostgreSQL};HOST={db.example.com}"),
+                "PostgreSQL"
+            )
+        );
+        assert_eq!(
+            "SQLite",
+            source_name(None, Some("Driver=SQLite3;Database=x.db"), "SQLite")
+        );
+    }
+}
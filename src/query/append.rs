@@ -0,0 +1,96 @@
+//! Continues an existing sequence of output files, rather than overwriting it. See `--append`.
+
+use std::{fs::File, sync::Arc};
+
+use anyhow::{bail, Context, Error};
+use parquet::{
+    file::reader::{FileReader, SerializedFileReader},
+    schema::types::{SchemaDescriptor, Type},
+};
+
+use crate::destination::Destination;
+
+/// Number of the last file of the sequence which already exists, e.g. `7` if `out_01.par` to
+/// `out_07.par` exist. `0` if there is none.
+pub fn last_existing_file(output: &Destination, suffix_length: usize) -> Result<u32, Error> {
+    let mut num_file = 0;
+    while output.with_suffix(num_file + 1, suffix_length)?.exists() {
+        num_file += 1;
+    }
+    Ok(num_file)
+}
+
+/// Fails, unless the columns of the existing file match the columns of `schema` in name, order,
+/// physical type, logical type and nullability.
+pub fn verify_schema(existing: &Destination, schema: &Type) -> Result<(), Error> {
+    let Destination::File(path) = existing else {
+        bail!("Only files in the local file system can be appended to.")
+    };
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open existing file '{}'.", path.display()))?;
+    let reader = SerializedFileReader::new(file)
+        .with_context(|| format!("Failed to read existing file '{}'.", path.display()))?;
+    let existing_schema = reader.metadata().file_metadata().schema_descr();
+    let schema = SchemaDescriptor::new(Arc::new(schema.clone()));
+    let describe = |schema: &SchemaDescriptor| -> Vec<String> {
+        schema
+            .columns()
+            .iter()
+            .map(|column| {
+                format!(
+                    "{} {} {:?} {:?}",
+                    column.path(),
+                    column.physical_type(),
+                    column.logical_type_ref(),
+                    column.self_type().get_basic_info().repetition()
+                )
+            })
+            .collect()
+    };
+    let (existing_columns, columns) = (describe(existing_schema), describe(&schema));
+    if existing_columns != columns {
+        bail!(
+            "Schema of the result set does not match the existing file '{}'. Existing columns: \
+            {}. New columns: {}.",
+            path.display(),
+            existing_columns.join(", "),
+            columns.join(", ")
+        )
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, sync::Arc};
+
+    use parquet::{file::writer::SerializedFileWriter, schema::parser::parse_message_type};
+    use tempfile::tempdir;
+
+    use crate::destination::Destination;
+
+    use super::{last_existing_file, verify_schema};
+
+    #[test]
+    fn continue_file_sequence() {
+        let dir = tempdir().unwrap();
+        let output = Destination::File(dir.path().join("out.par"));
+        assert_eq!(0, last_existing_file(&output, 2).unwrap());
+
+        let schema = Arc::new(parse_message_type("message schema { required int32 a; }").unwrap());
+        for num_file in 1..=3 {
+            let path = dir.path().join(format!("out_0{num_file}.par"));
+            let file = File::create(path).unwrap();
+            SerializedFileWriter::new(file, schema.clone(), Default::default())
+                .unwrap()
+                .close()
+                .unwrap();
+        }
+
+        assert_eq!(3, last_existing_file(&output, 2).unwrap());
+        let last = output.with_suffix(3, 2).unwrap();
+        verify_schema(&last, &schema).unwrap();
+        let other = parse_message_type("message schema { optional int32 a; }").unwrap();
+        assert!(verify_schema(&last, &other).is_err());
+    }
+}
@@ -0,0 +1,77 @@
+//! Arrow schema embedded into the footer of the parquet files (key `ARROW:schema`), like Arrow
+//! based writers do. Arrow based readers take it as a hint for types which can not be expressed
+//! in the parquet schema alone, e.g. the time zone of timestamps or dictionary arrays.
+
+use anyhow::Error;
+use parquet::{file::metadata::KeyValue, schema::types::Type};
+
+/// Hints for the Arrow types of the columns, in addition to the types derived from the parquet
+/// schema.
+#[cfg_attr(not(feature = "arrow"), allow(dead_code))]
+pub struct ArrowSchemaOptions {
+    /// Columns which should be read as Arrow dictionary arrays, i.e. categoricals in pandas.
+    pub dictionary_columns: Vec<String>,
+    /// Time zone for timestamps which are normalized to UTC, e.g. `Europe/Berlin`.
+    pub time_zone: Option<String>,
+}
+
+impl ArrowSchemaOptions {
+    /// Key value metadata containing the Arrow schema matching `parquet_schema`.
+    #[cfg(feature = "arrow")]
+    pub fn metadata(&self, parquet_schema: &Type) -> Result<KeyValue, Error> {
+        use std::sync::Arc;
+
+        use anyhow::bail;
+        use arrow_schema::{DataType, Field, Schema};
+        use parquet::{
+            arrow::{encode_arrow_schema, parquet_to_arrow_schema, ARROW_SCHEMA_META_KEY},
+            schema::types::SchemaDescriptor,
+        };
+
+        let descriptor = SchemaDescriptor::new(Arc::new(parquet_schema.clone()));
+        let schema = parquet_to_arrow_schema(&descriptor, None)?;
+
+        for name in &self.dictionary_columns {
+            let Some((_index, field)) = schema.column_with_name(name) else {
+                bail!("Arrow dictionary column '{name}' is not part of the parquet files.")
+            };
+            if !matches!(field.data_type(), DataType::Utf8 | DataType::Binary) {
+                bail!(
+                    "Arrow dictionary column '{name}' must be text or binary, but is of type {}.",
+                    field.data_type()
+                )
+            }
+        }
+
+        let fields: Vec<Field> = schema
+            .fields()
+            .iter()
+            .map(|field| {
+                let data_type = match field.data_type() {
+                    data_type if self.dictionary_columns.contains(field.name()) => {
+                        DataType::Dictionary(Box::new(DataType::Int32), Box::new(data_type.clone()))
+                    }
+                    // Only timestamps normalized to UTC have a time zone. Those without represent
+                    // local time and must not be moved to a different time zone.
+                    DataType::Timestamp(unit, Some(_)) if self.time_zone.is_some() => {
+                        DataType::Timestamp(*unit, self.time_zone.as_deref().map(Into::into))
+                    }
+                    data_type => data_type.clone(),
+                };
+                field.as_ref().clone().with_data_type(data_type)
+            })
+            .collect();
+
+        Ok(KeyValue::new(
+            ARROW_SCHEMA_META_KEY.to_owned(),
+            encode_arrow_schema(&Schema::new(fields)),
+        ))
+    }
+
+    #[cfg(not(feature = "arrow"))]
+    pub fn metadata(&self, _parquet_schema: &Type) -> Result<KeyValue, Error> {
+        unreachable!(
+            "Embedding the Arrow schema is rejected during validation without the feature."
+        )
+    }
+}
@@ -0,0 +1,157 @@
+//! Detects the memory available to this process, so the default batch size fits into small
+//! containers.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Memory available to this process in bytes. This is the smaller one of the memory available on
+/// the system and the memory left below the limit of the cgroup (e.g. the container) of the
+/// process, or any of its ancestors. `None` if neither can be determined, e.g. on operating
+/// systems other than Linux.
+pub fn available_memory() -> Option<u64> {
+    let system = fs::read_to_string("/proc/meminfo")
+        .ok()
+        .and_then(|meminfo| mem_available(&meminfo));
+    let cgroup = fs::read_to_string("/proc/self/cgroup")
+        .ok()
+        .and_then(|cgroups| cgroup_available_memory(&cgroups));
+    match (system, cgroup) {
+        (Some(system), Some(cgroup)) => Some(system.min(cgroup)),
+        (system, cgroup) => system.or(cgroup),
+    }
+}
+
+/// Value of `MemAvailable` in the contents of `/proc/meminfo`.
+fn mem_available(meminfo: &str) -> Option<u64> {
+    let line = meminfo
+        .lines()
+        .find_map(|line| line.strip_prefix("MemAvailable:"))?;
+    let kib = line.trim().strip_suffix("kB")?.trim().parse::<u64>().ok()?;
+    Some(kib * 1024)
+}
+
+/// Smallest amount of memory left below the limit of the cgroup of this process, or any of its
+/// ancestors.
+fn cgroup_available_memory(cgroups: &str) -> Option<u64> {
+    cgroup_memory_dirs(cgroups)
+        .into_iter()
+        .filter_map(|(dir, limit_file, usage_file)| {
+            let limit = read_limit(&dir.join(limit_file))?;
+            let usage = read_limit(&dir.join(usage_file)).unwrap_or(0);
+            Some(limit.saturating_sub(usage))
+        })
+        .min()
+}
+
+/// Directories of the memory controller for the cgroup of this process and all its ancestors,
+/// together with the names of the files holding limit and usage. Both cgroup v2 (unified
+/// hierarchy) and v1 are supported. In hybrid setups both are listed, yet only the files of the
+/// hierarchy with the memory controller exist.
+///
+/// # Parameters
+///
+/// * `cgroups`: Contents of `/proc/self/cgroup`
+fn cgroup_memory_dirs(cgroups: &str) -> Vec<(PathBuf, &'static str, &'static str)> {
+    let mut dirs = Vec::new();
+    for line in cgroups.lines() {
+        let mut fields = line.splitn(3, ':');
+        let (Some(_id), Some(controllers), Some(path)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let (root, limit_file, usage_file) = if controllers.is_empty() {
+            ("/sys/fs/cgroup", "memory.max", "memory.current")
+        } else if controllers
+            .split(',')
+            .any(|controller| controller == "memory")
+        {
+            (
+                "/sys/fs/cgroup/memory",
+                "memory.limit_in_bytes",
+                "memory.usage_in_bytes",
+            )
+        } else {
+            continue;
+        };
+        // Within a container the cgroup namespace usually makes its own cgroup the root.
+        dirs.extend(Path::new(path).ancestors().map(|ancestor| {
+            let relative = ancestor.strip_prefix("/").unwrap_or(ancestor);
+            (Path::new(root).join(relative), limit_file, usage_file)
+        }));
+    }
+    dirs
+}
+
+/// Reads a limit or usage from a file of the cgroup file system. `None` if the file does not exist
+/// or no limit is set.
+fn read_limit(path: &Path) -> Option<u64> {
+    parse_limit(&fs::read_to_string(path).ok()?)
+}
+
+/// cgroup v2 writes `max` if no limit is set, cgroup v1 a number close to `i64::MAX`.
+fn parse_limit(value: &str) -> Option<u64> {
+    let value = value.trim().parse::<u64>().ok()?;
+    (value < i64::MAX as u64 / 2).then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{cgroup_memory_dirs, mem_available, parse_limit};
+
+    #[test]
+    fn parse_mem_available() {
+        let meminfo = "MemTotal:       16314424 kB\nMemFree:         1017748 kB\n\
+            MemAvailable:    8147200 kB\nBuffers:          123456 kB\n";
+        assert_eq!(Some(8147200 * 1024), mem_available(meminfo));
+        assert_eq!(None, mem_available("MemTotal:       16314424 kB\n"));
+    }
+
+    #[test]
+    fn parse_cgroup_limits() {
+        assert_eq!(Some(536870912), parse_limit("536870912\n"));
+        assert_eq!(None, parse_limit("max\n"));
+        assert_eq!(None, parse_limit("9223372036854771712\n"));
+    }
+
+    #[test]
+    fn memory_controller_of_cgroup_and_ancestors() {
+        let v2 = "0::/system.slice/export.service\n";
+        assert_eq!(
+            vec![
+                (
+                    PathBuf::from("/sys/fs/cgroup/system.slice/export.service"),
+                    "memory.max",
+                    "memory.current"
+                ),
+                (
+                    PathBuf::from("/sys/fs/cgroup/system.slice"),
+                    "memory.max",
+                    "memory.current"
+                ),
+                (
+                    PathBuf::from("/sys/fs/cgroup"),
+                    "memory.max",
+                    "memory.current"
+                ),
+            ],
+            cgroup_memory_dirs(v2)
+        );
+
+        let v1 = "12:cpu,cpuacct:/docker/abc\n4:memory:/\n1:name=systemd:/docker/abc\n";
+        assert_eq!(
+            vec![(
+                PathBuf::from("/sys/fs/cgroup/memory"),
+                "memory.limit_in_bytes",
+                "memory.usage_in_bytes"
+            )],
+            cgroup_memory_dirs(v1)
+        );
+
+        assert!(cgroup_memory_dirs("").is_empty());
+    }
+}
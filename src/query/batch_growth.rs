@@ -0,0 +1,105 @@
+//! Fetches into buffers of growing size, so small result sets do not allocate a buffer for the
+//! maximum batch size. See `--adaptive-batch-size`.
+
+use std::{marker::PhantomData, mem::ManuallyDrop};
+
+use log::info;
+use odbc_api::{
+    buffers::ColumnarAnyBuffer,
+    handles::{Statement, StatementImpl},
+    Cursor, RowSetBuffer,
+};
+
+use super::{
+    concurrent_fetch::{fetch_into, unbind},
+    truncation::has_truncated_values,
+};
+
+/// Number of rows of the first batch. Large enough to not slow down fetching noticeably, small
+/// enough to be negligible in size.
+pub const INITIAL_BATCH_SIZE_ROWS: usize = 1024;
+
+/// Fetches the first batch into a buffer of [`INITIAL_BATCH_SIZE_ROWS`] rows. Each time a batch
+/// fills the buffer completely, more rows are likely to follow, so the buffer is replaced by one
+/// twice as large, until it reaches the maximum batch size.
+pub struct GrowingFetch<'c> {
+    /// Statement handle of the cursor. Owned by the cursor, so it must not be freed.
+    statement: ManuallyDrop<StatementImpl<'c>>,
+    buffer: ColumnarAnyBuffer,
+    max_rows: usize,
+    /// Allocates a buffer with capacity for the given number of rows.
+    allocate: Box<dyn Fn(usize) -> ColumnarAnyBuffer + 'c>,
+    /// The cursor must not be used or dropped while we fetch from its statement handle.
+    _cursor: PhantomData<&'c mut ()>,
+}
+
+impl<'c> GrowingFetch<'c> {
+    pub fn new(
+        cursor: &'c mut impl Cursor,
+        max_rows: usize,
+        allocate: impl Fn(usize) -> ColumnarAnyBuffer + 'c,
+    ) -> Self {
+        let handle = cursor.as_stmt_ref().as_sys();
+        // Safety: The handle stays valid as long as the cursor is borrowed.
+        let statement = ManuallyDrop::new(unsafe { StatementImpl::new(handle) });
+        let buffer = allocate(INITIAL_BATCH_SIZE_ROWS.min(max_rows));
+        Self {
+            statement,
+            buffer,
+            max_rows,
+            allocate: Box::new(allocate),
+            _cursor: PhantomData,
+        }
+    }
+
+    /// Next row set, together with whether the driver reported truncated values for it. `None` once
+    /// the result set is consumed.
+    pub fn fetch(&mut self) -> Result<Option<(&ColumnarAnyBuffer, bool)>, odbc_api::Error> {
+        let capacity = self.buffer.row_array_size();
+        let rows = next_capacity(capacity, self.buffer.num_rows(), self.max_rows);
+        if rows != capacity {
+            info!("Growing batch size to {rows} rows.");
+            // Bind the new buffer before the old one is deallocated.
+            let mut buffer = (self.allocate)(rows);
+            let has_row = unsafe { fetch_into(&mut self.statement, &mut buffer) };
+            self.buffer = buffer;
+            let truncated = has_truncated_values(&*self.statement);
+            return Ok(has_row?.then_some((&self.buffer, truncated)));
+        }
+        let has_row = unsafe { fetch_into(&mut self.statement, &mut self.buffer) }?;
+        let truncated = has_truncated_values(&*self.statement);
+        Ok(has_row.then_some((&self.buffer, truncated)))
+    }
+}
+
+impl Drop for GrowingFetch<'_> {
+    fn drop(&mut self) {
+        unbind(&mut self.statement);
+    }
+}
+
+/// Capacity of the buffer for the next batch. Doubles if the last batch filled the buffer, but does
+/// not exceed `max_rows`.
+fn next_capacity(capacity: usize, num_rows: usize, max_rows: usize) -> usize {
+    if num_rows == capacity {
+        capacity.saturating_mul(2).min(max_rows)
+    } else {
+        capacity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::next_capacity;
+
+    #[test]
+    fn grow_while_batches_are_full() {
+        assert_eq!(2048, next_capacity(1024, 1024, 65535));
+        assert_eq!(65535, next_capacity(65534, 65534, 65535));
+        assert_eq!(65535, next_capacity(65535, 65535, 65535));
+        // Last batch of the result set
+        assert_eq!(1024, next_capacity(1024, 10, 65535));
+        // Nothing fetched yet
+        assert_eq!(1024, next_capacity(1024, 0, 65535));
+    }
+}
@@ -2,12 +2,19 @@ use std::cmp::min;
 
 use anyhow::bail;
 use bytesize::ByteSize;
+use log::info;
+
+use super::available_memory::available_memory;
 
 #[cfg(target_pointer_width = "64")]
 const DEFAULT_BATCH_SIZE_BYTES: ByteSize = ByteSize::gib(2); // 2GB
 #[cfg(target_pointer_width = "32")]
 const DEFAULT_BATCH_SIZE_BYTES: ByteSize = ByteSize::gib(1); // 1GB
 
+/// Share of the available memory used for the fetch buffer by default. The remainder is left for
+/// encoding and compressing the batches, as well as for other processes.
+const DEFAULT_SHARE_OF_AVAILABLE_MEMORY: u64 = 4;
+
 /// We limit the maximum numbers of rows to 65535 by default to avoid trouble with ODBC drivers
 /// using a 16Bit integer to represent fetch size. Most drivers work fine though with larger
 /// batches. Anyway the trade off seems worth it because 65535 is already a pretty large batch size
@@ -15,6 +22,7 @@ const DEFAULT_BATCH_SIZE_BYTES: ByteSize = ByteSize::gib(1); // 1GB
 const DEFAULT_BATCH_SIZE_ROWS: usize = u16::MAX as usize; // 65535 rows
 
 /// Describes how we limit the size of individual parquet files.
+#[derive(Clone, Copy)]
 pub enum FileSizeLimit {
     /// No file size limit is applied. The entire output is written to one parquet file.
     None,
@@ -54,8 +62,25 @@ impl FileSizeLimit {
     }
 }
 
+/// Memory limit of a batch, if the user specified neither rows nor memory. A quarter of the memory
+/// available to this process (respecting the limit of its container), but no more than
+/// [`DEFAULT_BATCH_SIZE_BYTES`].
+fn default_batch_size_memory() -> ByteSize {
+    let Some(available) = available_memory() else {
+        return DEFAULT_BATCH_SIZE_BYTES;
+    };
+    let memory =
+        ByteSize::b(available / DEFAULT_SHARE_OF_AVAILABLE_MEMORY).min(DEFAULT_BATCH_SIZE_BYTES);
+    info!(
+        "{} of memory available. Limiting the size of a batch to {memory}.",
+        ByteSize::b(available)
+    );
+    memory
+}
+
 /// Batches can be limitied by either number of rows or the total size of the rows in the batch in
 /// bytes.
+#[derive(Clone, Copy)]
 pub enum BatchSizeLimit {
     Rows(usize),
     Bytes(ByteSize),
@@ -70,12 +95,22 @@ impl BatchSizeLimit {
             // User specified nothing => Use default
             (None, None) => BatchSizeLimit::Both {
                 rows: DEFAULT_BATCH_SIZE_ROWS,
-                memory: DEFAULT_BATCH_SIZE_BYTES,
+                memory: default_batch_size_memory(),
             },
             (Some(rows), Some(memory)) => BatchSizeLimit::Both { rows, memory },
         }
     }
 
+    /// Memory limit of a batch in bytes, if any.
+    pub fn memory(&self) -> Option<usize> {
+        match self {
+            BatchSizeLimit::Rows(_) => None,
+            BatchSizeLimit::Bytes(memory) | BatchSizeLimit::Both { memory, .. } => {
+                Some(memory.as_u64().try_into().unwrap())
+            }
+        }
+    }
+
     pub fn batch_size_in_rows(
         &self,
         total_mem_usage_per_row: usize,
@@ -9,14 +9,14 @@ use parquet::{
     schema::types::Type,
 };
 
-use crate::parquet_buffer::{BufferedDataType, ParquetBuffer};
+use crate::parquet_buffer::{shared_byte_arrays, BufferedDataType, ParquetBuffer};
 
 use super::strategy::ColumnFetchStrategy;
 
 pub struct Binary<Pdt> {
     repetition: Repetition,
     length: usize,
-    _phantom: PhantomData<Pdt>,
+    _phantom: PhantomData<fn() -> Pdt>,
 }
 
 impl<Pdt> Binary<Pdt> {
@@ -69,12 +69,35 @@ where
         column_view: AnyColumnView,
     ) -> Result<(), Error> {
         let cw = get_typed_column_writer_mut::<Pdt>(column_writer);
+        let is_fixed_length = Pdt::get_physical_type() == PhysicalType::FIXED_LEN_BYTE_ARRAY;
         if let AnyColumnView::Binary(view) = column_view {
+            // Fast path for required columns, mirroring `IdenticalRequired`. The values are
+            // written without allocating each of them separately.
+            if self.repetition == Repetition::REQUIRED
+                && !is_fixed_length
+                && parquet_buffer.writes_values_as_is()
+            {
+                if let Some(values) = view.iter().collect::<Option<Vec<&[u8]>>>() {
+                    let values: Vec<Pdt::T> = shared_byte_arrays(&values)
+                        .into_iter()
+                        .map(Into::into)
+                        .collect();
+                    cw.write_batch(&values, None, None)?;
+                    return Ok(());
+                }
+            }
             parquet_buffer.write_optional(
                 cw,
                 view.iter().map(|maybe_bytes| {
                     maybe_bytes.map(|bytes| {
-                        let byte_array: ByteArray = bytes.to_owned().into();
+                        let mut bytes = bytes.to_owned();
+                        if is_fixed_length {
+                            // Like `BINARY(n)` in SQL, pad shorter values with zeros. Some drivers
+                            // return them without padding, which would otherwise corrupt the
+                            // fixed length column.
+                            bytes.resize(self.length, 0);
+                        }
+                        let byte_array: ByteArray = bytes.into();
                         // Transforms ByteArray into FixedLenByteArray or does nothing depending `Pdt`.
                         let out: Pdt::T = byte_array.into();
                         out
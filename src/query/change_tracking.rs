@@ -0,0 +1,165 @@
+//! Exports the rows of a SQL Server table changed since the previous export, as recorded by change
+//! tracking. See `--change-tracking`. The version up to which changes have been exported is kept in
+//! a state file.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Error};
+use log::info;
+use odbc_api::{Connection, Cursor, IntoParameter, Nullable, ResultSetMetadata};
+use serde_json::{json, Value};
+
+use super::source_table::quote_table;
+
+/// Columns of `CHANGETABLE(CHANGES ...)` which are not part of the primary key start with this
+/// prefix.
+const SYSTEM_COLUMN_PREFIX: &str = "SYS_CHANGE_";
+
+pub struct ChangeTracking {
+    /// Name of the table, as passed at the command line.
+    table: String,
+    state_file: PathBuf,
+    /// Changes after this version are exported.
+    last_version: i64,
+    /// Changes up to this version are exported. Recorded in the state file once the export
+    /// succeeded.
+    current_version: i64,
+    /// Selects the operation (`I`, `U` or `D`) and the primary key of each changed row.
+    pub query: String,
+}
+
+impl ChangeTracking {
+    pub fn new(
+        conn: &Connection<'_>,
+        db_name: &str,
+        table: &str,
+        state_file: PathBuf,
+    ) -> Result<Self, Error> {
+        if db_name != "Microsoft SQL Server" {
+            bail!("Change tracking is only supported for Microsoft SQL Server.")
+        }
+        let quoted_table = quote_table(table, db_name)?;
+        let Some(min_valid_version) = query_version(
+            conn,
+            "SELECT CHANGE_TRACKING_MIN_VALID_VERSION(OBJECT_ID(?))",
+            Some(&quoted_table),
+        )?
+        else {
+            bail!("Change tracking is not enabled for table '{table}'.")
+        };
+        let last_version = match read_state(&state_file, table)? {
+            Some(last_version) if last_version < min_valid_version => bail!(
+                "Changes since version {last_version} of table '{table}' have already been cleaned \
+                up. Export the entire table and remove the state file '{}'.",
+                state_file.display()
+            ),
+            Some(last_version) => last_version,
+            // Without a state file, all changes still retained are exported.
+            None => min_valid_version,
+        };
+        let current_version =
+            query_version(conn, "SELECT CHANGE_TRACKING_CURRENT_VERSION()", None)?
+                .context("Change tracking is not enabled for the database.")?;
+
+        let changes = format!("CHANGETABLE(CHANGES {quoted_table}, {last_version}) AS CT");
+        let key_columns = key_columns(conn, &changes)?;
+        let query = format!(
+            "SELECT CT.SYS_CHANGE_OPERATION AS operation, {} FROM {changes} \
+            WHERE CT.SYS_CHANGE_VERSION <= {current_version}",
+            key_columns.join(", ")
+        );
+        info!("Exporting changes from version {last_version} to {current_version}: {query}");
+        Ok(Self {
+            table: table.to_owned(),
+            state_file,
+            last_version,
+            current_version,
+            query,
+        })
+    }
+
+    /// Records the exported version in the state file, so the next export continues after it. Must
+    /// be called after the export succeeded.
+    pub fn commit(&self) -> Result<(), Error> {
+        let state = json!({ "table": self.table, "version": self.current_version });
+        let path = &self.state_file;
+        fs::write(path, serde_json::to_string_pretty(&state).unwrap())
+            .with_context(|| format!("Failed to write state file '{}'.", path.display()))?;
+        info!(
+            "Exported changes of table '{}' from version {} to {}.",
+            self.table, self.last_version, self.current_version
+        );
+        Ok(())
+    }
+}
+
+/// Version recorded in the state file by the previous export. `None` if there is no state file.
+fn read_state(path: &Path, table: &str) -> Result<Option<i64>, Error> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read state file '{}'.", path.display()))?;
+    parse_state(&text, table)
+        .with_context(|| format!("Invalid state file '{}'.", path.display()))
+        .map(Some)
+}
+
+fn parse_state(text: &str, table: &str) -> Result<i64, Error> {
+    let state: Value = serde_json::from_str(text)?;
+    let (Some(state_table), Some(version)) = (state["table"].as_str(), state["version"].as_i64())
+    else {
+        bail!("State must contain table and version.")
+    };
+    if state_table != table {
+        bail!("State has been recorded for table '{state_table}', not '{table}'.")
+    }
+    Ok(version)
+}
+
+/// Executes a query returning a single version number, or NULL.
+fn query_version(
+    conn: &Connection<'_>,
+    query: &str,
+    parameter: Option<&str>,
+) -> Result<Option<i64>, Error> {
+    let cursor = match parameter {
+        Some(parameter) => conn.execute(query, &parameter.into_parameter())?,
+        None => conn.execute(query, ())?,
+    };
+    let mut cursor = cursor.context("Query for change tracking version returned no result set.")?;
+    let mut version = Nullable::<i64>::null();
+    if let Some(mut row) = cursor.next_row()? {
+        row.get_data(1, &mut version)?;
+    }
+    Ok(version.into_opt())
+}
+
+/// Columns of the primary key, as returned by `CHANGETABLE(CHANGES ...)`, qualified with `CT`.
+fn key_columns(conn: &Connection<'_>, changes: &str) -> Result<Vec<String>, Error> {
+    let mut prepared = conn.prepare(&format!("SELECT * FROM {changes}"))?;
+    let mut key_columns = Vec::new();
+    for column_number in 1..=prepared.num_result_cols()? {
+        let name = prepared.col_name(column_number as u16)?;
+        if !name.starts_with(SYSTEM_COLUMN_PREFIX) {
+            key_columns.push(format!("CT.[{}]", name.replace(']', "]]")));
+        }
+    }
+    Ok(key_columns)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_state;
+
+    #[test]
+    fn parse_state_file() {
+        let state = r#"{"table": "dbo.Orders", "version": 42}"#;
+        assert_eq!(42, parse_state(state, "dbo.Orders").unwrap());
+        assert!(parse_state(state, "dbo.Customers").is_err());
+        assert!(parse_state(r#"{"table": "dbo.Orders"}"#, "dbo.Orders").is_err());
+    }
+}
@@ -0,0 +1,232 @@
+//! Progress of an export recorded in a checkpoint file, so an export which crashed or has been
+//! killed can be resumed. See `--checkpoint` and `--resume`.
+
+use std::{
+    ffi::OsString,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Error};
+use log::info;
+use odbc_api::buffers::ColumnarAnyBuffer;
+use serde_json::{json, Value};
+
+use super::{parquet_writer::ParquetWriter, value_text::column_as_text};
+
+/// Progress of an export, as of the last completed file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Checkpoint {
+    /// Query the checkpoint has been recorded for.
+    pub query: String,
+    /// Number of files completed. Resuming continues with the next one.
+    pub files: u32,
+    /// Number of rows written into the completed files.
+    pub rows: u64,
+    /// Text representation of the first `--sorted-by` column in the last row of the completed
+    /// files. `None` if the result set is not sorted, or the value is NULL or unknown.
+    pub key: Option<String>,
+}
+
+impl Checkpoint {
+    /// Checkpoint of an export which did not complete any files yet.
+    pub fn start(query: String) -> Self {
+        Self {
+            query,
+            files: 0,
+            rows: 0,
+            key: None,
+        }
+    }
+
+    /// Location of the checkpoint file for an output, e.g. `out.par.checkpoint`.
+    pub fn path(output: &Path) -> PathBuf {
+        let mut path = OsString::from(output);
+        path.push(".checkpoint");
+        path.into()
+    }
+
+    /// Reads the checkpoint. `None` if the file does not exist.
+    pub fn read(path: &Path) -> Result<Option<Self>, Error> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read checkpoint '{}'.", path.display()))?;
+        Self::parse(&text)
+            .with_context(|| format!("Invalid checkpoint '{}'.", path.display()))
+            .map(Some)
+    }
+
+    fn parse(text: &str) -> Result<Self, Error> {
+        let value: Value = serde_json::from_str(text)?;
+        let (Some(query), Some(files), Some(rows), Some(key)) = (
+            value["query"].as_str(),
+            value["files"].as_u64(),
+            value["rows"].as_u64(),
+            value.get("key"),
+        ) else {
+            bail!("Checkpoint must contain query, files, rows and key.")
+        };
+        let key = match key {
+            Value::Null => None,
+            Value::String(key) => Some(key.clone()),
+            _ => bail!("Key must be a string or null."),
+        };
+        Ok(Self {
+            query: query.to_owned(),
+            files: files.try_into()?,
+            rows,
+            key,
+        })
+    }
+
+    /// Replaces the checkpoint file. The new checkpoint is written next to it first and then
+    /// renamed, so a crash never leaves a partially written checkpoint behind.
+    pub fn write(&self, path: &Path) -> Result<(), Error> {
+        let checkpoint = json!({
+            "query": self.query,
+            "files": self.files,
+            "rows": self.rows,
+            "key": self.key,
+        });
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        fs::write(
+            &tmp_path,
+            serde_json::to_string_pretty(&checkpoint).unwrap(),
+        )?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
+
+/// Updates the checkpoint file while the export progresses.
+pub struct Checkpointing {
+    path: PathBuf,
+    /// Checkpoint the export has been resumed from, or the start of the export.
+    resumed: Checkpoint,
+    /// Position of the first `--sorted-by` column in the fetch buffer.
+    key_column: Option<usize>,
+    /// Number of rows written at the end of each batch, together with the key of its last row.
+    /// Only batches which are not part of a completed file yet are kept.
+    batch_ends: Vec<(u64, Option<String>)>,
+    /// Number of rows written so far, including the ones written before resuming.
+    rows: u64,
+    /// Number of files completed in this run, as of the last update of the checkpoint.
+    files: usize,
+}
+
+impl Checkpointing {
+    pub fn new(path: PathBuf, resumed: Checkpoint, key_column: Option<usize>) -> Self {
+        Self {
+            path,
+            rows: resumed.rows,
+            resumed,
+            key_column,
+            batch_ends: Vec::new(),
+            files: 0,
+        }
+    }
+
+    /// Must be called for each batch, before it is written. `num_rows` is the number of rows
+    /// written, which may be less than the number of rows in the buffer after resuming.
+    pub fn batch(&mut self, buffer: &ColumnarAnyBuffer, num_rows: usize) {
+        self.rows += num_rows as u64;
+        let key = self
+            .key_column
+            .and_then(|index| column_as_text(buffer.column(index)).pop().flatten());
+        self.batch_ends.push((self.rows, key));
+    }
+
+    /// Writes a new checkpoint, if the writer completed another file.
+    pub fn update(&mut self, writer: &ParquetWriter) -> Result<(), Error> {
+        let completed = writer.completed_files();
+        if completed.len() == self.files {
+            return Ok(());
+        }
+        self.files = completed.len();
+        let rows = self.resumed.rows
+            + completed
+                .iter()
+                .flat_map(|file| &file.row_groups)
+                .map(|row_group| row_group.num_rows() as u64)
+                .sum::<u64>();
+        // The key is unknown, if the file ended within a batch, which may happen if row groups
+        // span several batches.
+        let key = self
+            .batch_ends
+            .iter()
+            .find(|(end, _)| *end == rows)
+            .and_then(|(_, key)| key.clone());
+        self.batch_ends.retain(|(end, _)| *end > rows);
+        let checkpoint = Checkpoint {
+            query: self.resumed.query.clone(),
+            files: self.resumed.files + self.files as u32,
+            rows,
+            key,
+        };
+        checkpoint.write(&self.path)?;
+        info!(
+            "Checkpoint: {} files with {} rows complete.",
+            checkpoint.files, checkpoint.rows
+        );
+        Ok(())
+    }
+
+    /// Number of the first file written, if the export is resumed.
+    pub fn first_file(&self) -> u32 {
+        self.resumed.files + 1
+    }
+
+    /// Restores the checkpoint the export started with, after the files written since have been
+    /// removed.
+    pub fn reset(&self) -> Result<(), Error> {
+        if self.resumed.files == 0 {
+            self.remove()
+        } else {
+            self.resumed.write(&self.path)
+        }
+    }
+
+    /// Removes the checkpoint file, once the export is complete.
+    pub fn remove(&self) -> Result<(), Error> {
+        if self.path.exists() {
+            fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use super::Checkpoint;
+
+    #[test]
+    fn write_and_read_checkpoint() {
+        let dir = tempdir().unwrap();
+        let path = Checkpoint::path(&dir.path().join("out.par"));
+        assert!(Checkpoint::read(&path).unwrap().is_none());
+
+        let checkpoint = Checkpoint {
+            query: "SELECT * FROM Orders ORDER BY id".to_owned(),
+            files: 3,
+            rows: 3000,
+            key: Some("2999".to_owned()),
+        };
+        checkpoint.write(&path).unwrap();
+
+        assert!(path.ends_with("out.par.checkpoint"));
+        assert_eq!(Some(checkpoint), Checkpoint::read(&path).unwrap());
+    }
+
+    #[test]
+    fn checkpoint_without_key() {
+        let checkpoint =
+            Checkpoint::parse(r#"{"query": "SELECT 1", "files": 1, "rows": 1, "key": null}"#)
+                .unwrap();
+        assert_eq!(None, checkpoint.key);
+    }
+}
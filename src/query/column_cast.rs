@@ -0,0 +1,159 @@
+//! Overrides of the parquet type derived from the column description, see `--cast-file`. Useful if
+//! drivers report misleading metadata, e.g. a precision of 0 for decimals.
+
+use std::{fs, path::Path};
+
+use anyhow::{anyhow, bail, Context, Error};
+use parquet::{
+    basic::{ConvertedType, Repetition},
+    data_type::{DoubleType, FloatType, Int32Type, Int64Type},
+};
+
+use crate::enum_args::{number_as_from_str, NumberAs};
+
+use super::{
+    boolean::Boolean,
+    date::Date,
+    decimal::{RescaledDecimal, NUMBER_TEXT_LENGTH},
+    identical::{fetch_identical, fetch_identical_with_converted_type},
+    strategy::ColumnFetchStrategy,
+};
+
+/// Parquet type a column is cast to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnCast {
+    Boolean,
+    Int32,
+    Int64,
+    Float,
+    Double,
+    Decimal { precision: u8, scale: i32 },
+    Date,
+    Text,
+}
+
+/// Reads the casts of columns from a file mapping column names to types.
+pub fn read_cast_file(path: &Path) -> Result<Vec<(String, ColumnCast)>, Error> {
+    let yaml = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read cast file '{}'.", path.display()))?;
+    column_casts(&yaml).with_context(|| format!("Invalid cast file '{}'.", path.display()))
+}
+
+/// Parses a flat YAML mapping of column names to types. E.g. `amount: decimal(38,10)`. Nested
+/// mappings, lists and multi line strings are not supported.
+fn column_casts(yaml: &str) -> Result<Vec<(String, ColumnCast)>, Error> {
+    let mut casts: Vec<(String, ColumnCast)> = Vec::new();
+    for (line_number, line) in yaml.lines().enumerate() {
+        let line_number = line_number + 1;
+        let content = line.split(" #").next().unwrap().trim_end();
+        if content.trim_start().is_empty() || content.starts_with('#') || content == "---" {
+            continue;
+        }
+        if content.starts_with(char::is_whitespace) {
+            bail!("Line {line_number}: Nested values are not supported.")
+        }
+        let (name, cast) = content
+            .rsplit_once(':')
+            .ok_or_else(|| anyhow!("Line {line_number}: Expected 'COLUMN: TYPE'."))?;
+        let name = unquote(name.trim());
+        let cast = column_cast_from_str(unquote(cast.trim()))
+            .with_context(|| format!("Line {line_number}: Invalid type of column '{name}'."))?;
+        if casts.iter().any(|(column, _)| column == name) {
+            bail!("Line {line_number}: Column '{name}' is cast more than once.")
+        }
+        casts.push((name.to_owned(), cast));
+    }
+    Ok(casts)
+}
+
+fn unquote(text: &str) -> &str {
+    ['"', '\'']
+        .iter()
+        .find_map(|&quote| text.strip_prefix(quote)?.strip_suffix(quote))
+        .unwrap_or(text)
+}
+
+/// Parses one of `boolean`, `int32`, `int64`, `float`, `double`, `decimal(PRECISION,SCALE)`,
+/// `date` or `text`.
+fn column_cast_from_str(source: &str) -> Result<ColumnCast, Error> {
+    let cast = match source.to_ascii_lowercase().as_str() {
+        "boolean" => ColumnCast::Boolean,
+        "int32" => ColumnCast::Int32,
+        "int64" => ColumnCast::Int64,
+        "float" => ColumnCast::Float,
+        "double" => ColumnCast::Double,
+        "date" => ColumnCast::Date,
+        "text" => ColumnCast::Text,
+        lower if lower.starts_with("decimal") => {
+            let NumberAs::Decimal { precision, scale } = number_as_from_str(source)? else {
+                unreachable!("Only decimals start with 'decimal'")
+            };
+            ColumnCast::Decimal { precision, scale }
+        }
+        _ => bail!(
+            "Type must be one of 'boolean', 'int32', 'int64', 'float', 'double', \
+            'decimal(PRECISION,SCALE)', 'date' or 'text', not '{source}'."
+        ),
+    };
+    Ok(cast)
+}
+
+/// Fetch strategy of a column cast to `cast`. `None` for text, whose length depends on the column.
+pub fn cast_fetch_strategy(
+    cast: ColumnCast,
+    repetition: Repetition,
+) -> Option<Box<dyn ColumnFetchStrategy>> {
+    let is_optional = repetition == Repetition::OPTIONAL;
+    let strategy: Box<dyn ColumnFetchStrategy> = match cast {
+        ColumnCast::Boolean => Box::new(Boolean::new(repetition)),
+        ColumnCast::Int32 => {
+            fetch_identical_with_converted_type::<Int32Type>(is_optional, ConvertedType::INT_32)
+        }
+        ColumnCast::Int64 => fetch_identical::<Int64Type>(is_optional),
+        ColumnCast::Float => fetch_identical::<FloatType>(is_optional),
+        ColumnCast::Double => fetch_identical::<DoubleType>(is_optional),
+        ColumnCast::Decimal { precision, scale } => Box::new(RescaledDecimal::new(
+            repetition,
+            precision,
+            scale,
+            NUMBER_TEXT_LENGTH,
+        )),
+        ColumnCast::Date => Box::new(Date::new(repetition)),
+        ColumnCast::Text => return None,
+    };
+    Some(strategy)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{column_casts, ColumnCast};
+
+    #[test]
+    fn parse_cast_file() {
+        let yaml = "---\n\
+            # Driver reports a precision of 0\n\
+            amount: decimal(38,10)\n\
+            \"flags\": INT32 # stored as text\n\
+            \n\
+            'order date': date\n";
+        assert_eq!(
+            vec![
+                (
+                    "amount".to_owned(),
+                    ColumnCast::Decimal {
+                        precision: 38,
+                        scale: 10
+                    }
+                ),
+                ("flags".to_owned(), ColumnCast::Int32),
+                ("order date".to_owned(), ColumnCast::Date),
+            ],
+            column_casts(yaml).unwrap()
+        );
+
+        assert!(column_casts("amount: money").is_err());
+        assert!(column_casts("amount").is_err());
+        assert!(column_casts("amount:\n  type: int32").is_err());
+        assert!(column_casts("a: int32\na: int64").is_err());
+    }
+}
@@ -0,0 +1,77 @@
+//! Selects the columns of the result set to write, see `--columns` and `--exclude-columns`.
+
+/// `true` if the column `name` is written. It must match any of the `include` patterns, unless
+/// there are none, and none of the `exclude` patterns.
+pub fn is_column_selected(name: &str, include: &[String], exclude: &[String]) -> bool {
+    (include.is_empty() || include.iter().any(|pattern| glob_match(pattern, name)))
+        && !exclude.iter().any(|pattern| glob_match(pattern, name))
+}
+
+/// Matches `text` against a glob `pattern`, in which `*` matches any sequence of characters and `?`
+/// matches a single character. Matching is case sensitive.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    // Position in pattern and text right after the last `*`, to backtrack to if the rest does not
+    // match.
+    let mut backtrack: Option<(usize, usize)> = None;
+    let (mut p, mut t) = (0, 0);
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                p += 1;
+                backtrack = Some((p, t));
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                // Let the last `*` consume one more character.
+                Some((star_p, star_t)) => {
+                    p = star_p;
+                    t = star_t + 1;
+                    backtrack = Some((star_p, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod test {
+    use super::{glob_match, is_column_selected};
+
+    #[test]
+    fn match_globs() {
+        assert!(glob_match("amount", "amount"));
+        assert!(!glob_match("amount", "amounts"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("raw_*", "raw_payload"));
+        assert!(glob_match("*_at", "created_at"));
+        assert!(glob_match("*pass*", "user_password_hash"));
+        assert!(glob_match("col?", "col1"));
+        assert!(!glob_match("col?", "col"));
+        assert!(glob_match("a*b*c", "axxbyybc"));
+        assert!(!glob_match("a*b*c", "axxbyyb"));
+        assert!(!glob_match("Amount", "amount"));
+    }
+
+    #[test]
+    fn select_columns() {
+        let patterns = |patterns: &[&str]| -> Vec<String> {
+            patterns.iter().map(|&pattern| pattern.to_owned()).collect()
+        };
+        assert!(is_column_selected("id", &[], &[]));
+        assert!(is_column_selected("id", &patterns(&["id", "name"]), &[]));
+        assert!(!is_column_selected("blob", &patterns(&["id", "name"]), &[]));
+        assert!(!is_column_selected("raw_blob", &[], &patterns(&["raw_*"])));
+        assert!(!is_column_selected(
+            "raw_id",
+            &patterns(&["*id"]),
+            &patterns(&["raw_*"])
+        ));
+    }
+}
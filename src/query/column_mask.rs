@@ -0,0 +1,135 @@
+//! Hash or redact the values of columns holding personal data, see `--mask`.
+
+use anyhow::Error;
+use odbc_api::buffers::{AnyColumnView, BufferDescription};
+use parquet::{
+    basic::{ConvertedType, LogicalType, Type as PhysicalType},
+    column::writer::ColumnWriter,
+    data_type::ByteArray,
+    schema::types::Type,
+};
+use sha2::{Digest, Sha256};
+
+use crate::{enum_args::Mask, parquet_buffer::ParquetBuffer};
+
+use super::{strategy::ColumnFetchStrategy, value_text::column_as_text};
+
+/// Wraps the strategy of a column, so its values are hashed or redacted before they are written.
+/// Redacted columns must be optional.
+pub struct MaskedColumn {
+    inner: Box<dyn ColumnFetchStrategy>,
+    mask: Mask,
+}
+
+impl MaskedColumn {
+    pub fn new(inner: Box<dyn ColumnFetchStrategy>, mask: Mask) -> Self {
+        Self { inner, mask }
+    }
+}
+
+impl ColumnFetchStrategy for MaskedColumn {
+    fn parquet_type(&self, name: &str) -> Type {
+        let inner = self.inner.parquet_type(name);
+        match &self.mask {
+            // Hashes are written as text, regardless of the type of the column.
+            Mask::Sha256 { .. } => Type::primitive_type_builder(name, PhysicalType::BYTE_ARRAY)
+                .with_logical_type(Some(LogicalType::String))
+                .with_converted_type(ConvertedType::UTF8)
+                .with_repetition(inner.get_basic_info().repetition())
+                .build()
+                .unwrap(),
+            Mask::Redact => inner,
+        }
+    }
+
+    fn buffer_description(&self) -> BufferDescription {
+        self.inner.buffer_description()
+    }
+
+    fn copy_odbc_to_parquet(
+        &self,
+        parquet_buffer: &mut ParquetBuffer,
+        column_writer: &mut ColumnWriter,
+        column_view: AnyColumnView,
+    ) -> Result<(), Error> {
+        match &self.mask {
+            Mask::Sha256 { salt } => {
+                let ColumnWriter::ByteArrayColumnWriter(cw) = column_writer else {
+                    panic!("Hashed columns must be written into a byte array column writer.")
+                };
+                let hashes = column_as_text(column_view).into_iter().map(|text| {
+                    text.map(|text| ByteArray::from(sha256_hex(salt, &text).into_bytes()))
+                });
+                parquet_buffer.write_optional(cw, hashes)
+            }
+            Mask::Redact => {
+                // Write all rows as NULL. The values are not even converted.
+                let num_rows = parquet_buffer.def_levels.len();
+                parquet_buffer.set_null_rows(Some(vec![true; num_rows]));
+                let result =
+                    self.inner
+                        .copy_odbc_to_parquet(parquet_buffer, column_writer, column_view);
+                parquet_buffer.set_null_rows(None);
+                result
+            }
+        }
+    }
+}
+
+/// SHA-256 of `salt` followed by `text`, as lower case hex digits.
+fn sha256_hex(salt: &str, text: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt.as_bytes());
+    hasher.update(text.as_bytes());
+    hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::enum_args::{mask_from_str, Mask};
+
+    use super::sha256_hex;
+
+    #[test]
+    fn parse_masks() {
+        assert_eq!(
+            (
+                "email".to_owned(),
+                Mask::Sha256 {
+                    salt: String::new()
+                }
+            ),
+            mask_from_str("email:sha256").unwrap()
+        );
+        assert_eq!(
+            (
+                "email".to_owned(),
+                Mask::Sha256 {
+                    salt: "pepper:42".to_owned()
+                }
+            ),
+            mask_from_str("email:sha256:pepper:42").unwrap()
+        );
+        assert_eq!(
+            ("phone".to_owned(), Mask::Redact),
+            mask_from_str("phone:REDACT").unwrap()
+        );
+        assert!(mask_from_str("email").is_err());
+        assert!(mask_from_str(":redact").is_err());
+        assert!(mask_from_str("phone:redact:salt").is_err());
+        assert!(mask_from_str("email:md5").is_err());
+    }
+
+    #[test]
+    fn salted_sha256() {
+        assert_eq!(
+            "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824",
+            sha256_hex("", "hello")
+        );
+        assert_eq!(sha256_hex("", "salthello"), sha256_hex("salt", "hello"));
+    }
+}
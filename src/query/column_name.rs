@@ -0,0 +1,110 @@
+//! Sanitizing column names, so they are accepted by engines like Hive, Spark or BigQuery. See
+//! `--sanitize-column-names`.
+
+use crate::enum_args::SanitizeColumnNames;
+
+/// Column names, which are unique after being sanitized.
+#[derive(Default)]
+pub struct ColumnNames {
+    names: Vec<String>,
+}
+
+impl ColumnNames {
+    /// Sanitizes `name` and appends a suffix like `_2`, should the result be taken by a previous
+    /// column.
+    pub fn sanitize(&mut self, name: &str, mode: SanitizeColumnNames) -> String {
+        let base = match mode {
+            SanitizeColumnNames::Lower => name.to_lowercase(),
+            SanitizeColumnNames::SnakeCase => snake_case(name, false),
+            SanitizeColumnNames::Strict => snake_case(name, true),
+        };
+        let base = if base.is_empty() {
+            "column".to_owned()
+        } else {
+            base
+        };
+        let mut name = base.clone();
+        let mut suffix = 2;
+        while self.names.contains(&name) {
+            name = format!("{base}_{suffix}");
+            suffix += 1;
+        }
+        self.names.push(name.clone());
+        name
+    }
+}
+
+/// Lower case with words separated by `_`, e.g. `Order Id` or `OrderID` become `order_id`. Any
+/// character which is not alphanumeric separates words. If `ascii_only` is set, non ASCII
+/// characters do so, too, and names starting with a digit are prefixed with `_`.
+fn snake_case(name: &str, ascii_only: bool) -> String {
+    let is_word_char = |c: char| {
+        if ascii_only {
+            c.is_ascii_alphanumeric()
+        } else {
+            c.is_alphanumeric()
+        }
+    };
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::with_capacity(name.len());
+    for (index, &c) in chars.iter().enumerate() {
+        if !is_word_char(c) {
+            if !snake.is_empty() && !snake.ends_with('_') {
+                snake.push('_');
+            }
+            continue;
+        }
+        if c.is_uppercase() && index > 0 && !snake.ends_with('_') {
+            let previous = chars[index - 1];
+            let next_is_lower = chars.get(index + 1).is_some_and(|next| next.is_lowercase());
+            // Word boundary in `orderId` or `HTTPServer`
+            if previous.is_lowercase()
+                || previous.is_numeric()
+                || previous.is_uppercase() && next_is_lower
+            {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+    let snake = snake.trim_end_matches('_');
+    if ascii_only && snake.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{snake}")
+    } else {
+        snake.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::enum_args::SanitizeColumnNames;
+
+    use super::{snake_case, ColumnNames};
+
+    #[test]
+    fn snake_case_names() {
+        assert_eq!("order_id", snake_case("Order Id", false));
+        assert_eq!("order_id", snake_case("OrderID", false));
+        assert_eq!("order_id", snake_case("orderId", false));
+        assert_eq!("http_server", snake_case("HTTPServer", false));
+        assert_eq!("unit_price_eur", snake_case("Unit Price (EUR)", false));
+        assert_eq!("größe", snake_case("Größe", false));
+        assert_eq!("gr_e", snake_case("Größe", true));
+        assert_eq!("_2nd_address", snake_case("2nd address", true));
+        assert_eq!("a1_b", snake_case("__a1 b__", false));
+    }
+
+    #[test]
+    fn deduplicate_sanitized_names() {
+        let mut names = ColumnNames::default();
+        let mode = SanitizeColumnNames::SnakeCase;
+        assert_eq!("order_id", names.sanitize("Order Id", mode));
+        assert_eq!("order_id_2", names.sanitize("OrderID", mode));
+        assert_eq!("order_id_3", names.sanitize("order_id", mode));
+        assert_eq!("column", names.sanitize("#", mode));
+        assert_eq!(
+            "amount",
+            names.sanitize("AMOUNT", SanitizeColumnNames::Lower)
+        );
+    }
+}
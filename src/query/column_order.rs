@@ -0,0 +1,75 @@
+//! Order of the columns in the output, independent of their order in the result set. See
+//! `--column-order` and `--column-order-file`.
+
+use std::{fs, path::Path};
+
+use anyhow::{bail, Context, Error};
+
+/// Reads the names of the columns in the desired order from a file, one name per line. Empty lines
+/// and lines starting with `#` are ignored.
+pub fn read_column_order_file(path: &Path) -> Result<Vec<String>, Error> {
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read column order file '{}'.", path.display()))?;
+    Ok(text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_owned)
+        .collect())
+}
+
+/// Moves the columns named in `order` to the front, in that order. All other columns follow in
+/// their original order.
+pub fn reorder_columns<T>(
+    columns: Vec<T>,
+    name: impl Fn(&T) -> &str,
+    order: &[String],
+) -> Result<Vec<T>, Error> {
+    let mut columns: Vec<Option<T>> = columns.into_iter().map(Some).collect();
+    let mut reordered = Vec::with_capacity(columns.len());
+    for (position, ordered) in order.iter().enumerate() {
+        if order[..position].contains(ordered) {
+            bail!("Column '{ordered}' is listed more than once in the column order.")
+        }
+        let Some(column) = columns
+            .iter_mut()
+            .find(|column| column.as_ref().is_some_and(|c| name(c) == ordered))
+        else {
+            bail!("Column order column '{ordered}' is not part of the result set.")
+        };
+        reordered.push(column.take().unwrap());
+    }
+    reordered.extend(columns.into_iter().flatten());
+    Ok(reordered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::reorder_columns;
+
+    fn reorder(columns: &[&'static str], order: &[&str]) -> Result<Vec<&'static str>, String> {
+        let order: Vec<String> = order.iter().map(|&name| name.to_owned()).collect();
+        reorder_columns(columns.to_vec(), |&name| name, &order).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn listed_columns_come_first() {
+        assert_eq!(
+            vec!["c", "a", "b", "d"],
+            reorder(&["a", "b", "c", "d"], &["c", "a"]).unwrap()
+        );
+        assert_eq!(vec!["a", "b"], reorder(&["a", "b"], &[]).unwrap());
+    }
+
+    #[test]
+    fn reject_unknown_and_duplicate_columns() {
+        assert_eq!(
+            "Column order column 'x' is not part of the result set.",
+            reorder(&["a", "b"], &["x"]).unwrap_err()
+        );
+        assert_eq!(
+            "Column 'a' is listed more than once in the column order.",
+            reorder(&["a", "b"], &["a", "a"]).unwrap_err()
+        );
+    }
+}
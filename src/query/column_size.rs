@@ -0,0 +1,196 @@
+//! Limits the size of the fetch buffers of text and binary columns, for drivers reporting
+//! excessive lengths. See `--max-column-size`. Also grows them, if their values have been truncated.
+//! See `--on-truncation`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use anyhow::Error;
+use log::{info, warn};
+use odbc_api::buffers::{AnyColumnView, BufferDescription, BufferKind};
+use parquet::{basic::Type as PhysicalType, column::writer::ColumnWriter, schema::types::Type};
+
+use crate::parquet_buffer::ParquetBuffer;
+
+use super::strategy::ColumnFetchStrategy;
+
+/// Wraps the strategy of a text or binary column, so its values are fetched into a buffer of at
+/// most `max_size` bytes. Strategies of columns with a smaller length, without a maximum length, or
+/// written with a fixed length are returned unchanged.
+pub fn limit_column_size(
+    inner: Box<dyn ColumnFetchStrategy>,
+    name: &str,
+    max_size: usize,
+) -> Box<dyn ColumnFetchStrategy> {
+    let description = inner.buffer_description();
+    let (length, max_len) = match description.kind {
+        BufferKind::Text { max_str_len } => (max_str_len, max_size),
+        BufferKind::WText { max_str_len } => (max_str_len, max_size / 2),
+        BufferKind::Binary { length } => (length, max_size),
+        _ => return inner,
+    };
+    // Values of fixed length must not be truncated, the parquet type relies on their length.
+    let is_fixed_length =
+        inner.parquet_type(name).get_physical_type() == PhysicalType::FIXED_LEN_BYTE_ARRAY;
+    // Columns without a maximum length are fetched in parts anyway.
+    if length == 0 || length <= max_len || is_fixed_length {
+        return inner;
+    }
+    let max_len = max_len.max(1);
+    info!(
+        "Limiting the buffer of column '{name}' to {max_size} bytes, since the driver reported a \
+        length of {length}."
+    );
+    Box::new(LimitedColumnSize {
+        inner,
+        name: name.to_owned(),
+        max_len,
+        max_size,
+        warned: AtomicBool::new(false),
+    })
+}
+
+/// Fetches the values of a column into a buffer smaller than the length reported by the driver.
+/// Values reaching the end of the buffer are likely truncated, which is warned about once.
+struct LimitedColumnSize {
+    inner: Box<dyn ColumnFetchStrategy>,
+    name: String,
+    /// Maximum length of a value, in characters of the fetch buffer (bytes for narrow text and
+    /// binary, `u16` for wide text).
+    max_len: usize,
+    /// Value of `--max-column-size` in bytes.
+    max_size: usize,
+    /// `true` once truncated values have been warned about.
+    warned: AtomicBool,
+}
+
+impl ColumnFetchStrategy for LimitedColumnSize {
+    fn parquet_type(&self, name: &str) -> Type {
+        self.inner.parquet_type(name)
+    }
+
+    fn buffer_description(&self) -> BufferDescription {
+        with_length(self.inner.buffer_description(), self.max_len)
+    }
+
+    fn copy_odbc_to_parquet(
+        &self,
+        parquet_buffer: &mut ParquetBuffer,
+        column_writer: &mut ColumnWriter,
+        column_view: AnyColumnView,
+    ) -> Result<(), Error> {
+        let is_truncated = |len: usize| len >= self.max_len;
+        let has_truncated_values = match &column_view {
+            AnyColumnView::Text(view) => view.iter().flatten().any(|v| is_truncated(v.len())),
+            AnyColumnView::WText(view) => view.iter().flatten().any(|v| is_truncated(v.len())),
+            AnyColumnView::Binary(view) => view.iter().flatten().any(|v| is_truncated(v.len())),
+            _ => false,
+        };
+        if has_truncated_values && !self.warned.swap(true, Ordering::Relaxed) {
+            warn!(
+                "Column '{}' holds values of at least {} bytes, which are likely truncated. \
+                Raise `--max-column-size` to fetch longer values.",
+                self.name, self.max_size
+            );
+        }
+        self.inner
+            .copy_odbc_to_parquet(parquet_buffer, column_writer, column_view)
+    }
+}
+
+/// Wraps the strategy of a text or binary column, so its values are fetched into a buffer of
+/// `length` characters, rather than the length reported by the driver. `length` is counted in `u16`
+/// for wide text, in bytes otherwise.
+pub fn grow_column_size(
+    inner: Box<dyn ColumnFetchStrategy>,
+    length: usize,
+) -> Box<dyn ColumnFetchStrategy> {
+    Box::new(GrownColumnSize { inner, length })
+}
+
+/// Fetches the values of a column into a buffer larger than the length reported by the driver,
+/// because values of the column have been truncated before.
+struct GrownColumnSize {
+    inner: Box<dyn ColumnFetchStrategy>,
+    length: usize,
+}
+
+impl ColumnFetchStrategy for GrownColumnSize {
+    fn parquet_type(&self, name: &str) -> Type {
+        self.inner.parquet_type(name)
+    }
+
+    fn buffer_description(&self) -> BufferDescription {
+        with_length(self.inner.buffer_description(), self.length)
+    }
+
+    fn copy_odbc_to_parquet(
+        &self,
+        parquet_buffer: &mut ParquetBuffer,
+        column_writer: &mut ColumnWriter,
+        column_view: AnyColumnView,
+    ) -> Result<(), Error> {
+        self.inner
+            .copy_odbc_to_parquet(parquet_buffer, column_writer, column_view)
+    }
+}
+
+/// Replaces the length of text and binary buffers. Other descriptions are returned unchanged.
+pub fn with_length(description: BufferDescription, length: usize) -> BufferDescription {
+    let kind = match description.kind {
+        BufferKind::Text { .. } => BufferKind::Text {
+            max_str_len: length,
+        },
+        BufferKind::WText { .. } => BufferKind::WText {
+            max_str_len: length,
+        },
+        BufferKind::Binary { .. } => BufferKind::Binary { length },
+        kind => kind,
+    };
+    BufferDescription {
+        kind,
+        ..description
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use odbc_api::buffers::BufferKind;
+    use parquet::{
+        basic::Repetition,
+        data_type::{ByteArrayType, FixedLenByteArrayType},
+    };
+
+    use crate::query::{
+        binary::Binary,
+        text::{Utf16ToUtf8, Utf8},
+    };
+
+    use super::limit_column_size;
+
+    #[test]
+    fn limit_buffers_of_variable_length() {
+        let limit = |strategy| {
+            limit_column_size(strategy, "a", 1024)
+                .buffer_description()
+                .kind
+        };
+
+        let text = Box::new(Utf8::with_bytes_length(Repetition::OPTIONAL, 2_000_000_000));
+        assert_eq!(BufferKind::Text { max_str_len: 1024 }, limit(text));
+        let wide_text = Box::new(Utf16ToUtf8::new(Repetition::OPTIONAL, 1_000_000_000));
+        assert_eq!(BufferKind::WText { max_str_len: 512 }, limit(wide_text));
+        let binary = Box::new(Binary::<ByteArrayType>::new(Repetition::OPTIONAL, 4096));
+        assert_eq!(BufferKind::Binary { length: 1024 }, limit(binary));
+
+        // Shorter columns, columns without maximum length, and fixed length columns are unchanged
+        let short = Box::new(Utf8::with_bytes_length(Repetition::OPTIONAL, 10));
+        assert_eq!(BufferKind::Text { max_str_len: 10 }, limit(short));
+        let unbounded = Box::new(Utf8::with_bytes_length(Repetition::OPTIONAL, 0));
+        assert_eq!(BufferKind::Text { max_str_len: 0 }, limit(unbounded));
+        let fixed = Box::new(Binary::<FixedLenByteArrayType>::new(
+            Repetition::REQUIRED,
+            4096,
+        ));
+        assert_eq!(BufferKind::Binary { length: 4096 }, limit(fixed));
+    }
+}
@@ -0,0 +1,213 @@
+//! Fetches row sets on a separate thread, while the row set fetched before is written. See
+//! `--fetch-buffers`.
+
+use std::{
+    marker::PhantomData,
+    mem::ManuallyDrop,
+    sync::mpsc::{channel, Receiver, Sender},
+    thread::{self, JoinHandle},
+};
+
+use odbc_api::{
+    buffers::ColumnarAnyBuffer,
+    force_send_sync,
+    handles::{Statement, StatementImpl},
+    sys::HStmt,
+    Cursor, RowSetBuffer,
+};
+
+use anyhow::Error;
+
+use super::{
+    batch_growth::GrowingFetch,
+    give_hint_about_flag_for_oracle_users,
+    spill::{SpilledColumn, SpillingFetch},
+    truncation::has_truncated_values,
+};
+
+/// A row set, together with the columns spilled into temporary files, and whether the driver
+/// reported truncated values for it.
+pub type RowSet<'a> = (&'a ColumnarAnyBuffer, &'a [SpilledColumn], bool);
+
+/// Source of the row sets written by `cursor_to_parquet`.
+pub enum Fetch<'c, C: Cursor> {
+    /// Fetches into a single buffer. Fetching the next row set waits until the current one has
+    /// been written.
+    Sequential(SequentialFetch<'c>),
+    /// Fetches into several buffers on a separate thread.
+    Concurrent(ConcurrentFetch<'c>),
+    /// Fetches into a buffer, which grows as long as the batches fill it.
+    Growing(GrowingFetch<'c>),
+    /// Fetches row by row, writing the values of columns too large for the buffer into temporary
+    /// files.
+    Spilling(SpillingFetch<'c, C>),
+}
+
+impl<'c, C: Cursor> Fetch<'c, C> {
+    /// Binds the buffer to the cursor, to fetch each row set into it. The cursor is borrowed
+    /// rather than consumed, so it stays open for the next result set.
+    pub fn sequential(cursor: &'c mut C, buffer: &'c mut ColumnarAnyBuffer) -> Self {
+        let handle = cursor.as_stmt_ref().as_sys();
+        // The cursor owns the statement handle, so it must not be freed here.
+        let statement = ManuallyDrop::new(unsafe { StatementImpl::new(handle) });
+        Fetch::Sequential(SequentialFetch { statement, buffer })
+    }
+
+    /// Next row set. `None` once the result set is consumed.
+    pub fn fetch(&mut self) -> Result<Option<RowSet<'_>>, Error> {
+        let row_set = match self {
+            Fetch::Sequential(sequential) => sequential.fetch(),
+            Fetch::Concurrent(concurrent) => concurrent.fetch(),
+            Fetch::Growing(growing) => growing.fetch(),
+            // Values fetched in parts are never truncated.
+            Fetch::Spilling(spilling) => {
+                let row_set = spilling.fetch()?;
+                return Ok(row_set.map(|(buffer, spilled)| (buffer, spilled, false)));
+            }
+        };
+        let row_set = row_set.map_err(give_hint_about_flag_for_oracle_users)?;
+        Ok(row_set.map(|(buffer, truncated)| (buffer, &[][..], truncated)))
+    }
+}
+
+/// Fetches each row set into the same buffer, borrowing the statement handle of the cursor.
+pub struct SequentialFetch<'c> {
+    statement: ManuallyDrop<StatementImpl<'c>>,
+    buffer: &'c mut ColumnarAnyBuffer,
+}
+
+impl SequentialFetch<'_> {
+    fn fetch(&mut self) -> Result<Option<(&ColumnarAnyBuffer, bool)>, odbc_api::Error> {
+        let has_row = unsafe { fetch_into(&mut self.statement, self.buffer) }?;
+        let truncated = has_truncated_values(&*self.statement);
+        Ok(has_row.then_some((&*self.buffer, truncated)))
+    }
+}
+
+impl Drop for SequentialFetch<'_> {
+    fn drop(&mut self) {
+        unbind(&mut self.statement);
+    }
+}
+
+/// Row set fetched by the thread, together with whether the driver reported truncated values for
+/// it. `None` once the result set is consumed.
+type FilledBuffer = Result<Option<(ColumnarAnyBuffer, bool)>, odbc_api::Error>;
+
+/// A thread fetching into each of the buffers in turn. Each buffer filled is handed over to the
+/// writer, and handed back to be filled again once it has been written. This way the round trip to
+/// the database overlaps with encoding and compressing the row set fetched before.
+pub struct ConcurrentFetch<'c> {
+    filled: Option<Receiver<FilledBuffer>>,
+    empty: Option<Sender<ColumnarAnyBuffer>>,
+    /// Row set currently written, and whether the driver reported truncated values for it.
+    current: Option<(ColumnarAnyBuffer, bool)>,
+    thread: Option<JoinHandle<()>>,
+    /// The thread uses the statement handle of the cursor, so the cursor must not be used or
+    /// dropped until the thread has been joined.
+    _cursor: PhantomData<&'c mut ()>,
+}
+
+impl<'c> ConcurrentFetch<'c> {
+    pub fn new(cursor: &'c mut impl Cursor, buffers: Vec<ColumnarAnyBuffer>) -> Self {
+        let (filled_sender, filled) = channel();
+        let (empty, empty_receiver) = channel();
+        for buffer in buffers {
+            empty.send(buffer).unwrap();
+        }
+        // Safety: The handle is only used by the fetch thread, which is joined before the borrow
+        // of the cursor ends.
+        let statement = unsafe { force_send_sync::Send::new(cursor.as_stmt_ref().as_sys()) };
+        let thread = thread::spawn(move || {
+            fetch_row_sets(statement.unwrap(), empty_receiver, filled_sender)
+        });
+        Self {
+            filled: Some(filled),
+            empty: Some(empty),
+            current: None,
+            thread: Some(thread),
+            _cursor: PhantomData,
+        }
+    }
+
+    fn fetch(&mut self) -> Result<Option<(&ColumnarAnyBuffer, bool)>, odbc_api::Error> {
+        // The previous row set has been written, so its buffer can be filled again.
+        if let Some((buffer, _truncated)) = self.current.take() {
+            // Fails only if the thread already stopped, because the result set is consumed.
+            let _ = self.empty.as_ref().unwrap().send(buffer);
+        }
+        match self.filled.as_ref().unwrap().recv() {
+            Ok(Ok(Some(row_set))) => {
+                self.current = Some(row_set);
+                Ok(self
+                    .current
+                    .as_ref()
+                    .map(|(buffer, truncated)| (buffer, *truncated)))
+            }
+            Ok(Err(error)) => Err(error),
+            Ok(Ok(None)) | Err(_) => Ok(None),
+        }
+    }
+}
+
+impl Drop for ConcurrentFetch<'_> {
+    fn drop(&mut self) {
+        // Closing the channels stops the thread after the row set it is currently fetching.
+        self.filled = None;
+        self.empty = None;
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn fetch_row_sets(
+    statement: HStmt,
+    empty: Receiver<ColumnarAnyBuffer>,
+    filled: Sender<FilledBuffer>,
+) {
+    // The cursor owns the statement handle, so it must not be freed here.
+    let mut statement = ManuallyDrop::new(unsafe { StatementImpl::new(statement) });
+    while let Ok(mut buffer) = empty.recv() {
+        let has_row = unsafe { fetch_into(&mut statement, &mut buffer) };
+        let done = !matches!(has_row, Ok(true));
+        let truncated = has_truncated_values(&*statement);
+        let row_set = has_row.map(|has_row| has_row.then_some((buffer, truncated)));
+        if filled.send(row_set).is_err() || done {
+            break;
+        }
+    }
+    unbind(&mut statement);
+}
+
+/// Do not leave the buffers bound to the statement, once they are deallocated.
+pub fn unbind(statement: &mut StatementImpl<'_>) {
+    let _ = statement.as_stmt_ref().unbind_cols();
+    unsafe {
+        let _ = statement.as_stmt_ref().set_num_rows_fetched(None);
+    }
+}
+
+/// Binds the buffer to the statement and fetches the next row set into it. `false` if the result
+/// set is consumed.
+///
+/// # Safety
+///
+/// The buffer stays bound to the statement, until another buffer is bound or the columns are
+/// unbound. It must not be deallocated before.
+pub unsafe fn fetch_into(
+    statement: &mut StatementImpl<'_>,
+    buffer: &mut ColumnarAnyBuffer,
+) -> Result<bool, odbc_api::Error> {
+    statement
+        .set_row_bind_type(buffer.bind_type())
+        .into_result(&*statement)?;
+    statement
+        .set_row_array_size(buffer.row_array_size())
+        .into_result(&*statement)?;
+    statement
+        .set_num_rows_fetched(Some(buffer.mut_num_fetch_rows()))
+        .into_result(&*statement)?;
+    buffer.bind_colmuns_to_cursor(statement.as_stmt_ref())?;
+    statement.fetch().into_result(&*statement)
+}
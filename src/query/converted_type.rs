@@ -0,0 +1,37 @@
+//! Legacy type annotations. Consumers based on old versions of parquet-mr only know the
+//! `ConvertedType` annotation and may fail to read files which also carry the newer `LogicalType`.
+
+use parquet::schema::types::Type;
+
+/// The same parquet type, but annotated only with its converted type. The converted type is derived
+/// from the logical type, if not already present. Logical types without a converted counterpart
+/// (e.g. timestamps with nanosecond precision) leave the column without any annotation.
+pub fn without_logical_type(parquet_type: Type) -> Type {
+    let info = parquet_type.get_basic_info();
+    let converted_type = info.converted_type();
+    let result = match &parquet_type {
+        Type::PrimitiveType {
+            physical_type,
+            type_length,
+            scale,
+            precision,
+            ..
+        } => Type::primitive_type_builder(info.name(), *physical_type)
+            .with_repetition(info.repetition())
+            .with_converted_type(converted_type)
+            .with_length(*type_length)
+            .with_precision(*precision)
+            .with_scale(*scale)
+            .with_id(info.has_id().then(|| info.id()))
+            .build(),
+        Type::GroupType { fields, .. } => Type::group_type_builder(info.name())
+            .with_repetition(info.repetition())
+            .with_converted_type(converted_type)
+            .with_fields(fields.clone())
+            .with_id(info.has_id().then(|| info.id()))
+            .build(),
+    };
+    // The builder populated the converted type from the logical type, and checked it to be
+    // consistent with the physical type, so the type is still valid without the logical type.
+    result.unwrap()
+}
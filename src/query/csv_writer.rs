@@ -0,0 +1,75 @@
+use anyhow::{bail, Error};
+use csv::{QuoteStyle, WriterBuilder};
+use log::info;
+use odbc_api::Cursor;
+
+use crate::destination::Destination;
+
+use super::{
+    batch_size_limit::BatchSizeLimit, fetch_buffer, give_hint_about_flag_for_oracle_users,
+    make_schema, strategy::MappingOptions, value_text::column_as_text,
+};
+
+/// Options influencing the output CSV format.
+#[derive(Clone)]
+pub struct CsvFormatOptions {
+    pub delimiter: u8,
+    pub quote_style: QuoteStyle,
+    /// Write the column names as the first record.
+    pub header: bool,
+}
+
+/// Writes the result set as CSV. Columns are fetched the same way as for parquet output, but the
+/// values are rendered as text. NULL is represented by an empty field.
+pub fn cursor_to_csv(
+    mut cursor: impl Cursor,
+    output: Destination,
+    batch_size: BatchSizeLimit,
+    mapping_options: MappingOptions,
+    format_options: CsvFormatOptions,
+) -> Result<(), Error> {
+    let strategies = make_schema(&mut cursor, mapping_options, false)?;
+
+    if strategies.is_empty() {
+        bail!("Resulting CSV file would not have any columns!")
+    }
+
+    let (mut odbc_buffer, _batch_size_row) = fetch_buffer(&strategies, batch_size, 0)?;
+    let mut row_set_cursor = cursor.bind_buffer(&mut odbc_buffer)?;
+
+    let mut writer = WriterBuilder::new()
+        .delimiter(format_options.delimiter)
+        .quote_style(format_options.quote_style)
+        .from_writer(output.create()?);
+
+    if format_options.header {
+        writer.write_record(strategies.iter().map(|(_index, name, _strategy)| name))?;
+    }
+
+    let mut num_batch = 0;
+    while let Some(buffer) = row_set_cursor
+        .fetch()
+        .map_err(give_hint_about_flag_for_oracle_users)?
+    {
+        num_batch += 1;
+        let num_rows = buffer.num_rows();
+        info!("Fetched batch {} with {} rows.", num_batch, num_rows);
+        let columns: Vec<_> = (0..strategies.len())
+            .map(|col_index| column_as_text(buffer.column(col_index)))
+            .collect();
+        for row_index in 0..num_rows {
+            writer.write_record(
+                columns
+                    .iter()
+                    .map(|column| column[row_index].as_deref().unwrap_or("")),
+            )?;
+        }
+    }
+
+    writer
+        .into_inner()
+        .map_err(|error| error.into_error())?
+        .finish()?;
+
+    Ok(())
+}
@@ -0,0 +1,70 @@
+//! `DECFLOAT` columns of IBM Db2. Drivers report them with the number of significant digits (16 or
+//! 34) as column size, which is too short for their text representation including the exponent.
+
+use odbc_api::sys::SqlDataType;
+use parquet::{basic::Repetition, data_type::DoubleType};
+
+use crate::enum_args::NumberAs;
+
+use super::{
+    decimal::RescaledDecimal, identical::fetch_identical, strategy::ColumnFetchStrategy, text::Utf8,
+};
+
+/// `SQL_DECFLOAT` reported by the IBM Db2 driver.
+pub const SQL_DECFLOAT: SqlDataType = SqlDataType(-360);
+
+/// Longest text representation of a `DECFLOAT(34)`, e.g.
+/// `-1.234567890123456789012345678901234E-6143`.
+const DECFLOAT_TEXT_LENGTH: usize = 42;
+
+/// Choose how to fetch `DECFLOAT` columns and store them in parquet.
+pub fn decfloat_fetch_strategy(
+    repetition: Repetition,
+    decfloat_as: NumberAs,
+) -> Box<dyn ColumnFetchStrategy> {
+    match decfloat_as {
+        NumberAs::Decimal { precision, scale } => Box::new(RescaledDecimal::new(
+            repetition,
+            precision,
+            scale,
+            DECFLOAT_TEXT_LENGTH,
+        )),
+        // The ODBC driver converts the values into doubles for us.
+        NumberAs::Double => fetch_identical::<DoubleType>(repetition == Repetition::OPTIONAL),
+        NumberAs::Text => Box::new(Utf8::with_bytes_length(repetition, DECFLOAT_TEXT_LENGTH)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use odbc_api::buffers::BufferKind;
+    use parquet::basic::{Repetition, Type as PhysicalType};
+
+    use crate::enum_args::NumberAs;
+
+    use super::decfloat_fetch_strategy;
+
+    #[test]
+    fn decfloat_as_decimal_or_double() {
+        let decimal = NumberAs::Decimal {
+            precision: 34,
+            scale: 8,
+        };
+        let strategy = decfloat_fetch_strategy(Repetition::OPTIONAL, decimal);
+        let parquet_type = strategy.parquet_type("a");
+        assert_eq!(
+            (34, 8),
+            (parquet_type.get_precision(), parquet_type.get_scale())
+        );
+        assert!(matches!(
+            strategy.buffer_description().kind,
+            BufferKind::Text { max_str_len: 42 }
+        ));
+
+        let strategy = decfloat_fetch_strategy(Repetition::REQUIRED, NumberAs::Double);
+        assert_eq!(
+            PhysicalType::DOUBLE,
+            strategy.parquet_type("a").get_physical_type()
+        );
+    }
+}
@@ -1,6 +1,6 @@
 use std::{convert::TryInto, marker::PhantomData};
 
-use anyhow::Error;
+use anyhow::{anyhow, bail, Error};
 use atoi::FromRadix10Signed;
 use odbc_api::{
     buffers::{AnyColumnView, BufferDescription, BufferKind},
@@ -9,16 +9,30 @@ use odbc_api::{
 use parquet::{
     basic::{ConvertedType, LogicalType, Repetition, Type as PhysicalType},
     column::writer::ColumnWriter,
-    data_type::{DataType as ParquetDataType, FixedLenByteArrayType, Int32Type, Int64Type},
+    data_type::{
+        ByteArray, DataType as ParquetDataType, DoubleType, FixedLenByteArrayType, Int32Type,
+        Int64Type,
+    },
     schema::types::Type,
 };
 
-use crate::parquet_buffer::{BufferedDataType, ParquetBuffer};
+use crate::{
+    enum_args::NumberAs,
+    parquet_buffer::{BufferedDataType, ParquetBuffer},
+};
 
 use super::{
-    identical::fetch_decimal_as_identical_with_precision, strategy::ColumnFetchStrategy, text::Utf8,
+    identical::{fetch_decimal_as_identical_with_precision, fetch_identical},
+    strategy::ColumnFetchStrategy,
+    text::Utf8,
 };
 
+/// Largest precision of decimals, whose values fit into an `i128`.
+const MAX_DECIMAL128_PRECISION: u8 = 38;
+
+/// Largest precision of decimals, whose values fit into 256 Bit.
+const MAX_DECIMAL256_PRECISION: u8 = 76;
+
 /// Choose how to fetch decimals from ODBC and store them in parquet
 pub fn decimal_fetch_strategy(
     is_optional: bool,
@@ -26,13 +40,24 @@ pub fn decimal_fetch_strategy(
     precision: u8,
     driver_does_support_i64: bool,
     prefer_int_over_decimal: bool,
+    decimal_as_double: bool,
+    decimal_as_text: bool,
 ) -> Box<dyn ColumnFetchStrategy> {
+    if decimal_as_double {
+        // The ODBC driver converts the values into doubles for us.
+        return fetch_identical::<DoubleType>(is_optional);
+    }
+
     let repetition = if is_optional {
         Repetition::OPTIONAL
     } else {
         Repetition::REQUIRED
     };
 
+    if decimal_as_text {
+        return decimal_as_utf8(repetition, scale, precision);
+    }
+
     match (precision, scale) {
         (0..=9, 0) => {
             // Values with scale 0 and precision <= 9 can be fetched as i32 from the ODBC and we can
@@ -40,7 +65,8 @@ pub fn decimal_fetch_strategy(
             fetch_decimal_as_identical_with_precision::<Int32Type>(
                 is_optional,
                 precision as i32,
-                prefer_int_over_decimal)
+                prefer_int_over_decimal,
+            )
         }
         (0..=9, 1..=9) => {
             // As these values have a scale unequal to 0 we read them from the datebase as text, but
@@ -57,7 +83,8 @@ pub fn decimal_fetch_strategy(
                 fetch_decimal_as_identical_with_precision::<Int64Type>(
                     is_optional,
                     precision as i32,
-                    prefer_int_over_decimal)
+                    prefer_int_over_decimal,
+                )
             } else {
                 // The database does not support 64Bit integers (looking at you Oracle). So we fetch
                 // the values from the database as text and convert them into 64Bit integers.
@@ -73,24 +100,253 @@ pub fn decimal_fetch_strategy(
                 precision, scale, repetition,
             ))
         }
-        (0..=38, _) => Box::new(DecimalAsBinary::new(repetition, scale, precision)),
-        (_, _) => {
-            let length = odbc_api::DataType::Decimal {
-                precision: precision as usize,
-                scale: scale.try_into().unwrap(),
+        // Up to a precision of 38 values fit into 128 Bit (decimal128), up to 76 into 256 Bit
+        // (decimal256). Readers like Arrow do not support decimals any larger.
+        (0..=MAX_DECIMAL256_PRECISION, _) => {
+            Box::new(DecimalAsBinary::new(repetition, scale, precision))
+        }
+        (_, _) => decimal_as_utf8(repetition, scale, precision),
+    }
+}
+
+/// Length of the text buffer for numbers whose length can not be derived from the column
+/// description. Oracle formats `NUMBER` values with at most 64 characters, we leave some room for
+/// other drivers.
+pub const NUMBER_TEXT_LENGTH: usize = 128;
+
+/// Choose how to fetch `NUMERIC` and `DECIMAL` columns without precision (e.g. `NUMBER` in Oracle).
+/// We can not derive a parquet type from the column description, so the user picks one.
+pub fn number_without_precision_fetch_strategy(
+    is_optional: bool,
+    default_number_as: NumberAs,
+) -> Box<dyn ColumnFetchStrategy> {
+    let repetition = if is_optional {
+        Repetition::OPTIONAL
+    } else {
+        Repetition::REQUIRED
+    };
+    match default_number_as {
+        NumberAs::Decimal { precision, scale } => Box::new(RescaledDecimal::new(
+            repetition,
+            precision,
+            scale,
+            NUMBER_TEXT_LENGTH,
+        )),
+        NumberAs::Double => fetch_identical::<DoubleType>(is_optional),
+        NumberAs::Text => Box::new(Utf8::with_bytes_length(repetition, NUMBER_TEXT_LENGTH)),
+    }
+}
+
+/// Fetches numbers as text in whatever format the database chooses, e.g. `.5` or `1E+3`, and
+/// writes them as decimals of a fixed precision and scale. Values which do not fit are an error.
+pub struct RescaledDecimal {
+    repetition: Repetition,
+    precision: u8,
+    scale: i32,
+    /// Length of the text buffer the values are fetched into.
+    text_length: usize,
+}
+
+impl RescaledDecimal {
+    pub fn new(repetition: Repetition, precision: u8, scale: i32, text_length: usize) -> Self {
+        Self {
+            repetition,
+            precision,
+            scale,
+            text_length,
+        }
+    }
+
+    /// Length of the two's complement in bytes, if stored in a fixed length byte array.
+    fn length_in_bytes(&self) -> usize {
+        let length_in_bits = self.precision as f64 * 10f64.log2() + 1.0;
+        (length_in_bits / 8.0).ceil() as usize
+    }
+}
+
+impl ColumnFetchStrategy for RescaledDecimal {
+    fn parquet_type(&self, name: &str) -> Type {
+        let (physical_type, length) = match self.precision {
+            0..=9 => (PhysicalType::INT32, -1),
+            10..=18 => (PhysicalType::INT64, -1),
+            _ => (
+                PhysicalType::FIXED_LEN_BYTE_ARRAY,
+                self.length_in_bytes().try_into().unwrap(),
+            ),
+        };
+        Type::primitive_type_builder(name, physical_type)
+            .with_length(length)
+            .with_logical_type(Some(LogicalType::decimal(
+                self.scale,
+                self.precision as i32,
+            )))
+            .with_precision(self.precision as i32)
+            .with_scale(self.scale)
+            .with_repetition(self.repetition)
+            .build()
+            .unwrap()
+    }
+
+    fn buffer_description(&self) -> BufferDescription {
+        BufferDescription {
+            kind: BufferKind::Text {
+                max_str_len: self.text_length,
+            },
+            nullable: true,
+        }
+    }
+
+    fn copy_odbc_to_parquet(
+        &self,
+        parquet_buffer: &mut ParquetBuffer,
+        column_writer: &mut ColumnWriter,
+        column_view: AnyColumnView,
+    ) -> Result<(), Error> {
+        let view = column_view.as_text_view().expect(
+            "Invalid Column view type. This is not supposed to happen. Please open a Bug at \
+            https://github.com/pacman82/odbc2parquet/issues.",
+        );
+        let mut digits = Vec::new();
+        let mut rescale = move |text: &[u8]| -> Result<Vec<u8>, Error> {
+            rescale_decimal_text(text, self.precision, self.scale, &mut digits)?;
+            Ok(digits.clone())
+        };
+        match column_writer {
+            ColumnWriter::Int32ColumnWriter(cw) => parquet_buffer.write_optional_falliable(
+                cw,
+                view.iter().map(|field| {
+                    field
+                        .map(|text| Ok(i32::from_radix_10_signed(&rescale(text)?).0))
+                        .transpose()
+                }),
+            ),
+            ColumnWriter::Int64ColumnWriter(cw) => parquet_buffer.write_optional_falliable(
+                cw,
+                view.iter().map(|field| {
+                    field
+                        .map(|text| Ok(i64::from_radix_10_signed(&rescale(text)?).0))
+                        .transpose()
+                }),
+            ),
+            ColumnWriter::FixedLenByteArrayColumnWriter(cw) => {
+                let length_in_bytes = self.length_in_bytes();
+                parquet_buffer.write_optional_falliable(
+                    cw,
+                    view.iter().map(|field| {
+                        field
+                            .map(|text| {
+                                let bytes = twos_complement(&rescale(text)?, length_in_bytes);
+                                // Vec<u8> -> ByteArray -> FixedLenByteArray
+                                let bytes: ByteArray = bytes.into();
+                                Ok(bytes.into())
+                            })
+                            .transpose()
+                    }),
+                )
             }
-            .display_size()
-            .unwrap();
-            Box::new(Utf8::with_bytes_length(repetition, length))
+            _ => panic!(
+                "Invalid ColumnWriter type. This is not supposed to happen. Please open a Bug at \
+                https://github.com/pacman82/odbc2parquet/issues."
+            ),
         }
     }
 }
 
+/// Fills `digits` with the sign and the digits of the number in `text` multiplied by `10^scale`.
+/// `text` may have any number of fractional digits and an exponent. Fails if digits would be lost,
+/// or if more than `precision` digits remain.
+pub fn rescale_decimal_text(
+    text: &[u8],
+    precision: u8,
+    scale: i32,
+    digits: &mut Vec<u8>,
+) -> Result<(), Error> {
+    let invalid = || {
+        anyhow!(
+            "Can not parse '{}' as a number.",
+            String::from_utf8_lossy(text)
+        )
+    };
+    let (mantissa, exponent) = match text.iter().position(|&c| c == b'e' || c == b'E') {
+        Some(index) => {
+            let exponent = std::str::from_utf8(&text[index + 1..])
+                .ok()
+                .and_then(|exponent| exponent.trim_start_matches('+').parse::<i32>().ok())
+                .ok_or_else(invalid)?;
+            (&text[..index], exponent)
+        }
+        None => (text, 0),
+    };
+    let (is_negative, mantissa) = match mantissa.split_first() {
+        Some((b'-', rest)) => (true, rest),
+        Some((b'+', rest)) => (false, rest),
+        _ => (false, mantissa),
+    };
+    let (integer, fraction) = match mantissa.iter().position(|&c| c == b'.') {
+        Some(index) => (&mantissa[..index], &mantissa[index + 1..]),
+        None => (mantissa, &mantissa[..0]),
+    };
+    if integer.is_empty() && fraction.is_empty()
+        || !integer.iter().chain(fraction).all(u8::is_ascii_digit)
+    {
+        return Err(invalid());
+    }
+
+    digits.clear();
+    if is_negative {
+        digits.push(b'-');
+    }
+    let sign_len = digits.len();
+    digits.extend(integer.iter().chain(fraction).skip_while(|&&c| c == b'0'));
+    // Number of zeros to append, or digits to remove if negative.
+    let shift = exponent as i64 - fraction.len() as i64 + scale as i64;
+    if shift >= 0 {
+        if digits.len() > sign_len {
+            digits.resize(digits.len() + shift as usize, b'0');
+        }
+    } else {
+        let removed = (-shift).min((digits.len() - sign_len) as i64) as usize;
+        if digits[digits.len() - removed..].iter().any(|&c| c != b'0') {
+            bail!(
+                "'{}' has more than {scale} fractional digits.",
+                String::from_utf8_lossy(text)
+            )
+        }
+        digits.truncate(digits.len() - removed);
+    }
+    if digits.len() - sign_len > precision as usize {
+        bail!(
+            "'{}' does not fit into a decimal with precision {precision} and scale {scale}.",
+            String::from_utf8_lossy(text)
+        )
+    }
+    if digits.len() == sign_len {
+        digits.truncate(0);
+        digits.push(b'0');
+    }
+    Ok(())
+}
+
+/// Fetches decimals as text and writes them as UTF8 strings, which works for any precision.
+fn decimal_as_utf8(
+    repetition: Repetition,
+    scale: i32,
+    precision: u8,
+) -> Box<dyn ColumnFetchStrategy> {
+    let length = odbc_api::DataType::Decimal {
+        precision: precision as usize,
+        scale: scale.try_into().unwrap(),
+    }
+    .display_size()
+    .unwrap();
+    Box::new(Utf8::with_bytes_length(repetition, length))
+}
+
 struct DecimalTextToInteger<Pdt> {
     precision: u8,
     scale: i32,
     repetition: Repetition,
-    _pdt: PhantomData<Pdt>,
+    _pdt: PhantomData<fn() -> Pdt>,
 }
 
 impl<Pdt> DecimalTextToInteger<Pdt> {
@@ -111,10 +367,10 @@ where
 {
     fn parquet_type(&self, name: &str) -> Type {
         Type::primitive_type_builder(name, Pdt::get_physical_type())
-            .with_logical_type(Some(LogicalType::Decimal {
-                scale: self.scale,
-                precision: self.precision as i32,
-            }))
+            .with_logical_type(Some(LogicalType::decimal(
+                self.scale,
+                self.precision as i32,
+            )))
             .with_precision(self.precision as i32)
             .with_scale(self.scale)
             .with_repetition(self.repetition)
@@ -166,7 +422,8 @@ where
     }
 }
 
-/// Strategy for fetching decimal values which can not be represented as either 32Bit or 64Bit
+/// Strategy for fetching decimal values which can not be represented as either 32Bit or 64Bit. The
+/// values are stored as twos complement in fixed sized byte arrays.
 struct DecimalAsBinary {
     repetition: Repetition,
     scale: i32,
@@ -250,6 +507,19 @@ fn write_decimal_col(
         https://github.com/pacman82/odbc2parquet/issues.",
     );
 
+    if precision > MAX_DECIMAL128_PRECISION {
+        return parquet_buffer.write_twos_complement(
+            column_writer,
+            view.iter().map(|field| {
+                field.map(|text| {
+                    digits.clear();
+                    digits.extend(text.iter().filter(|&&c| c != b'.'));
+                    twos_complement(&digits, length_in_bytes)
+                })
+            }),
+        );
+    }
+
     parquet_buffer.write_twos_complement_i128(
         column_writer,
         view.iter().map(|field| {
@@ -265,3 +535,134 @@ fn write_decimal_col(
 
     Ok(())
 }
+
+/// Big endian twos complement of an integer given as decimal digits with optional sign. Unlike
+/// `i128` this works for integers of any size, e.g. decimals with a precision of up to 76 digits
+/// (256 Bit).
+pub fn twos_complement(digits: &[u8], length_in_bytes: usize) -> Vec<u8> {
+    let (is_negative, digits) = match digits.split_first() {
+        Some((b'-', rest)) => (true, rest),
+        Some((b'+', rest)) => (false, rest),
+        _ => (false, digits),
+    };
+    let mut bytes = vec![0u8; length_in_bytes];
+    for digit in digits.iter().take_while(|digit| digit.is_ascii_digit()) {
+        // bytes = bytes * 10 + digit
+        let mut carry = u16::from(digit - b'0');
+        for byte in bytes.iter_mut().rev() {
+            let value = u16::from(*byte) * 10 + carry;
+            *byte = value as u8;
+            carry = value >> 8;
+        }
+    }
+    if is_negative {
+        // Invert all bits and add one
+        let mut carry = true;
+        for byte in bytes.iter_mut().rev() {
+            (*byte, carry) = (!*byte).overflowing_add(u8::from(carry));
+        }
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod test {
+    use crate::enum_args::{number_as_from_str, NumberAs};
+
+    use parquet::basic::{Repetition, Type as PhysicalType};
+
+    use crate::query::strategy::ColumnFetchStrategy;
+
+    use super::{rescale_decimal_text, twos_complement, RescaledDecimal};
+
+    #[test]
+    fn physical_type_of_rescaled_decimals() {
+        let physical_type = |precision| {
+            RescaledDecimal::new(Repetition::OPTIONAL, precision, 2, 128)
+                .parquet_type("a")
+                .get_physical_type()
+        };
+        assert_eq!(PhysicalType::INT32, physical_type(9));
+        assert_eq!(PhysicalType::INT64, physical_type(18));
+        assert_eq!(PhysicalType::FIXED_LEN_BYTE_ARRAY, physical_type(38));
+    }
+
+    #[test]
+    fn rescale_numbers_formatted_by_the_database() {
+        let rescale = |text: &str, precision, scale| {
+            let mut digits = Vec::new();
+            rescale_decimal_text(text.as_bytes(), precision, scale, &mut digits)
+                .map(|()| String::from_utf8(digits).unwrap())
+        };
+        assert_eq!("150", rescale("1.5", 5, 2).unwrap());
+        assert_eq!("50", rescale(".5", 5, 2).unwrap());
+        assert_eq!("-50", rescale("-0.50000", 5, 2).unwrap());
+        assert_eq!("123000", rescale("1.23E+3", 6, 2).unwrap());
+        assert_eq!("12", rescale("1.2e-1", 6, 2).unwrap());
+        assert_eq!("0", rescale("0", 5, 2).unwrap());
+        assert_eq!("0", rescale("-0.00", 5, 2).unwrap());
+        assert_eq!("42", rescale("42", 2, 0).unwrap());
+        // Digits would be lost
+        assert!(rescale("1.234", 5, 2).is_err());
+        assert!(rescale("1E-3", 5, 2).is_err());
+        // Precision exceeded
+        assert!(rescale("1234", 5, 2).is_err());
+        assert!(rescale("1E+100", 38, 0).is_err());
+        // Not a number
+        assert!(rescale("abc", 5, 2).is_err());
+        assert!(rescale(".", 5, 2).is_err());
+        assert!(rescale("1E", 5, 2).is_err());
+    }
+
+    #[test]
+    fn parse_default_number_as() {
+        assert_eq!(
+            NumberAs::Decimal {
+                precision: 38,
+                scale: 10
+            },
+            number_as_from_str("decimal(38,10)").unwrap()
+        );
+        assert_eq!(
+            NumberAs::Decimal {
+                precision: 5,
+                scale: 0
+            },
+            number_as_from_str("DECIMAL( 5, 0 )").unwrap()
+        );
+        assert_eq!(NumberAs::Double, number_as_from_str("double").unwrap());
+        assert_eq!(NumberAs::Text, number_as_from_str("Text").unwrap());
+        assert!(number_as_from_str("decimal(38)").is_err());
+        assert!(number_as_from_str("decimal(0,0)").is_err());
+        assert!(number_as_from_str("decimal(5,6)").is_err());
+        assert!(number_as_from_str("float").is_err());
+    }
+
+    #[test]
+    fn twos_complement_of_large_decimals() {
+        assert_eq!(vec![0, 0, 1, 0], twos_complement(b"256", 4));
+        assert_eq!(vec![0xff, 0xff, 0xff, 0x00], twos_complement(b"-256", 4));
+        assert_eq!(vec![0xff, 0xff, 0xff, 0xff], twos_complement(b"-1", 4));
+        assert_eq!(vec![0, 0, 0, 0], twos_complement(b"-0", 4));
+        assert_eq!(vec![0, 0, 0, 42], twos_complement(b"+42", 4));
+
+        // Same representation as `i128` for values fitting into it
+        let digits = b"-12345678901234567890123456789012345678";
+        let expected = (-12345678901234567890123456789012345678i128).to_be_bytes();
+        assert_eq!(expected.to_vec(), twos_complement(digits, 16));
+
+        // 10^75 requires more than 128 Bit
+        let hex = |bytes: Vec<u8>| -> String { bytes.iter().map(|b| format!("{b:02x}")).collect() };
+        let mut digits = b"1".to_vec();
+        digits.extend([b'0'; 75]);
+        assert_eq!(
+            "0235fadd81c2822bb3f07877973d50f28bf22a31be8ee8000000000000000000",
+            hex(twos_complement(&digits, 32))
+        );
+        digits.insert(0, b'-');
+        assert_eq!(
+            "fdca05227e3d7dd44c0f878868c2af0d740dd5ce417118000000000000000000",
+            hex(twos_complement(&digits, 32))
+        );
+    }
+}
@@ -0,0 +1,419 @@
+//! Appending the output of a query to a Delta Lake table.
+
+use std::{
+    fs::{self, create_dir_all, read_to_string, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Error};
+use chrono::Utc;
+use log::{info, warn};
+use parquet::{
+    basic::{ConvertedType, LogicalType, Repetition, TimestampType, Type as PhysicalType},
+    file::statistics::Statistics,
+    schema::types::{ColumnDescriptor, Type},
+};
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use crate::destination::Destination;
+
+use super::parquet_writer::WrittenFile;
+
+/// Name of the directory containing the transaction log of a Delta Lake table.
+const DELTA_LOG_DIR: &str = "_delta_log";
+
+/// A Delta Lake table in the local file system. The data files are written into the table
+/// directory and are made visible to readers by a single commit to the transaction log.
+pub struct DeltaTable {
+    path: PathBuf,
+}
+
+impl DeltaTable {
+    pub fn new(output: &Destination) -> Result<Self, Error> {
+        match output {
+            Destination::File(path) => Ok(Self { path: path.clone() }),
+            _ => bail!("Delta Lake tables are only supported in the local file system."),
+        }
+    }
+
+    /// Destination for the data files. Each run uses a unique name, so we never overwrite files
+    /// of previous commits.
+    pub fn data_files(&self) -> Destination {
+        Destination::File(
+            self.path
+                .join(format!("part-{{seq}}-{}.parquet", Uuid::new_v4())),
+        )
+    }
+
+    /// Adds the written files to the table, by writing a new commit to the transaction log. If the
+    /// table does not exist yet, the first commit also creates it with the schema of the files.
+    /// Otherwise the schema of the files must match the one of the table.
+    pub fn commit(&self, schema: &Type, files: &[WrittenFile]) -> Result<(), Error> {
+        let log_dir = self.path.join(DELTA_LOG_DIR);
+        create_dir_all(&log_dir)?;
+        let latest = latest_version(&log_dir)?;
+        let (fields, protocol) = delta_schema(schema);
+        if let Some(latest) = latest {
+            verify_schema(&log_dir, latest, &fields)?;
+        }
+        let version = match latest {
+            Some(latest) => latest + 1,
+            None => 0,
+        };
+        let now = Utc::now().timestamp_millis();
+
+        let mut actions = vec![json!({
+            "commitInfo": {
+                "timestamp": now,
+                "operation": "WRITE",
+                "operationParameters": { "mode": "Append" },
+                "isBlindAppend": true,
+                "engineInfo": concat!("odbc2parquet/", env!("CARGO_PKG_VERSION")),
+            }
+        })];
+        if version == 0 {
+            actions.push(json!({ "protocol": protocol }));
+            actions.push(json!({
+                "metaData": {
+                    "id": Uuid::new_v4().to_string(),
+                    "format": { "provider": "parquet", "options": {} },
+                    "schemaString": json!({ "type": "struct", "fields": fields }).to_string(),
+                    "partitionColumns": [],
+                    "configuration": {},
+                    "createdTime": now,
+                }
+            }));
+        }
+        for file in files {
+            actions.push(self.add_action(file, now)?);
+        }
+
+        let mut commit = String::new();
+        for action in actions {
+            commit.push_str(&action.to_string());
+            commit.push('\n');
+        }
+        let commit_path = log_dir.join(format!("{version:020}.json"));
+        // Creating the file fails if it already exists. This way we can not overwrite a commit,
+        // which has been written concurrently by another process.
+        let mut commit_file = match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&commit_path)
+        {
+            Ok(file) => file,
+            Err(error) if error.kind() == ErrorKind::AlreadyExists => bail!(
+                "Version {version} of the Delta Lake table has been committed concurrently by \
+                another writer. The data files written have not been added to the table."
+            ),
+            Err(error) => return Err(error.into()),
+        };
+        commit_file.write_all(commit.as_bytes())?;
+        info!("Committed version {version} to Delta Lake table.");
+        Ok(())
+    }
+
+    fn add_action(&self, file: &WrittenFile, modification_time: i64) -> Result<Value, Error> {
+        let Destination::File(path) = &file.destination else {
+            panic!("Data files of a Delta Lake table must be in the local file system.")
+        };
+        let relative = path
+            .strip_prefix(&self.path)
+            .context("Data file must be located within the Delta Lake table directory.")?;
+        let size = fs::metadata(path)?.len();
+        Ok(json!({
+            "add": {
+                "path": relative.to_string_lossy(),
+                "partitionValues": {},
+                "size": size,
+                "modificationTime": modification_time,
+                "dataChange": true,
+                "stats": file_statistics(file).to_string(),
+            }
+        }))
+    }
+}
+
+/// Highest version found in the transaction log, or `None` if the table does not exist yet.
+fn latest_version(log_dir: &Path) -> Result<Option<u64>, Error> {
+    let mut latest = None;
+    for entry in fs::read_dir(log_dir)? {
+        let file_name = entry?.file_name();
+        let Some(version) = file_name
+            .to_str()
+            .and_then(|name| name.strip_suffix(".json"))
+            .and_then(|version| version.parse::<u64>().ok())
+        else {
+            continue;
+        };
+        latest = latest.max(Some(version));
+    }
+    Ok(latest)
+}
+
+/// Fails, unless the fields of the data files match the schema of the table, as stated by the
+/// latest metadata in the transaction log. Columns are matched by name. Nullable columns can not be
+/// appended to columns of the table which are not.
+fn verify_schema(log_dir: &Path, latest: u64, fields: &[Value]) -> Result<(), Error> {
+    let Some(table_fields) = table_schema(log_dir, latest)? else {
+        warn!(
+            "No metadata found in the JSON commits of the Delta Lake table. Its schema is not \
+            verified."
+        );
+        return Ok(());
+    };
+    let name = |field: &Value| field["name"].as_str().unwrap_or_default().to_owned();
+    for table_field in &table_fields {
+        if !fields.iter().any(|field| name(field) == name(table_field)) {
+            bail!(
+                "Column '{}' of the Delta Lake table is not part of the query.",
+                name(table_field)
+            )
+        }
+    }
+    for field in fields {
+        let Some(table_field) = table_fields.iter().find(|other| name(other) == name(field)) else {
+            bail!(
+                "Column '{}' is not part of the Delta Lake table.",
+                name(field)
+            )
+        };
+        if field["type"] != table_field["type"] {
+            bail!(
+                "Column '{}' has type {} in the query, but {} in the Delta Lake table.",
+                name(field),
+                field["type"],
+                table_field["type"]
+            )
+        }
+        if field["nullable"] == true && table_field["nullable"] == false {
+            bail!(
+                "Column '{}' is nullable in the query, but not in the Delta Lake table.",
+                name(field)
+            )
+        }
+    }
+    Ok(())
+}
+
+/// Fields of the schema stated by the latest metadata in the JSON commits up to `latest`. `None` if
+/// there is none, e.g. because older commits have been replaced by a checkpoint.
+fn table_schema(log_dir: &Path, latest: u64) -> Result<Option<Vec<Value>>, Error> {
+    for version in (0..=latest).rev() {
+        let commit = match read_to_string(log_dir.join(format!("{version:020}.json"))) {
+            Ok(commit) => commit,
+            Err(error) if error.kind() == ErrorKind::NotFound => continue,
+            Err(error) => return Err(error.into()),
+        };
+        for line in commit.lines().filter(|line| !line.trim().is_empty()) {
+            let action: Value = serde_json::from_str(line).with_context(|| {
+                format!("Invalid action in commit {version} of Delta Lake table.")
+            })?;
+            let Some(schema) = action["metaData"]["schemaString"].as_str() else {
+                continue;
+            };
+            let schema: Value = serde_json::from_str(schema)
+                .context("Invalid schema in metadata of Delta Lake table.")?;
+            let fields = schema["fields"].as_array().cloned().unwrap_or_default();
+            return Ok(Some(fields));
+        }
+    }
+    Ok(None)
+}
+
+/// Translates the parquet schema into the fields of a Delta Lake schema. Also returns the protocol
+/// required to read and write a table with these fields.
+fn delta_schema(schema: &Type) -> (Vec<Value>, Value) {
+    let mut uses_timestamp_ntz = false;
+    let mut fields = Vec::new();
+    for field in schema.get_fields() {
+        let info = field.get_basic_info();
+        let data_type = delta_type(field);
+        uses_timestamp_ntz |= data_type == "timestamp_ntz";
+        fields.push(json!({
+            "name": info.name(),
+            "type": data_type,
+            "nullable": info.repetition() != Repetition::REQUIRED,
+            "metadata": {},
+        }));
+    }
+    let protocol = if uses_timestamp_ntz {
+        json!({
+            "minReaderVersion": 3,
+            "minWriterVersion": 7,
+            "readerFeatures": ["timestampNtz"],
+            "writerFeatures": ["timestampNtz"],
+        })
+    } else {
+        json!({ "minReaderVersion": 1, "minWriterVersion": 2 })
+    };
+    (fields, protocol)
+}
+
+fn delta_type(field: &Type) -> String {
+    let info = field.get_basic_info();
+    if info.converted_type() == ConvertedType::DECIMAL {
+        return format!("decimal({},{})", field.get_precision(), field.get_scale());
+    }
+    let data_type = match field.get_physical_type() {
+        PhysicalType::BOOLEAN => "boolean",
+        PhysicalType::INT32 => match info.converted_type() {
+            ConvertedType::INT_8 => "byte",
+            ConvertedType::INT_16 => "short",
+            // Delta Lake has no unsigned types. Use the smallest signed type holding all values,
+            // like Spark does reading unsigned parquet columns.
+            ConvertedType::UINT_8 => "short",
+            ConvertedType::UINT_16 => "integer",
+            ConvertedType::UINT_32 => "long",
+            ConvertedType::DATE => "date",
+            _ => "integer",
+        },
+        PhysicalType::INT64 if info.converted_type() == ConvertedType::UINT_64 => "decimal(20,0)",
+        PhysicalType::INT64 => match info.logical_type_ref() {
+            Some(LogicalType::Timestamp(TimestampType {
+                is_adjusted_to_u_t_c: true,
+                ..
+            })) => "timestamp",
+            Some(LogicalType::Timestamp(TimestampType {
+                is_adjusted_to_u_t_c: false,
+                ..
+            })) => "timestamp_ntz",
+            _ => "long",
+        },
+        PhysicalType::INT96 => "timestamp",
+        PhysicalType::FLOAT => "float",
+        PhysicalType::DOUBLE => "double",
+        PhysicalType::BYTE_ARRAY
+            if matches!(
+                info.converted_type(),
+                ConvertedType::UTF8 | ConvertedType::JSON
+            ) =>
+        {
+            "string"
+        }
+        PhysicalType::BYTE_ARRAY | PhysicalType::FIXED_LEN_BYTE_ARRAY => "binary",
+    };
+    data_type.to_owned()
+}
+
+/// Statistics of a data file, in the format expected by Delta Lake readers for data skipping.
+/// Minimum and maximum values are only stated for types there the representation in the
+/// statistics is unambiguous. Columns without statistics are omitted.
+fn file_statistics(file: &WrittenFile) -> Value {
+    let num_records: i64 = file.row_groups.iter().map(|rg| rg.num_rows()).sum();
+    let mut min_values = Map::new();
+    let mut max_values = Map::new();
+    let mut null_count = Map::new();
+    let num_columns = file.row_groups.first().map_or(0, |rg| rg.num_columns());
+    for col_index in 0..num_columns {
+        let column = file.row_groups[0].column(col_index).column_descr();
+        let name = column.name().to_owned();
+        let statistics: Option<Vec<&Statistics>> = file
+            .row_groups
+            .iter()
+            .map(|rg| rg.column(col_index).statistics())
+            .collect();
+        let Some(statistics) = statistics else {
+            continue;
+        };
+        let nulls: Option<u64> = statistics.iter().map(|s| s.null_count_opt()).sum();
+        if let Some(nulls) = nulls {
+            null_count.insert(name.clone(), nulls.into());
+        }
+        if let Some((min, max)) = min_max(column, &statistics) {
+            min_values.insert(name.clone(), min);
+            max_values.insert(name, max);
+        }
+    }
+    json!({
+        "numRecords": num_records,
+        "minValues": min_values,
+        "maxValues": max_values,
+        "nullCount": null_count,
+    })
+}
+
+/// Minimum and maximum of a column across all row groups. `None` if any row group lacks them, or
+/// the type of the column is not supported.
+fn min_max(column: &ColumnDescriptor, statistics: &[&Statistics]) -> Option<(Value, Value)> {
+    let plain_integer = matches!(
+        column.converted_type(),
+        ConvertedType::NONE
+            | ConvertedType::INT_8
+            | ConvertedType::INT_16
+            | ConvertedType::INT_32
+            | ConvertedType::INT_64
+    );
+    let mut min: Option<Value> = None;
+    let mut max: Option<Value> = None;
+    for statistic in statistics {
+        let (row_group_min, row_group_max) = match statistic {
+            Statistics::Int32(s) if plain_integer => (json!(s.min_opt()?), json!(s.max_opt()?)),
+            Statistics::Int64(s) if plain_integer => (json!(s.min_opt()?), json!(s.max_opt()?)),
+            Statistics::Float(s) => (float(*s.min_opt()? as f64)?, float(*s.max_opt()? as f64)?),
+            Statistics::Double(s) => (float(*s.min_opt()?)?, float(*s.max_opt()?)?),
+            Statistics::ByteArray(s) if column.converted_type() == ConvertedType::UTF8 => (
+                json!(s.min_opt()?.as_utf8().ok()?),
+                json!(s.max_opt()?.as_utf8().ok()?),
+            ),
+            _ => return None,
+        };
+        min = Some(match min {
+            Some(min) if compare(&min, &row_group_min)?.is_le() => min,
+            _ => row_group_min,
+        });
+        max = Some(match max {
+            Some(max) if compare(&max, &row_group_max)?.is_ge() => max,
+            _ => row_group_max,
+        });
+    }
+    Some((min?, max?))
+}
+
+/// JSON can not represent NaN or infinity, so we must not state them as bounds.
+fn float(value: f64) -> Option<Value> {
+    serde_json::Number::from_f64(value).map(Value::Number)
+}
+
+fn compare(a: &Value, b: &Value) -> Option<std::cmp::Ordering> {
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => match (a.as_i64(), b.as_i64()) {
+            (Some(a), Some(b)) => Some(a.cmp(&b)),
+            _ => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        },
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use parquet::schema::parser::parse_message_type;
+
+    use crate::destination::Destination;
+
+    use super::DeltaTable;
+
+    #[test]
+    fn append_with_mismatched_schema_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        let table = DeltaTable::new(&Destination::File(dir.path().to_owned())).unwrap();
+        let schema = parse_message_type("message schema { REQUIRED INT32 a; }").unwrap();
+        table.commit(&schema, &[]).unwrap();
+        // Appending with the same schema is fine.
+        table.commit(&schema, &[]).unwrap();
+
+        let other_type = parse_message_type("message schema { REQUIRED INT64 a; }").unwrap();
+        let error = table.commit(&other_type, &[]).unwrap_err();
+        assert_eq!(
+            "Column 'a' has type \"long\" in the query, but \"integer\" in the Delta Lake table.",
+            error.to_string()
+        );
+        let other_name = parse_message_type("message schema { REQUIRED INT32 b; }").unwrap();
+        assert!(table.commit(&other_name, &[]).is_err());
+        let nullable = parse_message_type("message schema { OPTIONAL INT32 a; }").unwrap();
+        assert!(table.commit(&nullable, &[]).is_err());
+    }
+}
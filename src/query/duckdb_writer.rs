@@ -0,0 +1,105 @@
+use anyhow::{bail, Error};
+use duckdb::{appender_params_from_iter, types::Value, Connection};
+use log::info;
+use odbc_api::{buffers::AnyColumnView, Cursor};
+
+use crate::destination::Destination;
+
+use super::{
+    batch_size_limit::BatchSizeLimit, fetch_buffer, give_hint_about_flag_for_oracle_users,
+    make_schema, sql_table::create_table_statement, strategy::MappingOptions,
+    value_text::column_as_text,
+};
+
+/// Writes the result set into a table of a DuckDB database file. The table is created, if it does
+/// not exist yet. Otherwise the rows are appended to it.
+pub fn cursor_to_duckdb(
+    mut cursor: impl Cursor,
+    output: Destination,
+    batch_size: BatchSizeLimit,
+    mapping_options: MappingOptions,
+    table: &str,
+) -> Result<(), Error> {
+    let Destination::File(path) = output else {
+        bail!("DuckDB database files must be written to the local file system.")
+    };
+
+    let strategies = make_schema(&mut cursor, mapping_options, false)?;
+
+    if strategies.is_empty() {
+        bail!("Resulting table would not have any columns!")
+    }
+
+    let conn = Connection::open(&path)?;
+    let create_table = create_table_statement(table, &strategies);
+    info!("Create table statement: {create_table}");
+    conn.execute_batch(&create_table)?;
+
+    let (mut odbc_buffer, _batch_size_row) = fetch_buffer(&strategies, batch_size, 0)?;
+    let mut row_set_cursor = cursor.bind_buffer(&mut odbc_buffer)?;
+    let mut appender = conn.appender(table)?;
+
+    let mut num_batch = 0;
+    while let Some(buffer) = row_set_cursor
+        .fetch()
+        .map_err(give_hint_about_flag_for_oracle_users)?
+    {
+        num_batch += 1;
+        let num_rows = buffer.num_rows();
+        info!("Fetched batch {} with {} rows.", num_batch, num_rows);
+        let mut columns: Vec<_> = (0..strategies.len())
+            .map(|col_index| column_values(buffer.column(col_index)).into_iter())
+            .collect();
+        for _ in 0..num_rows {
+            let row = columns.iter_mut().map(|column| column.next().unwrap());
+            appender.append_row(appender_params_from_iter(row))?;
+        }
+    }
+
+    appender.flush()?;
+
+    Ok(())
+}
+
+/// Values of a fetched column. Numbers, booleans and binary data are passed on as is. Everything
+/// else is passed as text and converted to the type of the column by DuckDB.
+fn column_values(view: AnyColumnView) -> Vec<Value> {
+    fn natives<T: Copy>(values: &[T], f: impl Fn(T) -> Value) -> Vec<Value> {
+        values.iter().copied().map(f).collect()
+    }
+    fn nullable<'a, T: Copy + 'a>(
+        values: impl Iterator<Item = Option<&'a T>>,
+        f: impl Fn(T) -> Value,
+    ) -> Vec<Value> {
+        values
+            .map(|value| value.copied().map(&f).unwrap_or(Value::Null))
+            .collect()
+    }
+
+    match view {
+        AnyColumnView::I8(values) => natives(values, Value::TinyInt),
+        AnyColumnView::I16(values) => natives(values, Value::SmallInt),
+        AnyColumnView::I32(values) => natives(values, Value::Int),
+        AnyColumnView::I64(values) => natives(values, Value::BigInt),
+        AnyColumnView::U8(values) => natives(values, Value::UTinyInt),
+        AnyColumnView::F32(values) => natives(values, Value::Float),
+        AnyColumnView::F64(values) => natives(values, Value::Double),
+        AnyColumnView::Bit(values) => natives(values, |bit| Value::Boolean(bit.as_bool())),
+        AnyColumnView::NullableI8(values) => nullable(values, Value::TinyInt),
+        AnyColumnView::NullableI16(values) => nullable(values, Value::SmallInt),
+        AnyColumnView::NullableI32(values) => nullable(values, Value::Int),
+        AnyColumnView::NullableI64(values) => nullable(values, Value::BigInt),
+        AnyColumnView::NullableU8(values) => nullable(values, Value::UTinyInt),
+        AnyColumnView::NullableF32(values) => nullable(values, Value::Float),
+        AnyColumnView::NullableF64(values) => nullable(values, Value::Double),
+        AnyColumnView::NullableBit(values) => nullable(values, |bit| Value::Boolean(bit.as_bool())),
+        AnyColumnView::Binary(view) => view
+            .iter()
+            .map(|value| value.map_or(Value::Null, |bytes| Value::Blob(bytes.to_vec())))
+            .collect(),
+        other => column_as_text(other)
+            .into_iter()
+            .map(|value| value.map_or(Value::Null, Value::Text))
+            .collect(),
+    }
+}
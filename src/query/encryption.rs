@@ -0,0 +1,107 @@
+//! Parquet modular encryption of the output files.
+
+use std::{process::Command, sync::Arc};
+
+use anyhow::{bail, Context, Error};
+use parquet::encryption::encrypt::FileEncryptionProperties;
+
+use crate::enum_args::{column_encryption_key_from_str, encryption_key_from_str, EncryptionKey};
+
+/// Properties used to encrypt the output files, or `None` if no keys have been specified.
+///
+/// # Parameters
+///
+/// * `footer_key`: Key used to encrypt the footer, and all columns without a column key.
+/// * `column_keys`: Keys for individual columns. If any are specified, only these columns are
+///   encrypted.
+/// * `key_command`: Shell command printing additional keys to standard out. One key per line,
+///   either `KEY` for the footer key, or `COLUMN:KEY` for a column key.
+pub fn file_encryption_properties(
+    mut footer_key: Option<EncryptionKey>,
+    mut column_keys: Vec<(String, EncryptionKey)>,
+    key_command: Option<&str>,
+) -> Result<Option<Arc<FileEncryptionProperties>>, Error> {
+    if let Some(key_command) = key_command {
+        for line in run_key_command(key_command)?.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if line.contains(':') {
+                column_keys.push(column_encryption_key_from_str(line)?);
+            } else if footer_key.is_some() {
+                bail!("The encryption footer key must only be specified once.")
+            } else {
+                footer_key = Some(encryption_key_from_str(line)?);
+            }
+        }
+    }
+
+    let Some(footer_key) = footer_key else {
+        if !column_keys.is_empty() {
+            bail!("Encrypting columns requires an encryption footer key.")
+        }
+        return Ok(None);
+    };
+    let mut builder = FileEncryptionProperties::builder(footer_key.0);
+    for (column, key) in column_keys {
+        builder = builder.with_column_key(&column, key.0);
+    }
+    Ok(Some(builder.build()?))
+}
+
+fn run_key_command(key_command: &str) -> Result<String, Error> {
+    #[cfg(target_os = "windows")]
+    let output = Command::new("cmd").args(["/C", key_command]).output();
+    #[cfg(not(target_os = "windows"))]
+    let output = Command::new("sh").args(["-c", key_command]).output();
+    let output = output.context("Failed to execute encryption key command.")?;
+    if !output.status.success() {
+        bail!(
+            "Encryption key command failed with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::file_encryption_properties;
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn keys_from_key_command() {
+        let command = "echo 00112233445566778899aabbccddeeff; \
+            echo ssn:ffeeddccbbaa99887766554433221100";
+
+        let properties = file_encryption_properties(None, Vec::new(), Some(command))
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(
+            &hex_decode("00112233445566778899aabbccddeeff"),
+            properties.footer_key()
+        );
+        let (columns, keys, _metadata) = properties.column_keys();
+        assert_eq!(vec!["ssn".to_owned()], columns);
+        assert_eq!(vec![hex_decode("ffeeddccbbaa99887766554433221100")], keys);
+    }
+
+    #[test]
+    fn column_keys_require_footer_key() {
+        let column_key = crate::enum_args::column_encryption_key_from_str(
+            "ssn:ffeeddccbbaa99887766554433221100",
+        )
+        .unwrap();
+
+        let result = file_encryption_properties(None, vec![column_key], None);
+
+        assert!(result.is_err());
+    }
+
+    fn hex_decode(hex: &str) -> Vec<u8> {
+        crate::enum_args::encryption_key_from_str(hex).unwrap().0
+    }
+}
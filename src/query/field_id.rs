@@ -0,0 +1,129 @@
+//! Parquet field IDs. Iceberg identifies columns by these IDs rather than by their names, so it can
+//! adopt files written by other tools only if they carry IDs matching the table schema.
+
+use std::{collections::HashMap, fs};
+
+use anyhow::{anyhow, bail, Context, Error};
+use parquet::schema::types::Type;
+use serde_json::Value;
+
+use crate::enum_args::FieldIdsArgument;
+
+/// Field IDs of the columns of the result set, in order.
+///
+/// # Parameters
+///
+/// * `field_ids`: How IDs are assigned, as specified with `--field-ids`.
+/// * `column_names`: Names of all the columns of the result set. Partition columns are included,
+///   so the IDs of the other columns do not depend on how the output is partitioned.
+pub fn field_ids(field_ids: &FieldIdsArgument, column_names: &[&str]) -> Result<Vec<i32>, Error> {
+    match field_ids {
+        FieldIdsArgument::Sequential => (1..=column_names.len())
+            .map(|id| Ok(id.try_into()?))
+            .collect(),
+        FieldIdsArgument::NameMapping(path) => {
+            let json = fs::read_to_string(path).with_context(|| {
+                format!("Failed to read name mapping file '{}'.", path.display())
+            })?;
+            let mapping = name_mapping(&json)
+                .with_context(|| format!("Invalid name mapping file '{}'.", path.display()))?;
+            column_names
+                .iter()
+                .map(|&name| {
+                    mapping.get(name).copied().ok_or_else(|| {
+                        anyhow!(
+                            "Column '{name}' is missing in the name mapping file '{}'.",
+                            path.display()
+                        )
+                    })
+                })
+                .collect()
+        }
+    }
+}
+
+/// Parses a name mapping, as stored by Iceberg in the table property
+/// `schema.name-mapping.default`. E.g. `[{"field-id": 1, "names": ["id", "record_id"]}]`. Only the
+/// top level fields are considered, since result sets do not contain nested columns.
+fn name_mapping(json: &str) -> Result<HashMap<String, i32>, Error> {
+    let Value::Array(fields) = serde_json::from_str(json)? else {
+        bail!("Name mapping must be a JSON array.")
+    };
+    let mut mapping = HashMap::new();
+    for field in fields {
+        // Fields without an ID are allowed in a name mapping. They do not match any column.
+        let Some(id) = field.get("field-id") else {
+            continue;
+        };
+        let id = id
+            .as_i64()
+            .and_then(|id| i32::try_from(id).ok())
+            .ok_or_else(|| anyhow!("'field-id' must be a 32 Bit integer, but is: {id}"))?;
+        let Some(Value::Array(names)) = field.get("names") else {
+            bail!("Field with ID {id} must have an array of 'names'.")
+        };
+        for name in names {
+            let name = name
+                .as_str()
+                .ok_or_else(|| anyhow!("Names of field {id} must be strings, but got: {name}"))?;
+            mapping.insert(name.to_owned(), id);
+        }
+    }
+    Ok(mapping)
+}
+
+/// The same parquet type, but with the field ID set.
+pub fn with_field_id(parquet_type: Type, id: i32) -> Type {
+    let info = parquet_type.get_basic_info();
+    let result = match &parquet_type {
+        Type::PrimitiveType {
+            physical_type,
+            type_length,
+            scale,
+            precision,
+            ..
+        } => Type::primitive_type_builder(info.name(), *physical_type)
+            .with_repetition(info.repetition())
+            .with_converted_type(info.converted_type())
+            .with_logical_type(info.logical_type_ref().cloned())
+            .with_length(*type_length)
+            .with_precision(*precision)
+            .with_scale(*scale)
+            .with_id(Some(id))
+            .build(),
+        Type::GroupType { fields, .. } => Type::group_type_builder(info.name())
+            .with_repetition(info.repetition())
+            .with_converted_type(info.converted_type())
+            .with_logical_type(info.logical_type_ref().cloned())
+            .with_fields(fields.clone())
+            .with_id(Some(id))
+            .build(),
+    };
+    // The type has been valid without the ID, so it is still valid with it.
+    result.unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::name_mapping;
+
+    #[test]
+    fn parse_name_mapping() {
+        let mapping = name_mapping(
+            r#"[
+                {"field-id": 1, "names": ["id", "record_id"]},
+                {"field-id": 2, "names": ["data"], "fields": []},
+                {"names": ["dropped"]}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(Some(&1), mapping.get("id"));
+        assert_eq!(Some(&1), mapping.get("record_id"));
+        assert_eq!(Some(&2), mapping.get("data"));
+        assert_eq!(None, mapping.get("dropped"));
+
+        assert!(name_mapping(r#"{"field-id": 1, "names": ["id"]}"#).is_err());
+        assert!(name_mapping(r#"[{"field-id": "1", "names": ["id"]}]"#).is_err());
+    }
+}
@@ -0,0 +1,123 @@
+//! Half precision floating point numbers. ODBC has no C type for them, so we fetch them as 32 Bit
+//! floats and convert them to the `FLOAT16` logical type of parquet.
+
+use anyhow::Error;
+use half::f16;
+use odbc_api::{
+    buffers::{AnyColumnView, BufferDescription, BufferKind, Item},
+    DataType,
+};
+use parquet::{
+    basic::{LogicalType, Repetition, Type as PhysicalType},
+    column::writer::{get_typed_column_writer_mut, ColumnWriter},
+    data_type::{ByteArray, DoubleType, FixedLenByteArrayType, FloatType},
+    schema::types::Type,
+};
+
+use crate::parquet_buffer::ParquetBuffer;
+
+use super::{identical::fetch_identical, strategy::ColumnFetchStrategy};
+
+/// Fetch strategy for `REAL` and `FLOAT` columns. A small precision of a `FLOAT` says nothing about
+/// the range of its exponent, so they are only written as `FLOAT16` if `real_as_float16` is set.
+pub fn float_fetch_strategy(
+    data_type: DataType,
+    repetition: Repetition,
+    real_as_float16: bool,
+) -> Box<dyn ColumnFetchStrategy> {
+    let is_optional = repetition == Repetition::OPTIONAL;
+    match data_type {
+        DataType::Float { precision: 0..=24 } | DataType::Real if real_as_float16 => {
+            Box::new(Float16::new(repetition))
+        }
+        DataType::Float { precision: 0..=24 } | DataType::Real => {
+            fetch_identical::<FloatType>(is_optional)
+        }
+        // Map all precisions larger than 24 to double. Double would be technically precision 53.
+        _ => fetch_identical::<DoubleType>(is_optional),
+    }
+}
+
+/// Writes floats as `FLOAT16`. Values outside of its range become infinite.
+pub struct Float16 {
+    repetition: Repetition,
+}
+
+impl Float16 {
+    pub fn new(repetition: Repetition) -> Self {
+        Self { repetition }
+    }
+}
+
+impl ColumnFetchStrategy for Float16 {
+    fn parquet_type(&self, name: &str) -> Type {
+        Type::primitive_type_builder(name, PhysicalType::FIXED_LEN_BYTE_ARRAY)
+            .with_length(2)
+            .with_logical_type(Some(LogicalType::Float16))
+            .with_repetition(self.repetition)
+            .build()
+            .unwrap()
+    }
+
+    fn buffer_description(&self) -> BufferDescription {
+        BufferDescription {
+            kind: BufferKind::F32,
+            nullable: true,
+        }
+    }
+
+    fn copy_odbc_to_parquet(
+        &self,
+        parquet_buffer: &mut ParquetBuffer,
+        column_writer: &mut ColumnWriter,
+        column_view: AnyColumnView,
+    ) -> Result<(), Error> {
+        let it = f32::as_nullable_slice(column_view).unwrap();
+        let column_writer = get_typed_column_writer_mut::<FixedLenByteArrayType>(column_writer);
+        parquet_buffer.write_optional(
+            column_writer,
+            it.map(|value| {
+                value.map(|&value| {
+                    // Parquet stores `FLOAT16` in little endian byte order.
+                    let bytes: ByteArray = f16::from_f32(value).to_le_bytes().to_vec().into();
+                    bytes.into()
+                })
+            }),
+        )?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use odbc_api::DataType;
+    use parquet::basic::{Repetition, Type as PhysicalType};
+
+    use super::float_fetch_strategy;
+
+    #[test]
+    fn float16_only_on_request() {
+        let float = DataType::Float { precision: 10 };
+        let strategy = float_fetch_strategy(float, Repetition::OPTIONAL, false);
+        assert_eq!(
+            PhysicalType::FLOAT,
+            strategy.parquet_type("a").get_physical_type()
+        );
+
+        let strategy = float_fetch_strategy(float, Repetition::OPTIONAL, true);
+        assert_eq!(
+            PhysicalType::FIXED_LEN_BYTE_ARRAY,
+            strategy.parquet_type("a").get_physical_type()
+        );
+
+        let strategy = float_fetch_strategy(
+            DataType::Float { precision: 53 },
+            Repetition::OPTIONAL,
+            true,
+        );
+        assert_eq!(
+            PhysicalType::DOUBLE,
+            strategy.parquet_type("a").get_physical_type()
+        );
+    }
+}
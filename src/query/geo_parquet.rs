@@ -0,0 +1,114 @@
+//! GeoParquet metadata (key `geo`), which tells GIS tools which columns hold geometries and how
+//! these are encoded. See <https://geoparquet.org/releases/v1.1.0/>.
+
+use std::{fs, path::PathBuf};
+
+use anyhow::{bail, Context, Error};
+use parquet::{
+    basic::{ConvertedType, Type as PhysicalType},
+    file::metadata::KeyValue,
+    schema::types::Type,
+};
+use serde_json::{json, Map, Value};
+
+/// Version of the GeoParquet specification the metadata conforms to.
+const GEO_PARQUET_VERSION: &str = "1.1.0";
+
+/// Columns holding geometries as Well Known Binary (WKB).
+pub struct GeoParquetOptions {
+    /// Names of the geometry columns. The first one is the primary geometry column.
+    pub columns: Vec<String>,
+    /// File containing the coordinate reference system of all geometry columns as PROJJSON. If
+    /// not set, readers assume longitude/latitude on WGS84 (`OGC:CRS84`).
+    pub crs: Option<PathBuf>,
+}
+
+impl GeoParquetOptions {
+    /// Key value metadata describing the geometry columns of `parquet_schema`.
+    pub fn metadata(&self, parquet_schema: &Type) -> Result<KeyValue, Error> {
+        let crs = self
+            .crs
+            .as_ref()
+            .map(|path| -> Result<Value, Error> {
+                let projjson = fs::read_to_string(path)
+                    .with_context(|| format!("Failed to read CRS file '{}'.", path.display()))?;
+                serde_json::from_str(&projjson)
+                    .with_context(|| format!("CRS file '{}' is not valid JSON.", path.display()))
+            })
+            .transpose()?;
+
+        let mut columns = Map::new();
+        for name in &self.columns {
+            let Some(field) = parquet_schema
+                .get_fields()
+                .iter()
+                .find(|field| field.name() == name)
+            else {
+                bail!("Geometry column '{name}' is not part of the parquet files.")
+            };
+            let is_binary = field.is_primitive()
+                && field.get_physical_type() == PhysicalType::BYTE_ARRAY
+                && field.get_basic_info().converted_type() == ConvertedType::NONE
+                && field.get_basic_info().logical_type_ref().is_none();
+            if !is_binary {
+                bail!(
+                    "Geometry column '{name}' must be binary and hold Well Known Binary (WKB). \
+                    E.g. use `geom.STAsBinary()` in Microsoft SQL Server or `ST_AsBinary(geom)` in \
+                    PostGIS."
+                )
+            }
+            let mut column = json!({
+                "encoding": "WKB",
+                // Empty means the geometry types are unknown, which is the case since we do not
+                // inspect the values.
+                "geometry_types": [],
+            });
+            if let Some(crs) = &crs {
+                column["crs"] = crs.clone();
+            }
+            columns.insert(name.clone(), column);
+        }
+
+        let geo = json!({
+            "version": GEO_PARQUET_VERSION,
+            "primary_column": self.columns[0],
+            "columns": columns,
+        });
+        Ok(KeyValue::new("geo".to_owned(), geo.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use parquet::schema::parser::parse_message_type;
+    use serde_json::{json, Value};
+
+    use super::GeoParquetOptions;
+
+    #[test]
+    fn metadata_of_geometry_column() {
+        let schema = parse_message_type(
+            "message schema { optional binary geom; optional binary name (UTF8); }",
+        )
+        .unwrap();
+        let options = |column: &str| GeoParquetOptions {
+            columns: vec![column.to_owned()],
+            crs: None,
+        };
+
+        let metadata = options("geom").metadata(&schema).unwrap();
+
+        assert_eq!("geo", metadata.key);
+        let geo: Value = serde_json::from_str(metadata.value.as_deref().unwrap()).unwrap();
+        assert_eq!(
+            json!({
+                "version": "1.1.0",
+                "primary_column": "geom",
+                "columns": {"geom": {"encoding": "WKB", "geometry_types": []}}
+            }),
+            geo
+        );
+        assert!(options("name").metadata(&schema).is_err());
+        assert!(options("missing").metadata(&schema).is_err());
+    }
+}
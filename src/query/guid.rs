@@ -0,0 +1,105 @@
+use anyhow::{bail, Error};
+use odbc_api::buffers::{AnyColumnView, BufferDescription, BufferKind};
+use parquet::{
+    basic::{LogicalType, Repetition, Type as PhysicalType},
+    column::writer::ColumnWriter,
+    data_type::{ByteArray, DataType, FixedLenByteArray, FixedLenByteArrayType},
+    schema::types::Type,
+};
+
+use crate::parquet_buffer::ParquetBuffer;
+
+use super::strategy::ColumnFetchStrategy;
+
+/// Length of the text representation of a GUID, e.g. `6F9619FF-8B86-D011-B42D-00C04FC964FF`.
+const GUID_TEXT_LEN: usize = 36;
+
+/// Stores GUIDs (e.g. `UNIQUEIDENTIFIER` in Microsoft SQL Server) as 16 Byte UUIDs. We fetch them
+/// as text, since the binary representation of a GUID depends on the driver and the endianness of
+/// the platform, while the text representation is the same everywhere.
+pub struct Guid {
+    repetition: Repetition,
+}
+
+impl Guid {
+    pub fn new(repetition: Repetition) -> Self {
+        Self { repetition }
+    }
+}
+
+impl ColumnFetchStrategy for Guid {
+    fn parquet_type(&self, name: &str) -> Type {
+        Type::primitive_type_builder(name, PhysicalType::FIXED_LEN_BYTE_ARRAY)
+            .with_length(16)
+            .with_logical_type(Some(LogicalType::Uuid))
+            .with_repetition(self.repetition)
+            .build()
+            .unwrap()
+    }
+
+    fn buffer_description(&self) -> BufferDescription {
+        BufferDescription {
+            kind: BufferKind::Text {
+                max_str_len: GUID_TEXT_LEN,
+            },
+            nullable: self.repetition == Repetition::OPTIONAL,
+        }
+    }
+
+    fn copy_odbc_to_parquet(
+        &self,
+        parquet_buffer: &mut ParquetBuffer,
+        column_writer: &mut ColumnWriter,
+        column_view: AnyColumnView,
+    ) -> Result<(), Error> {
+        let column_writer = FixedLenByteArrayType::get_column_writer_mut(column_writer).unwrap();
+        let view = column_view.as_text_view().expect(
+            "Invalid Column view type. This is not supposed to happen. Please open a Bug at \
+            https://github.com/pacman82/odbc2parquet/issues.",
+        );
+        parquet_buffer.write_optional_falliable(
+            column_writer,
+            view.iter().map(|text| text.map(uuid_bytes).transpose()),
+        )
+    }
+}
+
+/// Parses the text representation of a GUID into the 16 bytes of a UUID, in the order they are
+/// written. Braces, as used by some drivers, are ignored.
+fn uuid_bytes(text: &[u8]) -> Result<FixedLenByteArray, Error> {
+    let digits: Vec<u8> = text
+        .iter()
+        .filter(|c| !matches!(c, b'-' | b'{' | b'}'))
+        .map(|&c| char::from(c).to_digit(16).map(|digit| digit as u8))
+        .collect::<Option<_>>()
+        .unwrap_or_default();
+    if digits.len() != 32 {
+        bail!("Invalid GUID: '{}'", String::from_utf8_lossy(text))
+    }
+    let bytes: Vec<u8> = digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair[1])
+        .collect();
+    let bytes: ByteArray = bytes.into();
+    Ok(bytes.into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::uuid_bytes;
+
+    #[test]
+    fn parse_guid() {
+        let expected = [
+            0x6f, 0x96, 0x19, 0xff, 0x8b, 0x86, 0xd0, 0x11, 0xb4, 0x2d, 0x00, 0xc0, 0x4f, 0xc9,
+            0x64, 0xff,
+        ];
+        let actual = uuid_bytes(b"6F9619FF-8B86-D011-B42D-00C04FC964FF").unwrap();
+        assert_eq!(&expected, actual.data());
+        let actual = uuid_bytes(b"{6f9619ff-8b86-d011-b42d-00c04fc964ff}").unwrap();
+        assert_eq!(&expected, actual.data());
+
+        assert!(uuid_bytes(b"6F9619FF-8B86-D011-B42D").is_err());
+        assert!(uuid_bytes(b"6F9619FF-8B86-D011-B42D-00C04FC964FG").is_err());
+    }
+}
@@ -19,14 +19,14 @@ use super::ColumnFetchStrategy;
 pub struct IdenticalOptional<Pdt> {
     converted_type: ConvertedType,
     precision: Option<i32>,
-    _parquet_data_type: PhantomData<Pdt>,
+    _parquet_data_type: PhantomData<fn() -> Pdt>,
 }
 
 /// Columnar fetch strategy to be applied if Parquet and Odbc value type are binary identical.
 /// Generic argument is a parquet data type.
 impl<Pdt> IdenticalOptional<Pdt>
 where
-    Pdt: DataType
+    Pdt: DataType,
 {
     pub fn new() -> Self {
         Self::with_converted_type(ConvertedType::NONE)
@@ -48,21 +48,17 @@ where
         let physical_type = Pdt::get_physical_type();
         if prefer_int_over_decimal {
             match physical_type {
-                PhysicalType::INT32 => {
-                    Self {
-                        converted_type: ConvertedType::INT_32,
-                        precision: None,
-                        _parquet_data_type: PhantomData,
-                    }
-                }
-                PhysicalType::INT64 => {
-                    Self {
-                        converted_type: ConvertedType::INT_64,
-                        precision: None,
-                        _parquet_data_type: PhantomData,
-                    }
-                }
-                _ => panic!("Only INT32 and INT64 are allowed to represent Decimal with scale 0")
+                PhysicalType::INT32 => Self {
+                    converted_type: ConvertedType::INT_32,
+                    precision: None,
+                    _parquet_data_type: PhantomData,
+                },
+                PhysicalType::INT64 => Self {
+                    converted_type: ConvertedType::INT_64,
+                    precision: None,
+                    _parquet_data_type: PhantomData,
+                },
+                _ => panic!("Only INT32 and INT64 are allowed to represent Decimal with scale 0"),
             }
         } else {
             Self {
@@ -115,12 +111,12 @@ where
 pub struct IdenticalRequired<Pdt> {
     converted_type: ConvertedType,
     precision: Option<i32>,
-    _parquet_data_type: PhantomData<Pdt>,
+    _parquet_data_type: PhantomData<fn() -> Pdt>,
 }
 
 impl<Pdt> IdenticalRequired<Pdt>
 where
-    Pdt: DataType
+    Pdt: DataType,
 {
     pub fn new() -> Self {
         Self::with_converted_type(ConvertedType::NONE)
@@ -142,21 +138,17 @@ where
         let physical_type = Pdt::get_physical_type();
         if prefer_int_over_decimal {
             match physical_type {
-                PhysicalType::INT32 => {
-                    Self {
-                        converted_type: ConvertedType::INT_32,
-                        precision: None,
-                        _parquet_data_type: PhantomData,
-                    }
-                }
-                PhysicalType::INT64 => {
-                    Self {
-                        converted_type: ConvertedType::INT_64,
-                        precision: None,
-                        _parquet_data_type: PhantomData,
-                    }
-                }
-                _ => panic!("Only INT32 and INT64 are allowed to represent Decimal with scale 0")
+                PhysicalType::INT32 => Self {
+                    converted_type: ConvertedType::INT_32,
+                    precision: None,
+                    _parquet_data_type: PhantomData,
+                },
+                PhysicalType::INT64 => Self {
+                    converted_type: ConvertedType::INT_64,
+                    precision: None,
+                    _parquet_data_type: PhantomData,
+                },
+                _ => panic!("Only INT32 and INT64 are allowed to represent Decimal with scale 0"),
             }
         } else {
             Self {
@@ -193,16 +185,21 @@ where
 
     fn copy_odbc_to_parquet(
         &self,
-        _parquet_buffer: &mut ParquetBuffer,
+        parquet_buffer: &mut ParquetBuffer,
         column_writer: &mut ColumnWriter,
         column_view: AnyColumnView,
     ) -> Result<(), Error> {
-        // We do not require to buffer the values, as they must neither be transformed, nor contain
-        // any gaps due to null, we can use the ODBC buffer directly to write the batch.
-
         let values = Pdt::T::as_slice(column_view).unwrap();
         let column_writer = get_typed_column_writer_mut::<Pdt>(column_writer);
-        column_writer.write_batch(values, None, None)?;
+        if parquet_buffer.selected_rows().is_some() {
+            // Only a subset of the rows is to be written, so we need to go through the parquet
+            // buffer, which takes care of skipping the other ones.
+            parquet_buffer.write_optional(column_writer, values.iter().copied().map(Some))?;
+        } else {
+            // We do not require to buffer the values, as they must neither be transformed, nor
+            // contain any gaps due to null, we can use the ODBC buffer directly to write the batch.
+            column_writer.write_batch(values, None, None)?;
+        }
         Ok(())
     }
 }
@@ -248,8 +245,14 @@ where
     Pdt::T: Item + BufferedDataType,
 {
     if is_optional {
-        Box::new(IdenticalOptional::<Pdt>::decimal_with_precision(precision, prefer_int_over_decimal))
+        Box::new(IdenticalOptional::<Pdt>::decimal_with_precision(
+            precision,
+            prefer_int_over_decimal,
+        ))
     } else {
-        Box::new(IdenticalRequired::<Pdt>::decimal_with_precision(precision, prefer_int_over_decimal))
+        Box::new(IdenticalRequired::<Pdt>::decimal_with_precision(
+            precision,
+            prefer_int_over_decimal,
+        ))
     }
 }
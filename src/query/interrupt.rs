@@ -0,0 +1,120 @@
+//! Interrupting an export with Ctrl+C, without leaving corrupt files behind. See `--on-interrupt`.
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{channel, RecvTimeoutError, Sender},
+    },
+    thread::{self, sleep, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use libc::{c_int, sighandler_t, signal, SIGINT, SIG_DFL};
+use log::warn;
+use odbc_api::{
+    force_send_sync,
+    sys::{HStmt, SQLCancel},
+};
+
+/// Time between checking whether Ctrl+C has been pressed, while a statement is watched.
+const WATCH_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Set once Ctrl+C has been pressed.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_interrupt(_signal: c_int) {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+    // Pressing Ctrl+C a second time terminates the process, e.g. if it is stuck waiting for the
+    // data source.
+    unsafe {
+        signal(SIGINT, SIG_DFL);
+    }
+}
+
+/// From now on Ctrl+C no longer terminates the process, but is recorded, so the export can be
+/// stopped after the current batch.
+pub fn handle_interrupts() {
+    unsafe {
+        signal(SIGINT, on_interrupt as extern "C" fn(c_int) as sighandler_t);
+    }
+}
+
+/// Records Ctrl+C like [`handle_interrupts`], but only while `f` is running. Afterwards Ctrl+C is
+/// handled as before again, unless it has been pressed in the meantime.
+pub fn handle_interrupts_during<T>(f: impl FnOnce() -> T) -> T {
+    let previous = unsafe { signal(SIGINT, on_interrupt as extern "C" fn(c_int) as sighandler_t) };
+    let result = f();
+    if !is_interrupted() {
+        unsafe {
+            signal(SIGINT, previous);
+        }
+    }
+    result
+}
+
+/// `true` if Ctrl+C has been pressed since [`handle_interrupts`] has been called.
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Waits for `duration`, unless Ctrl+C is pressed in the meantime. `false` if interrupted.
+pub fn sleep_unless_interrupted(duration: Duration) -> bool {
+    let until = Instant::now() + duration;
+    while Instant::now() < until {
+        if is_interrupted() {
+            return false;
+        }
+        let remaining = until.saturating_duration_since(Instant::now());
+        sleep(remaining.min(Duration::from_secs(1)));
+    }
+    !is_interrupted()
+}
+
+/// Cancels a statement once Ctrl+C is pressed, so a call blocked in the data source returns, e.g.
+/// executing the query or fetching a row set. The statement is watched on a thread of its own,
+/// until the guard is dropped.
+pub struct CancelOnInterrupt {
+    done: Option<Sender<()>>,
+    watcher: Option<JoinHandle<()>>,
+}
+
+impl CancelOnInterrupt {
+    /// # Safety
+    ///
+    /// `statement` must be a valid statement handle, until the guard is dropped.
+    pub unsafe fn new(statement: HStmt) -> Self {
+        let (done, finished) = channel::<()>();
+        let statement = force_send_sync::Send::new(statement);
+        let watcher = thread::spawn(move || {
+            let statement = statement.unwrap();
+            loop {
+                match finished.recv_timeout(WATCH_INTERVAL) {
+                    Err(RecvTimeoutError::Timeout) if is_interrupted() => {
+                        warn!("Cancelling the statement.");
+                        // Cancelling is allowed while another thread is executing a function on
+                        // the statement. Otherwise it has no effect. Should it fail, we keep
+                        // waiting for the call to return.
+                        let _ = unsafe { SQLCancel(statement) };
+                        return;
+                    }
+                    Err(RecvTimeoutError::Timeout) => (),
+                    Ok(()) | Err(RecvTimeoutError::Disconnected) => return,
+                }
+            }
+        });
+        Self {
+            done: Some(done),
+            watcher: Some(watcher),
+        }
+    }
+}
+
+impl Drop for CancelOnInterrupt {
+    fn drop(&mut self) {
+        // Closing the channel stops the watcher.
+        self.done = None;
+        if let Some(watcher) = self.watcher.take() {
+            let _ = watcher.join();
+        }
+    }
+}
@@ -0,0 +1,378 @@
+//! SQL intervals (`SQL_INTERVAL_*`). Drivers render these as text like `-1-02` for
+//! `INTERVAL YEAR TO MONTH` or `3 04:05:06.5` for `INTERVAL DAY TO SECOND`, which we parse and write
+//! either as parquet `INTERVAL` or as ISO 8601 duration.
+
+use anyhow::{anyhow, bail, Error};
+use odbc_api::{
+    buffers::{AnyColumnView, BufferDescription, BufferKind},
+    sys::SqlDataType,
+    DataType,
+};
+use parquet::{
+    basic::{ConvertedType, Repetition, Type as PhysicalType},
+    column::writer::ColumnWriter,
+    data_type::{ByteArray, ByteArrayType, DataType as _, FixedLenByteArrayType},
+    schema::types::Type,
+};
+
+use crate::parquet_buffer::ParquetBuffer;
+
+use super::strategy::ColumnFetchStrategy;
+
+/// Fields of an interval, from the most to the least significant one.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum Field {
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+/// Leading and trailing field of the interval type, if `data_type` is one.
+fn interval_fields(data_type: &DataType) -> Option<(Field, Field)> {
+    let DataType::Other {
+        data_type: SqlDataType(code),
+        ..
+    } = data_type
+    else {
+        return None;
+    };
+    use Field::*;
+    let fields = match code {
+        101 => (Year, Year),
+        102 => (Month, Month),
+        103 => (Day, Day),
+        104 => (Hour, Hour),
+        105 => (Minute, Minute),
+        106 => (Second, Second),
+        107 => (Year, Month),
+        108 => (Day, Hour),
+        109 => (Day, Minute),
+        110 => (Day, Second),
+        111 => (Hour, Minute),
+        112 => (Hour, Second),
+        113 => (Minute, Second),
+        _ => return None,
+    };
+    Some(fields)
+}
+
+/// `true` if the driver reports the column to hold intervals.
+pub fn is_interval(data_type: &DataType) -> bool {
+    interval_fields(data_type).is_some()
+}
+
+pub struct Interval {
+    repetition: Repetition,
+    leading: Field,
+    trailing: Field,
+    /// Maximum length of the text representation.
+    display_size: usize,
+    /// Write ISO 8601 durations instead of parquet `INTERVAL`.
+    as_iso8601: bool,
+}
+
+impl Interval {
+    /// # Panics
+    ///
+    /// If `data_type` is not an interval type.
+    pub fn new(
+        data_type: &DataType,
+        repetition: Repetition,
+        display_size: usize,
+        as_iso8601: bool,
+    ) -> Self {
+        let (leading, trailing) = interval_fields(data_type).expect("Must be an interval type");
+        Self {
+            repetition,
+            leading,
+            trailing,
+            display_size,
+            as_iso8601,
+        }
+    }
+}
+
+impl ColumnFetchStrategy for Interval {
+    fn parquet_type(&self, name: &str) -> Type {
+        let builder = if self.as_iso8601 {
+            Type::primitive_type_builder(name, PhysicalType::BYTE_ARRAY)
+                .with_converted_type(ConvertedType::UTF8)
+        } else {
+            Type::primitive_type_builder(name, PhysicalType::FIXED_LEN_BYTE_ARRAY)
+                .with_length(12)
+                .with_converted_type(ConvertedType::INTERVAL)
+        };
+        builder.with_repetition(self.repetition).build().unwrap()
+    }
+
+    fn buffer_description(&self) -> BufferDescription {
+        BufferDescription {
+            kind: BufferKind::Text {
+                max_str_len: self.display_size,
+            },
+            nullable: self.repetition == Repetition::OPTIONAL,
+        }
+    }
+
+    fn copy_odbc_to_parquet(
+        &self,
+        parquet_buffer: &mut ParquetBuffer,
+        column_writer: &mut ColumnWriter,
+        column_view: AnyColumnView,
+    ) -> Result<(), Error> {
+        let view = column_view.as_text_view().expect(
+            "Invalid Column view type. This is not supposed to happen. Please open a Bug at \
+            https://github.com/pacman82/odbc2parquet/issues.",
+        );
+        let values = view.iter().map(|text| {
+            text.map(|text| IntervalValue::parse(text, self.leading, self.trailing))
+                .transpose()
+        });
+        if self.as_iso8601 {
+            let column_writer = ByteArrayType::get_column_writer_mut(column_writer).unwrap();
+            parquet_buffer.write_optional_falliable(
+                column_writer,
+                values.map(|value| {
+                    let value = value?.map(|value| value.to_iso8601().into_bytes().into());
+                    Ok(value)
+                }),
+            )
+        } else {
+            let column_writer =
+                FixedLenByteArrayType::get_column_writer_mut(column_writer).unwrap();
+            parquet_buffer.write_optional_falliable(
+                column_writer,
+                values.map(|value| {
+                    value?
+                        .map(|value| {
+                            let bytes: ByteArray = value.to_parquet_interval()?.to_vec().into();
+                            Ok(bytes.into())
+                        })
+                        .transpose()
+                }),
+            )
+        }
+    }
+}
+
+/// An interval value, with each field as rendered by the driver.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct IntervalValue {
+    negative: bool,
+    years: u64,
+    months: u64,
+    days: u64,
+    hours: u64,
+    minutes: u64,
+    seconds: u64,
+    nanoseconds: u32,
+}
+
+impl IntervalValue {
+    /// Parses the text representation of an interval with fields from `leading` to `trailing`. E.g.
+    /// `-1-02` for years to months or `3 04:05:06.5` for days to seconds.
+    fn parse(text: &[u8], leading: Field, trailing: Field) -> Result<Self, Error> {
+        let invalid = || {
+            anyhow!(
+                "Invalid interval: '{}'",
+                String::from_utf8_lossy(text).trim()
+            )
+        };
+        let text = std::str::from_utf8(text).map_err(|_| invalid())?.trim();
+        let (negative, unsigned) = match text.as_bytes().first() {
+            Some(b'-') => (true, &text[1..]),
+            Some(b'+') => (false, &text[1..]),
+            _ => (false, text),
+        };
+        let parts: Vec<&str> = unsigned.trim_start().split(['-', ' ', ':']).collect();
+        let fields: Vec<Field> = [
+            Field::Year,
+            Field::Month,
+            Field::Day,
+            Field::Hour,
+            Field::Minute,
+            Field::Second,
+        ]
+        .into_iter()
+        .filter(|field| (leading..=trailing).contains(field))
+        .collect();
+        if parts.len() != fields.len() {
+            return Err(invalid());
+        }
+
+        let mut value = IntervalValue {
+            negative,
+            ..Default::default()
+        };
+        for (field, part) in fields.into_iter().zip(parts) {
+            let (integer, fraction) = match part.split_once('.') {
+                Some((integer, fraction)) if field == Field::Second => (integer, Some(fraction)),
+                _ => (part, None),
+            };
+            let integer: u64 = integer.parse().map_err(|_| invalid())?;
+            match field {
+                Field::Year => value.years = integer,
+                Field::Month => value.months = integer,
+                Field::Day => value.days = integer,
+                Field::Hour => value.hours = integer,
+                Field::Minute => value.minutes = integer,
+                Field::Second => value.seconds = integer,
+            }
+            if let Some(fraction) = fraction {
+                if fraction.is_empty() || !fraction.bytes().all(|c| c.is_ascii_digit()) {
+                    return Err(invalid());
+                }
+                // Digits beyond nanoseconds are truncated.
+                let digits = &fraction[..fraction.len().min(9)];
+                value.nanoseconds =
+                    digits.parse::<u32>().unwrap() * 10u32.pow(9 - digits.len() as u32);
+            }
+        }
+        Ok(value)
+    }
+
+    /// ISO 8601 duration, e.g. `P1Y2M` or `-P3DT4H5M6.5S`.
+    fn to_iso8601(&self) -> String {
+        let mut iso = String::from(if self.negative { "-P" } else { "P" });
+        for (amount, designator) in [(self.years, 'Y'), (self.months, 'M'), (self.days, 'D')] {
+            if amount != 0 {
+                iso.push_str(&format!("{amount}{designator}"));
+            }
+        }
+        let has_time = self.hours != 0 || self.minutes != 0 || self.seconds != 0;
+        if has_time || self.nanoseconds != 0 || iso.ends_with('P') {
+            iso.push('T');
+            for (amount, designator) in [(self.hours, 'H'), (self.minutes, 'M')] {
+                if amount != 0 {
+                    iso.push_str(&format!("{amount}{designator}"));
+                }
+            }
+            if self.seconds != 0 || self.nanoseconds != 0 || iso.ends_with('T') {
+                iso.push_str(&self.seconds.to_string());
+                if self.nanoseconds != 0 {
+                    let fraction = format!("{:09}", self.nanoseconds);
+                    iso.push('.');
+                    iso.push_str(fraction.trim_end_matches('0'));
+                }
+                iso.push('S');
+            }
+        }
+        iso
+    }
+
+    /// Parquet `INTERVAL`. Three little endian unsigned 32 Bit integers for months, days and
+    /// milliseconds. Precision beyond milliseconds is truncated.
+    fn to_parquet_interval(&self) -> Result<[u8; 12], Error> {
+        if self.negative {
+            bail!(
+                "Parquet INTERVAL can not represent the negative interval {}. Use \
+                `--intervals-as iso8601` to write intervals as ISO 8601 durations instead.",
+                self.to_iso8601()
+            )
+        }
+        let too_large = || {
+            anyhow!(
+                "The interval {} is too large for parquet INTERVAL. Use `--intervals-as iso8601` \
+                to write intervals as ISO 8601 durations instead.",
+                self.to_iso8601()
+            )
+        };
+        let months = self
+            .years
+            .checked_mul(12)
+            .and_then(|months| months.checked_add(self.months))
+            .and_then(|months| u32::try_from(months).ok())
+            .ok_or_else(too_large)?;
+        let days = u32::try_from(self.days).map_err(|_| too_large())?;
+        let milliseconds = self
+            .hours
+            .checked_mul(60)
+            .and_then(|minutes| minutes.checked_add(self.minutes))
+            .and_then(|minutes| minutes.checked_mul(60))
+            .and_then(|seconds| seconds.checked_add(self.seconds))
+            .and_then(|seconds| seconds.checked_mul(1000))
+            .and_then(|ms| ms.checked_add((self.nanoseconds / 1_000_000) as u64))
+            .and_then(|ms| u32::try_from(ms).ok())
+            .ok_or_else(too_large)?;
+        let mut bytes = [0; 12];
+        bytes[..4].copy_from_slice(&months.to_le_bytes());
+        bytes[4..8].copy_from_slice(&days.to_le_bytes());
+        bytes[8..].copy_from_slice(&milliseconds.to_le_bytes());
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Field, IntervalValue};
+
+    #[test]
+    fn parse_intervals() {
+        let year_to_month = IntervalValue::parse(b"-1-02", Field::Year, Field::Month).unwrap();
+        assert_eq!(
+            IntervalValue {
+                negative: true,
+                years: 1,
+                months: 2,
+                ..Default::default()
+            },
+            year_to_month
+        );
+
+        let day_to_second =
+            IntervalValue::parse(b"3 04:05:06.5", Field::Day, Field::Second).unwrap();
+        assert_eq!(
+            IntervalValue {
+                days: 3,
+                hours: 4,
+                minutes: 5,
+                seconds: 6,
+                nanoseconds: 500_000_000,
+                ..Default::default()
+            },
+            day_to_second
+        );
+
+        let hours = IntervalValue::parse(b"  1500", Field::Hour, Field::Hour).unwrap();
+        assert_eq!(1500, hours.hours);
+
+        assert!(IntervalValue::parse(b"3 04:05", Field::Day, Field::Second).is_err());
+        assert!(IntervalValue::parse(b"1-x", Field::Year, Field::Month).is_err());
+    }
+
+    #[test]
+    fn iso8601_durations() {
+        let iso = |text: &[u8], leading, trailing| {
+            IntervalValue::parse(text, leading, trailing)
+                .unwrap()
+                .to_iso8601()
+        };
+        assert_eq!("-P1Y2M", iso(b"-1-02", Field::Year, Field::Month));
+        assert_eq!(
+            "P3DT4H5M6.5S",
+            iso(b"3 04:05:06.5", Field::Day, Field::Second)
+        );
+        assert_eq!("PT90M", iso(b"90", Field::Minute, Field::Minute));
+        assert_eq!("PT0S", iso(b"0 00:00:00", Field::Day, Field::Second));
+        assert_eq!("PT0.25S", iso(b"0.250", Field::Second, Field::Second));
+    }
+
+    #[test]
+    fn parquet_intervals() {
+        let interval = |text: &[u8], leading, trailing| {
+            IntervalValue::parse(text, leading, trailing)
+                .unwrap()
+                .to_parquet_interval()
+        };
+        let bytes = interval(b"1-02", Field::Year, Field::Month).unwrap();
+        assert_eq!([14, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0], bytes);
+        let bytes = interval(b"3 00:00:01.5", Field::Day, Field::Second).unwrap();
+        assert_eq!([0, 0, 0, 0, 3, 0, 0, 0, 0xdc, 0x05, 0, 0], bytes);
+
+        assert!(interval(b"-1-02", Field::Year, Field::Month).is_err());
+        assert!(interval(b"2000", Field::Hour, Field::Hour).is_err());
+    }
+}
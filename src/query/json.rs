@@ -0,0 +1,64 @@
+//! JSON logical type. Engines like BigQuery and DuckDB parse columns annotated as JSON natively,
+//! rather than treating them as plain strings.
+
+use anyhow::{bail, Error};
+use parquet::{
+    basic::{ConvertedType, LogicalType, Type as PhysicalType},
+    schema::types::Type,
+};
+
+/// The same text column, annotated with the JSON logical type. Fails for columns not holding text.
+pub fn with_json_logical_type(parquet_type: Type) -> Result<Type, Error> {
+    let info = parquet_type.get_basic_info();
+    let is_text = parquet_type.is_primitive()
+        && parquet_type.get_physical_type() == PhysicalType::BYTE_ARRAY
+        && matches!(
+            info.converted_type(),
+            ConvertedType::UTF8 | ConvertedType::JSON
+        );
+    if !is_text {
+        bail!(
+            "JSON column '{}' must hold text. E.g. cast it to `NVARCHAR(MAX)` in Microsoft SQL \
+            Server.",
+            info.name()
+        )
+    }
+    let json = Type::primitive_type_builder(info.name(), PhysicalType::BYTE_ARRAY)
+        .with_repetition(info.repetition())
+        .with_logical_type(Some(LogicalType::Json))
+        .with_id(info.has_id().then(|| info.id()))
+        .build()
+        .unwrap();
+    Ok(json)
+}
+
+#[cfg(test)]
+mod test {
+    use parquet::{
+        basic::{ConvertedType, LogicalType, Repetition, Type as PhysicalType},
+        schema::types::Type,
+    };
+
+    use super::with_json_logical_type;
+
+    #[test]
+    fn annotate_text_as_json() {
+        let text = Type::primitive_type_builder("doc", PhysicalType::BYTE_ARRAY)
+            .with_converted_type(ConvertedType::UTF8)
+            .with_repetition(Repetition::OPTIONAL)
+            .build()
+            .unwrap();
+
+        let json = with_json_logical_type(text).unwrap();
+
+        let info = json.get_basic_info();
+        assert_eq!(Some(&LogicalType::Json), info.logical_type_ref());
+        assert_eq!(ConvertedType::JSON, info.converted_type());
+        assert_eq!(Repetition::OPTIONAL, info.repetition());
+
+        let int = Type::primitive_type_builder("id", PhysicalType::INT32)
+            .build()
+            .unwrap();
+        assert!(with_json_logical_type(int).is_err());
+    }
+}
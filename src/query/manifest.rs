@@ -0,0 +1,74 @@
+//! Files written once all data files of an export are complete, so orchestrators can tell a
+//! finished export apart from one still in progress or one which failed halfway.
+
+use std::io::Write;
+
+use anyhow::Error;
+use log::info;
+use parquet::schema::{printer::print_schema, types::Type};
+use serde_json::json;
+
+use crate::destination::Destination;
+
+use super::parquet_writer::WrittenFile;
+
+/// Name of the empty file marking a complete export, as used by Hadoop based tools.
+const SUCCESS_MARKER: &str = "_SUCCESS";
+
+/// Name of the manifest listing the files of the export. The leading underscore causes engines
+/// like Spark or Hive to ignore it, when reading the directory as a dataset.
+const MANIFEST: &str = "_manifest.json";
+
+/// Marker and manifest written after the data files of an export.
+pub struct Completion {
+    /// Directory the marker and the manifest are written into.
+    pub directory: Destination,
+    pub success_marker: bool,
+    pub manifest: bool,
+}
+
+impl Completion {
+    /// Must be called after all files have been written successfully.
+    pub fn write(&self, schema: &Type, files: &[WrittenFile]) -> Result<(), Error> {
+        if self.manifest {
+            let manifest = self.manifest(schema, files);
+            let mut sink = self.directory.join(MANIFEST).create()?;
+            sink.write_all(manifest.as_bytes())?;
+            sink.finish()?;
+            info!("Written manifest of {} files.", files.len());
+        }
+        // The marker goes last, so its presence implies the manifest is complete, too.
+        if self.success_marker {
+            self.directory.join(SUCCESS_MARKER).create()?.finish()?;
+        }
+        Ok(())
+    }
+
+    fn manifest(&self, schema: &Type, files: &[WrittenFile]) -> String {
+        let files: Vec<_> = files
+            .iter()
+            .map(|file| {
+                // Files are always written into the directory of the manifest, so the path is
+                // relative to it. This way the manifest stays valid if the export is moved.
+                let path = file
+                    .destination
+                    .relative_to(&self.directory)
+                    .expect("Written files must be located within the directory of the manifest.");
+                let num_rows: i64 = file.row_groups.iter().map(|rg| rg.num_rows()).sum();
+                json!({ "path": path, "num_rows": num_rows, "size": file.size })
+            })
+            .collect();
+        let num_rows: i64 = files
+            .iter()
+            .map(|file| file["num_rows"].as_i64().unwrap())
+            .sum();
+        let mut printed_schema = Vec::new();
+        print_schema(&mut printed_schema, schema);
+        let manifest = json!({
+            "files": files,
+            "num_rows": num_rows,
+            "schema": String::from_utf8(printed_schema).unwrap(),
+        });
+        serde_json::to_string_pretty(&manifest).unwrap()
+    }
+}
@@ -0,0 +1,84 @@
+//! Columns written as `REQUIRED` regardless of the nullability reported by the driver, see
+//! `--assert-not-null`.
+
+use anyhow::{Context, Error};
+use odbc_api::buffers::{AnyColumnView, BufferDescription};
+use parquet::{basic::Repetition, column::writer::ColumnWriter, schema::types::Type};
+
+use crate::parquet_buffer::ParquetBuffer;
+
+use super::strategy::ColumnFetchStrategy;
+
+/// Wraps the strategy of an optional column, so it is written as `REQUIRED`. Fails if a NULL is
+/// fetched, rather than trusting the user blindly.
+pub struct NotNull {
+    inner: Box<dyn ColumnFetchStrategy>,
+    name: String,
+}
+
+impl NotNull {
+    pub fn new(inner: Box<dyn ColumnFetchStrategy>, name: String) -> Self {
+        Self { inner, name }
+    }
+}
+
+impl ColumnFetchStrategy for NotNull {
+    fn parquet_type(&self, name: &str) -> Type {
+        as_required(self.inner.parquet_type(name))
+    }
+
+    fn buffer_description(&self) -> BufferDescription {
+        // Keep the indicators, so we can detect NULLs.
+        self.inner.buffer_description()
+    }
+
+    fn copy_odbc_to_parquet(
+        &self,
+        parquet_buffer: &mut ParquetBuffer,
+        column_writer: &mut ColumnWriter,
+        column_view: AnyColumnView,
+    ) -> Result<(), Error> {
+        self.inner
+            .copy_odbc_to_parquet(parquet_buffer, column_writer, column_view)
+            .with_context(|| {
+                format!(
+                    "Column '{}' is asserted not to contain NULL, see --assert-not-null.",
+                    self.name
+                )
+            })
+    }
+}
+
+/// The same primitive parquet type, but with `REQUIRED` repetition.
+pub fn as_required(parquet_type: Type) -> Type {
+    with_repetition(parquet_type, Repetition::REQUIRED)
+}
+
+/// The same primitive parquet type, but with `OPTIONAL` repetition.
+pub fn as_optional(parquet_type: Type) -> Type {
+    with_repetition(parquet_type, Repetition::OPTIONAL)
+}
+
+fn with_repetition(parquet_type: Type, repetition: Repetition) -> Type {
+    let info = parquet_type.get_basic_info();
+    match &parquet_type {
+        Type::PrimitiveType {
+            physical_type,
+            type_length,
+            scale,
+            precision,
+            ..
+        } => Type::primitive_type_builder(info.name(), *physical_type)
+            .with_repetition(repetition)
+            .with_converted_type(info.converted_type())
+            .with_logical_type(info.logical_type_ref().cloned())
+            .with_length(*type_length)
+            .with_precision(*precision)
+            .with_scale(*scale)
+            .with_id(info.has_id().then(|| info.id()))
+            .build()
+            .unwrap(),
+        // Strategies only create primitive types.
+        Type::GroupType { .. } => parquet_type,
+    }
+}
@@ -0,0 +1,185 @@
+//! Constants written instead of NULL, see `--null-default`.
+
+use anyhow::{anyhow, bail, Context, Error};
+use atoi::FromRadix10Signed;
+use chrono::NaiveDate;
+use odbc_api::buffers::{AnyColumnView, BufferDescription};
+use parquet::{
+    basic::{ConvertedType, LogicalType, Type as PhysicalType},
+    column::writer::ColumnWriter,
+    data_type::ByteArray,
+    schema::types::Type,
+};
+
+use crate::parquet_buffer::{NullDefault, ParquetBuffer};
+
+use super::{
+    decimal::{rescale_decimal_text, twos_complement},
+    not_null::as_required,
+    strategy::ColumnFetchStrategy,
+};
+
+/// Wraps the strategy of an optional column, so NULLs are replaced with a default value. Since the
+/// column can no longer contain NULL, it is written as `REQUIRED`.
+pub struct WithNullDefault {
+    inner: Box<dyn ColumnFetchStrategy>,
+    null_default: NullDefault,
+}
+
+impl WithNullDefault {
+    /// Fails if `text` can not be represented in the parquet type of the column.
+    pub fn new(inner: Box<dyn ColumnFetchStrategy>, name: &str, text: &str) -> Result<Self, Error> {
+        let null_default = parse_null_default(&inner.parquet_type(name), text)
+            .with_context(|| format!("Invalid default for NULLs in column '{name}'."))?;
+        Ok(Self {
+            inner,
+            null_default,
+        })
+    }
+}
+
+impl ColumnFetchStrategy for WithNullDefault {
+    fn parquet_type(&self, name: &str) -> Type {
+        as_required(self.inner.parquet_type(name))
+    }
+
+    fn buffer_description(&self) -> BufferDescription {
+        self.inner.buffer_description()
+    }
+
+    fn copy_odbc_to_parquet(
+        &self,
+        parquet_buffer: &mut ParquetBuffer,
+        column_writer: &mut ColumnWriter,
+        column_view: AnyColumnView,
+    ) -> Result<(), Error> {
+        parquet_buffer.set_null_default(Some(self.null_default.clone()));
+        let result = self
+            .inner
+            .copy_odbc_to_parquet(parquet_buffer, column_writer, column_view);
+        parquet_buffer.set_null_default(None);
+        result
+    }
+}
+
+/// Physical value of `text` in a column of type `parquet_type`.
+fn parse_null_default(parquet_type: &Type, text: &str) -> Result<NullDefault, Error> {
+    let info = parquet_type.get_basic_info();
+    let converted_type = info.converted_type();
+    let type_name = if converted_type == ConvertedType::NONE {
+        parquet_type.get_physical_type().to_string()
+    } else {
+        format!("{} ({converted_type})", parquet_type.get_physical_type())
+    };
+    let invalid = || anyhow!("Can not parse '{text}' as a value of type {type_name}.");
+    if converted_type == ConvertedType::DECIMAL {
+        let precision = parquet_type.get_precision().try_into().unwrap();
+        let mut digits = Vec::new();
+        rescale_decimal_text(
+            text.as_bytes(),
+            precision,
+            parquet_type.get_scale(),
+            &mut digits,
+        )?;
+        return Ok(match parquet_type.get_physical_type() {
+            PhysicalType::INT32 => NullDefault::Int32(i32::from_radix_10_signed(&digits).0),
+            PhysicalType::INT64 => NullDefault::Int64(i64::from_radix_10_signed(&digits).0),
+            _ => {
+                let Type::PrimitiveType { type_length, .. } = parquet_type else {
+                    unreachable!("Decimals are primitive types.")
+                };
+                let length_in_bytes = (*type_length).try_into().unwrap();
+                let bytes: ByteArray = twos_complement(&digits, length_in_bytes).into();
+                NullDefault::FixedLenByteArray(bytes.into())
+            }
+        });
+    }
+    let is_integer = matches!(
+        info.logical_type_ref(),
+        None | Some(LogicalType::Integer { .. })
+    );
+    let null_default = match (parquet_type.get_physical_type(), converted_type) {
+        (PhysicalType::BOOLEAN, _) => NullDefault::Boolean(text.parse().map_err(|_| invalid())?),
+        (PhysicalType::INT32, ConvertedType::DATE) => {
+            let date = NaiveDate::parse_from_str(text, "%Y-%m-%d").map_err(|_| invalid())?;
+            let unix_epoch = NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+            let days = date.signed_duration_since(unix_epoch).num_days();
+            NullDefault::Int32(days.try_into().map_err(|_| invalid())?)
+        }
+        (PhysicalType::INT32, _) if is_integer => {
+            NullDefault::Int32(text.parse().map_err(|_| invalid())?)
+        }
+        (PhysicalType::INT64, _) if is_integer => {
+            NullDefault::Int64(text.parse().map_err(|_| invalid())?)
+        }
+        (PhysicalType::FLOAT, _) => NullDefault::Float(text.parse().map_err(|_| invalid())?),
+        (PhysicalType::DOUBLE, _) => NullDefault::Double(text.parse().map_err(|_| invalid())?),
+        (PhysicalType::BYTE_ARRAY, _) => NullDefault::ByteArray(text.as_bytes().to_vec().into()),
+        // E.g. timestamps or times
+        _ => bail!("Defaults for NULLs are not supported for columns of type {type_name}."),
+    };
+    Ok(null_default)
+}
+
+#[cfg(test)]
+mod tests {
+    use parquet::{
+        basic::{LogicalType, Repetition, TimeUnit, Type as PhysicalType},
+        data_type::ByteArray,
+        schema::types::Type,
+    };
+
+    use crate::parquet_buffer::NullDefault;
+
+    use super::parse_null_default;
+
+    fn parquet_type(physical_type: PhysicalType, logical_type: Option<LogicalType>) -> Type {
+        Type::primitive_type_builder("a", physical_type)
+            .with_logical_type(logical_type)
+            .with_repetition(Repetition::OPTIONAL)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn parse_defaults_according_to_column_type() {
+        let int32 = parquet_type(PhysicalType::INT32, None);
+        assert_eq!(
+            NullDefault::Int32(-1),
+            parse_null_default(&int32, "-1").unwrap()
+        );
+        let date = parquet_type(PhysicalType::INT32, Some(LogicalType::Date));
+        assert_eq!(
+            NullDefault::Int32(1),
+            parse_null_default(&date, "1970-01-02").unwrap()
+        );
+        let text = parquet_type(PhysicalType::BYTE_ARRAY, Some(LogicalType::String));
+        assert_eq!(
+            NullDefault::ByteArray(ByteArray::from(Vec::new())),
+            parse_null_default(&text, "").unwrap()
+        );
+        let decimal = Type::primitive_type_builder("a", PhysicalType::INT64)
+            .with_logical_type(Some(LogicalType::decimal(2, 12)))
+            .with_precision(12)
+            .with_scale(2)
+            .build()
+            .unwrap();
+        assert_eq!(
+            NullDefault::Int64(150),
+            parse_null_default(&decimal, "1.5").unwrap()
+        );
+    }
+
+    #[test]
+    fn reject_invalid_defaults() {
+        let int32 = parquet_type(PhysicalType::INT32, None);
+        assert!(parse_null_default(&int32, "").is_err());
+        let boolean = parquet_type(PhysicalType::BOOLEAN, None);
+        assert!(parse_null_default(&boolean, "yes").is_err());
+        let timestamp = parquet_type(
+            PhysicalType::INT64,
+            Some(LogicalType::timestamp(false, TimeUnit::MICROS)),
+        );
+        assert!(parse_null_default(&timestamp, "0").is_err());
+    }
+}
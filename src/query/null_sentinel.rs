@@ -0,0 +1,54 @@
+//! Values written as NULL, e.g. `0000-00-00` or `-9999` used by legacy applications to mark missing
+//! values. See `--null-value`.
+
+use anyhow::Error;
+use odbc_api::buffers::{AnyColumnView, BufferDescription};
+use parquet::{column::writer::ColumnWriter, schema::types::Type};
+
+use crate::parquet_buffer::ParquetBuffer;
+
+use super::{strategy::ColumnFetchStrategy, value_text::column_as_text};
+
+/// Wraps the strategy of a column, so values with one of the `sentinels` as text representation
+/// are written as NULL. The column must be optional.
+pub struct NullSentinels {
+    inner: Box<dyn ColumnFetchStrategy>,
+    sentinels: Vec<String>,
+}
+
+impl NullSentinels {
+    pub fn new(inner: Box<dyn ColumnFetchStrategy>, sentinels: Vec<String>) -> Self {
+        Self { inner, sentinels }
+    }
+}
+
+impl ColumnFetchStrategy for NullSentinels {
+    fn parquet_type(&self, name: &str) -> Type {
+        self.inner.parquet_type(name)
+    }
+
+    fn buffer_description(&self) -> BufferDescription {
+        self.inner.buffer_description()
+    }
+
+    fn copy_odbc_to_parquet(
+        &self,
+        parquet_buffer: &mut ParquetBuffer,
+        column_writer: &mut ColumnWriter,
+        column_view: AnyColumnView,
+    ) -> Result<(), Error> {
+        let null_rows = column_as_text(column_view)
+            .iter()
+            .map(|text| {
+                text.as_ref()
+                    .is_some_and(|text| self.sentinels.contains(text))
+            })
+            .collect();
+        parquet_buffer.set_null_rows(Some(null_rows));
+        let result = self
+            .inner
+            .copy_odbc_to_parquet(parquet_buffer, column_writer, column_view);
+        parquet_buffer.set_null_rows(None);
+        result
+    }
+}
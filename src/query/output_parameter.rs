@@ -0,0 +1,229 @@
+//! Output and input/output parameters of a statement, e.g. return codes of stored procedures. See
+//! `--output-parameter` and `--inout-parameter`.
+
+use std::io::Write;
+
+use anyhow::{bail, Error};
+use log::info;
+use odbc_api::{
+    buffers::Indicator, handles::Statement, parameter::VarCharBox, sys::ParamType,
+    ParameterCollection,
+};
+use serde_json::{Map, Value};
+
+use crate::destination::Destination;
+
+/// Maximum length of the value of an output parameter in bytes. Output parameters are fetched as
+/// text.
+const MAX_OUTPUT_PARAMETER_LENGTH: usize = 4000;
+
+/// Parameters of a statement with at least one output or input/output parameter. Bound to the
+/// placeholders in order.
+pub struct ProcedureParameters {
+    parameters: Vec<ProcedureParameter>,
+}
+
+struct ProcedureParameter {
+    /// Name of the parameter in the output parameters file. `None` for input parameters.
+    name: Option<String>,
+    direction: ParamType,
+    value: VarCharBox,
+}
+
+impl ProcedureParameters {
+    /// Assigns the positional parameters in order to all placeholders, which are not bound as pure
+    /// output parameters.
+    pub fn new(
+        positional: Vec<String>,
+        output: &[(u16, String)],
+        inout: &[(u16, String)],
+    ) -> Result<Self, Error> {
+        let num_placeholders = positional.len() + output.len();
+        let mut named: Vec<(u16, &str, ParamType)> = output
+            .iter()
+            .map(|(position, name)| (*position, name.as_str(), ParamType::Output))
+            .chain(
+                inout
+                    .iter()
+                    .map(|(position, name)| (*position, name.as_str(), ParamType::InputOutput)),
+            )
+            .collect();
+        named.sort_by_key(|&(position, _, _)| position);
+        for window in named.windows(2) {
+            if window[0].0 == window[1].0 {
+                bail!(
+                    "Parameters '{}' and '{}' are both bound to placeholder {}.",
+                    window[0].1,
+                    window[1].1,
+                    window[0].0
+                )
+            }
+        }
+        if let Some(&(position, name, _)) = named.last() {
+            if usize::from(position) > num_placeholders {
+                bail!(
+                    "Parameter '{name}' is bound to placeholder {position}, but there are only \
+                    {num_placeholders} placeholders. Each placeholder which is not an output \
+                    parameter requires a positional parameter."
+                )
+            }
+        }
+
+        let mut positional = positional.into_iter();
+        let parameters = (1..=num_placeholders)
+            .map(|position| {
+                let named = named
+                    .iter()
+                    .find(|&&(named_position, _, _)| usize::from(named_position) == position);
+                match named {
+                    Some(&(_, name, ParamType::Output)) => ProcedureParameter {
+                        name: Some(name.to_owned()),
+                        direction: ParamType::Output,
+                        value: output_buffer(None),
+                    },
+                    Some(&(_, name, direction)) => ProcedureParameter {
+                        name: Some(name.to_owned()),
+                        direction,
+                        value: output_buffer(positional.next()),
+                    },
+                    None => ProcedureParameter {
+                        name: None,
+                        direction: ParamType::Input,
+                        value: VarCharBox::from_string(
+                            positional
+                                .next()
+                                .expect("One positional parameter per placeholder"),
+                        ),
+                    },
+                }
+            })
+            .collect();
+        Ok(Self { parameters })
+    }
+
+    /// Writes the values of the output and input/output parameters as a JSON object. Must be called
+    /// after the statement has been executed and its cursor has been closed, since drivers may
+    /// only return output parameters after the entire result set has been consumed.
+    pub fn write(&self, destination: &Destination) -> Result<(), Error> {
+        let mut values = Map::new();
+        for parameter in &self.parameters {
+            let Some(name) = &parameter.name else {
+                continue;
+            };
+            if !parameter.value.is_complete() {
+                bail!(
+                    "Value of output parameter '{name}' is longer than \
+                    {MAX_OUTPUT_PARAMETER_LENGTH} bytes."
+                )
+            }
+            let value = match parameter.value.as_bytes() {
+                Some(bytes) => Value::String(String::from_utf8_lossy(bytes).into_owned()),
+                None => Value::Null,
+            };
+            values.insert(name.clone(), value);
+        }
+        let mut sink = destination.create()?;
+        sink.write_all(serde_json::to_string_pretty(&values)?.as_bytes())?;
+        sink.finish()?;
+        info!("Written {} output parameters.", values.len());
+        Ok(())
+    }
+}
+
+/// Buffer large enough to hold any value returned for an output parameter. Initialized with the
+/// input value for input/output parameters.
+fn output_buffer(input: Option<String>) -> VarCharBox {
+    let mut buffer = vec![0; MAX_OUTPUT_PARAMETER_LENGTH + 1];
+    match input {
+        Some(input) => {
+            let input = input.into_bytes();
+            if input.len() > MAX_OUTPUT_PARAMETER_LENGTH {
+                return VarCharBox::from_vec(input);
+            }
+            buffer[..input.len()].copy_from_slice(&input);
+            VarCharBox::from_buffer(buffer.into_boxed_slice(), Indicator::Length(input.len()))
+        }
+        None => VarCharBox::from_buffer(buffer.into_boxed_slice(), Indicator::Null),
+    }
+}
+
+unsafe impl ParameterCollection for ProcedureParameters {
+    fn parameter_set_size(&self) -> usize {
+        1
+    }
+
+    unsafe fn bind_parameters_to(
+        &mut self,
+        stmt: &mut impl Statement,
+    ) -> Result<(), odbc_api::Error> {
+        for (index, parameter) in self.parameters.iter_mut().enumerate() {
+            let parameter_number = index as u16 + 1;
+            // The buffers are owned by `self` and live on the heap, so moving `self` does not
+            // invalidate them. Callers keep `self` alive until the cursor of the statement is
+            // closed, since the driver writes the values of output parameters only then.
+            let result = if parameter.direction == ParamType::Input {
+                stmt.bind_input_parameter(parameter_number, &parameter.value)
+            } else {
+                stmt.bind_parameter(parameter_number, parameter.direction, &mut parameter.value)
+            };
+            result.into_result(stmt)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use odbc_api::sys::ParamType;
+
+    use super::ProcedureParameters;
+
+    /// Name, direction and value of a parameter bound to a placeholder.
+    type Bound<'a> = (Option<&'a str>, ParamType, Option<&'a [u8]>);
+
+    fn bound(parameters: &ProcedureParameters) -> Vec<Bound<'_>> {
+        parameters
+            .parameters
+            .iter()
+            .map(|parameter| {
+                (
+                    parameter.name.as_deref(),
+                    parameter.direction,
+                    parameter.value.as_bytes(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn positional_parameters_fill_remaining_placeholders() {
+        let parameters = ProcedureParameters::new(
+            vec!["2023".to_owned(), "10".to_owned()],
+            &[(1, "return_code".to_owned())],
+            &[(3, "total".to_owned())],
+        )
+        .unwrap();
+
+        assert_eq!(
+            vec![
+                (Some("return_code"), ParamType::Output, None),
+                (None, ParamType::Input, Some(&b"2023"[..])),
+                (Some("total"), ParamType::InputOutput, Some(&b"10"[..])),
+            ],
+            bound(&parameters)
+        );
+    }
+
+    #[test]
+    fn reject_invalid_positions() {
+        let same_placeholder = ProcedureParameters::new(
+            vec!["1".to_owned()],
+            &[(1, "a".to_owned())],
+            &[(1, "b".to_owned())],
+        );
+        assert!(same_placeholder.is_err());
+        let beyond_last_placeholder =
+            ProcedureParameters::new(Vec::new(), &[(2, "a".to_owned())], &[]);
+        assert!(beyond_last_placeholder.is_err());
+    }
+}
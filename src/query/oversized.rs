@@ -0,0 +1,329 @@
+//! Values of text and binary columns larger than `--max-value-size`, which are written as NULL,
+//! truncated, or fail the export. See `--oversized`.
+
+use anyhow::{bail, Error};
+use log::{info, warn};
+use odbc_api::buffers::{AnyColumnView, BufferDescription, BufferKind, ColumnarAnyBuffer};
+use parquet::{basic::Type as PhysicalType, column::writer::ColumnWriter, schema::types::Type};
+
+use crate::{enum_args::OversizedPolicy, parquet_buffer::ParquetBuffer};
+
+use super::{
+    column_size::with_length, not_null::as_optional, spill::SpilledColumn,
+    strategy::ColumnFetchStrategy, value_text::column_as_text, ColumnInfo,
+};
+
+/// Policy for the oversized values of a column. A policy for the column itself takes precedence
+/// over one for all columns, the last one wins otherwise. Oversized values fail the export by
+/// default.
+pub fn oversized_policy(
+    name: &str,
+    oversized: &[(Option<String>, OversizedPolicy)],
+) -> OversizedPolicy {
+    let for_column = oversized
+        .iter()
+        .rev()
+        .find(|(column, _policy)| column.as_deref() == Some(name));
+    let for_all = oversized
+        .iter()
+        .rev()
+        .find(|(column, _policy)| column.is_none());
+    for_column
+        .or(for_all)
+        .map_or(OversizedPolicy::Error, |&(_, policy)| policy)
+}
+
+/// `max_value_size` in characters of the fetch buffer of the column, i.e. bytes for narrow text and
+/// binary, `u16` for wide text. `None` for columns which are neither text nor binary, or are written
+/// with a fixed length.
+pub fn value_limit(
+    strategy: &dyn ColumnFetchStrategy,
+    name: &str,
+    max_value_size: usize,
+) -> Option<usize> {
+    let limit = match strategy.buffer_description().kind {
+        BufferKind::Text { .. } | BufferKind::Binary { .. } => max_value_size,
+        BufferKind::WText { .. } => max_value_size / 2,
+        _ => return None,
+    };
+    let is_fixed_length =
+        strategy.parquet_type(name).get_physical_type() == PhysicalType::FIXED_LEN_BYTE_ARRAY;
+    (!is_fixed_length).then_some(limit.max(1))
+}
+
+/// Wraps the strategy of a text or binary column, so values larger than `max_value_size` bytes are
+/// handled as told by `policy`. Strategies of other columns are returned unchanged.
+pub fn limit_value_size(
+    inner: Box<dyn ColumnFetchStrategy>,
+    name: &str,
+    max_value_size: usize,
+    policy: OversizedPolicy,
+) -> Box<dyn ColumnFetchStrategy> {
+    let Some(limit) = value_limit(&*inner, name, max_value_size) else {
+        return inner;
+    };
+    Box::new(LimitedValueSize {
+        inner,
+        limit,
+        policy,
+    })
+}
+
+/// Fetches the values of a column into a buffer no larger than required to tell oversized values.
+/// Columns whose oversized values are written as NULL, or into side files, are optional.
+struct LimitedValueSize {
+    inner: Box<dyn ColumnFetchStrategy>,
+    /// Maximum length of a value, in characters of the fetch buffer.
+    limit: usize,
+    policy: OversizedPolicy,
+}
+
+impl ColumnFetchStrategy for LimitedValueSize {
+    fn parquet_type(&self, name: &str) -> Type {
+        let inner = self.inner.parquet_type(name);
+        match self.policy {
+            OversizedPolicy::Null | OversizedPolicy::File => as_optional(inner),
+            OversizedPolicy::Error | OversizedPolicy::Truncate => inner,
+        }
+    }
+
+    fn buffer_description(&self) -> BufferDescription {
+        let description = self.inner.buffer_description();
+        let length = match description.kind {
+            BufferKind::Text { max_str_len } | BufferKind::WText { max_str_len } => max_str_len,
+            BufferKind::Binary { length } => length,
+            _ => return description,
+        };
+        // Truncated values fill the buffer. Otherwise it holds one more character, so oversized
+        // values can be told apart. Columns without a maximum length are fetched in parts, which
+        // stop at the limit.
+        let max_len = match self.policy {
+            OversizedPolicy::Truncate => self.limit,
+            OversizedPolicy::Error | OversizedPolicy::Null => self.limit + 1,
+            // Values written into side files are always fetched in parts, since they must be
+            // fetched completely.
+            OversizedPolicy::File => return with_length(description, 0),
+        };
+        if length == 0 || length <= max_len {
+            description
+        } else {
+            with_length(description, max_len)
+        }
+    }
+
+    fn copy_odbc_to_parquet(
+        &self,
+        parquet_buffer: &mut ParquetBuffer,
+        column_writer: &mut ColumnWriter,
+        column_view: AnyColumnView,
+    ) -> Result<(), Error> {
+        // Oversized values fail the export before the batch is written, truncated ones are written
+        // as they are. Values written into side files have been fetched as NULL.
+        let oversized = longer_than(column_view, self.limit);
+        if self.policy != OversizedPolicy::Null || !oversized.contains(&true) {
+            return self
+                .inner
+                .copy_odbc_to_parquet(parquet_buffer, column_writer, column_view);
+        }
+        // Keep the rows written as NULL by other options, e.g. `--null-value`.
+        let previous = parquet_buffer.null_rows().map(<[bool]>::to_vec);
+        let null_rows = match &previous {
+            Some(previous) => previous
+                .iter()
+                .zip(&oversized)
+                .map(|(&previous, &oversized)| previous || oversized)
+                .collect(),
+            None => oversized,
+        };
+        parquet_buffer.set_null_rows(Some(null_rows));
+        let result = self
+            .inner
+            .copy_odbc_to_parquet(parquet_buffer, column_writer, column_view);
+        parquet_buffer.set_null_rows(previous);
+        result
+    }
+}
+
+/// Marks the values of a text or binary column longer than `limit` characters.
+fn longer_than(view: AnyColumnView, limit: usize) -> Vec<bool> {
+    match view {
+        AnyColumnView::Text(view) => view
+            .iter()
+            .map(|value| value.is_some_and(|value| value.len() > limit))
+            .collect(),
+        AnyColumnView::WText(view) => view
+            .iter()
+            .map(|value| value.is_some_and(|value| value.len() > limit))
+            .collect(),
+        AnyColumnView::Binary(view) => view
+            .iter()
+            .map(|value| value.is_some_and(|value| value.len() > limit))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// A text or binary column, whose values may be oversized.
+struct OversizedColumn {
+    buffer_index: usize,
+    column_number: u16,
+    name: String,
+    /// Maximum length of a value, in characters of the fetch buffer.
+    limit: usize,
+    policy: OversizedPolicy,
+}
+
+/// Reports the oversized values of each batch, together with the row they are part of, or fails
+/// the export as told by `--oversized`.
+pub struct OversizedCheck {
+    columns: Vec<OversizedColumn>,
+    /// Value of `--max-value-size` in bytes.
+    max_value_size: usize,
+    /// Position of the first `--sorted-by` column in the fetch buffer. Its value is reported as the
+    /// key of the row.
+    key_column: Option<usize>,
+    /// Number of rows fetched before the current batch.
+    num_rows: u64,
+}
+
+impl OversizedCheck {
+    pub fn new(
+        strategies: &[ColumnInfo],
+        max_value_size: Option<usize>,
+        oversized: &[(Option<String>, OversizedPolicy)],
+        key_column: Option<usize>,
+    ) -> Self {
+        let columns = match max_value_size {
+            Some(max_value_size) => strategies
+                .iter()
+                .enumerate()
+                .filter_map(|(buffer_index, (column_number, name, strategy))| {
+                    let limit = value_limit(&**strategy, name, max_value_size)?;
+                    Some(OversizedColumn {
+                        buffer_index,
+                        column_number: *column_number,
+                        name: name.clone(),
+                        limit,
+                        policy: oversized_policy(name, oversized),
+                    })
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        Self {
+            columns,
+            max_value_size: max_value_size.unwrap_or_default(),
+            key_column,
+            num_rows: 0,
+        }
+    }
+
+    /// Invoked for each batch, before it is written. The first `num_skipped` rows have been written
+    /// before resuming, and are not reported again.
+    pub fn check(
+        &mut self,
+        buffer: &ColumnarAnyBuffer,
+        spilled: &[SpilledColumn],
+        num_skipped: usize,
+    ) -> Result<(), Error> {
+        let first_row = self.num_rows;
+        self.num_rows += buffer.num_rows() as u64;
+        let mut keys = None;
+        for column in &self.columns {
+            let spilled = spilled
+                .iter()
+                .find(|spilled| spilled.column_number() == column.column_number);
+            let rows: Vec<usize> = match spilled {
+                Some(spilled) => spilled.oversized_rows().to_vec(),
+                None => {
+                    // Truncated values fill the buffer, so we can not tell whether they have been
+                    // truncated, or are exactly as long as the limit.
+                    let limit = match column.policy {
+                        OversizedPolicy::Truncate => column.limit - 1,
+                        OversizedPolicy::Error | OversizedPolicy::Null | OversizedPolicy::File => {
+                            column.limit
+                        }
+                    };
+                    longer_than(buffer.column(column.buffer_index), limit)
+                        .into_iter()
+                        .enumerate()
+                        .filter_map(|(row, oversized)| oversized.then_some(row))
+                        .collect()
+                }
+            };
+            for index in rows.into_iter().filter(|&row| row >= num_skipped) {
+                let keys = keys.get_or_insert_with(|| {
+                    self.key_column
+                        .map(|index| column_as_text(buffer.column(index)))
+                });
+                let key = match keys.as_ref().map(|keys| &keys[index]) {
+                    Some(Some(key)) => format!(" (key '{key}')"),
+                    Some(None) => " (key NULL)".to_owned(),
+                    None => String::new(),
+                };
+                let row = first_row + index as u64 + 1;
+                let (name, max_value_size) = (&column.name, self.max_value_size);
+                match column.policy {
+                    OversizedPolicy::Error => bail!(
+                        "The value of column '{name}' in row {row}{key} is larger than \
+                        {max_value_size} bytes. Use `--oversized {name}:null` to write it as \
+                        NULL, or `--oversized {name}:truncate` to truncate it."
+                    ),
+                    OversizedPolicy::Null => warn!(
+                        "Writing the value of column '{name}' in row {row}{key} as NULL, since it \
+                        is larger than {max_value_size} bytes."
+                    ),
+                    OversizedPolicy::Truncate => warn!(
+                        "Truncating the value of column '{name}' in row {row}{key} to \
+                        {max_value_size} bytes."
+                    ),
+                    OversizedPolicy::File => {
+                        let path = spilled
+                            .and_then(|spilled| spilled.side_file_paths()?[index].as_deref())
+                            .unwrap_or_default();
+                        info!(
+                            "Writing the value of column '{name}' in row {row}{key} into the side \
+                            file '{path}', since it is larger than {max_value_size} bytes."
+                        )
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::enum_args::{oversized_from_str, OversizedPolicy};
+
+    use super::oversized_policy;
+
+    #[test]
+    fn parse_oversized() {
+        assert_eq!(
+            (None, OversizedPolicy::Null),
+            oversized_from_str("NULL").unwrap()
+        );
+        assert_eq!(
+            (Some("doc:v2".to_owned()), OversizedPolicy::Truncate),
+            oversized_from_str("doc:v2:truncate").unwrap()
+        );
+        assert!(oversized_from_str(":null").is_err());
+        assert!(oversized_from_str("doc:skip").is_err());
+    }
+
+    #[test]
+    fn policy_for_column_takes_precedence() {
+        let oversized = [
+            (Some("doc".to_owned()), OversizedPolicy::Truncate),
+            (None, OversizedPolicy::Null),
+        ];
+        assert_eq!(
+            OversizedPolicy::Truncate,
+            oversized_policy("doc", &oversized)
+        );
+        assert_eq!(OversizedPolicy::Null, oversized_policy("pdf", &oversized));
+        assert_eq!(OversizedPolicy::Error, oversized_policy("pdf", &[]));
+    }
+}
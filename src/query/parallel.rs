@@ -0,0 +1,100 @@
+//! Splits the query into ranges of an integer column, which are fetched in parallel using a
+//! connection each. See `--partition-on`.
+
+use anyhow::{Context, Error};
+use log::info;
+use odbc_api::{Connection, Cursor, IntoParameter, Nullable};
+
+use super::source_table::quote_identifier;
+
+/// Queries fetching a part of the result set each. Together they return all rows of `query`. The
+/// rows are split by the value of `column` into ranges of equal width. Rows for which `column` is
+/// NULL are part of the first range. Fewer than `concurrency` queries are returned, if there are
+/// fewer distinct values, and a single one, if the result set is empty.
+pub fn partition_queries(
+    conn: &Connection<'_>,
+    db_name: &str,
+    query: &str,
+    parameters: &[String],
+    column: &str,
+    concurrency: usize,
+) -> Result<Vec<String>, Error> {
+    let column = quote_identifier(column, db_name);
+    // The query is used as a subquery, which must not be terminated.
+    let query = query.trim_end().trim_end_matches(';');
+    let bounds_query =
+        format!("SELECT MIN({column}), MAX({column}) FROM ({query}) AS partition_bounds");
+    let Some((min, max)) = bounds(conn, &bounds_query, parameters)
+        .with_context(|| format!("Failed to determine the range of values of {column}."))?
+    else {
+        info!("Result set is empty or {column} is always NULL. Fetching it as a single partition.");
+        return Ok(vec![query.to_owned()]);
+    };
+    let queries = ranges(min, max, concurrency)
+        .into_iter()
+        .enumerate()
+        .map(|(index, (start, end))| {
+            let mut predicate = format!("({column} >= {start} AND {column} <= {end})");
+            if index == 0 {
+                predicate = format!("{predicate} OR {column} IS NULL");
+            }
+            format!("SELECT * FROM ({query}) AS partition_query WHERE {predicate}")
+        })
+        .collect();
+    Ok(queries)
+}
+
+/// Smallest and largest value of the column. `None` if there are no values except NULL.
+fn bounds(
+    conn: &Connection<'_>,
+    query: &str,
+    parameters: &[String],
+) -> Result<Option<(i64, i64)>, Error> {
+    let parameters: Vec<_> = parameters
+        .iter()
+        .map(|parameter| parameter.as_str().into_parameter())
+        .collect();
+    let mut cursor = conn
+        .execute(query, parameters.as_slice())?
+        .context("Query for partition bounds returned no result set.")?;
+    let (mut min, mut max) = (Nullable::<i64>::null(), Nullable::<i64>::null());
+    if let Some(mut row) = cursor.next_row()? {
+        row.get_data(1, &mut min)?;
+        row.get_data(2, &mut max)?;
+    }
+    Ok(min.into_opt().zip(max.into_opt()))
+}
+
+/// Splits the values from `min` to `max` (inclusive) into at most `concurrency` ranges of equal
+/// width. Each range is described by its first and last value.
+fn ranges(min: i64, max: i64, concurrency: usize) -> Vec<(i64, i64)> {
+    let width = max as i128 - min as i128 + 1;
+    let concurrency = (concurrency as i128).min(width);
+    (0..concurrency)
+        .map(|index| {
+            let start = min as i128 + width * index / concurrency;
+            let end = min as i128 + width * (index + 1) / concurrency - 1;
+            (start as i64, end as i64)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ranges;
+
+    #[test]
+    fn split_into_ranges_of_equal_width() {
+        assert_eq!(
+            vec![(1, 25), (26, 50), (51, 75), (76, 100)],
+            ranges(1, 100, 4)
+        );
+        assert_eq!(vec![(0, 2), (3, 5), (6, 9)], ranges(0, 9, 3));
+        // Fewer values than partitions
+        assert_eq!(vec![(5, 5), (6, 6)], ranges(5, 6, 4));
+        assert_eq!(
+            vec![(i64::MIN, -1), (0, i64::MAX)],
+            ranges(i64::MIN, i64::MAX, 2)
+        );
+    }
+}
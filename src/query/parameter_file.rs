@@ -0,0 +1,155 @@
+//! Sets of parameters, each used for one execution of the query, see `--parameter-file`.
+
+use std::{fmt, fs, path::Path};
+
+use anyhow::{anyhow, bail, Context, Error};
+use serde::{
+    de::{MapAccess, SeqAccess, Visitor},
+    Deserializer,
+};
+use serde_json::Value;
+
+/// Names and values of the parameters for one execution of the query, in the order they are bound
+/// to the placeholders.
+pub type ParameterSet = Vec<(String, String)>;
+
+/// Reads the parameter sets from a JSON file (if the extension is `.json`) or a CSV file. CSV files
+/// must have a header naming the parameters. JSON files contain an array of objects, whose keys
+/// name the parameters.
+pub fn read_parameter_file(path: &Path) -> Result<Vec<ParameterSet>, Error> {
+    let is_json = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"));
+    let text = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read parameter file '{}'.", path.display()))?;
+    let parameter_sets = if is_json {
+        parameter_sets_from_json(&text)
+    } else {
+        parameter_sets_from_csv(&text)
+    }
+    .with_context(|| format!("Invalid parameter file '{}'.", path.display()))?;
+    if parameter_sets.is_empty() {
+        bail!("Parameter file '{}' is empty.", path.display())
+    }
+    Ok(parameter_sets)
+}
+
+fn parameter_sets_from_csv(text: &str) -> Result<Vec<ParameterSet>, Error> {
+    let mut reader = csv::Reader::from_reader(text.as_bytes());
+    let names = reader.headers()?.clone();
+    reader
+        .records()
+        .map(|record| {
+            let record = record?;
+            Ok(names
+                .iter()
+                .zip(record.iter())
+                .map(|(name, value)| (name.to_owned(), value.to_owned()))
+                .collect())
+        })
+        .collect()
+}
+
+fn parameter_sets_from_json(text: &str) -> Result<Vec<ParameterSet>, Error> {
+    let mut deserializer = serde_json::Deserializer::from_str(text);
+    let parameter_sets = deserializer.deserialize_seq(ParameterSetsVisitor)?;
+    deserializer.end()?;
+    parameter_sets
+}
+
+/// Visits an array of objects, keeping the keys in the order of the file. `serde_json::Map` would
+/// sort them.
+struct ParameterSetsVisitor;
+
+impl<'de> Visitor<'de> for ParameterSetsVisitor {
+    type Value = Result<Vec<ParameterSet>, Error>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array of objects")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut parameter_sets = Vec::new();
+        while let Some(parameter_set) = seq.next_element_seed(ParameterSetVisitor)? {
+            match parameter_set {
+                Ok(parameter_set) => parameter_sets.push(parameter_set),
+                Err(error) => return Ok(Err(error)),
+            }
+        }
+        Ok(Ok(parameter_sets))
+    }
+}
+
+/// Visits a single object, mapping its keys to the text representation of the values.
+struct ParameterSetVisitor;
+
+impl<'de> serde::de::DeserializeSeed<'de> for ParameterSetVisitor {
+    type Value = Result<ParameterSet, Error>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for ParameterSetVisitor {
+    type Value = Result<ParameterSet, Error>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an object mapping parameter names to values")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut parameter_set = Vec::new();
+        while let Some((name, value)) = map.next_entry::<String, Value>()? {
+            let value = match value {
+                Value::String(text) => text,
+                Value::Number(number) => number.to_string(),
+                Value::Bool(boolean) => boolean.to_string(),
+                other => {
+                    return Ok(Err(anyhow!(
+                        "Parameter '{name}' must be a string, number or boolean, not {other}."
+                    )))
+                }
+            };
+            parameter_set.push((name, value));
+        }
+        Ok(Ok(parameter_set))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parameter_sets_from_csv, parameter_sets_from_json};
+
+    fn owned(parameter_set: &[(&str, &str)]) -> Vec<(String, String)> {
+        parameter_set
+            .iter()
+            .map(|&(name, value)| (name.to_owned(), value.to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn read_parameter_sets_from_csv() {
+        assert_eq!(
+            vec![
+                owned(&[("tenant", "acme"), ("day", "2023-01-01")]),
+                owned(&[("tenant", "globex"), ("day", "2023-01-02")]),
+            ],
+            parameter_sets_from_csv("tenant,day\nacme,2023-01-01\nglobex,2023-01-02\n").unwrap()
+        );
+    }
+
+    #[test]
+    fn read_parameter_sets_from_json_in_order_of_keys() {
+        assert_eq!(
+            vec![owned(&[
+                ("tenant", "acme"),
+                ("id", "42"),
+                ("active", "true")
+            ])],
+            parameter_sets_from_json(r#"[{"tenant": "acme", "id": 42, "active": true}]"#).unwrap()
+        );
+        assert!(parameter_sets_from_json(r#"[{"tenant": null}]"#).is_err());
+        assert!(parameter_sets_from_json(r#"{"tenant": "acme"}"#).is_err());
+    }
+}
@@ -1,165 +1,700 @@
 use std::{
-    fs::File,
-    io::Write,
-    mem::swap,
-    path::{Path, PathBuf},
-    sync::Arc,
+    io::{self, Write},
+    mem::{swap, take},
+    sync::{Arc, Mutex},
+    thread,
 };
 
-use anyhow::{format_err, Error};
+use anyhow::Error;
+use bytes::Bytes;
 use bytesize::ByteSize;
-use io_arg::IoArg;
 use parquet::{
     basic::{Compression, Encoding},
+    column::{
+        page::{CompressedPage, PageWriteSpec, PageWriter},
+        writer::{get_column_writer, ColumnWriter},
+    },
+    encryption::encrypt::FileEncryptionProperties,
     errors::ParquetError,
     file::{
-        properties::WriterProperties,
-        writer::{SerializedFileWriter, SerializedRowGroupWriter},
+        metadata::{KeyValue, RowGroupMetaDataPtr, SortingColumn},
+        properties::{EnabledStatistics, WriterProperties, WriterVersion},
+        writer::{
+            SerializedFileWriter, SerializedPageWriter, SerializedRowGroupWriter, TrackedWrite,
+        },
     },
-    schema::types::{ColumnPath, Type},
+    schema::types::{ColumnPath, SchemaDescriptor, Type},
 };
 
+use crate::destination::{Destination, Sink};
+
 use super::batch_size_limit::FileSizeLimit;
 
 /// Options influencing the output parquet format.
+#[derive(Clone)]
 pub struct ParquetFormatOptions {
     pub column_compression_default: Compression,
+    /// Overrides `column_compression_default` for individual columns.
+    pub column_compressions: Vec<(String, Compression)>,
+    pub writer_version: WriterVersion,
     pub column_encodings: Vec<(String, Encoding)>,
+    /// Whether columns are dictionary encoded, unless overwritten in `column_dictionaries`.
+    pub dictionary: bool,
+    pub column_dictionaries: Vec<(String, bool)>,
+    /// Granularity of the statistics, unless overwritten in `column_statistics`.
+    pub statistics: EnabledStatistics,
+    pub column_statistics: Vec<(String, EnabledStatistics)>,
+    /// Maximum length of min/max values of byte array columns in the statistics and column index.
+    /// `None` means they are not truncated.
+    pub statistics_truncate_length: Option<usize>,
+    /// Write the column index for columns with page level statistics.
+    pub column_index: bool,
+    /// Write the offset index. Must be `true` if the column index is written.
+    pub offset_index: bool,
+    /// Overrides the default size limit of data pages.
+    pub data_page_size: Option<ByteSize>,
+    /// Overrides the default size limit of dictionary pages.
+    pub dictionary_page_size: Option<ByteSize>,
+    /// Columns for which a bloom filter is written, optionally with its false positive probability.
+    pub bloom_filters: Vec<(String, Option<f64>)>,
+    /// Recorded in the metadata of each row group, if the result set is known to be sorted.
+    pub sorting_columns: Option<Vec<SortingColumn>>,
+    /// If set, row groups are assembled from several fetched batches, until their uncompressed size
+    /// reaches this limit. Otherwise each batch is written as a row group of its own.
+    pub row_group_size: Option<ByteSize>,
+    /// Write a CRC32 checksum of each page into its header, so readers can detect corruption.
+    pub page_checksums: bool,
+    /// Number of threads encoding and compressing the columns of a row group. If greater than one,
+    /// columns are encoded into memory first.
+    pub encoding_threads: usize,
+    /// Additional key value metadata written into the footer, e.g. the Arrow schema.
+    pub key_value_metadata: Vec<KeyValue>,
+    /// Encrypt the output files, if set.
+    pub encryption: Option<Arc<FileEncryptionProperties>>,
+}
+
+/// A parquet file which has been completely written by [`ParquetWriter`].
+pub struct WrittenFile {
+    pub destination: Destination,
+    /// Metadata of the row groups within the file, including the column statistics.
+    pub row_groups: Vec<RowGroupMetaDataPtr>,
+    /// Size of the complete file in bytes.
+    pub size: u64,
 }
 
 /// Wraps parquet SerializedFileWriter. Handles splitting into new files after maximum amount of
 /// batches is reached.
 pub struct ParquetWriter {
-    path: Option<PathBuf>,
+    output: Destination,
     schema: Arc<Type>,
     properties: Arc<WriterProperties>,
-    writer: SerializedFileWriter<Box<dyn Write>>,
+    writer: SerializedFileWriter<CountingSink>,
+    /// Destination of the file `writer` is currently writing to.
+    current_destination: Destination,
+    /// Metadata of the row groups written into the current file so far.
+    current_row_groups: Vec<RowGroupMetaDataPtr>,
+    /// Files which have already been completed.
+    written_files: Vec<WrittenFile>,
     file_size: FileSizeLimit,
     num_file: u32,
+    /// Number of row groups written so far, across all files.
+    num_row_groups: u32,
     /// Keep track of curret file size so we can split it, should it get too large.
     current_file_size: ByteSize,
     /// Length of the suffix, appended to the end of a file in case they are numbered.
     suffix_length: usize,
+    row_group_size: Option<ByteSize>,
+    page_checksums: bool,
+    encoding_threads: usize,
+    /// Row group assembled from the batches fetched so far, if `row_group_size` is set.
+    buffered_row_group: Option<BufferedRowGroup>,
 }
 
 impl ParquetWriter {
+    /// `first_file` is the number of the first file written, if the output is split into several
+    /// files. It is greater than one, if an export is resumed.
     pub fn new(
-        output: IoArg,
+        output: Destination,
         schema: Arc<Type>,
         file_size: FileSizeLimit,
         format_options: ParquetFormatOptions,
         suffix_length: usize,
+        first_file: u32,
     ) -> Result<Self, Error> {
+        // Page level statistics are only written into the column index, since we do not write
+        // statistics into the page headers. Without column index chunk level statistics remain.
+        let column_index = format_options.column_index;
+        let statistics_level = |statistics| match statistics {
+            EnabledStatistics::Page if !column_index => EnabledStatistics::Chunk,
+            statistics => statistics,
+        };
+
         // Write properties
         // Seems to also work fine without setting the batch size explicitly, but what the heck. Just to
         // be on the safe side.
-        let mut wpb =
-            WriterProperties::builder().set_compression(format_options.column_compression_default);
+        let mut wpb = WriterProperties::builder()
+            .set_compression(format_options.column_compression_default)
+            .set_writer_version(format_options.writer_version)
+            .set_dictionary_enabled(format_options.dictionary)
+            .set_statistics_enabled(statistics_level(format_options.statistics))
+            .set_statistics_truncate_length(format_options.statistics_truncate_length)
+            .set_column_index_truncate_length(format_options.statistics_truncate_length)
+            .set_offset_index_disabled(!format_options.offset_index);
+        if let Some(data_page_size) = format_options.data_page_size {
+            wpb = wpb.set_data_page_size_limit(data_page_size.as_u64().try_into()?);
+        }
+        if let Some(dictionary_page_size) = format_options.dictionary_page_size {
+            wpb = wpb.set_dictionary_page_size_limit(dictionary_page_size.as_u64().try_into()?);
+        }
+        for (column_name, compression) in format_options.column_compressions {
+            let col = ColumnPath::new(vec![column_name]);
+            wpb = wpb.set_column_compression(col, compression)
+        }
+        for (column_name, enabled) in format_options.column_dictionaries {
+            let col = ColumnPath::new(vec![column_name]);
+            wpb = wpb.set_column_dictionary_enabled(col, enabled)
+        }
+        for (column_name, statistics) in format_options.column_statistics {
+            let col = ColumnPath::new(vec![column_name]);
+            wpb = wpb.set_column_statistics_enabled(col, statistics_level(statistics))
+        }
         for (column_name, encoding) in format_options.column_encodings {
             let col = ColumnPath::new(vec![column_name]);
             wpb = wpb.set_column_encoding(col, encoding)
         }
+        for (column_name, fpp) in format_options.bloom_filters {
+            let col = ColumnPath::new(vec![column_name]);
+            wpb = wpb.set_column_bloom_filter_enabled(col.clone(), true);
+            if let Some(fpp) = fpp {
+                wpb = wpb.set_column_bloom_filter_fpp(col, fpp);
+            }
+        }
+        wpb = wpb.set_sorting_columns(format_options.sorting_columns);
+        if !format_options.key_value_metadata.is_empty() {
+            wpb = wpb.set_key_value_metadata(Some(format_options.key_value_metadata));
+        }
+        if let Some(encryption) = format_options.encryption {
+            wpb = wpb.with_file_encryption_properties(encryption);
+        }
+        let row_group_size = format_options.row_group_size;
+        let page_checksums = format_options.page_checksums;
+        let encoding_threads = format_options.encoding_threads;
         let properties = Arc::new(wpb.build());
 
-        let (output, path): (Box<dyn Write>, _) = match output {
-            IoArg::StdStream => {
-                let output = output.open_as_output()?.into_write();
-                (output, None)
-            }
-            IoArg::File(path) => {
-                let file = if file_size.output_is_splitted() {
-                    File::create(Self::path_with_suffix(&path, 1, suffix_length)?)?
-                } else {
-                    File::create(&path)?
-                };
-                (Box::new(file), Some(path))
-            }
-        };
+        let current_destination =
+            if file_size.output_is_splitted() || output.has_sequence_placeholder() {
+                output.with_suffix(first_file, suffix_length)?
+            } else {
+                output.clone()
+            };
+        let sink = CountingSink::new(current_destination.create()?);
 
-        let writer = SerializedFileWriter::new(output, schema.clone(), properties.clone())?;
+        let writer = SerializedFileWriter::new(sink, schema.clone(), properties.clone())?;
 
         Ok(Self {
-            path,
+            output,
             schema,
             properties,
             writer,
+            current_destination,
+            current_row_groups: Vec::new(),
+            written_files: Vec::new(),
             file_size,
-            num_file: 1,
+            num_file: first_file,
+            num_row_groups: 0,
             current_file_size: ByteSize::b(0),
             suffix_length,
+            row_group_size,
+            page_checksums,
+            encoding_threads,
+            buffered_row_group: None,
         })
     }
 
-    pub fn update_current_file_size(&mut self, row_group_size: i64) {
-        self.current_file_size += ByteSize::b(row_group_size.try_into().unwrap());
+    /// Limit for the uncompressed size of row groups, if they are assembled from several batches.
+    pub fn row_group_size(&self) -> Option<ByteSize> {
+        self.row_group_size
     }
 
-    /// Retrieve the next row group writer. May trigger creation of a new file if limit of the
-    /// previous one is reached.
+    /// `true` if rows must be written using [`Self::buffer_rows`]. This is the case if row groups
+    /// are assembled from several batches, if the pages are written with checksums, which the
+    /// column writers of a [`SerializedRowGroupWriter`] do not support, or if columns are encoded
+    /// concurrently.
+    pub fn buffers_row_groups(&self) -> bool {
+        self.row_group_size.is_some() || self.page_checksums || self.encoding_threads > 1
+    }
+
+    /// Number of threads columns are encoded with. See [`Self::buffer_rows_concurrently`].
+    pub fn encoding_threads(&self) -> usize {
+        self.encoding_threads
+    }
+
+    /// Uncompressed size of the rows in the current buffered row group.
+    pub fn buffered_row_group_size(&self) -> u64 {
+        self.buffered_row_group
+            .as_ref()
+            .map_or(0, |row_group| row_group.size)
+    }
+
+    /// Adds rows to the buffered row group. `write_column` is invoked with the index and the writer
+    /// of each column.
+    ///
+    /// # Parameters
+    ///
+    /// * `size`: Uncompressed size of the added rows.
+    pub fn buffer_rows(
+        &mut self,
+        size: u64,
+        mut write_column: impl FnMut(usize, &mut ColumnWriter<'static>) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        let row_group = self.buffered_row_group();
+        for (index, (column_writer, _chunk)) in row_group.columns.iter_mut().enumerate() {
+            write_column(index, column_writer)?;
+        }
+        row_group.size += size;
+        Ok(())
+    }
+
+    /// Like [`Self::buffer_rows`], but the columns are distributed across
+    /// [`Self::encoding_threads`] threads. Each thread takes the next column not yet written,
+    /// until all are. Encoding and compressing a column is independent of all others, so this
+    /// scales with the number of columns.
     ///
-    /// # Parametersc
+    /// # Parameters
     ///
-    /// * `num_batch`: Zero based num batch index
-    pub fn next_row_group(
+    /// * `size`: Uncompressed size of the added rows.
+    /// * `init`: Invoked once by each thread to create state it uses for writing its columns, e.g.
+    ///   buffers for the values.
+    /// * `write_column`: Invoked with the state of the thread, the index and the writer of each
+    ///   column.
+    pub fn buffer_rows_concurrently<S>(
         &mut self,
-        num_batch: u32,
-    ) -> Result<SerializedRowGroupWriter<'_, Box<dyn Write>>, Error> {
+        size: u64,
+        init: impl Fn() -> S + Sync,
+        write_column: impl Fn(&mut S, usize, &mut ColumnWriter<'static>) -> Result<(), Error> + Sync,
+    ) -> Result<(), Error> {
+        let threads = self.encoding_threads;
+        let row_group = self.buffered_row_group();
+        let columns = Mutex::new(row_group.columns.iter_mut().enumerate());
+        thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut state = init();
+                        loop {
+                            let next = columns.lock().unwrap().next();
+                            let Some((index, (column_writer, _chunk))) = next else {
+                                return Ok(());
+                            };
+                            write_column(&mut state, index, column_writer)?;
+                        }
+                    })
+                })
+                .collect();
+            // Join all threads, before reporting the first error.
+            let results: Vec<Result<(), Error>> = workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap())
+                .collect();
+            results.into_iter().collect::<Result<(), Error>>()
+        })?;
+        row_group.size += size;
+        Ok(())
+    }
+
+    /// Row group the rows are currently added to. Created, if this is the first batch of rows.
+    fn buffered_row_group(&mut self) -> &mut BufferedRowGroup {
+        match &mut self.buffered_row_group {
+            Some(row_group) => row_group,
+            empty => empty.insert(BufferedRowGroup::new(
+                &self.schema,
+                &self.properties,
+                self.page_checksums,
+            )),
+        }
+    }
+
+    /// Writes the buffered row group into the file.
+    pub fn flush_row_group(&mut self) -> Result<(), Error> {
+        let Some(row_group) = self.buffered_row_group.take() else {
+            return Ok(());
+        };
+        let mut row_group_writer = self.next_row_group()?;
+        for (column_writer, chunk) in row_group.columns {
+            let close = column_writer.close()?;
+            let chunk = Bytes::from(take(&mut *chunk.0.lock().unwrap()));
+            row_group_writer.append_column(&chunk, close)?;
+        }
+        let metadata = row_group_writer.close()?;
+        self.finish_row_group(metadata);
+        Ok(())
+    }
+
+    /// Must be called with the metadata returned by closing the row group writer. Keeps track of
+    /// the file size and the statistics of the written row groups.
+    pub fn finish_row_group(&mut self, metadata: RowGroupMetaDataPtr) {
+        self.current_file_size += ByteSize::b(metadata.compressed_size().try_into().unwrap());
+        self.current_row_groups.push(metadata);
+    }
+
+    /// Retrieve the next row group writer. May trigger creation of a new file if limit of the
+    /// previous one is reached.
+    pub fn next_row_group(&mut self) -> Result<SerializedRowGroupWriter<'_, CountingSink>, Error> {
         // Check if we need to write the next batch into a new file
+        let num_batch = self.num_row_groups;
+        self.num_row_groups += 1;
         if self
             .file_size
             .should_start_new_file(num_batch, self.current_file_size)
         {
             self.num_file += 1;
             self.current_file_size = ByteSize::b(0);
-            let path = Self::path_with_suffix(
-                self.path.as_deref().unwrap(),
-                self.num_file,
-                self.suffix_length,
-            )?;
-            let file: Box<dyn Write> = Box::new(File::create(path)?);
+            let mut destination = self.output.with_suffix(self.num_file, self.suffix_length)?;
+            let sink = CountingSink::new(destination.create()?);
 
             // Create new writer as tmp writer
             let mut tmp_writer =
-                SerializedFileWriter::new(file, self.schema.clone(), self.properties.clone())?;
+                SerializedFileWriter::new(sink, self.schema.clone(), self.properties.clone())?;
             // Make the old writer the tmp_writer, so we can call .close on it, which destroys it.
             // Make the new writer self.writer, so we will use it to insert the new data.
             swap(&mut self.writer, &mut tmp_writer);
-            tmp_writer.close()?;
+            let size = finish_file(tmp_writer)?;
+            swap(&mut self.current_destination, &mut destination);
+            self.written_files.push(WrittenFile {
+                destination,
+                row_groups: take(&mut self.current_row_groups),
+                size,
+            });
         }
         Ok(self.writer.next_row_group()?)
     }
 
-    pub fn close(self) -> Result<(), ParquetError> {
-        self.writer.close()?;
+    /// Files completed so far. Does not include the file currently written.
+    pub fn completed_files(&self) -> &[WrittenFile] {
+        &self.written_files
+    }
+
+    /// Finishes the current file and returns all the files written.
+    pub fn close(mut self) -> Result<Vec<WrittenFile>, Error> {
+        self.flush_row_group()?;
+        let size = finish_file(self.writer)?;
+        self.written_files.push(WrittenFile {
+            destination: self.current_destination,
+            row_groups: self.current_row_groups,
+            size,
+        });
+        Ok(self.written_files)
+    }
+}
+
+/// Writes the footer of the file and finishes its sink. Returns the size of the file.
+fn finish_file(writer: SerializedFileWriter<CountingSink>) -> Result<u64, Error> {
+    let sink = writer.into_inner()?;
+    let size = sink.size;
+    sink.sink.finish()?;
+    Ok(size)
+}
+
+/// Keeps track of the number of bytes written into the sink, so we know the size of the file once
+/// the footer has been written.
+pub struct CountingSink {
+    sink: Box<dyn Sink>,
+    size: u64,
+}
+
+impl CountingSink {
+    fn new(sink: Box<dyn Sink>) -> Self {
+        Self { sink, size: 0 }
+    }
+}
+
+impl Write for CountingSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.sink.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.sink.flush()
+    }
+}
+
+/// Row group assembled from several fetched batches. Each column is encoded into a chunk in memory,
+/// so all columns can be written to at the same time. Once complete, the chunks are appended to the
+/// file one after another.
+struct BufferedRowGroup {
+    columns: Vec<(ColumnWriter<'static>, SharedChunk)>,
+    /// Uncompressed size of the rows written so far.
+    size: u64,
+}
+
+impl BufferedRowGroup {
+    fn new(schema: &Arc<Type>, properties: &Arc<WriterProperties>, checksums: bool) -> Self {
+        let columns = SchemaDescriptor::new(schema.clone())
+            .columns()
+            .iter()
+            .map(|column| {
+                let chunk = SharedChunk::default();
+                let page_writer = ChunkPageWriter {
+                    sink: TrackedWrite::new(chunk.clone()),
+                    checksums,
+                };
+                let column_writer =
+                    get_column_writer(column.clone(), properties.clone(), Box::new(page_writer));
+                (column_writer, chunk)
+            })
+            .collect();
+        Self { columns, size: 0 }
+    }
+}
+
+/// Encoded pages of a column chunk. Shared between the page writer, which is owned by the column
+/// writer, and the buffered row group, which appends the chunk to the file.
+#[derive(Clone, Default)]
+struct SharedChunk(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedChunk {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
         Ok(())
     }
+}
 
-    fn path_with_suffix(
-        path: &Path,
-        num_file: u32,
-        suffix_length: usize,
-    ) -> Result<PathBuf, Error> {
-        let suffix = pad_number(num_file, suffix_length);
-        let mut stem = path
-            .file_stem()
-            .ok_or_else(|| format_err!("Output needs To have a file stem."))?
-            .to_owned();
-        stem.push(suffix);
-        let mut path_with_suffix = path.with_file_name(stem);
-        path_with_suffix = path_with_suffix.with_extension("par");
-        Ok(path_with_suffix)
+/// Serializes the pages of a column into a [`SharedChunk`].
+struct ChunkPageWriter {
+    sink: TrackedWrite<SharedChunk>,
+    /// Write the CRC32 checksum of the page data into the page headers.
+    checksums: bool,
+}
+
+impl PageWriter for ChunkPageWriter {
+    fn write_page(&mut self, page: CompressedPage) -> parquet::errors::Result<PageWriteSpec> {
+        if !self.checksums {
+            return SerializedPageWriter::new(&mut self.sink).write_page(page);
+        }
+        let crc = crc32fast::hash(page.data());
+        let data_len = page.data().len();
+        let mut serialized = TrackedWrite::new(Vec::new());
+        let mut spec = SerializedPageWriter::new(&mut serialized).write_page(page)?;
+        let serialized = serialized.into_inner()?;
+        let header_len = serialized.len() - data_len;
+        let page = with_crc(&serialized, header_len, crc)?;
+
+        let added = (page.len() - serialized.len()) as u64;
+        spec.offset = self.sink.bytes_written() as u64;
+        spec.bytes_written += added;
+        spec.compressed_size += added as usize;
+        spec.uncompressed_size += added as usize;
+        self.sink.write_all(&page)?;
+        Ok(spec)
+    }
+
+    fn close(&mut self) -> parquet::errors::Result<()> {
+        Ok(())
+    }
+}
+
+/// Inserts the checksum into a serialized page. The parquet crate never writes the optional `crc`
+/// field of the page header, so we add it ourselves. The header is encoded using the thrift compact
+/// protocol and starts with the required fields 1 to 3 (type and sizes of the page), all of which
+/// are `i32`. The `crc` is field 4, also an `i32`, and must be inserted right after them. Field
+/// ids are encoded as the delta to the previous field, so the id of the next field (the header
+/// specific to the page type) must be adjusted as well.
+///
+/// # Parameters
+///
+/// * `page`: Serialized page header followed by the page data.
+/// * `header_len`: Length of the serialized page header in bytes.
+/// * `crc`: CRC32 checksum of the page data.
+fn with_crc(page: &[u8], header_len: usize, crc: u32) -> Result<Vec<u8>, ParquetError> {
+    // Short form field header of an `i32` field, whose id is the previous one plus one.
+    const NEXT_I32_FIELD: u8 = 0x15;
+    let unexpected = || ParquetError::General("Unexpected encoding of page header.".to_owned());
+
+    let mut pos = 0;
+    for _field in 1..=3 {
+        if page.get(pos) != Some(&NEXT_I32_FIELD) {
+            return Err(unexpected());
+        }
+        pos += 1;
+        // Skip the varint encoded value
+        while page.get(pos).ok_or_else(unexpected)? & 0x80 != 0 {
+            pos += 1;
+        }
+        pos += 1;
+    }
+    let next_field = *page
+        .get(pos)
+        .filter(|_| pos < header_len)
+        .ok_or_else(unexpected)?;
+    let delta = next_field >> 4;
+    if delta < 2 {
+        return Err(unexpected());
     }
+
+    let mut with_crc = Vec::with_capacity(page.len() + 6);
+    with_crc.extend_from_slice(&page[..pos]);
+    with_crc.push(NEXT_I32_FIELD);
+    // Zigzag and varint encoded
+    let crc = crc as i32;
+    let mut value = ((crc << 1) ^ (crc >> 31)) as u32;
+    while value >= 0x80 {
+        with_crc.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+    with_crc.push(value as u8);
+    with_crc.push(((delta - 1) << 4) | (next_field & 0x0f));
+    with_crc.extend_from_slice(&page[pos + 1..]);
+    Ok(with_crc)
 }
 
-fn pad_number(num_file: u32, suffix_length: usize) -> String {
-    let num_file = num_file.to_string();
-    let num_leading_zeroes = if suffix_length > num_file.len() {
-        suffix_length - num_file.len()
-    } else {
-        // Suffix is already large enough (if not too large) without leading zeroes
-        0
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use parquet::{
+        basic::Compression,
+        column::writer::ColumnWriter,
+        data_type::ByteArray,
+        file::{
+            properties::{EnabledStatistics, WriterVersion},
+            reader::FileReader,
+            serialized_reader::SerializedFileReader,
+        },
+        schema::parser::parse_message_type,
     };
-    let padding = "0".repeat(num_leading_zeroes);
-    let suffix = format!("_{padding}{num_file}");
-    suffix
+    use tempfile::tempdir;
+
+    use crate::{destination::Destination, query::batch_size_limit::FileSizeLimit};
+
+    use super::{ParquetFormatOptions, ParquetWriter};
+
+    fn format_options(writer_version: WriterVersion) -> ParquetFormatOptions {
+        ParquetFormatOptions {
+            column_compression_default: Compression::SNAPPY,
+            column_compressions: Vec::new(),
+            writer_version,
+            column_encodings: Vec::new(),
+            dictionary: true,
+            column_dictionaries: Vec::new(),
+            statistics: EnabledStatistics::Page,
+            column_statistics: Vec::new(),
+            statistics_truncate_length: Some(64),
+            column_index: true,
+            offset_index: true,
+            data_page_size: None,
+            dictionary_page_size: None,
+            bloom_filters: Vec::new(),
+            sorting_columns: None,
+            row_group_size: None,
+            page_checksums: true,
+            encoding_threads: 1,
+            key_value_metadata: Vec::new(),
+            encryption: None,
+        }
+    }
+
+    #[test]
+    fn page_checksums_are_verified_by_reader() {
+        for writer_version in [WriterVersion::PARQUET_1_0, WriterVersion::PARQUET_2_0] {
+            let dir = tempdir().unwrap();
+            let path = dir.path().join("out.par");
+            let schema =
+                parse_message_type("message schema { optional binary a (UTF8); }").unwrap();
+            let mut writer = ParquetWriter::new(
+                Destination::File(path.clone()),
+                schema.into(),
+                FileSizeLimit::new(0, None),
+                format_options(writer_version),
+                2,
+                1,
+            )
+            .unwrap();
+            writer
+                .buffer_rows(0, |_col_index, column_writer| {
+                    let ColumnWriter::ByteArrayColumnWriter(column_writer) = column_writer else {
+                        panic!("Column must be of type binary")
+                    };
+                    let values = [ByteArray::from("Hello"), ByteArray::from("World")];
+                    column_writer.write_batch(&values, Some(&[1, 0, 1]), None)?;
+                    Ok(())
+                })
+                .unwrap();
+            writer.close().unwrap();
+
+            let read_values = || -> Result<Vec<String>, parquet::errors::ParquetError> {
+                let reader = SerializedFileReader::new(fs::File::open(&path).unwrap())?;
+                reader
+                    .get_row_iter(None)?
+                    .map(|row| row.map(|row| row.to_string()))
+                    .collect()
+            };
+            assert_eq!(
+                vec!["{a: \"Hello\"}", "{a: null}", "{a: \"World\"}"],
+                read_values().unwrap()
+            );
+
+            // Corrupt the value 'World', which is stored in the dictionary page.
+            let mut bytes = fs::read(&path).unwrap();
+            let pos = bytes.windows(5).position(|w| w == b"World").unwrap();
+            bytes[pos] = b'w';
+            fs::write(&path, bytes).unwrap();
+            let error = read_values().unwrap_err();
+            assert!(error.to_string().contains("CRC"), "{error}");
+        }
+    }
+
+    #[test]
+    fn columns_encoded_concurrently_are_written_in_order() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("out.par");
+        let schema = parse_message_type(
+            "message schema { required int32 a; required int32 b; required int32 c; }",
+        )
+        .unwrap();
+        let mut options = format_options(WriterVersion::PARQUET_1_0);
+        options.page_checksums = false;
+        options.encoding_threads = 2;
+        let mut writer = ParquetWriter::new(
+            Destination::File(path.clone()),
+            schema.into(),
+            FileSizeLimit::new(0, None),
+            options,
+            2,
+            1,
+        )
+        .unwrap();
+        assert!(writer.buffers_row_groups());
+        for batch in 0..2 {
+            writer
+                .buffer_rows_concurrently(
+                    0,
+                    || (),
+                    |(), col_index, column_writer| {
+                        let ColumnWriter::Int32ColumnWriter(column_writer) = column_writer else {
+                            panic!("Column must be of type int32")
+                        };
+                        let value = batch * 10 + col_index as i32;
+                        column_writer.write_batch(&[value], None, None)?;
+                        Ok(())
+                    },
+                )
+                .unwrap();
+            writer.flush_row_group().unwrap();
+        }
+        writer.close().unwrap();
+
+        let reader = SerializedFileReader::new(fs::File::open(&path).unwrap()).unwrap();
+        assert_eq!(2, reader.metadata().num_row_groups());
+        let rows: Vec<String> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| row.unwrap().to_string())
+            .collect();
+        assert_eq!(vec!["{a: 0, b: 1, c: 2}", "{a: 10, b: 11, c: 12}"], rows);
+    }
 }
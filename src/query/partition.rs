@@ -0,0 +1,188 @@
+//! Routing of fetched rows into the partitions of a Hive-style partitioned dataset.
+
+use std::{collections::BTreeMap, fs, path::Path};
+
+use anyhow::{bail, Context, Error};
+use log::info;
+use odbc_api::buffers::ColumnarAnyBuffer;
+
+use crate::destination::Destination;
+
+use super::{parquet_writer::WrittenFile, value_text::column_as_text};
+
+/// Value used by Hive (and Spark) for the directory of rows there the partition column is NULL.
+const DEFAULT_PARTITION_NAME: &str = "__HIVE_DEFAULT_PARTITION__";
+
+/// Describes how the rows of the result set are split into partitions.
+pub struct Partitioning {
+    /// Position of the partition columns within the fetch buffer, together with their names.
+    columns: Vec<(usize, String)>,
+}
+
+impl Partitioning {
+    /// # Parameters
+    ///
+    /// * `partition_by`: Names of the partition columns, as specified by the user.
+    /// * `column_names`: Names of all columns in the fetch buffer.
+    pub fn new(partition_by: &[String], column_names: &[&str]) -> Result<Self, Error> {
+        let columns = partition_by
+            .iter()
+            .map(|partition_column| {
+                let Some(index) = column_names
+                    .iter()
+                    .position(|name| name == partition_column)
+                else {
+                    bail!(
+                        "Partition column '{partition_column}' is not part of the result set. \
+                        Available columns are: {}",
+                        column_names.join(", ")
+                    )
+                };
+                Ok((index, partition_column.clone()))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { columns })
+    }
+
+    /// `true` if the column at this position within the fetch buffer is used to partition the
+    /// output. Partition columns are not written into the parquet files, since their values are
+    /// part of the path.
+    pub fn is_partition_column(&self, buffer_index: usize) -> bool {
+        self.columns.iter().any(|(index, _)| *index == buffer_index)
+    }
+
+    /// Splits a fetched batch. Returns the relative path of each partition (e.g.
+    /// `country=DE/year=2022`) together with the ascending indices of the rows belonging to it.
+    pub fn split_batch(&self, batch: &ColumnarAnyBuffer) -> BTreeMap<String, Vec<usize>> {
+        let values: Vec<_> = self
+            .columns
+            .iter()
+            .map(|(index, _name)| column_as_text(batch.column(*index)))
+            .collect();
+
+        let mut partitions: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for row_index in 0..batch.num_rows() {
+            let path = self
+                .columns
+                .iter()
+                .zip(&values)
+                .map(|((_index, name), values)| {
+                    let value = values[row_index]
+                        .as_deref()
+                        .map(escape_path_name)
+                        .unwrap_or_else(|| DEFAULT_PARTITION_NAME.to_owned());
+                    format!("{}={value}", escape_path_name(name))
+                })
+                .collect::<Vec<_>>()
+                .join("/");
+            partitions.entry(path).or_default().push(row_index);
+        }
+        partitions
+    }
+}
+
+/// Removes the files within the directory of a partition, which have not been written by this
+/// export, e.g. files left over from a previous run which wrote more files into the partition.
+/// Hidden files and files starting with `_` are kept, just like subdirectories, since readers like
+/// Spark ignore them anyway.
+pub fn remove_stale_files(partition: &Destination, written: &[WrittenFile]) -> Result<(), Error> {
+    let Destination::File(directory) = partition else {
+        bail!("Overwriting partitions is only supported in the local file system.")
+    };
+    let entries = fs::read_dir(directory)
+        .with_context(|| format!("Failed to list partition '{}'.", directory.display()))?;
+    for entry in entries {
+        let path = entry?.path();
+        if !path.is_file() || is_hidden(&path) || is_written(&path, written) {
+            continue;
+        }
+        info!("Removing stale file '{}'.", path.display());
+        fs::remove_file(&path)
+            .with_context(|| format!("Failed to remove '{}'.", path.display()))?;
+    }
+    Ok(())
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.starts_with('.') || name.starts_with('_'))
+}
+
+fn is_written(path: &Path, written: &[WrittenFile]) -> bool {
+    written
+        .iter()
+        .any(|file| matches!(&file.destination, Destination::File(written) if written == path))
+}
+
+/// Escapes characters which are not allowed in partition directory names, the same way Hive does.
+/// E.g. `2022/01` becomes `2022%2F01`.
+fn escape_path_name(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_control()
+            || matches!(
+                c,
+                '"' | '#' | '%' | '\'' | '*' | '/' | ':' | '=' | '?' | '\\' | '{' | '[' | ']' | '^'
+            )
+        {
+            escaped.push_str(&format!("%{:02X}", c as u32));
+        } else {
+            escaped.push(c);
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use tempfile::tempdir;
+
+    use crate::destination::Destination;
+
+    use super::{escape_path_name, remove_stale_files, WrittenFile};
+
+    #[test]
+    fn remove_stale_files_from_partition() {
+        let dir = tempdir().unwrap();
+        for name in [
+            "part_01.par",
+            "part_02.par",
+            "part_03.par",
+            "_SUCCESS",
+            ".part.par.1.tmp",
+        ] {
+            fs::write(dir.path().join(name), b"").unwrap();
+        }
+        let written: Vec<_> = ["part_01.par", "part_02.par"]
+            .into_iter()
+            .map(|name| WrittenFile {
+                destination: Destination::File(dir.path().join(name)),
+                row_groups: Vec::new(),
+                size: 0,
+            })
+            .collect();
+
+        remove_stale_files(&Destination::File(dir.path().to_owned()), &written).unwrap();
+
+        let mut remaining: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        remaining.sort();
+        assert_eq!(
+            vec![".part.par.1.tmp", "_SUCCESS", "part_01.par", "part_02.par"],
+            remaining
+        );
+    }
+
+    #[test]
+    fn escape_partition_values() {
+        assert_eq!("Hello World", escape_path_name("Hello World"));
+        assert_eq!("2022%2F01", escape_path_name("2022/01"));
+        assert_eq!("a%3Db", escape_path_name("a=b"));
+        assert_eq!("12%3A30%3A00", escape_path_name("12:30:00"));
+    }
+}
@@ -0,0 +1,109 @@
+//! Executes queries, so long running queries can be cancelled with Ctrl+C. Either in ODBC
+//! asynchronous polling mode, see `--async-execution`, or by cancelling the statement from another
+//! thread.
+
+use std::{future::ready, mem::forget, thread::sleep, time::Duration};
+
+use anyhow::{bail, Error};
+use log::{info, warn};
+use odbc_api::{
+    handles::{AsStatementRef, Statement, StatementImpl},
+    sys::SQLCancel,
+    Connection, CursorImpl, ParameterCollectionRef,
+};
+
+use super::interrupt::{handle_interrupts_during, is_interrupted, CancelOnInterrupt};
+
+/// Time between checking whether the execution of the statement has finished.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Executes `query` like [`Connection::execute`]. If `async_execution` is `true` and the driver
+/// supports it, the statement is executed in polling mode. Otherwise it is cancelled from another
+/// thread. Either way, pressing Ctrl+C while the data source is still executing the statement
+/// cancels it. The returned cursor is synchronous again, so the result set is fetched like any
+/// other.
+pub fn execute<'c>(
+    conn: &'c Connection<'_>,
+    async_execution: bool,
+    query: &str,
+    params: impl ParameterCollectionRef,
+) -> Result<Option<CursorImpl<StatementImpl<'c>>>, Error> {
+    if !async_execution {
+        return execute_synchronously(conn, query, params);
+    }
+    let mut statement = match conn.preallocate()?.into_polling() {
+        Ok(statement) => statement,
+        Err(error) => {
+            info!("Driver does not support asynchronous execution ({error}). Executing synchronously.");
+            return execute_synchronously(conn, query, params);
+        }
+    };
+    let handle = statement.as_stmt_ref().as_sys();
+    let mut cancelled = false;
+    let poll = || {
+        if is_interrupted() && !cancelled {
+            warn!("Cancelling the execution of the query.");
+            // Safety: The handle is valid, as long as `statement` is. Cancelling is the one thing
+            // allowed, while a function is still executing asynchronously on the statement. Should
+            // it fail, we keep waiting for the execution to finish.
+            let _ = unsafe { SQLCancel(handle) };
+            cancelled = true;
+        }
+        sleep(POLL_INTERVAL);
+        ready(())
+    };
+    // Polling blocks this thread anyway, so a minimal runtime suffices to drive the future.
+    let runtime = tokio::runtime::Builder::new_current_thread().build()?;
+    let result =
+        handle_interrupts_during(|| runtime.block_on(statement.execute(query, params, poll)));
+    if is_interrupted() {
+        bail!("Interrupted while executing the query.")
+    }
+    let Some(cursor) = result? else {
+        return Ok(None);
+    };
+    // The cursor would close itself, if dropped. We hand the statement over to a synchronous
+    // cursor instead.
+    forget(cursor);
+    statement
+        .as_stmt_ref()
+        .set_async_enable(false)
+        .into_result(&statement.as_stmt_ref())?;
+    // Ownership of the handle passes from `statement` to the synchronous cursor, which frees it
+    // once dropped.
+    forget(statement);
+    // Safety: The handle is valid and in cursor state, since execution returned a cursor.
+    let cursor = unsafe { CursorImpl::new(StatementImpl::new(handle)) };
+    Ok(Some(cursor))
+}
+
+/// Executes `query` synchronously. Pressing Ctrl+C cancels the statement from another thread,
+/// while the data source is still executing it.
+fn execute_synchronously<'c>(
+    conn: &'c Connection<'_>,
+    query: &str,
+    params: impl ParameterCollectionRef,
+) -> Result<Option<CursorImpl<StatementImpl<'c>>>, Error> {
+    let mut statement = conn.preallocate()?;
+    let handle = statement.as_stmt_ref().as_sys();
+    let has_cursor = handle_interrupts_during(|| {
+        // Safety: The handle is valid, as long as `statement` is.
+        let _cancel = unsafe { CancelOnInterrupt::new(handle) };
+        // The cursor borrows the statement, so we hand the statement over to a cursor owning it
+        // instead.
+        statement.execute(query, params).map(|cursor| {
+            let has_cursor = cursor.is_some();
+            forget(cursor);
+            has_cursor
+        })
+    });
+    if is_interrupted() {
+        bail!("Interrupted while executing the query.")
+    }
+    if !has_cursor? {
+        return Ok(None);
+    }
+    // Safety: The handle is in cursor state, since execution returned a cursor.
+    let cursor = unsafe { CursorImpl::new(statement.into_statement()) };
+    Ok(Some(cursor))
+}
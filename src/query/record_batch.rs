@@ -0,0 +1,174 @@
+//! Conversion of fetched batches into Arrow record batches, so they can be served via Arrow Flight.
+
+use std::sync::Arc;
+
+use anyhow::{bail, Error};
+use arrow_array::{
+    ArrayRef, BinaryArray, BooleanArray, Float32Array, Float64Array, Int16Array, Int32Array,
+    Int64Array, Int8Array, RecordBatch, StringArray, UInt8Array,
+};
+use arrow_cast::cast;
+use arrow_schema::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use log::info;
+use odbc_api::{buffers::AnyColumnView, Cursor};
+use parquet::{
+    basic::{
+        ConvertedType, LogicalType, Repetition, TimeType, TimeUnit as ParquetTimeUnit,
+        TimestampType, Type as PhysicalType,
+    },
+    schema::types::Type,
+};
+
+use super::{
+    fetch_buffer, give_hint_about_flag_for_oracle_users, make_schema, value_text::column_as_text,
+    ColumnInfo,
+};
+
+pub use super::{batch_size_limit::BatchSizeLimit, strategy::MappingOptions};
+
+/// Fetches the result set and passes it on as Arrow record batches. The schema is passed to
+/// `on_schema` before the first batch is fetched, so it is known even if the result set is empty.
+/// Columns have the Arrow type matching the type they would have in a parquet file.
+pub fn cursor_to_record_batches(
+    mut cursor: impl Cursor,
+    batch_size: BatchSizeLimit,
+    mapping_options: MappingOptions,
+    on_schema: impl FnOnce(SchemaRef) -> Result<(), Error>,
+    mut on_batch: impl FnMut(RecordBatch) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let strategies = make_schema(&mut cursor, mapping_options, false)?;
+
+    if strategies.is_empty() {
+        bail!("Resulting record batches would not have any columns!")
+    }
+
+    let schema = arrow_schema(&strategies);
+    on_schema(schema.clone())?;
+
+    let (mut odbc_buffer, _batch_size_row) = fetch_buffer(&strategies, batch_size, 0)?;
+    let mut row_set_cursor = cursor.bind_buffer(&mut odbc_buffer)?;
+
+    let mut num_batch = 0;
+    while let Some(buffer) = row_set_cursor
+        .fetch()
+        .map_err(give_hint_about_flag_for_oracle_users)?
+    {
+        num_batch += 1;
+        info!(
+            "Fetched batch {} with {} rows.",
+            num_batch,
+            buffer.num_rows()
+        );
+        let columns = schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(col_index, field)| column_to_array(buffer.column(col_index), field.data_type()))
+            .collect::<Result<_, _>>()?;
+        on_batch(RecordBatch::try_new(schema.clone(), columns)?)?;
+    }
+
+    Ok(())
+}
+
+fn arrow_schema(strategies: &[ColumnInfo]) -> SchemaRef {
+    let fields: Vec<_> = strategies
+        .iter()
+        .map(|(_index, name, strategy)| {
+            let parquet_type = strategy.parquet_type(name);
+            let nullable = parquet_type.get_basic_info().repetition() != Repetition::REQUIRED;
+            Field::new(name, arrow_type(&parquet_type), nullable)
+        })
+        .collect();
+    Arc::new(Schema::new(fields))
+}
+
+/// Arrow type of a column, derived from its parquet type.
+fn arrow_type(field: &Type) -> DataType {
+    let info = field.get_basic_info();
+    if info.converted_type() == ConvertedType::DECIMAL {
+        return DataType::Decimal128(field.get_precision() as u8, field.get_scale() as i8);
+    }
+    if info.logical_type_ref() == Some(&LogicalType::Float16) {
+        return DataType::Float16;
+    }
+    match field.get_physical_type() {
+        PhysicalType::BOOLEAN => DataType::Boolean,
+        PhysicalType::INT32 => match info.converted_type() {
+            ConvertedType::INT_8 => DataType::Int8,
+            ConvertedType::INT_16 => DataType::Int16,
+            ConvertedType::UINT_8 => DataType::UInt8,
+            ConvertedType::UINT_16 => DataType::UInt16,
+            ConvertedType::UINT_32 => DataType::UInt32,
+            ConvertedType::DATE => DataType::Date32,
+            ConvertedType::TIME_MILLIS => DataType::Time32(TimeUnit::Millisecond),
+            _ => DataType::Int32,
+        },
+        PhysicalType::INT64 if info.converted_type() == ConvertedType::UINT_64 => DataType::UInt64,
+        PhysicalType::INT64 => match info.logical_type_ref() {
+            Some(LogicalType::Timestamp(TimestampType {
+                is_adjusted_to_u_t_c,
+                unit,
+            })) => {
+                let unit = match unit {
+                    ParquetTimeUnit::MILLIS => TimeUnit::Millisecond,
+                    ParquetTimeUnit::MICROS => TimeUnit::Microsecond,
+                    ParquetTimeUnit::NANOS => TimeUnit::Nanosecond,
+                };
+                let time_zone = is_adjusted_to_u_t_c.then(|| "+00:00".into());
+                DataType::Timestamp(unit, time_zone)
+            }
+            Some(LogicalType::Time(TimeType { unit, .. })) => match unit {
+                ParquetTimeUnit::MICROS => DataType::Time64(TimeUnit::Microsecond),
+                _ => DataType::Time64(TimeUnit::Nanosecond),
+            },
+            _ => DataType::Int64,
+        },
+        PhysicalType::INT96 => DataType::Timestamp(TimeUnit::Nanosecond, None),
+        PhysicalType::FLOAT => DataType::Float32,
+        PhysicalType::DOUBLE => DataType::Float64,
+        PhysicalType::BYTE_ARRAY if info.converted_type() == ConvertedType::UTF8 => DataType::Utf8,
+        PhysicalType::BYTE_ARRAY | PhysicalType::FIXED_LEN_BYTE_ARRAY => DataType::Binary,
+    }
+}
+
+/// Converts a fetched column into an Arrow array of the given type. Numbers, booleans and binary
+/// data are copied as is, everything else is rendered as text and parsed by Arrow. E.g. dates,
+/// timestamps and decimals.
+fn column_to_array(view: AnyColumnView, data_type: &DataType) -> Result<ArrayRef, Error> {
+    let array: ArrayRef = match view {
+        AnyColumnView::I8(values) => Arc::new(Int8Array::from(values.to_vec())),
+        AnyColumnView::I16(values) => Arc::new(Int16Array::from(values.to_vec())),
+        AnyColumnView::I32(values) => Arc::new(Int32Array::from(values.to_vec())),
+        AnyColumnView::I64(values) => Arc::new(Int64Array::from(values.to_vec())),
+        AnyColumnView::U8(values) => Arc::new(UInt8Array::from(values.to_vec())),
+        AnyColumnView::F32(values) => Arc::new(Float32Array::from(values.to_vec())),
+        AnyColumnView::F64(values) => Arc::new(Float64Array::from(values.to_vec())),
+        AnyColumnView::Bit(values) => Arc::new(BooleanArray::from_iter(
+            values.iter().map(|bit| Some(bit.as_bool())),
+        )),
+        AnyColumnView::NullableI8(values) => Arc::new(Int8Array::from_iter(values.map(copied))),
+        AnyColumnView::NullableI16(values) => Arc::new(Int16Array::from_iter(values.map(copied))),
+        AnyColumnView::NullableI32(values) => Arc::new(Int32Array::from_iter(values.map(copied))),
+        AnyColumnView::NullableI64(values) => Arc::new(Int64Array::from_iter(values.map(copied))),
+        AnyColumnView::NullableU8(values) => Arc::new(UInt8Array::from_iter(values.map(copied))),
+        AnyColumnView::NullableF32(values) => Arc::new(Float32Array::from_iter(values.map(copied))),
+        AnyColumnView::NullableF64(values) => Arc::new(Float64Array::from_iter(values.map(copied))),
+        AnyColumnView::NullableBit(values) => Arc::new(BooleanArray::from_iter(
+            values.map(|bit| bit.map(|bit| bit.as_bool())),
+        )),
+        AnyColumnView::Binary(view) => Arc::new(BinaryArray::from_iter(view.iter())),
+        other => Arc::new(StringArray::from(column_as_text(other))),
+    };
+    // E.g. decimals fetched as integers, or dates fetched as text.
+    let array = if array.data_type() == data_type {
+        array
+    } else {
+        cast(&array, data_type)?
+    };
+    Ok(array)
+}
+
+fn copied<T: Copy>(value: Option<&T>) -> Option<T> {
+    value.copied()
+}
@@ -0,0 +1,50 @@
+//! Statements returning more than one result set, e.g. stored procedures or batches of several
+//! `SELECT` statements. Each result set is written into an output of its own.
+
+use std::mem::ManuallyDrop;
+
+use anyhow::Error;
+use odbc_api::{
+    handles::{AsStatementRef, SqlResult, Statement, StatementImpl},
+    sys::{SQLMoreResults, SqlReturn},
+    CursorImpl, ResultSetMetadata,
+};
+
+/// Advances the cursor to the next result set with columns. Row counts of statements like an
+/// `UPDATE` within a batch are skipped. `None` once all result sets have been consumed.
+pub fn next_result_set(
+    mut cursor: CursorImpl<StatementImpl<'_>>,
+) -> Result<Option<CursorImpl<StatementImpl<'_>>>, Error> {
+    loop {
+        let handle = cursor.as_stmt_ref().as_sys();
+        // Safety: The handle is in cursor state. Unfetched rows of the current result set are
+        // discarded.
+        let result = match unsafe { SQLMoreResults(handle) } {
+            SqlReturn::NO_DATA => {
+                release(cursor);
+                return Ok(None);
+            }
+            SqlReturn::SUCCESS => SqlResult::Success(()),
+            SqlReturn::SUCCESS_WITH_INFO => SqlResult::SuccessWithInfo(()),
+            _ => SqlResult::Error {
+                function: "SQLMoreResults",
+            },
+        };
+        if let Err(error) = result.into_result(&cursor.as_stmt_ref()) {
+            release(cursor);
+            return Err(error.into());
+        }
+        if cursor.num_result_cols()? != 0 {
+            return Ok(Some(cursor));
+        }
+    }
+}
+
+/// Frees the statement handle of the cursor without closing it. Once `SQLMoreResults` found no
+/// further result set, there is no open cursor left to close.
+fn release(cursor: CursorImpl<StatementImpl<'_>>) {
+    let mut cursor = ManuallyDrop::new(cursor);
+    let handle = cursor.as_stmt_ref().as_sys();
+    // Safety: Ownership of the handle passes from the cursor to the statement, which frees it.
+    drop(unsafe { StatementImpl::new(handle) });
+}
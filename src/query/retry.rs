@@ -0,0 +1,103 @@
+//! Retries exports failing due to transient errors, like a lost connection or a deadlock. See
+//! `--retries`.
+
+use std::time::Duration;
+
+use anyhow::Error;
+use log::warn;
+use odbc_api::{Connection, Environment};
+
+use crate::{open_connection, ConnectOpts};
+
+use super::interrupt::{is_interrupted, sleep_unless_interrupted};
+
+/// SQLSTATEs indicating a failure which may not occur again, if the export is repeated.
+const TRANSIENT_STATES: &[&str] = &[
+    "08001", // Client unable to establish connection
+    "08007", // Link failure during transaction
+    "08S01", // Communication link failure, e.g. the connection has been reset
+    "40001", // Serialization failure, e.g. chosen as deadlock victim by SQL Server
+    "40P01", // Deadlock detected by PostgreSQL
+    "HYT00", // Timeout expired
+    "HYT01", // Connection timeout expired
+];
+
+/// `true` if the error has been caused by an ODBC diagnostic with a transient SQLSTATE.
+pub fn is_transient(error: &Error) -> bool {
+    error.chain().any(|cause| {
+        matches!(
+            cause.downcast_ref::<odbc_api::Error>(),
+            Some(odbc_api::Error::Diagnostics { record, .. })
+                if TRANSIENT_STATES.contains(&record.state.as_str())
+        )
+    })
+}
+
+/// Invokes `run` and repeats it up to `retries` times, as long as it fails with a transient error.
+/// A new connection is opened before each retry. The time waited before a retry starts with
+/// `backoff` and doubles with each retry. `run` is told whether it is a retry, so it may resume
+/// the previous attempt.
+pub fn with_retries<'e>(
+    environment: &'e Environment,
+    connect_opts: &ConnectOpts,
+    odbc_conn: &mut Connection<'e>,
+    retries: u32,
+    backoff: Duration,
+    mut run: impl FnMut(&Connection<'e>, bool) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let mut attempt = 0;
+    loop {
+        let result = if attempt == 0 {
+            run(odbc_conn, false)
+        } else {
+            open_connection(environment, connect_opts).and_then(|new_conn| {
+                *odbc_conn = new_conn;
+                run(odbc_conn, true)
+            })
+        };
+        let error = match result {
+            Ok(()) => return Ok(()),
+            Err(error) => error,
+        };
+        if attempt == retries || is_interrupted() || !is_transient(&error) {
+            return Err(error);
+        }
+        attempt += 1;
+        let wait = backoff.saturating_mul(1 << (attempt - 1).min(16));
+        warn!(
+            "Transient failure: {error:#}. Retry {attempt} of {retries} in {}s.",
+            wait.as_secs()
+        );
+        if !sleep_unless_interrupted(wait) {
+            return Err(error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Error;
+    use odbc_api::handles::{Record, State};
+
+    use super::is_transient;
+
+    fn diagnostics(state: &[u8; 5]) -> Error {
+        let error = odbc_api::Error::Diagnostics {
+            record: Record {
+                state: State(*state),
+                native_error: 0,
+                message: Vec::new(),
+            },
+            function: "SQLExecDirect",
+        };
+        Error::from(error).context("Failed to execute query.")
+    }
+
+    #[test]
+    fn classify_transient_errors() {
+        assert!(is_transient(&diagnostics(b"08S01")));
+        assert!(is_transient(&diagnostics(b"40001")));
+        assert!(!is_transient(&diagnostics(b"42S02")));
+        assert!(!is_transient(&Error::msg("Output already exists.")));
+    }
+}
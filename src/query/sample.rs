@@ -0,0 +1,100 @@
+//! Sizes the fetch buffers of text and binary columns from the longest values within a sample of
+//! the result set, rather than from the lengths reported by the driver. See `--sample-lengths`.
+
+use std::io;
+
+use anyhow::Error;
+use log::info;
+use odbc_api::{buffers::BufferKind, sys::CDataType, Cursor};
+use parquet::basic::Type as PhysicalType;
+
+use super::{make_schema, spill::Part, strategy::MappingOptions};
+
+/// A text or binary column, whose values are measured.
+struct SampledColumn {
+    column_number: u16,
+    name: String,
+    /// Length of the buffer derived from the column description, in `u16` for wide text and in
+    /// bytes otherwise.
+    length: usize,
+    /// Fetches the values in parts, since they may be longer than any buffer.
+    part: Part,
+    /// `true` for wide text, whose length is counted in `u16`.
+    is_wide: bool,
+    /// Length of the longest value so far.
+    longest: usize,
+}
+
+/// Fetches up to `rows` rows of the result set, to find the longest value of each text and binary
+/// column. Returns the length of the buffer for each column, whose longest value is shorter than
+/// the length reported by the driver.
+pub fn sample_column_lengths(
+    mut cursor: impl Cursor,
+    mapping_options: MappingOptions,
+    rows: usize,
+) -> Result<Vec<(String, usize)>, Error> {
+    let strategies = make_schema(&mut cursor, mapping_options, true)?;
+    let mut columns = Vec::new();
+    for (column_number, name, strategy) in &strategies {
+        let (length, c_type) = match strategy.buffer_description().kind {
+            BufferKind::Text { max_str_len } => (max_str_len, CDataType::Char),
+            BufferKind::WText { max_str_len } => (max_str_len, CDataType::WChar),
+            BufferKind::Binary { length } => (length, CDataType::Binary),
+            _ => continue,
+        };
+        // Columns without a maximum length are fetched in parts anyway, and values of fixed length
+        // always fill their buffer.
+        let is_fixed_length =
+            strategy.parquet_type(name).get_physical_type() == PhysicalType::FIXED_LEN_BYTE_ARRAY;
+        if length == 0 || is_fixed_length {
+            continue;
+        }
+        columns.push(SampledColumn {
+            column_number: *column_number,
+            name: name.clone(),
+            length,
+            part: Part::new(c_type),
+            is_wide: c_type == CDataType::WChar,
+            longest: 0,
+        });
+    }
+    if columns.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut num_rows = 0;
+    while num_rows < rows {
+        let Some(mut row) = cursor.next_row()? else {
+            break;
+        };
+        for column in &mut columns {
+            let length = column
+                .part
+                .stream(&mut row, column.column_number, &mut io::sink())?;
+            if let Some(bytes) = length {
+                let length = if column.is_wide { bytes / 2 } else { bytes };
+                column.longest = column.longest.max(length);
+            }
+        }
+        num_rows += 1;
+    }
+    info!("Sampled {num_rows} rows to size the buffers of text and binary columns.");
+    // Without any rows, there is nothing to size the buffers from.
+    if num_rows == 0 {
+        return Ok(Vec::new());
+    }
+
+    let column_lengths = columns
+        .into_iter()
+        .filter(|column| column.longest < column.length)
+        .map(|column| {
+            let length = column.longest.max(1);
+            info!(
+                "Sizing the buffer of column '{}' to a length of {length}, rather than {}.",
+                column.name, column.length
+            );
+            (column.name, length)
+        })
+        .collect();
+    Ok(column_lengths)
+}
@@ -0,0 +1,230 @@
+//! Oversized values written into files of their own, next to the parquet files, which hold the
+//! relative paths of these side files instead. See `--oversized COLUMN:file`.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Seek, Write},
+};
+
+use anyhow::{anyhow, bail, Error};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    destination::Destination,
+    enum_args::{OversizedPolicy, SideFileNames},
+};
+
+use super::{
+    oversized::{oversized_policy, value_limit},
+    ColumnInfo,
+};
+
+/// Keys are cut to this many bytes, when used as file names.
+const MAX_NAME_LEN: usize = 200;
+
+/// Buffer indices of the columns, whose oversized values are written into side files.
+pub fn side_file_columns(
+    strategies: &[ColumnInfo],
+    max_value_size: Option<usize>,
+    oversized: &[(Option<String>, OversizedPolicy)],
+) -> Vec<usize> {
+    let Some(max_value_size) = max_value_size else {
+        return Vec::new();
+    };
+    strategies
+        .iter()
+        .enumerate()
+        .filter(|(_, (_, name, strategy))| {
+            value_limit(strategy.as_ref(), name, max_value_size).is_some()
+                && oversized_policy(name, oversized) == OversizedPolicy::File
+        })
+        .map(|(buffer_index, _)| buffer_index)
+        .collect()
+}
+
+/// Where the side files are written to, and how they are named.
+#[derive(Debug, Clone)]
+pub struct SideFiles {
+    /// Directory the paths held by the parquet files are relative to.
+    root: Destination,
+    /// Directory of the side files, relative to `root`.
+    directory: String,
+    names: SideFileNames,
+    /// Name of the column the side files hold the values of.
+    column: String,
+}
+
+impl SideFiles {
+    /// # Parameters
+    ///
+    /// * `root`: Directory of the output. Standard out can not hold side files.
+    /// * `directory`: Directory of the side files, relative to `root`, see `--side-file-dir`.
+    pub fn new(root: Destination, directory: &str, names: SideFileNames) -> Result<Self, Error> {
+        if root.is_std_out() {
+            bail!("Side files can not be written, if the output is written to standard out.")
+        }
+        Ok(Self {
+            root,
+            directory: directory.trim_end_matches('/').to_owned(),
+            names,
+            column: String::new(),
+        })
+    }
+
+    /// Side files of a single column, which are written into a subdirectory named after it.
+    pub fn for_column(&self, column: &str) -> Self {
+        Self {
+            directory: format!("{}/{}", self.directory, file_name(column)),
+            column: column.to_owned(),
+            ..self.clone()
+        }
+    }
+
+    /// Writes a value larger than the limit of `value` into a side file. Returns the path of the
+    /// side file relative to the output directory. `key` is only invoked, if the file is named by
+    /// the key of the row.
+    pub fn write(
+        &self,
+        value: SpooledValue,
+        key: impl FnOnce() -> Option<String>,
+    ) -> Result<String, Error> {
+        let Some(file) = value.file else {
+            bail!("Only values larger than the limit are written into side files.")
+        };
+        let mut file = file
+            .into_inner()
+            .map_err(|error| anyhow!(error.into_error()))?;
+        file.rewind()?;
+        let name = match self.names {
+            SideFileNames::Hash => value
+                .hasher
+                .finalize()
+                .iter()
+                .map(|byte| format!("{byte:02x}"))
+                .collect(),
+            SideFileNames::Key => {
+                let Some(key) = key() else {
+                    bail!(
+                        "The side file for a value of column '{}' is named by the key of its row, \
+                        which is NULL.",
+                        self.column
+                    )
+                };
+                file_name(&key)
+            }
+        };
+        let path = format!("{}/{name}", self.directory);
+        let mut sink = self.root.join(&path).create()?;
+        io::copy(&mut file, &mut sink)?;
+        sink.finish()?;
+        Ok(path)
+    }
+}
+
+/// Receives a value part by part. It is kept in memory, unless it turns out to be larger than the
+/// limit. Larger values are written into a temporary file instead, and hashed on the way.
+pub struct SpooledValue {
+    limit: usize,
+    /// The value, as long as it is no larger than `limit`.
+    head: Vec<u8>,
+    /// The value, once it is larger than `limit`.
+    file: Option<BufWriter<File>>,
+    hasher: Sha256,
+}
+
+impl SpooledValue {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            limit,
+            head: Vec::new(),
+            file: None,
+            hasher: Sha256::new(),
+        }
+    }
+
+    /// `true` if the value is larger than the limit, and has been written into a temporary file.
+    pub fn is_spooled(&self) -> bool {
+        self.file.is_some()
+    }
+
+    /// The value, unless it is spooled.
+    pub fn head(&self) -> &[u8] {
+        &self.head
+    }
+}
+
+impl Write for SpooledValue {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.file.is_none() && self.head.len() + buf.len() > self.limit {
+            let mut file = BufWriter::new(tempfile::tempfile()?);
+            file.write_all(&self.head)?;
+            self.hasher.update(&self.head);
+            self.head = Vec::new();
+            self.file = Some(file);
+        }
+        match &mut self.file {
+            Some(file) => {
+                file.write_all(buf)?;
+                self.hasher.update(buf);
+            }
+            None => self.head.extend_from_slice(buf),
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if let Some(file) = &mut self.file {
+            file.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Name of a file or directory derived from `text`. Characters other than ASCII letters, digits,
+/// `-`, `_` and `.` are replaced with `_`, and so is a leading `.`, so the name can neither refer to
+/// another directory, nor be hidden.
+fn file_name(text: &str) -> String {
+    let mut name: String = text
+        .chars()
+        .enumerate()
+        .map(|(index, c)| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
+            '.' if index != 0 => c,
+            _ => '_',
+        })
+        .collect();
+    name.truncate(MAX_NAME_LEN);
+    if name.is_empty() {
+        name.push('_');
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::{file_name, SpooledValue};
+
+    #[test]
+    fn keys_as_file_names() {
+        assert_eq!("2024-01-31_12_00_00", file_name("2024-01-31 12:00:00"));
+        assert_eq!("_.", file_name(".."));
+        assert_eq!("_etc_passwd", file_name("/etc/passwd"));
+        assert_eq!("Stra_e.pdf", file_name("Straße.pdf"));
+        assert_eq!("_", file_name(""));
+        assert_eq!(200, file_name(&"a".repeat(300)).len());
+    }
+
+    #[test]
+    fn spool_values_larger_than_the_limit() {
+        let mut value = SpooledValue::new(4);
+        value.write_all(b"one").unwrap();
+        assert!(!value.is_spooled());
+        assert_eq!(b"one", value.head());
+
+        value.write_all(b"two").unwrap();
+        assert!(value.is_spooled());
+        assert!(value.head().is_empty());
+    }
+}
@@ -0,0 +1,331 @@
+//! Sort order of the result set, as claimed by the user with `--sorted-by`. It is recorded in the
+//! `sorting_columns` metadata of the row groups and can optionally be verified while copying.
+
+use std::cmp::Ordering;
+
+use anyhow::{bail, Error};
+use odbc_api::buffers::{AnyColumnView, ColumnarAnyBuffer};
+use parquet::{basic::ConvertedType, file::metadata::SortingColumn};
+
+use crate::enum_args::SortColumn;
+
+use super::ColumnInfo;
+
+/// Columns the result set is sorted by, in order of precedence.
+pub struct SortOrder {
+    columns: Vec<SortOrderColumn>,
+}
+
+struct SortOrderColumn {
+    /// Position of the column within the fetch buffer.
+    buffer_index: usize,
+    name: String,
+    descending: bool,
+    nulls_first: bool,
+    /// Decimals with a scale are fetched as text, yet must be compared as numbers.
+    is_decimal: bool,
+}
+
+impl SortOrder {
+    pub fn new(sorted_by: &[SortColumn], strategies: &[ColumnInfo]) -> Result<Self, Error> {
+        let columns = sorted_by
+            .iter()
+            .map(|sort_column| {
+                let Some(buffer_index) = strategies
+                    .iter()
+                    .position(|(_index, name, _strategy)| *name == sort_column.name)
+                else {
+                    let column_names: Vec<_> = strategies
+                        .iter()
+                        .map(|(_index, name, _strategy)| name.as_str())
+                        .collect();
+                    bail!(
+                        "Sort column '{}' is not part of the result set. Available columns are: {}",
+                        sort_column.name,
+                        column_names.join(", ")
+                    )
+                };
+                let (_index, name, strategy) = &strategies[buffer_index];
+                let is_decimal = strategy
+                    .parquet_type(name)
+                    .get_basic_info()
+                    .converted_type()
+                    == ConvertedType::DECIMAL;
+                Ok(SortOrderColumn {
+                    buffer_index,
+                    name: name.clone(),
+                    descending: sort_column.descending,
+                    nulls_first: sort_column.nulls_first,
+                    is_decimal,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { columns })
+    }
+
+    /// `sorting_columns` metadata of the row groups.
+    ///
+    /// # Parameters
+    ///
+    /// * `parquet_columns`: Positions within the fetch buffer of the columns written into the
+    ///   parquet files. Sort columns not written into the files (i.e. partition columns) are
+    ///   omitted. Their value is the same for all rows within a file, so the remaining columns are
+    ///   still sorted.
+    pub fn sorting_columns(&self, parquet_columns: &[usize]) -> Vec<SortingColumn> {
+        self.columns
+            .iter()
+            .filter_map(|column| {
+                let column_idx = parquet_columns
+                    .iter()
+                    .position(|&index| index == column.buffer_index)?;
+                Some(SortingColumn {
+                    column_idx: column_idx as i32,
+                    descending: column.descending,
+                    nulls_first: column.nulls_first,
+                })
+            })
+            .collect()
+    }
+
+    /// Checks the rows of the fetched batches against the sort order, while they are being copied.
+    pub fn verifier(self) -> SortOrderVerifier {
+        SortOrderVerifier {
+            order: self,
+            last_row: None,
+            num_rows: 0,
+        }
+    }
+}
+
+/// Fails the export, if the rows of the result set are not in the claimed sort order.
+pub struct SortOrderVerifier {
+    order: SortOrder,
+    /// Values of the sort columns of the last row of the previous batch.
+    last_row: Option<Vec<Option<SortValue>>>,
+    /// Number of rows verified so far, across all batches.
+    num_rows: usize,
+}
+
+impl SortOrderVerifier {
+    pub fn verify(&mut self, batch: &ColumnarAnyBuffer) -> Result<(), Error> {
+        let columns: Vec<_> = self
+            .order
+            .columns
+            .iter()
+            .map(|column| sort_values(batch.column(column.buffer_index), column.is_decimal))
+            .collect();
+
+        for row_index in 0..batch.num_rows() {
+            let row: Vec<_> = columns
+                .iter()
+                .map(|values| values[row_index].clone())
+                .collect();
+            if let Some(last_row) = &self.last_row {
+                if let Some(column) = self.violating_column(last_row, &row) {
+                    bail!(
+                        "Row {} of the result set violates the sort order specified with \
+                        `--sorted-by`. It must not precede the previous row in column '{}'.",
+                        self.num_rows + row_index + 1,
+                        column.name
+                    )
+                }
+            }
+            self.last_row = Some(row);
+        }
+        self.num_rows += batch.num_rows();
+        Ok(())
+    }
+
+    /// The sort column deciding that `current` should have been placed before `previous`, or
+    /// `None` if the rows are in order.
+    fn violating_column(
+        &self,
+        previous: &[Option<SortValue>],
+        current: &[Option<SortValue>],
+    ) -> Option<&SortOrderColumn> {
+        for (column, (previous, current)) in
+            self.order.columns.iter().zip(previous.iter().zip(current))
+        {
+            let ordering = match (previous, current) {
+                (None, None) => Ordering::Equal,
+                (None, Some(_)) if column.nulls_first => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some(_), None) if column.nulls_first => Ordering::Greater,
+                (Some(_), None) => Ordering::Less,
+                (Some(previous), Some(current)) => {
+                    let ordering = previous.partial_cmp(current).unwrap_or(Ordering::Equal);
+                    if column.descending {
+                        ordering.reverse()
+                    } else {
+                        ordering
+                    }
+                }
+            };
+            match ordering {
+                Ordering::Less => return None,
+                Ordering::Equal => (),
+                Ordering::Greater => return Some(column),
+            }
+        }
+        None
+    }
+}
+
+/// Value of a sort column, comparable with other values of the same column. Text and binary data
+/// is compared byte wise, which is also the sort order parquet assumes for these columns.
+#[derive(Clone, PartialEq, PartialOrd)]
+enum SortValue {
+    Int(i64),
+    Float(f64),
+    Bytes(Vec<u8>),
+    /// Date and time of day as `yyyymmddHHMMSS` together with the fraction of the second.
+    Timestamp(i64, u32),
+}
+
+fn sort_values(view: AnyColumnView, is_decimal: bool) -> Vec<Option<SortValue>> {
+    use SortValue::{Bytes, Float, Int};
+    match view {
+        AnyColumnView::Text(view) if is_decimal => view
+            .iter()
+            .map(|value| {
+                value.map(|bytes| {
+                    Float(
+                        std::str::from_utf8(bytes)
+                            .ok()
+                            .and_then(|text| text.trim().parse().ok())
+                            .unwrap_or(f64::NAN),
+                    )
+                })
+            })
+            .collect(),
+        AnyColumnView::Text(view) => view
+            .iter()
+            .map(|value| value.map(|bytes| Bytes(bytes.to_vec())))
+            .collect(),
+        AnyColumnView::WText(view) => view
+            .iter()
+            .map(|value| {
+                value.map(|chars| Bytes(String::from_utf16_lossy(chars.as_slice()).into_bytes()))
+            })
+            .collect(),
+        AnyColumnView::Binary(view) => view
+            .iter()
+            .map(|value| value.map(|bytes| Bytes(bytes.to_vec())))
+            .collect(),
+        AnyColumnView::Date(values) => values.iter().map(|d| Some(date(d))).collect(),
+        AnyColumnView::NullableDate(values) => values.map(|d| d.map(date)).collect(),
+        AnyColumnView::Time(values) => values.iter().map(|t| Some(time(t))).collect(),
+        AnyColumnView::NullableTime(values) => values.map(|t| t.map(time)).collect(),
+        AnyColumnView::Timestamp(values) => values.iter().map(|ts| Some(timestamp(ts))).collect(),
+        AnyColumnView::NullableTimestamp(values) => values.map(|ts| ts.map(timestamp)).collect(),
+        AnyColumnView::Bit(values) => values
+            .iter()
+            .map(|bit| Some(Int(bit.as_bool().into())))
+            .collect(),
+        AnyColumnView::NullableBit(values) => values
+            .map(|bit| bit.map(|bit| Int(bit.as_bool().into())))
+            .collect(),
+        AnyColumnView::F64(values) => values.iter().map(|&v| Some(Float(v))).collect(),
+        AnyColumnView::F32(values) => values.iter().map(|&v| Some(Float(v.into()))).collect(),
+        AnyColumnView::I8(values) => values.iter().map(|&v| Some(Int(v.into()))).collect(),
+        AnyColumnView::I16(values) => values.iter().map(|&v| Some(Int(v.into()))).collect(),
+        AnyColumnView::I32(values) => values.iter().map(|&v| Some(Int(v.into()))).collect(),
+        AnyColumnView::I64(values) => values.iter().map(|&v| Some(Int(v))).collect(),
+        AnyColumnView::U8(values) => values.iter().map(|&v| Some(Int(v.into()))).collect(),
+        AnyColumnView::NullableF64(values) => values.map(|v| v.map(|&v| Float(v))).collect(),
+        AnyColumnView::NullableF32(values) => values.map(|v| v.map(|&v| Float(v.into()))).collect(),
+        AnyColumnView::NullableI8(values) => values.map(|v| v.map(|&v| Int(v.into()))).collect(),
+        AnyColumnView::NullableI16(values) => values.map(|v| v.map(|&v| Int(v.into()))).collect(),
+        AnyColumnView::NullableI32(values) => values.map(|v| v.map(|&v| Int(v.into()))).collect(),
+        AnyColumnView::NullableI64(values) => values.map(|v| v.map(|&v| Int(v))).collect(),
+        AnyColumnView::NullableU8(values) => values.map(|v| v.map(|&v| Int(v.into()))).collect(),
+    }
+}
+
+fn date(date: &odbc_api::sys::Date) -> SortValue {
+    SortValue::Int(date.year as i64 * 10_000 + date.month as i64 * 100 + date.day as i64)
+}
+
+fn time(time: &odbc_api::sys::Time) -> SortValue {
+    SortValue::Int(time.hour as i64 * 10_000 + time.minute as i64 * 100 + time.second as i64)
+}
+
+fn timestamp(ts: &odbc_api::sys::Timestamp) -> SortValue {
+    let date = ts.year as i64 * 10_000 + ts.month as i64 * 100 + ts.day as i64;
+    let time = ts.hour as i64 * 10_000 + ts.minute as i64 * 100 + ts.second as i64;
+    SortValue::Timestamp(date * 1_000_000 + time, ts.fraction)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::enum_args::sort_column_from_str;
+
+    use super::{SortOrder, SortOrderColumn, SortValue};
+
+    #[test]
+    fn parse_sort_columns() {
+        let column = sort_column_from_str("amount").unwrap();
+        assert_eq!(
+            ("amount", false, true),
+            (column.name.as_str(), column.descending, column.nulls_first)
+        );
+
+        let column = sort_column_from_str("amount DESC").unwrap();
+        assert_eq!(
+            ("amount", true, false),
+            (column.name.as_str(), column.descending, column.nulls_first)
+        );
+
+        let column = sort_column_from_str(" amount asc nulls last").unwrap();
+        assert_eq!(
+            ("amount", false, false),
+            (column.name.as_str(), column.descending, column.nulls_first)
+        );
+
+        assert!(sort_column_from_str("amount FIRST").is_err());
+    }
+
+    #[test]
+    fn detect_violation_of_sort_order() {
+        let column = |name: &str, descending, nulls_first| SortOrderColumn {
+            buffer_index: 0,
+            name: name.to_owned(),
+            descending,
+            nulls_first,
+            is_decimal: false,
+        };
+        let verifier = SortOrder {
+            columns: vec![column("a", false, true), column("b", true, false)],
+        }
+        .verifier();
+        let row = |a: Option<i64>, b: Option<i64>| [a.map(SortValue::Int), b.map(SortValue::Int)];
+        let violation = |previous: [Option<SortValue>; 2], current: [Option<SortValue>; 2]| {
+            verifier
+                .violating_column(&previous, &current)
+                .map(|column| column.name.as_str())
+        };
+
+        assert_eq!(
+            None,
+            violation(row(Some(1), Some(1)), row(Some(2), Some(2)))
+        );
+        assert_eq!(
+            None,
+            violation(row(Some(1), Some(2)), row(Some(1), Some(1)))
+        );
+        assert_eq!(None, violation(row(Some(1), Some(1)), row(Some(1), None)));
+        assert_eq!(None, violation(row(None, Some(1)), row(Some(1), Some(1))));
+        assert_eq!(
+            Some("a"),
+            violation(row(Some(2), Some(1)), row(Some(1), Some(1)))
+        );
+        assert_eq!(
+            Some("b"),
+            violation(row(Some(1), Some(1)), row(Some(1), Some(2)))
+        );
+        assert_eq!(
+            Some("b"),
+            violation(row(Some(1), None), row(Some(1), Some(2)))
+        );
+    }
+}
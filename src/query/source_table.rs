@@ -0,0 +1,61 @@
+//! Query generated for exporting an entire table, see `--source-table`.
+
+use anyhow::{bail, Error};
+
+/// `SELECT` statement fetching all columns of `table`. `table` may be qualified with schema and
+/// catalog, e.g. `sales.orders`.
+pub fn select_from_table(table: &str, db_name: &str) -> Result<String, Error> {
+    Ok(format!("SELECT * FROM {}", quote_table(table, db_name)?))
+}
+
+/// Quotes each part of a table name qualified with schema and catalog, the way the data source
+/// named `db_name` expects identifiers to be quoted.
+pub fn quote_table(table: &str, db_name: &str) -> Result<String, Error> {
+    let parts: Vec<&str> = table.split('.').collect();
+    if parts.len() > 3 || parts.iter().any(|part| part.is_empty()) {
+        bail!("Invalid source table '{table}'. Expected format: '[[CATALOG.]SCHEMA.]TABLE'.")
+    }
+    let table: Vec<String> = parts
+        .into_iter()
+        .map(|part| quote_identifier(part, db_name))
+        .collect();
+    Ok(table.join("."))
+}
+
+/// Quotes a single identifier, e.g. the name of a column, the way the data source named `db_name`
+/// expects it.
+pub fn quote_identifier(name: &str, db_name: &str) -> String {
+    match db_name {
+        "Microsoft SQL Server" => format!("[{}]", name.replace(']', "]]")),
+        "MySQL" | "MariaDB" => format!("`{}`", name.replace('`', "``")),
+        // Double quotes are the identifier quote character of the SQL standard.
+        _ => format!("\"{}\"", name.replace('"', "\"\"")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::select_from_table;
+
+    #[test]
+    fn quote_table_name_for_data_source() {
+        assert_eq!(
+            "SELECT * FROM [sales].[order]",
+            select_from_table("sales.order", "Microsoft SQL Server").unwrap()
+        );
+        assert_eq!(
+            "SELECT * FROM `shop`.`order`",
+            select_from_table("shop.order", "MySQL").unwrap()
+        );
+        assert_eq!(
+            "SELECT * FROM \"db\".\"public\".\"My \"\"Table\"\"\"",
+            select_from_table("db.public.My \"Table\"", "PostgreSQL").unwrap()
+        );
+    }
+
+    #[test]
+    fn reject_malformed_table_names() {
+        assert!(select_from_table("sales..order", "PostgreSQL").is_err());
+        assert!(select_from_table("a.b.c.d", "PostgreSQL").is_err());
+    }
+}
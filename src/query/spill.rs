@@ -0,0 +1,947 @@
+//! Fetches columns, which are too large to hold even a single row in the fetch buffer, into
+//! temporary files instead. Columns without a maximum length, e.g. `VARCHAR(max)` or `BLOB`, are
+//! fetched in parts into memory. Their values are streamed into the column writers in chunks, which fit into
+//! the memory limit of the batch.
+
+use std::{
+    ffi::c_void,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+};
+
+use anyhow::Error;
+use log::info;
+use odbc_api::{
+    buffers::{
+        AnyColumnBuffer, BufferDescription, BufferKind, ColumnBuffer, ColumnarAnyBuffer,
+        ColumnarBuffer,
+    },
+    handles::{CData, CDataMut, HasDataType},
+    sys::{CDataType, NO_TOTAL, NULL_DATA},
+    Cursor, CursorRow, DataType, OutputParameter, RowSetBuffer,
+};
+use parquet::column::writer::ColumnWriter;
+
+use crate::{enum_args::OversizedPolicy, parquet_buffer::ParquetBuffer};
+
+use super::{
+    oversized::oversized_policy,
+    side_file::{SideFiles, SpooledValue},
+    strategy::ColumnFetchStrategy,
+    value_text::column_as_text,
+    ColumnInfo,
+};
+
+/// Columns are spilled until at least this many rows fit into a batch, so fetching is not slowed
+/// down by tiny batches.
+const MIN_BATCH_SIZE_ROWS: usize = 1024;
+
+/// Size of the parts in which the values of spilled columns are fetched.
+const PART_SIZE_BYTES: usize = 64 * 1024;
+
+/// Memory limit for the values of a streamed column written into the column writer at once, if the
+/// batch size is not limited by memory.
+const STREAMED_CHUNK_MEMORY_BYTES: usize = 64 * 1024 * 1024;
+
+/// `true` if columns fetched into buffers of this kind can be spilled. These are the ones of
+/// variable length, which may be large.
+pub fn is_spillable(description: &BufferDescription) -> bool {
+    matches!(
+        description.kind,
+        BufferKind::Text { .. } | BufferKind::WText { .. } | BufferKind::Binary { .. }
+    )
+}
+
+/// `true` for text and binary columns, for which the driver reports no maximum length, e.g.
+/// `VARCHAR(max)` or `VARBINARY(max)`. Their values are streamed, since no buffer could be bound for
+/// them.
+pub fn is_unbounded(description: &BufferDescription) -> bool {
+    matches!(
+        description.kind,
+        BufferKind::Text { max_str_len: 0 }
+            | BufferKind::WText { max_str_len: 0 }
+            | BufferKind::Binary { length: 0 }
+    )
+}
+
+/// Describes the buffer taking the place of a spilled column within the fetch buffer. It holds no
+/// values, but keeps the positions of the other columns intact.
+pub fn placeholder(description: BufferDescription) -> BufferDescription {
+    let kind = match description.kind {
+        BufferKind::Text { .. } => BufferKind::Text { max_str_len: 0 },
+        BufferKind::WText { .. } => BufferKind::WText { max_str_len: 0 },
+        BufferKind::Binary { .. } => BufferKind::Binary { length: 0 },
+        kind => kind,
+    };
+    BufferDescription {
+        kind,
+        ..description
+    }
+}
+
+/// Buffer indices of the columns to spill, largest first, until at least [`MIN_BATCH_SIZE_ROWS`]
+/// rows fit into `memory`. Empty if a single row fits without spilling. `None` if it does not even
+/// fit after spilling all candidates.
+///
+/// # Parameters
+///
+/// * `bytes_per_row`: Memory required per row without spilling.
+/// * `candidates`: Buffer index of each column which can be spilled, together with the memory per
+///   row saved by spilling it.
+/// * `memory`: Memory limit of a batch.
+pub fn columns_to_spill(
+    mut bytes_per_row: usize,
+    candidates: &[(usize, usize)],
+    memory: usize,
+) -> Option<Vec<usize>> {
+    if bytes_per_row <= memory {
+        return Some(Vec::new());
+    }
+    let mut candidates = candidates.to_vec();
+    candidates.sort_by_key(|&(_index, saved)| std::cmp::Reverse(saved));
+    let mut spilled = Vec::new();
+    for (index, saved) in candidates {
+        if bytes_per_row.saturating_mul(MIN_BATCH_SIZE_ROWS) <= memory {
+            break;
+        }
+        spilled.push(index);
+        bytes_per_row -= saved;
+    }
+    (bytes_per_row <= memory).then_some(spilled)
+}
+
+/// Fetches the result set row by row. Columns which are neither spilled nor streamed are gathered in
+/// a fetch buffer, just like it would have been filled by a block cursor. The values of spilled
+/// columns are fetched into temporary files, the ones of streamed columns into memory. The paths of
+/// side files are appended to the fetch buffer, as text columns following the fetched ones.
+pub struct SpillingFetch<'c, C> {
+    cursor: &'c mut C,
+    /// Column number and description of each column of the fetch buffer. Spilled and streamed
+    /// columns are described by a [`placeholder`].
+    descriptions: Vec<(u16, BufferDescription)>,
+    /// Columns in the order their values are fetched, since `SQLGetData` is only guaranteed to
+    /// work in ascending order of the columns.
+    order: Vec<(u16, Target)>,
+    spilled: Vec<SpilledColumn>,
+    batch_size_rows: usize,
+    /// Position of the first `--sorted-by` column in the fetch buffer, which may name side files.
+    key_column: Option<usize>,
+    /// Buffer the values of variable length are fetched into, part by part.
+    part: Part,
+    /// Batch fetched last.
+    buffer: Option<ColumnarAnyBuffer>,
+}
+
+/// Where the values of a column are fetched into.
+#[derive(Clone, Copy)]
+enum Target {
+    /// Column of the fetch buffer with this index.
+    Buffer(usize),
+    /// Spilled or streamed column with this index.
+    Spilled(usize),
+}
+
+impl<'c, C: Cursor> SpillingFetch<'c, C> {
+    /// # Parameters
+    ///
+    /// * `spilled`: Buffer indices of the columns written to temporary files.
+    /// * `streamed`: Buffer indices of the columns without maximum length, held in memory.
+    /// * `memory`: Memory limit of a batch, if any. Values of spilled and streamed columns are
+    ///   written in chunks, each of which fits into this limit, unless a single value is larger.
+    /// * `max_value_size`: Value of `--max-value-size` in bytes. Values of spilled and streamed
+    ///   columns are not fetched beyond it.
+    /// * `oversized`: Policies for values larger than `max_value_size`, see `--oversized`.
+    /// * `side_files`: Where oversized values of columns with the `file` policy are written to.
+    /// * `key_column`: Position of the first `--sorted-by` column in the fetch buffer.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        cursor: &'c mut C,
+        strategies: &[ColumnInfo],
+        spilled: &[usize],
+        streamed: &[usize],
+        batch_size_rows: usize,
+        memory: Option<usize>,
+        max_value_size: Option<usize>,
+        oversized: &[(Option<String>, OversizedPolicy)],
+        side_files: Option<&SideFiles>,
+        key_column: Option<usize>,
+    ) -> Result<Self, Error> {
+        let chunk_memory = memory.unwrap_or(STREAMED_CHUNK_MEMORY_BYTES);
+        let mut descriptions = Vec::new();
+        let mut order = Vec::new();
+        let mut spilled_columns = Vec::new();
+        for (buffer_index, (column_number, name, strategy)) in strategies.iter().enumerate() {
+            let description = strategy.buffer_description();
+            let values = if spilled.contains(&buffer_index) {
+                info!("Spilling the values of column '{name}' into a temporary file.");
+                Some(Values::File(BufWriter::new(tempfile::tempfile()?)))
+            } else if streamed.contains(&buffer_index) {
+                info!("Column '{name}' has no maximum length. Fetching its values in parts.");
+                Some(Values::Memory(Vec::new()))
+            } else {
+                None
+            };
+            let target = if let Some(values) = values {
+                let max_value_size =
+                    max_value_size.map(|size| (size, oversized_policy(name, oversized)));
+                let side_files = match max_value_size {
+                    Some((_, OversizedPolicy::File)) => {
+                        let side_files = side_files.expect("Side files must be configured.");
+                        Some(side_files.for_column(name))
+                    }
+                    _ => None,
+                };
+                let column = SpilledColumn::new(
+                    *column_number,
+                    description,
+                    values,
+                    chunk_memory,
+                    max_value_size,
+                    side_files,
+                );
+                spilled_columns.push(column);
+                descriptions.push((*column_number, placeholder(description)));
+                Target::Spilled(spilled_columns.len() - 1)
+            } else {
+                descriptions.push((*column_number, description));
+                Target::Buffer(buffer_index)
+            };
+            order.push((*column_number, target));
+        }
+        order.sort_by_key(|(column_number, _target)| *column_number);
+        Ok(Self {
+            cursor,
+            descriptions,
+            order,
+            spilled: spilled_columns,
+            batch_size_rows,
+            key_column,
+            part: Part::new(CDataType::Char),
+            buffer: None,
+        })
+    }
+
+    /// Next batch together with the spilled columns holding its values. `None` once the result set
+    /// is consumed.
+    pub fn fetch(&mut self) -> Result<Option<(&ColumnarAnyBuffer, &[SpilledColumn])>, Error> {
+        // Free the previous batch, before allocating the next one.
+        self.buffer = None;
+        let mut columns: Vec<_> = self
+            .descriptions
+            .iter()
+            .map(|(_column_number, description)| {
+                AnyColumnBuffer::from_description(self.batch_size_rows, *description)
+            })
+            .collect();
+        for spilled in &mut self.spilled {
+            spilled.clear()?;
+        }
+        let mut num_rows = 0;
+        while num_rows < self.batch_size_rows {
+            let Some(mut row) = self.cursor.next_row()? else {
+                break;
+            };
+            for &(column_number, target) in &self.order {
+                match target {
+                    Target::Buffer(index) => fetch_cell(
+                        &mut row,
+                        column_number,
+                        &mut columns[index],
+                        num_rows,
+                        &mut self.part,
+                    )?,
+                    Target::Spilled(index) => {
+                        self.spilled[index].append(&mut row, &mut self.part)?
+                    }
+                }
+            }
+            // Side files may be named by a key fetched after their values.
+            for spilled in &mut self.spilled {
+                spilled.write_side_file(|| {
+                    let index = self.key_column?;
+                    column_as_text(columns[index].view(num_rows + 1))
+                        .pop()
+                        .flatten()
+                })?;
+            }
+            num_rows += 1;
+        }
+        if num_rows == 0 {
+            return Ok(None);
+        }
+        for spilled in &mut self.spilled {
+            spilled.flush()?;
+        }
+        let mut column_numbers: Vec<u16> = self
+            .descriptions
+            .iter()
+            .map(|(column_number, _description)| *column_number)
+            .collect();
+        // Column numbers must be unique, even for the columns holding the paths of side files.
+        let first_path_column = column_numbers.iter().max().map_or(1, |max| max + 1);
+        let side_file_paths = self
+            .spilled
+            .iter()
+            .filter_map(SpilledColumn::side_file_paths);
+        for (column_number, paths) in (first_path_column..).zip(side_file_paths) {
+            columns.push(path_column(paths, self.batch_size_rows));
+            column_numbers.push(column_number);
+        }
+        let mut buffer = ColumnarBuffer::new(column_numbers.into_iter().zip(columns).collect());
+        *buffer.mut_num_fetch_rows() = num_rows;
+        let buffer = self.buffer.insert(buffer);
+        Ok(Some((buffer, &self.spilled)))
+    }
+}
+
+/// Values of a column of the current batch, fetched in parts.
+pub struct SpilledColumn {
+    column_number: u16,
+    /// Description of the fetch buffer the strategy of the column expects.
+    description: BufferDescription,
+    /// Values of the batch, one after another.
+    values: Values,
+    /// Length in bytes of each value of the batch. `None` for NULL.
+    lengths: Vec<Option<usize>>,
+    /// Memory limit for the values written into the column writer at once.
+    chunk_memory: usize,
+    /// Size in bytes beyond which values are not fetched, together with what happens to them.
+    max_value_size: Option<(usize, OversizedPolicy)>,
+    /// Rows of the current batch, whose values are larger than `max_value_size`. Unless they are
+    /// truncated, they are NULL in the batch.
+    oversized: Vec<usize>,
+    /// Where oversized values are written to, if the policy of the column is `file`.
+    side_files: Option<SideFiles>,
+    /// Oversized value of the current row, which is yet to be written into a side file.
+    pending: Option<SpooledValue>,
+    /// Path of the side file of each row of the current batch, relative to the output directory.
+    side_file_paths: Vec<Option<String>>,
+}
+
+impl SpilledColumn {
+    fn new(
+        column_number: u16,
+        description: BufferDescription,
+        values: Values,
+        chunk_memory: usize,
+        max_value_size: Option<(usize, OversizedPolicy)>,
+        side_files: Option<SideFiles>,
+    ) -> Self {
+        Self {
+            column_number,
+            description,
+            values,
+            lengths: Vec::new(),
+            chunk_memory,
+            max_value_size,
+            oversized: Vec::new(),
+            side_files,
+            pending: None,
+            side_file_paths: Vec::new(),
+        }
+    }
+
+    /// Number of the column in the result set.
+    pub fn column_number(&self) -> u16 {
+        self.column_number
+    }
+
+    /// Length in bytes of each value of the current batch, `None` for NULL.
+    pub fn lengths(&self) -> &[Option<usize>] {
+        &self.lengths
+    }
+
+    /// Rows of the current batch, whose values are larger than `--max-value-size`.
+    pub fn oversized_rows(&self) -> &[usize] {
+        &self.oversized
+    }
+
+    /// Path of the side file of each row of the current batch, if its value has been written into
+    /// one. `None` unless the column writes oversized values into side files.
+    pub fn side_file_paths(&self) -> Option<&[Option<String>]> {
+        self.side_files.as_ref()?;
+        Some(&self.side_file_paths)
+    }
+
+    /// Discards the values of the previous batch.
+    fn clear(&mut self) -> Result<(), Error> {
+        match &mut self.values {
+            Values::File(file) => {
+                file.rewind()?;
+                file.get_ref().set_len(0)?;
+            }
+            Values::Memory(bytes) => bytes.clear(),
+        }
+        self.lengths.clear();
+        self.oversized.clear();
+        self.side_file_paths.clear();
+        Ok(())
+    }
+
+    /// Streams the value of the current row into the file, or memory. Values larger than
+    /// `--max-value-size` are kept truncated, or discarded and appended as NULL.
+    fn append(&mut self, row: &mut CursorRow, part: &mut Part) -> Result<(), Error> {
+        part.c_type = c_type(&self.description);
+        let limit = self
+            .max_value_size
+            .map_or(usize::MAX, |(size, _policy)| size);
+        if self.side_files.is_some() {
+            return self.append_or_spool(row, part, limit);
+        }
+        let streamed = match &mut self.values {
+            Values::File(file) => part.stream_at_most(row, self.column_number, file, limit)?,
+            Values::Memory(bytes) => part.stream_at_most(row, self.column_number, bytes, limit)?,
+        };
+        let length = match streamed {
+            Some((length, false)) => {
+                self.oversized.push(self.lengths.len());
+                if matches!(self.max_value_size, Some((_, OversizedPolicy::Truncate))) {
+                    Some(length)
+                } else {
+                    self.discard(length)?;
+                    None
+                }
+            }
+            streamed => streamed.map(|(length, _complete)| length),
+        };
+        self.lengths.push(length);
+        Ok(())
+    }
+
+    /// Streams the value of the current row into the file, or memory, unless it is larger than
+    /// `limit`. Larger values are fetched completely into a temporary file and appended as NULL.
+    /// They are written into a side file by [`Self::write_side_file`].
+    fn append_or_spool(
+        &mut self,
+        row: &mut CursorRow,
+        part: &mut Part,
+        limit: usize,
+    ) -> Result<(), Error> {
+        let mut value = SpooledValue::new(limit);
+        let length = part.stream(row, self.column_number, &mut value)?;
+        let length = if value.is_spooled() {
+            self.oversized.push(self.lengths.len());
+            self.pending = Some(value);
+            None
+        } else {
+            if length.is_some() {
+                match &mut self.values {
+                    Values::File(file) => file.write_all(value.head())?,
+                    Values::Memory(bytes) => bytes.extend_from_slice(value.head()),
+                }
+            }
+            length
+        };
+        self.lengths.push(length);
+        self.side_file_paths.push(None);
+        Ok(())
+    }
+
+    /// Writes the oversized value of the current row into a side file, if there is one. `key`
+    /// returns the key of the current row.
+    fn write_side_file(&mut self, key: impl FnOnce() -> Option<String>) -> Result<(), Error> {
+        let (Some(side_files), Some(value)) = (&self.side_files, self.pending.take()) else {
+            return Ok(());
+        };
+        let path = side_files.write(value, key)?;
+        *self.side_file_paths.last_mut().unwrap() = Some(path);
+        Ok(())
+    }
+
+    /// Removes the last `length` bytes written, so they are overwritten by the next value.
+    fn discard(&mut self, length: usize) -> Result<(), Error> {
+        match &mut self.values {
+            Values::File(file) => {
+                file.seek(SeekFrom::Current(-(length as i64)))?;
+            }
+            Values::Memory(bytes) => bytes.truncate(bytes.len() - length),
+        }
+        Ok(())
+    }
+
+    /// Writes the values of the batch buffered so far into the file.
+    fn flush(&mut self) -> Result<(), Error> {
+        if let Values::File(file) = &mut self.values {
+            file.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Writes the (selected) values of the current batch into the column writer. The values are
+    /// read in chunks, which are converted by the strategy of the column, just as if they had been
+    /// fetched into the fetch buffer.
+    ///
+    /// # Parameters
+    ///
+    /// * `pb`: Buffer of the batch. Tells which rows are selected.
+    pub fn copy_to_parquet(
+        &self,
+        strategy: &dyn ColumnFetchStrategy,
+        pb: &ParquetBuffer,
+        column_writer: &mut ColumnWriter,
+    ) -> Result<(), Error> {
+        let selection = pb.selected_rows();
+        let mut reader: Box<dyn Read + '_> = match &self.values {
+            Values::File(file) => {
+                let mut file = file.get_ref();
+                file.rewind()?;
+                Box::new(BufReader::new(file))
+            }
+            Values::Memory(bytes) => Box::new(bytes.as_slice()),
+        };
+        let mut value = Vec::new();
+        let mut start = 0;
+        while start < self.lengths.len() {
+            let (end, max_len) = chunk(&self.lengths[start..], self.chunk_memory);
+            let end = start + end;
+            let num_rows = end - start;
+            let mut column = AnyColumnBuffer::from_description(
+                num_rows,
+                with_max_len(self.description, max_len),
+            );
+            for (index, length) in self.lengths[start..end].iter().enumerate() {
+                let Some(length) = *length else {
+                    set_value(&mut column, index, None);
+                    continue;
+                };
+                value.resize(length, 0);
+                reader.read_exact(&mut value)?;
+                set_value(&mut column, index, Some(&value));
+            }
+            let mut buffer = ColumnarBuffer::new(vec![(self.column_number, column)]);
+            *buffer.mut_num_fetch_rows() = num_rows;
+
+            let mut chunk_pb = ParquetBuffer::new(num_rows);
+            chunk_pb.set_num_rows_fetched(num_rows);
+            if let Some(selection) = selection {
+                chunk_pb.select_rows(Some(
+                    selection
+                        .iter()
+                        .filter(|&&row| (start..end).contains(&row))
+                        .map(|row| row - start)
+                        .collect(),
+                ));
+            }
+            strategy.copy_odbc_to_parquet(&mut chunk_pb, column_writer, buffer.column(0))?;
+            start = end;
+        }
+        Ok(())
+    }
+}
+
+/// Storage of the values of a column fetched in parts.
+enum Values {
+    /// Temporary file, which is deleted once closed. Used for spilled columns.
+    File(BufWriter<File>),
+    /// Used for streamed columns, i.e. the ones without a maximum length.
+    Memory(Vec<u8>),
+}
+
+/// Number of values of the next chunk, and the length of the largest one. A chunk holds at least
+/// one value, and as many more as fit into `memory`, with each one taking up the space of the
+/// largest.
+fn chunk(lengths: &[Option<usize>], memory: usize) -> (usize, usize) {
+    let mut max_len = 0;
+    for (index, length) in lengths.iter().enumerate() {
+        let next_max_len = max_len.max(length.unwrap_or(0));
+        if index != 0 && (index + 1).saturating_mul(next_max_len) > memory {
+            return (index, max_len);
+        }
+        max_len = next_max_len;
+    }
+    (lengths.len(), max_len)
+}
+
+/// ODBC C type the values of a spilled column are fetched as.
+fn c_type(description: &BufferDescription) -> CDataType {
+    match description.kind {
+        BufferKind::WText { .. } => CDataType::WChar,
+        BufferKind::Binary { .. } => CDataType::Binary,
+        _ => CDataType::Char,
+    }
+}
+
+/// Same kind of buffer, with room for values up to `max_len` bytes.
+fn with_max_len(description: BufferDescription, max_len: usize) -> BufferDescription {
+    let kind = match description.kind {
+        BufferKind::Text { .. } => BufferKind::Text {
+            max_str_len: max_len,
+        },
+        BufferKind::WText { .. } => BufferKind::WText {
+            max_str_len: max_len / 2,
+        },
+        BufferKind::Binary { .. } => BufferKind::Binary { length: max_len },
+        kind => kind,
+    };
+    BufferDescription {
+        kind,
+        ..description
+    }
+}
+
+/// Sets a value of variable length. Values of wide text columns are given in bytes.
+fn set_value(column: &mut AnyColumnBuffer, index: usize, value: Option<&[u8]>) {
+    match column {
+        AnyColumnBuffer::Text(column) => {
+            column.set_value(index, value.map(|value| truncate(value, column.max_len())))
+        }
+        AnyColumnBuffer::WText(column) => {
+            let value = value.map(|value| {
+                value
+                    .chunks_exact(2)
+                    .map(|pair| u16::from_ne_bytes([pair[0], pair[1]]))
+                    .collect::<Vec<_>>()
+            });
+            column.set_value(
+                index,
+                value
+                    .as_deref()
+                    .map(|value| truncate(value, column.max_len())),
+            )
+        }
+        AnyColumnBuffer::Binary(column) => {
+            column.set_value(index, value.map(|value| truncate(value, column.max_len())))
+        }
+        _ => unreachable!("Only columns of variable length are fetched in parts."),
+    }
+}
+
+/// Text column holding the paths of the side files of a batch.
+fn path_column(paths: &[Option<String>], batch_size_rows: usize) -> AnyColumnBuffer {
+    let max_str_len = paths.iter().flatten().map(String::len).max().unwrap_or(0);
+    let description = BufferDescription {
+        kind: BufferKind::Text {
+            max_str_len: max_str_len.max(1),
+        },
+        nullable: true,
+    };
+    let mut column = AnyColumnBuffer::from_description(batch_size_rows, description);
+    for (index, path) in paths.iter().enumerate() {
+        set_value(&mut column, index, path.as_deref().map(str::as_bytes));
+    }
+    column
+}
+
+/// Values longer than the buffer are truncated, like they would have been by the driver.
+fn truncate<T>(value: &[T], max_len: usize) -> &[T] {
+    &value[..value.len().min(max_len)]
+}
+
+/// Fetches the value of a column of the current row into the fetch buffer.
+fn fetch_cell(
+    row: &mut CursorRow,
+    column_number: u16,
+    column: &mut AnyColumnBuffer,
+    index: usize,
+    part: &mut Part,
+) -> Result<(), Error> {
+    match column {
+        AnyColumnBuffer::Text(_) | AnyColumnBuffer::WText(_) | AnyColumnBuffer::Binary(_) => {
+            part.c_type = match column {
+                AnyColumnBuffer::WText(_) => CDataType::WChar,
+                AnyColumnBuffer::Binary(_) => CDataType::Binary,
+                _ => CDataType::Char,
+            };
+            let mut value = Vec::new();
+            let length = part.stream(row, column_number, &mut value)?;
+            set_value(column, index, length.map(|_| value.as_slice()));
+        }
+        AnyColumnBuffer::Date(values) => {
+            values[index] = get_cell(row, column_number)?.unwrap_or_default()
+        }
+        AnyColumnBuffer::Time(values) => {
+            values[index] = get_cell(row, column_number)?.unwrap_or_default()
+        }
+        AnyColumnBuffer::Timestamp(values) => {
+            values[index] = get_cell(row, column_number)?.unwrap_or_default()
+        }
+        AnyColumnBuffer::F64(values) => {
+            values[index] = get_cell(row, column_number)?.unwrap_or_default()
+        }
+        AnyColumnBuffer::F32(values) => {
+            values[index] = get_cell(row, column_number)?.unwrap_or_default()
+        }
+        AnyColumnBuffer::I8(values) => {
+            values[index] = get_cell(row, column_number)?.unwrap_or_default()
+        }
+        AnyColumnBuffer::I16(values) => {
+            values[index] = get_cell(row, column_number)?.unwrap_or_default()
+        }
+        AnyColumnBuffer::I32(values) => {
+            values[index] = get_cell(row, column_number)?.unwrap_or_default()
+        }
+        AnyColumnBuffer::I64(values) => {
+            values[index] = get_cell(row, column_number)?.unwrap_or_default()
+        }
+        AnyColumnBuffer::U8(values) => {
+            values[index] = get_cell(row, column_number)?.unwrap_or_default()
+        }
+        AnyColumnBuffer::Bit(values) => {
+            values[index] = get_cell(row, column_number)?.unwrap_or_default()
+        }
+        AnyColumnBuffer::NullableDate(column) => column
+            .writer_n(index + 1)
+            .set_cell(index, get_cell(row, column_number)?),
+        AnyColumnBuffer::NullableTime(column) => column
+            .writer_n(index + 1)
+            .set_cell(index, get_cell(row, column_number)?),
+        AnyColumnBuffer::NullableTimestamp(column) => column
+            .writer_n(index + 1)
+            .set_cell(index, get_cell(row, column_number)?),
+        AnyColumnBuffer::NullableF64(column) => column
+            .writer_n(index + 1)
+            .set_cell(index, get_cell(row, column_number)?),
+        AnyColumnBuffer::NullableF32(column) => column
+            .writer_n(index + 1)
+            .set_cell(index, get_cell(row, column_number)?),
+        AnyColumnBuffer::NullableI8(column) => column
+            .writer_n(index + 1)
+            .set_cell(index, get_cell(row, column_number)?),
+        AnyColumnBuffer::NullableI16(column) => column
+            .writer_n(index + 1)
+            .set_cell(index, get_cell(row, column_number)?),
+        AnyColumnBuffer::NullableI32(column) => column
+            .writer_n(index + 1)
+            .set_cell(index, get_cell(row, column_number)?),
+        AnyColumnBuffer::NullableI64(column) => column
+            .writer_n(index + 1)
+            .set_cell(index, get_cell(row, column_number)?),
+        AnyColumnBuffer::NullableU8(column) => column
+            .writer_n(index + 1)
+            .set_cell(index, get_cell(row, column_number)?),
+        AnyColumnBuffer::NullableBit(column) => column
+            .writer_n(index + 1)
+            .set_cell(index, get_cell(row, column_number)?),
+    }
+    Ok(())
+}
+
+/// Fetches a value of fixed size. `None` if it is NULL.
+fn get_cell<T>(row: &mut CursorRow, column_number: u16) -> Result<Option<T>, Error>
+where
+    T: CDataMut + Default + Copy,
+{
+    let mut cell = Cell {
+        value: T::default(),
+        indicator: NULL_DATA,
+    };
+    row.get_data(column_number, &mut cell)?;
+    Ok((cell.indicator != NULL_DATA).then_some(cell.value))
+}
+
+/// Target for fetching a single value of fixed size. Unlike [`odbc_api::Nullable`] it works with
+/// each type of the fetch buffer, including timestamps.
+struct Cell<T> {
+    value: T,
+    indicator: isize,
+}
+
+unsafe impl<T: CData> CData for Cell<T> {
+    fn cdata_type(&self) -> CDataType {
+        self.value.cdata_type()
+    }
+
+    fn indicator_ptr(&self) -> *const isize {
+        &self.indicator as *const isize
+    }
+
+    fn value_ptr(&self) -> *const c_void {
+        self.value.value_ptr()
+    }
+
+    fn buffer_length(&self) -> isize {
+        0
+    }
+}
+
+unsafe impl<T: CDataMut> CDataMut for Cell<T> {
+    fn mut_indicator_ptr(&mut self) -> *mut isize {
+        &mut self.indicator as *mut isize
+    }
+
+    fn mut_value_ptr(&mut self) -> *mut c_void {
+        self.value.mut_value_ptr()
+    }
+}
+
+impl<T> HasDataType for Cell<T> {
+    fn data_type(&self) -> DataType {
+        // Only used to fetch values, never bound as a parameter.
+        DataType::Unknown
+    }
+}
+
+unsafe impl<T: CDataMut> OutputParameter for Cell<T> {}
+
+/// Target for fetching a value of variable length in parts of [`PART_SIZE_BYTES`].
+pub struct Part {
+    c_type: CDataType,
+    buffer: Vec<u8>,
+    indicator: isize,
+}
+
+impl Part {
+    pub fn new(c_type: CDataType) -> Self {
+        Self {
+            c_type,
+            buffer: vec![0; PART_SIZE_BYTES],
+            indicator: NULL_DATA,
+        }
+    }
+
+    /// Fetches the value of the column in the current row, and writes it into `sink` part by part.
+    /// Returns the length of the value in bytes, or `None` if it is NULL.
+    pub fn stream(
+        &mut self,
+        row: &mut CursorRow,
+        column_number: u16,
+        sink: &mut impl Write,
+    ) -> Result<Option<usize>, Error> {
+        let streamed = self.stream_at_most(row, column_number, sink, usize::MAX)?;
+        Ok(streamed.map(|(length, _complete)| length))
+    }
+
+    /// Like [`Self::stream`], but stops once the value turns out to be longer than `limit` bytes.
+    /// Only its first bytes are written then, cut at a character boundary for text. The rest of
+    /// the value is never fetched. Returns the number of bytes written, and whether the value is
+    /// complete.
+    pub fn stream_at_most(
+        &mut self,
+        row: &mut CursorRow,
+        column_number: u16,
+        sink: &mut impl Write,
+        limit: usize,
+    ) -> Result<Option<(usize, bool)>, Error> {
+        // Text is terminated by zero, which is not part of the value.
+        let terminator = match self.c_type {
+            CDataType::Char => 1,
+            CDataType::WChar => 2,
+            _ => 0,
+        };
+        let capacity = self.buffer.len() - terminator;
+        let mut length = 0;
+        loop {
+            row.get_data(column_number, self)?;
+            // The indicator holds the length of the remainder of the value, including the part
+            // just fetched.
+            let (received, complete) = match self.indicator {
+                NULL_DATA => return Ok(None),
+                NO_TOTAL => (capacity, false),
+                remainder => {
+                    let remainder = remainder as usize;
+                    (remainder.min(capacity), remainder <= capacity)
+                }
+            };
+            if received > limit - length {
+                let end = self.boundary(limit - length);
+                sink.write_all(&self.buffer[..end])?;
+                return Ok(Some((length + end, false)));
+            }
+            sink.write_all(&self.buffer[..received])?;
+            length += received;
+            if complete {
+                return Ok(Some((length, true)));
+            }
+        }
+    }
+
+    /// Largest position up to `end` within the part fetched last, at which the value can be cut
+    /// without splitting a character.
+    fn boundary(&self, end: usize) -> usize {
+        match self.c_type {
+            CDataType::Char => {
+                // Do not keep the leading bytes of a multi byte UTF-8 sequence.
+                let mut end = end;
+                while end > 0 && self.buffer[end] & 0b1100_0000 == 0b1000_0000 {
+                    end -= 1;
+                }
+                end
+            }
+            CDataType::WChar => {
+                let end = end - end % 2;
+                // Do not keep the high surrogate of a surrogate pair.
+                let is_high_surrogate = end >= 2
+                    && (0xD800..0xDC00).contains(&u16::from_ne_bytes([
+                        self.buffer[end - 2],
+                        self.buffer[end - 1],
+                    ]));
+                if is_high_surrogate {
+                    end - 2
+                } else {
+                    end
+                }
+            }
+            _ => end,
+        }
+    }
+}
+
+unsafe impl CData for Part {
+    fn cdata_type(&self) -> CDataType {
+        self.c_type
+    }
+
+    fn indicator_ptr(&self) -> *const isize {
+        &self.indicator as *const isize
+    }
+
+    fn value_ptr(&self) -> *const c_void {
+        self.buffer.as_ptr() as *const c_void
+    }
+
+    fn buffer_length(&self) -> isize {
+        self.buffer.len().try_into().unwrap()
+    }
+}
+
+unsafe impl CDataMut for Part {
+    fn mut_indicator_ptr(&mut self) -> *mut isize {
+        &mut self.indicator as *mut isize
+    }
+
+    fn mut_value_ptr(&mut self) -> *mut c_void {
+        self.buffer.as_mut_ptr() as *mut c_void
+    }
+}
+
+impl HasDataType for Part {
+    fn data_type(&self) -> DataType {
+        // Only used to fetch values, never bound as a parameter.
+        DataType::Unknown
+    }
+}
+
+unsafe impl OutputParameter for Part {}
+
+#[cfg(test)]
+mod tests {
+    use super::{chunk, columns_to_spill};
+
+    #[test]
+    fn spill_largest_columns_first() {
+        // A row fits, nothing is spilled.
+        assert_eq!(Some(vec![]), columns_to_spill(100, &[(1, 50)], 1000));
+        // Spilling the largest column suffices to fit 1024 rows.
+        assert_eq!(
+            Some(vec![1]),
+            columns_to_spill(1_000_100, &[(1, 1_000_000), (2, 50)], 500_000)
+        );
+        // Both columns are spilled, even though fewer than 1024 rows fit.
+        assert_eq!(
+            Some(vec![2, 1]),
+            columns_to_spill(20_100, &[(1, 4_000), (2, 16_000)], 1024)
+        );
+        // Columns which can not be spilled are too large.
+        assert_eq!(None, columns_to_spill(20_100, &[(1, 4_000)], 1024));
+    }
+
+    #[test]
+    fn chunks_fit_into_memory() {
+        assert_eq!((3, 4), chunk(&[Some(4), None, Some(2)], 100));
+        assert_eq!((2, 4), chunk(&[Some(4), None, Some(2)], 11));
+        // A single value larger than the limit is a chunk of its own.
+        assert_eq!((1, 200), chunk(&[Some(200), Some(1)], 100));
+        assert_eq!((1, 4), chunk(&[Some(4), Some(200)], 100));
+        assert_eq!((0, 0), chunk(&[], 100));
+    }
+}
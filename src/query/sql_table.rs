@@ -0,0 +1,81 @@
+//! Creation of tables for the database file formats, e.g. DuckDB or SQLite.
+
+use parquet::{
+    basic::{ConvertedType, LogicalType, Repetition, TimestampType, Type as PhysicalType},
+    schema::types::Type,
+};
+
+use super::ColumnInfo;
+
+/// `CREATE TABLE` statement for a table with one column for each column of the result set. Does
+/// nothing if the table already exists.
+pub fn create_table_statement(table: &str, strategies: &[ColumnInfo]) -> String {
+    let columns: Vec<_> = strategies
+        .iter()
+        .map(|(_index, name, strategy)| {
+            let parquet_type = strategy.parquet_type(name);
+            let nullability = match parquet_type.get_basic_info().repetition() {
+                Repetition::REQUIRED => " NOT NULL",
+                _ => "",
+            };
+            format!(
+                "{} {}{nullability}",
+                quote_identifier(name),
+                sql_type(&parquet_type)
+            )
+        })
+        .collect();
+    format!(
+        "CREATE TABLE IF NOT EXISTS {} ({});",
+        quote_identifier(table),
+        columns.join(", ")
+    )
+}
+
+pub fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// SQL type of a column, derived from its parquet type. The names are understood by DuckDB and map
+/// to the intended type affinity in SQLite.
+fn sql_type(field: &Type) -> String {
+    let info = field.get_basic_info();
+    if info.converted_type() == ConvertedType::DECIMAL {
+        return format!("DECIMAL({},{})", field.get_precision(), field.get_scale());
+    }
+    if info.logical_type_ref() == Some(&LogicalType::Float16) {
+        return "FLOAT".to_owned();
+    }
+    let data_type = match field.get_physical_type() {
+        PhysicalType::BOOLEAN => "BOOLEAN",
+        PhysicalType::INT32 => match info.converted_type() {
+            ConvertedType::INT_8 => "TINYINT",
+            ConvertedType::INT_16 => "SMALLINT",
+            ConvertedType::UINT_8 => "UTINYINT",
+            ConvertedType::UINT_16 => "USMALLINT",
+            ConvertedType::UINT_32 => "UINTEGER",
+            ConvertedType::DATE => "DATE",
+            ConvertedType::TIME_MILLIS => "TIME",
+            _ => "INTEGER",
+        },
+        PhysicalType::INT64 if info.converted_type() == ConvertedType::UINT_64 => "UBIGINT",
+        PhysicalType::INT64 => match info.logical_type_ref() {
+            Some(LogicalType::Timestamp(TimestampType {
+                is_adjusted_to_u_t_c: true,
+                ..
+            })) => "TIMESTAMPTZ",
+            Some(LogicalType::Timestamp(TimestampType {
+                is_adjusted_to_u_t_c: false,
+                ..
+            })) => "TIMESTAMP",
+            Some(LogicalType::Time(_)) => "TIME",
+            _ => "BIGINT",
+        },
+        PhysicalType::INT96 => "TIMESTAMP",
+        PhysicalType::FLOAT => "FLOAT",
+        PhysicalType::DOUBLE => "DOUBLE",
+        PhysicalType::BYTE_ARRAY if info.converted_type() == ConvertedType::UTF8 => "VARCHAR",
+        PhysicalType::BYTE_ARRAY | PhysicalType::FIXED_LEN_BYTE_ARRAY => "BLOB",
+    };
+    data_type.to_owned()
+}
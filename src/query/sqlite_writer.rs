@@ -0,0 +1,118 @@
+use anyhow::{bail, Error};
+use log::info;
+use odbc_api::{buffers::AnyColumnView, Cursor};
+use rusqlite::{params_from_iter, types::Value, Connection};
+
+use crate::destination::Destination;
+
+use super::{
+    batch_size_limit::BatchSizeLimit,
+    fetch_buffer, give_hint_about_flag_for_oracle_users, make_schema,
+    sql_table::{create_table_statement, quote_identifier},
+    strategy::MappingOptions,
+    value_text::column_as_text,
+};
+
+/// Writes the result set into a table of a SQLite database file. The table is created, if it does
+/// not exist yet. Otherwise the rows are appended to it. All rows are inserted within one
+/// transaction, so the table is not changed if the export fails.
+pub fn cursor_to_sqlite(
+    mut cursor: impl Cursor,
+    output: Destination,
+    batch_size: BatchSizeLimit,
+    mapping_options: MappingOptions,
+    table: &str,
+) -> Result<(), Error> {
+    let Destination::File(path) = output else {
+        bail!("SQLite database files must be written to the local file system.")
+    };
+
+    let strategies = make_schema(&mut cursor, mapping_options, false)?;
+
+    if strategies.is_empty() {
+        bail!("Resulting table would not have any columns!")
+    }
+
+    let mut conn = Connection::open(&path)?;
+    let transaction = conn.transaction()?;
+    let create_table = create_table_statement(table, &strategies);
+    info!("Create table statement: {create_table}");
+    transaction.execute_batch(&create_table)?;
+
+    let placeholders = vec!["?"; strategies.len()].join(", ");
+    let insert = format!(
+        "INSERT INTO {} VALUES ({placeholders});",
+        quote_identifier(table)
+    );
+    let mut statement = transaction.prepare(&insert)?;
+
+    let (mut odbc_buffer, _batch_size_row) = fetch_buffer(&strategies, batch_size, 0)?;
+    let mut row_set_cursor = cursor.bind_buffer(&mut odbc_buffer)?;
+
+    let mut num_batch = 0;
+    while let Some(buffer) = row_set_cursor
+        .fetch()
+        .map_err(give_hint_about_flag_for_oracle_users)?
+    {
+        num_batch += 1;
+        let num_rows = buffer.num_rows();
+        info!("Fetched batch {} with {} rows.", num_batch, num_rows);
+        let mut columns: Vec<_> = (0..strategies.len())
+            .map(|col_index| column_values(buffer.column(col_index)).into_iter())
+            .collect();
+        for _ in 0..num_rows {
+            let row = columns.iter_mut().map(|column| column.next().unwrap());
+            statement.execute(params_from_iter(row))?;
+        }
+    }
+
+    drop(statement);
+    transaction.commit()?;
+
+    Ok(())
+}
+
+/// Values of a fetched column. Integers, booleans, floating point numbers and binary data are
+/// stored using the respective SQLite storage class. Everything else is stored as text, e.g. dates
+/// as `2022-10-15`.
+fn column_values(view: AnyColumnView) -> Vec<Value> {
+    fn natives<T: Copy>(values: &[T], f: impl Fn(T) -> Value) -> Vec<Value> {
+        values.iter().copied().map(f).collect()
+    }
+    fn nullable<'a, T: Copy + 'a>(
+        values: impl Iterator<Item = Option<&'a T>>,
+        f: impl Fn(T) -> Value,
+    ) -> Vec<Value> {
+        values
+            .map(|value| value.copied().map(&f).unwrap_or(Value::Null))
+            .collect()
+    }
+    let integer = |i: i64| Value::Integer(i);
+
+    match view {
+        AnyColumnView::I8(values) => natives(values, |i| integer(i.into())),
+        AnyColumnView::I16(values) => natives(values, |i| integer(i.into())),
+        AnyColumnView::I32(values) => natives(values, |i| integer(i.into())),
+        AnyColumnView::I64(values) => natives(values, integer),
+        AnyColumnView::U8(values) => natives(values, |i| integer(i.into())),
+        AnyColumnView::F32(values) => natives(values, |f| Value::Real(f.into())),
+        AnyColumnView::F64(values) => natives(values, Value::Real),
+        AnyColumnView::Bit(values) => natives(values, |bit| integer(bit.as_bool().into())),
+        AnyColumnView::NullableI8(values) => nullable(values, |i| integer(i.into())),
+        AnyColumnView::NullableI16(values) => nullable(values, |i| integer(i.into())),
+        AnyColumnView::NullableI32(values) => nullable(values, |i| integer(i.into())),
+        AnyColumnView::NullableI64(values) => nullable(values, integer),
+        AnyColumnView::NullableU8(values) => nullable(values, |i| integer(i.into())),
+        AnyColumnView::NullableF32(values) => nullable(values, |f| Value::Real(f.into())),
+        AnyColumnView::NullableF64(values) => nullable(values, Value::Real),
+        AnyColumnView::NullableBit(values) => nullable(values, |bit| integer(bit.as_bool().into())),
+        AnyColumnView::Binary(view) => view
+            .iter()
+            .map(|value| value.map_or(Value::Null, |bytes| Value::Blob(bytes.to_vec())))
+            .collect(),
+        other => column_as_text(other)
+            .into_iter()
+            .map(|value| value.map_or(Value::Null, Value::Text))
+            .collect(),
+    }
+}
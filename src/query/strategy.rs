@@ -1,39 +1,50 @@
 use std::convert::TryInto;
 
-use anyhow::Error;
+use anyhow::{bail, Error};
+use chrono::FixedOffset;
 use log::{debug, info, warn};
 use odbc_api::{
-    buffers::{AnyColumnView, BufferDescription, BufferKind},
+    buffers::{AnyColumnView, BufferDescription},
     sys::SqlDataType,
     ColumnDescription, Cursor, DataType, Nullability,
 };
 use parquet::{
-    basic::{ConvertedType, Repetition},
+    basic::{ConvertedType, Repetition, TimeUnit},
     column::writer::ColumnWriter,
-    data_type::{
-        ByteArrayType, DoubleType, FixedLenByteArrayType, FloatType, Int32Type, Int64Type,
-    },
+    data_type::{ByteArrayType, DoubleType, FixedLenByteArrayType, Int32Type, Int64Type},
     schema::types::Type,
 };
 
 use crate::{
+    enum_args::{Mask, NumberAs, OversizedPolicy, SanitizeColumnNames},
     parquet_buffer::ParquetBuffer,
     query::{
         binary::Binary,
         boolean::Boolean,
+        column_cast::{cast_fetch_strategy, ColumnCast},
+        column_size::{grow_column_size, limit_column_size},
         date::Date,
-        decimal::decimal_fetch_strategy,
+        decfloat::{decfloat_fetch_strategy, SQL_DECFLOAT},
+        decimal::{decimal_fetch_strategy, number_without_precision_fetch_strategy},
+        float16::float_fetch_strategy,
+        guid::Guid,
         identical::{fetch_identical, fetch_identical_with_converted_type},
+        interval::{is_interval, Interval},
         text::{Utf16ToUtf8, Utf8},
-        timestamp::TimestampToInt,
-        timestamp_tz::timestamp_tz,
+        time::Time,
+        timestamp::{precision_to_time_unit, TimestampToInt, TimestampToInt96},
+        timestamp_tz::{timestamp_tz, SQL_SS_TIMESTAMPOFFSET},
+        unsigned::unsigned_fetch_strategy,
+        wkb::{Wkb, SQL_SS_UDT},
+        xml::{is_xml, Xml},
     },
 };
 
 /// Decisions on how to handle a particular column of the ODBC result set. What buffer to bind to it
 /// for fetching, into what parquet type it is going to be translated and how to translate it from
-/// the odbc buffer elements to afformentioned parquet type.
-pub trait ColumnFetchStrategy {
+/// the odbc buffer elements to afformentioned parquet type. Strategies must be `Sync`, since the
+/// columns of a row group may be written by several threads (see `--encoding-threads`).
+pub trait ColumnFetchStrategy: Sync {
     /// Parquet column type used in parquet schema
     fn parquet_type(&self, name: &str) -> Type;
     /// Description of the buffer bound to the ODBC data source.
@@ -55,6 +66,81 @@ pub struct MappingOptions<'a> {
     pub prefer_varbinary: bool,
     pub driver_does_support_i64: bool,
     pub prefer_int_over_decimal: bool,
+    /// Write `TINYINT` and `SMALLINT` as `INT32` without converted type.
+    pub plain_narrow_integers: bool,
+    /// Write `REAL` and `FLOAT` columns with a precision of up to 24 bits as `FLOAT16`.
+    pub real_as_float16: bool,
+    /// Fetch decimals as doubles, trading exactness for compatibility.
+    pub decimal_as_double: bool,
+    /// Fetch decimals as text, so values of any precision are written without loss.
+    pub decimal_as_text: bool,
+    /// Parquet type of decimals without precision. If `None` they are fetched as doubles if
+    /// `decimal_as_double` is set, or as text otherwise.
+    pub default_number_as: Option<NumberAs>,
+    /// Parquet type of `DECFLOAT` columns of IBM Db2.
+    pub decfloat_as: NumberAs,
+    /// Store timestamps using the legacy INT96 physical type, instead of INT64.
+    pub timestamps_as_int96: bool,
+    /// Offset of the time zone timestamps without time zone are local to. If set they are
+    /// normalized to UTC, otherwise they are written as naive timestamps.
+    pub timestamps_utc_offset: Option<FixedOffset>,
+    /// Unit of timestamps in individual columns, overriding the one derived from the precision
+    /// reported by the driver.
+    pub timestamp_units: &'a [(String, TimeUnit)],
+    /// Unit of `TIME` columns. If `None` it is chosen based on the precision of the column.
+    pub time_unit: Option<TimeUnit>,
+    /// Store `TIME` columns as text, instead of the `TIME` logical type.
+    pub time_as_text: bool,
+    /// Store GUIDs as 16 Byte UUIDs, instead of their 36 character text representation.
+    pub guid_as_uuid: bool,
+    /// Maximum length of XML documents, in characters of the fetch buffer.
+    pub xml_max_length: usize,
+    /// Write intervals as ISO 8601 durations, instead of parquet `INTERVAL`.
+    pub intervals_as_iso8601: bool,
+    /// Columns holding geometries as Well Known Binary (WKB).
+    pub geometry_columns: &'a [String],
+    /// Maximum length of geometries in bytes.
+    pub wkb_max_length: usize,
+    /// Upper bound for the size of the buffers of text and binary columns in bytes, regardless of
+    /// the length reported by the driver.
+    pub max_column_size: Option<usize>,
+    /// Columns whose buffers have grown, because their values have been truncated, together with
+    /// the length of their buffers. See `--on-truncation`.
+    pub column_lengths: &'a [(String, usize)],
+    /// Fetch columns of unknown types as text, even if the driver can not tell their length.
+    pub unknown_types_as_text: bool,
+    /// Remove the trailing spaces padding the values of all fixed length `CHAR` columns.
+    pub trim_char: bool,
+    /// Character columns whose trailing spaces are removed.
+    pub trim_char_columns: &'a [String],
+    /// Columns whose parquet type is specified by the user, rather than derived from the column
+    /// description.
+    pub column_casts: &'a [(String, ColumnCast)],
+    /// Names of the columns to write first, in this order.
+    pub column_order: &'a [String],
+    /// Columns of the result set which are written under a different name. All other options refer
+    /// to the columns by their new name.
+    pub renames: &'a [(String, String)],
+    /// Glob patterns of the columns to write. All columns are written if empty.
+    pub columns: &'a [String],
+    /// Glob patterns of columns not to write, even if they match `columns`.
+    pub exclude_columns: &'a [String],
+    /// Sanitize the column names (after renaming), so engines like Hive accept them.
+    pub sanitize_column_names: Option<SanitizeColumnNames>,
+    /// Values of columns which are written as NULL, e.g. sentinels like `0000-00-00`.
+    pub null_values: &'a [(String, String)],
+    /// Values written instead of NULL.
+    pub null_defaults: &'a [(String, String)],
+    /// Columns holding personal data, which are hashed or redacted.
+    pub masks: &'a [(String, Mask)],
+    /// Size in bytes of the largest value of a text or binary column, see `--max-value-size`.
+    pub max_value_size: Option<usize>,
+    /// What happens to values larger than `max_value_size`, for single columns or all of them.
+    pub oversized: &'a [(Option<String>, OversizedPolicy)],
+    /// Columns written as `REQUIRED`, regardless of the nullability reported by the driver.
+    pub not_null_columns: &'a [String],
+    /// Columns written as `OPTIONAL`, regardless of the nullability reported by the driver.
+    pub nullable_columns: &'a [String],
 }
 
 pub fn strategy_from_column_description(
@@ -63,13 +149,47 @@ pub fn strategy_from_column_description(
     mapping_options: MappingOptions,
     cursor: &mut impl Cursor,
     index: i16,
-) -> Result<Option<Box<dyn ColumnFetchStrategy>>, Error> {
+) -> Result<Box<dyn ColumnFetchStrategy>, Error> {
     let MappingOptions {
         db_name,
         use_utf16,
         prefer_varbinary,
         driver_does_support_i64,
         prefer_int_over_decimal,
+        plain_narrow_integers,
+        real_as_float16,
+        decimal_as_double,
+        decimal_as_text,
+        default_number_as,
+        decfloat_as,
+        timestamps_as_int96,
+        timestamps_utc_offset,
+        timestamp_units,
+        time_unit,
+        time_as_text,
+        guid_as_uuid,
+        xml_max_length,
+        intervals_as_iso8601,
+        geometry_columns,
+        wkb_max_length,
+        max_column_size,
+        column_lengths,
+        unknown_types_as_text,
+        trim_char,
+        trim_char_columns,
+        column_casts,
+        column_order: _,
+        renames: _,
+        columns: _,
+        exclude_columns: _,
+        sanitize_column_names: _,
+        null_values: _,
+        null_defaults: _,
+        masks: _,
+        max_value_size: _,
+        oversized: _,
+        not_null_columns: _,
+        nullable_columns: _,
     } = mapping_options;
 
     // Convert ODBC nullability to Parquet repetition. If the ODBC driver can not tell wether a
@@ -79,15 +199,45 @@ pub fn strategy_from_column_description(
         Nullability::NoNulls => Repetition::REQUIRED,
     };
 
+    if let Some(&(_, cast)) = column_casts.iter().find(|(column, _)| column == name) {
+        info!("Casting column '{name}' to {cast:?}.");
+        let strategy = cast_fetch_strategy(cast, repetition)
+            .unwrap_or_else(|| text_of_display_size(cursor, index, repetition));
+        return Ok(strategy);
+    }
+
+    // The last one wins, should a column be specified multiple times.
+    let timestamp_unit = |precision: i16| {
+        timestamp_units
+            .iter()
+            .rev()
+            .find(|(column, _unit)| column == name)
+            .map(|&(_, unit)| unit)
+            .unwrap_or_else(|| precision_to_time_unit(precision.try_into().unwrap()))
+    };
+
+    let is_geometry = geometry_columns.iter().any(|column| column == name);
+
     let is_optional = cd.could_be_nullable();
 
+    // E.g. MySQL reports integer columns declared `UNSIGNED` as such.
+    let is_unsigned = matches!(
+        cd.data_type,
+        DataType::TinyInt | DataType::SmallInt | DataType::Integer | DataType::BigInt
+    ) && cursor.column_is_unsigned(index.try_into().unwrap())?;
+
     let strategy: Box<dyn ColumnFetchStrategy> = match cd.data_type {
-        DataType::Float { precision: 0..=24 } | DataType::Real => {
-            fetch_identical::<FloatType>(is_optional)
+        DataType::Float { .. } | DataType::Real => {
+            float_fetch_strategy(cd.data_type, repetition, real_as_float16)
         }
-        // Map all precisions larger than 24 to double. Double would be technically precision 53.
-        DataType::Float { precision: _ } => fetch_identical::<DoubleType>(is_optional),
         DataType::Double => fetch_identical::<DoubleType>(is_optional),
+        DataType::TinyInt | DataType::SmallInt if plain_narrow_integers => {
+            fetch_identical::<Int32Type>(is_optional)
+        }
+        DataType::TinyInt if is_unsigned => unsigned_fetch_strategy(is_optional, 8),
+        DataType::SmallInt if is_unsigned => unsigned_fetch_strategy(is_optional, 16),
+        DataType::Integer if is_unsigned => unsigned_fetch_strategy(is_optional, 32),
+        DataType::BigInt if is_unsigned => unsigned_fetch_strategy(is_optional, 64),
         DataType::SmallInt => {
             fetch_identical_with_converted_type::<Int32Type>(is_optional, ConvertedType::INT_16)
         }
@@ -95,6 +245,15 @@ pub fn strategy_from_column_description(
             fetch_identical_with_converted_type::<Int32Type>(is_optional, ConvertedType::INT_32)
         }
         DataType::Date => Box::new(Date::new(repetition)),
+        // Oracle reports `NUMBER` columns declared without precision with a precision of 0.
+        DataType::Numeric { precision: 0, .. } | DataType::Decimal { precision: 0, .. } => {
+            let default_number_as = default_number_as.unwrap_or(if decimal_as_double {
+                NumberAs::Double
+            } else {
+                NumberAs::Text
+            });
+            number_without_precision_fetch_strategy(is_optional, default_number_as)
+        }
         DataType::Numeric { scale, precision } | DataType::Decimal { scale, precision } => {
             decimal_fetch_strategy(
                 is_optional,
@@ -102,19 +261,40 @@ pub fn strategy_from_column_description(
                 precision.try_into().unwrap(),
                 driver_does_support_i64,
                 prefer_int_over_decimal,
+                decimal_as_double,
+                decimal_as_text,
             )
         }
+        DataType::Timestamp { precision: _ } if timestamps_as_int96 => {
+            Box::new(TimestampToInt96::new(repetition, timestamps_utc_offset))
+        }
         DataType::Timestamp { precision } => Box::new(TimestampToInt::new(
             repetition,
-            precision.try_into().unwrap(),
+            timestamp_unit(precision),
+            timestamps_utc_offset,
         )),
         DataType::BigInt => fetch_identical::<Int64Type>(is_optional),
         DataType::Bit => Box::new(Boolean::new(repetition)),
         DataType::TinyInt => {
             fetch_identical_with_converted_type::<Int32Type>(is_optional, ConvertedType::INT_8)
         }
+        DataType::Binary { .. } | DataType::Varbinary { .. } | DataType::LongVarbinary { .. }
+            if is_geometry =>
+        {
+            Box::new(Wkb::new(repetition, wkb_max_length))
+        }
+        DataType::Other {
+            data_type: SQL_SS_UDT,
+            ..
+        } if is_geometry => bail!(
+            "Geometry column '{name}' holds a spatial type of Microsoft SQL Server, rather than \
+            Well Known Binary (WKB). Select it with `{name}.STAsBinary()` or pass \
+            `--cast-geometry-to-wkb`."
+        ),
         DataType::Binary { length } => {
-            if prefer_varbinary {
+            // Without a length reported by the driver, the values can not be written with a fixed
+            // length.
+            if prefer_varbinary || length == 0 {
                 Box::new(Binary::<ByteArrayType>::new(repetition, length))
             } else {
                 Box::new(Binary::<FixedLenByteArrayType>::new(repetition, length))
@@ -132,14 +312,22 @@ pub fn strategy_from_column_description(
         | DataType::WVarchar { length: _ }
         | DataType::LongVarchar { length: _ }
         | DataType::WChar { length: _ }) => {
+            let trim_end = trim_char
+                && matches!(dt, DataType::Char { .. } | DataType::WChar { .. })
+                || trim_char_columns.iter().any(|column| column == name);
             if use_utf16 {
-                Box::new(Utf16ToUtf8::new(repetition, dt.utf16_len().unwrap()))
+                Box::new(
+                    Utf16ToUtf8::new(repetition, dt.utf16_len().unwrap()).with_trim_end(trim_end),
+                )
             } else {
-                Box::new(Utf8::with_bytes_length(repetition, dt.utf8_len().unwrap()))
+                Box::new(
+                    Utf8::with_bytes_length(repetition, dt.utf8_len().unwrap())
+                        .with_trim_end(trim_end),
+                )
             }
         }
         DataType::Other {
-            data_type: SqlDataType(-155),
+            data_type: SQL_SS_TIMESTAMPOFFSET,
             column_size: _,
             decimal_digits: precision,
         } => {
@@ -147,38 +335,108 @@ pub fn strategy_from_column_description(
                 // -155 is an indication for "Timestamp with timezone" on Microsoft SQL Server. We
                 // give it special treatment so users can sort by time instead lexographically.
                 info!("Detected Timestamp type with time zone. Appyling instant semantics for column {}.", cd.name_to_string()?);
-                timestamp_tz(precision.try_into().unwrap(), repetition)?
+                timestamp_tz(
+                    precision.try_into().unwrap(),
+                    timestamp_unit(precision),
+                    repetition,
+                    timestamps_as_int96,
+                )?
             } else {
                 unknown_non_char_type(cd, cursor, index, repetition)?
             }
         }
+        DataType::Other {
+            data_type: SQL_DECFLOAT,
+            ..
+        } => decfloat_fetch_strategy(repetition, decfloat_as),
+        DataType::Other {
+            data_type: SqlDataType::EXT_GUID,
+            ..
+        } if guid_as_uuid => Box::new(Guid::new(repetition)),
+        dt @ DataType::Other { .. } if is_xml(&dt) => {
+            Box::new(Xml::new(repetition, xml_max_length, use_utf16))
+        }
+        dt @ DataType::Other { .. } if is_interval(&dt) => {
+            let display_size = cursor.col_display_size(index.try_into().unwrap())? as usize;
+            Box::new(Interval::new(
+                &dt,
+                repetition,
+                display_size,
+                intervals_as_iso8601,
+            ))
+        }
+        // -154 is `TIME` with fractional seconds (`SQL_SS_TIME2`) on Microsoft SQL Server.
+        DataType::Time { precision }
+        | DataType::Other {
+            data_type: SqlDataType(-154),
+            decimal_digits: precision,
+            ..
+        } if !time_as_text => {
+            let unit =
+                time_unit.unwrap_or_else(|| precision_to_time_unit(precision.try_into().unwrap()));
+            Box::new(Time::new(repetition, unit))
+        }
+        DataType::Unknown | DataType::Other { .. } if unknown_types_as_text => {
+            warn!(
+                "Column '{name}' has the unknown SQL type {:?}. Fetching it as text.",
+                cd.data_type
+            );
+            text_of_display_size(cursor, index, repetition)
+        }
         DataType::Unknown | DataType::Time { .. } | DataType::Other { .. } => {
             unknown_non_char_type(cd, cursor, index, repetition)?
         }
     };
 
+    // Only the lengths reported by the driver are limited, not the ones given by the user.
+    let reports_length = matches!(
+        cd.data_type,
+        DataType::Char { .. }
+            | DataType::Varchar { .. }
+            | DataType::WVarchar { .. }
+            | DataType::LongVarchar { .. }
+            | DataType::WChar { .. }
+            | DataType::Binary { .. }
+            | DataType::Varbinary { .. }
+            | DataType::LongVarbinary { .. }
+            | DataType::Unknown
+    ) && !is_geometry;
+    let strategy = match column_lengths.iter().find(|(column, _)| column == name) {
+        Some(&(_, length)) => grow_column_size(strategy, length),
+        _ => strategy,
+    };
+    let strategy = match max_column_size {
+        Some(max_size) if reports_length => limit_column_size(strategy, name, max_size),
+        _ => strategy,
+    };
+
     debug!(
         "ODBC buffer description for column {}: {:?}",
         index,
         strategy.buffer_description()
     );
 
-    if matches!(
-        strategy.buffer_description().kind,
-        BufferKind::Text { max_str_len: 0 } | BufferKind::WText { max_str_len: 0 }
-    ) {
-        warn!(
-            "Ignoring column '{}' with index {}. Driver reported a display length of 0. \
-              This can happen for types without a fixed size limit. If you feel this should be \
-              supported open an issue (or PR) at \
-              <https://github.com/pacman82/odbc2parquet/issues>.",
-            name, index
-        );
-
-        Ok(None)
-    } else {
-        Ok(Some(strategy))
-    }
+    Ok(strategy)
+}
+
+/// Length of the text buffer for columns fetched as text, if the driver does not report a display
+/// size.
+const FALLBACK_TEXT_LENGTH: usize = 4096;
+
+/// Binds a column to a text buffer sized by its display size, e.g. columns of unknown types. Unlike
+/// [`unknown_non_char_type`] this never fails or results in an empty buffer.
+fn text_of_display_size(cursor: &mut impl Cursor, index: i16, repetition: Repetition) -> Box<Utf8> {
+    let length = match cursor.col_display_size(index.try_into().unwrap()) {
+        Ok(display_size) if display_size > 0 => display_size as usize,
+        _ => {
+            warn!(
+                "Driver reported no display size for column {index}. Fetching values up to a \
+                length of {FALLBACK_TEXT_LENGTH} bytes."
+            );
+            FALLBACK_TEXT_LENGTH
+        }
+    };
+    Box::new(Utf8::with_bytes_length(repetition, length))
 }
 
 fn unknown_non_char_type(
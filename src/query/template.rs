@@ -0,0 +1,81 @@
+//! Substitution of variables in the query text, see `--var`. Unlike parameters, variables may be
+//! used in places the SQL grammar does not allow placeholders, e.g. for table names.
+
+use anyhow::{anyhow, bail, Error};
+
+/// Replaces each `${NAME}` in `query` with the value of the variable `NAME`. `$${` is written as a
+/// literal `${`. Fails if a variable is not defined. The last definition wins, should a variable be
+/// defined multiple times.
+pub fn substitute_variables(query: &str, variables: &[(String, String)]) -> Result<String, Error> {
+    let mut result = String::with_capacity(query.len());
+    let mut rest = query;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            // Escaped, keep `${` literally.
+            result.push_str(&rest[..start - 1]);
+            result.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        result.push_str(&rest[..start]);
+        let after_brace = &rest[start + 2..];
+        let end = after_brace
+            .find('}')
+            .ok_or_else(|| anyhow!("Unterminated variable '${{{after_brace}' in query."))?;
+        let name = &after_brace[..end];
+        let Some((_, value)) = variables
+            .iter()
+            .rev()
+            .find(|(variable, _)| variable == name)
+        else {
+            bail!(
+                "Variable '{name}' is used in the query, but not defined. Use --var {name}=VALUE."
+            )
+        };
+        result.push_str(value);
+        rest = &after_brace[end + 1..];
+    }
+    result.push_str(rest);
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::substitute_variables;
+
+    fn substitute(query: &str, variables: &[(&str, &str)]) -> Result<String, String> {
+        let variables: Vec<_> = variables
+            .iter()
+            .map(|&(name, value)| (name.to_owned(), value.to_owned()))
+            .collect();
+        substitute_variables(query, &variables).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn substitute_defined_variables() {
+        assert_eq!(
+            "SELECT * FROM sales.orders_2023 WHERE day >= '2023-01-01'",
+            substitute(
+                "SELECT * FROM ${schema}.orders_2023 WHERE day >= '${from}'",
+                &[
+                    ("schema", "staging"),
+                    ("from", "2023-01-01"),
+                    ("schema", "sales")
+                ]
+            )
+            .unwrap()
+        );
+        // Dollars not followed by a brace are not touched, e.g. PostgreSQL dollar quoting.
+        assert_eq!("SELECT $$a$$", substitute("SELECT $$a$$", &[]).unwrap());
+        assert_eq!("SELECT '${a}'", substitute("SELECT '$${a}'", &[]).unwrap());
+    }
+
+    #[test]
+    fn reject_undefined_variables() {
+        assert_eq!(
+            "Variable 'b' is used in the query, but not defined. Use --var b=VALUE.",
+            substitute("SELECT ${a}, ${b}", &[("a", "1")]).unwrap_err()
+        );
+        assert!(substitute("SELECT ${a", &[("a", "1")]).is_err());
+    }
+}
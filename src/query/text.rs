@@ -1,8 +1,10 @@
 use std::borrow::Cow;
 
 use anyhow::Error;
+use bytes::Bytes;
+use encoding_rs::mem::convert_utf16_to_utf8;
 use log::warn;
-use odbc_api::buffers::{AnyColumnView, BufferDescription, BufferKind};
+use odbc_api::buffers::{AnyColumnView, BufferDescription, BufferKind, TextColumnView};
 use parquet::{
     basic::{ConvertedType, Repetition, Type as PhysicalType},
     column::writer::ColumnWriter,
@@ -10,7 +12,7 @@ use parquet::{
     schema::types::Type,
 };
 
-use crate::parquet_buffer::ParquetBuffer;
+use crate::parquet_buffer::{shared_byte_arrays, ParquetBuffer};
 
 use super::strategy::ColumnFetchStrategy;
 
@@ -18,11 +20,22 @@ pub struct Utf16ToUtf8 {
     repetition: Repetition,
     /// Length of the column elements in `u16` (as opposed to code points).
     length: usize,
+    /// Remove trailing spaces, e.g. the padding of `CHAR` columns.
+    trim_end: bool,
 }
 
 impl Utf16ToUtf8 {
     pub fn new(repetition: Repetition, length: usize) -> Self {
-        Self { repetition, length }
+        Self {
+            repetition,
+            length,
+            trim_end: false,
+        }
+    }
+
+    /// Remove trailing spaces from the values, e.g. the padding of `CHAR` columns.
+    pub fn with_trim_end(self, trim_end: bool) -> Self {
+        Self { trim_end, ..self }
     }
 }
 
@@ -50,7 +63,7 @@ impl ColumnFetchStrategy for Utf16ToUtf8 {
         column_writer: &mut ColumnWriter,
         column_view: AnyColumnView,
     ) -> Result<(), Error> {
-        write_utf16_to_utf8(parquet_buffer, column_writer, column_view)
+        write_utf16_to_utf8(parquet_buffer, column_writer, column_view, self.trim_end)
     }
 }
 
@@ -58,23 +71,12 @@ fn write_utf16_to_utf8(
     pb: &mut ParquetBuffer,
     column_writer: &mut ColumnWriter,
     column_reader: AnyColumnView,
+    trim_end: bool,
 ) -> Result<(), Error> {
     if let (ColumnWriter::ByteArrayColumnWriter(cw), AnyColumnView::WText(view)) =
         (column_writer, column_reader)
     {
-        pb.write_optional(
-            cw,
-            view.iter().map(|item| {
-                item.map(|ustr| {
-                    let byte_array: ByteArray = ustr
-                        .to_string()
-                        .expect("Data source must return valid UTF16 in wide character buffer")
-                        .into_bytes()
-                        .into();
-                    byte_array
-                })
-            }),
-        )?;
+        pb.write_optional(cw, utf16_to_utf8_byte_arrays(view, trim_end).into_iter())?;
     } else {
         panic!(
             "Invalid Column view type. This is not supposed to happen. Please open a Bug at \
@@ -84,15 +86,57 @@ fn write_utf16_to_utf8(
     Ok(())
 }
 
+/// Transcodes all values of the batch at once into a single allocation, rather than decoding and
+/// allocating each value separately. Unpaired surrogates are replaced with U+FFFD.
+fn utf16_to_utf8_byte_arrays(view: TextColumnView<u16>, trim_end: bool) -> Vec<Option<ByteArray>> {
+    // Each UTF-16 code unit becomes at most three bytes of UTF-8.
+    let max_len = view.iter().flatten().map(|value| value.len() * 3).sum();
+    let mut data = vec![0u8; max_len];
+    let mut end = 0;
+    let ranges: Vec<_> = view
+        .iter()
+        .map(|value| {
+            value.map(|value| {
+                let start = end;
+                end += convert_utf16_to_utf8(value.as_slice(), &mut data[start..]);
+                let mut value_end = end;
+                if trim_end {
+                    while value_end > start && data[value_end - 1] == b' ' {
+                        value_end -= 1;
+                    }
+                }
+                start..value_end
+            })
+        })
+        .collect();
+    data.truncate(end);
+    let data = Bytes::from(data);
+    ranges
+        .into_iter()
+        .map(|range| range.map(|range| ByteArray::from(data.slice(range))))
+        .collect()
+}
+
 pub struct Utf8 {
     repetition: Repetition,
     // Maximum string length in bytes
     length: usize,
+    /// Remove trailing spaces, e.g. the padding of `CHAR` columns.
+    trim_end: bool,
 }
 
 impl Utf8 {
     pub fn with_bytes_length(repetition: Repetition, length: usize) -> Self {
-        Self { repetition, length }
+        Self {
+            repetition,
+            length,
+            trim_end: false,
+        }
+    }
+
+    /// Remove trailing spaces from the values, e.g. the padding of `CHAR` columns.
+    pub fn with_trim_end(self, trim_end: bool) -> Self {
+        Self { trim_end, ..self }
     }
 }
 
@@ -120,7 +164,14 @@ impl ColumnFetchStrategy for Utf8 {
         column_writer: &mut ColumnWriter,
         column_view: AnyColumnView,
     ) -> Result<(), Error> {
-        write_to_utf8(parquet_buffer, column_writer, column_view)
+        let required = self.repetition == Repetition::REQUIRED;
+        write_to_utf8(
+            parquet_buffer,
+            column_writer,
+            column_view,
+            required,
+            self.trim_end,
+        )
     }
 }
 
@@ -128,13 +179,38 @@ fn write_to_utf8(
     pb: &mut ParquetBuffer,
     column_writer: &mut ColumnWriter,
     column_reader: AnyColumnView,
+    required: bool,
+    trim_end: bool,
 ) -> Result<(), Error> {
     if let (ColumnWriter::ByteArrayColumnWriter(cw), AnyColumnView::Text(view)) =
         (column_writer, column_reader)
     {
+        // Fast path for required columns, mirroring `IdenticalRequired`. Valid UTF-8 is written
+        // without allocating each value separately. Otherwise we go through the parquet buffer,
+        // which takes care of replacing invalid characters.
+        if required && !trim_end && pb.writes_values_as_is() {
+            if let Some(values) = view
+                .iter()
+                .map(|value| value.filter(|bytes| std::str::from_utf8(bytes).is_ok()))
+                .collect::<Option<Vec<&[u8]>>>()
+            {
+                cw.write_batch(&shared_byte_arrays(&values), None, None)?;
+                return Ok(());
+            }
+        }
         pb.write_optional(
             cw,
-            view.iter().map(|item| item.map(utf8_bytes_to_byte_array)),
+            view.iter().map(|item| {
+                item.map(|bytes| {
+                    if trim_end {
+                        let len =
+                            bytes.len() - bytes.iter().rev().take_while(|&&c| c == b' ').count();
+                        utf8_bytes_to_byte_array(&bytes[..len])
+                    } else {
+                        utf8_bytes_to_byte_array(bytes)
+                    }
+                })
+            }),
         )?;
     } else {
         panic!(
@@ -145,7 +221,7 @@ fn write_to_utf8(
     Ok(())
 }
 
-fn utf8_bytes_to_byte_array(bytes: &[u8]) -> ByteArray {
+pub fn utf8_bytes_to_byte_array(bytes: &[u8]) -> ByteArray {
     // Allocate string into a ByteArray and make sure it is all UTF-8 characters
     let utf8_str = String::from_utf8_lossy(bytes);
     // We need to allocate the string anyway to create a ByteArray (yikes!), yet if it already
@@ -160,3 +236,38 @@ fn utf8_bytes_to_byte_array(bytes: &[u8]) -> ByteArray {
     }
     utf8_str.into_owned().into_bytes().into()
 }
+
+#[cfg(test)]
+mod tests {
+    use odbc_api::buffers::{ColumnBuffer, TextColumn};
+    use parquet::data_type::ByteArray;
+
+    use super::utf16_to_utf8_byte_arrays;
+
+    #[test]
+    fn transcode_utf16_to_utf8() {
+        let values = ["Hello  ", "Grüße", "😀"];
+        let mut column = TextColumn::<u16>::new(4, 10);
+        for (index, value) in values.iter().enumerate() {
+            let utf16: Vec<u16> = value.encode_utf16().collect();
+            column.set_value(index, Some(&utf16));
+        }
+        column.set_value(3, None);
+
+        let expected = |values: &[&str]| -> Vec<Option<ByteArray>> {
+            values
+                .iter()
+                .map(|value| Some(ByteArray::from(*value)))
+                .chain([None])
+                .collect()
+        };
+        assert_eq!(
+            expected(&values),
+            utf16_to_utf8_byte_arrays(column.view(4), false)
+        );
+        assert_eq!(
+            expected(&["Hello", "Grüße", "😀"]),
+            utf16_to_utf8_byte_arrays(column.view(4), true)
+        );
+    }
+}
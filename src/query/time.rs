@@ -0,0 +1,128 @@
+use anyhow::{anyhow, Error};
+use odbc_api::buffers::{AnyColumnView, BufferDescription, BufferKind};
+use parquet::{
+    basic::{LogicalType, Repetition, TimeUnit, Type as PhysicalType},
+    column::writer::ColumnWriter,
+    data_type::{DataType, Int32Type, Int64Type},
+    schema::types::Type,
+};
+
+use crate::parquet_buffer::ParquetBuffer;
+
+use super::strategy::ColumnFetchStrategy;
+
+/// Length of the longest text representation of a time, with nanoseconds, i.e.
+/// `HH:MM:SS.fffffffff`.
+const TIME_TEXT_LEN: usize = 18;
+
+/// Writes times of day as `TIME` with the given unit. Times are fetched as text, since ODBC time
+/// structs do not hold fractional seconds.
+pub struct Time {
+    repetition: Repetition,
+    unit: TimeUnit,
+}
+
+impl Time {
+    pub fn new(repetition: Repetition, unit: TimeUnit) -> Self {
+        Self { repetition, unit }
+    }
+}
+
+impl ColumnFetchStrategy for Time {
+    fn parquet_type(&self, name: &str) -> Type {
+        let physical_type = match self.unit {
+            TimeUnit::MILLIS => PhysicalType::INT32,
+            TimeUnit::MICROS | TimeUnit::NANOS => PhysicalType::INT64,
+        };
+        Type::primitive_type_builder(name, physical_type)
+            .with_logical_type(Some(LogicalType::time(false, self.unit)))
+            .with_repetition(self.repetition)
+            .build()
+            .unwrap()
+    }
+
+    fn buffer_description(&self) -> BufferDescription {
+        BufferDescription {
+            kind: BufferKind::Text {
+                max_str_len: TIME_TEXT_LEN,
+            },
+            nullable: self.repetition == Repetition::OPTIONAL,
+        }
+    }
+
+    fn copy_odbc_to_parquet(
+        &self,
+        parquet_buffer: &mut ParquetBuffer,
+        column_writer: &mut ColumnWriter,
+        column_view: AnyColumnView,
+    ) -> Result<(), Error> {
+        let view = column_view.as_text_view().expect(
+            "Invalid Column view type. This is not supposed to happen. Please open a Bug at \
+            https://github.com/pacman82/odbc2parquet/issues.",
+        );
+        let divisor = match self.unit {
+            TimeUnit::MILLIS => 1_000_000,
+            TimeUnit::MICROS => 1_000,
+            TimeUnit::NANOS => 1,
+        };
+        let values = view.iter().map(|text| {
+            text.map(|text| Ok(nanoseconds_since_midnight(text)? / divisor))
+                .transpose()
+        });
+        if self.unit == TimeUnit::MILLIS {
+            let column_writer = Int32Type::get_column_writer_mut(column_writer).unwrap();
+            // Milliseconds of a day always fit into 32 Bit.
+            parquet_buffer.write_optional_falliable(
+                column_writer,
+                values.map(|value| Ok(value?.map(|ms| ms as i32))),
+            )
+        } else {
+            let column_writer = Int64Type::get_column_writer_mut(column_writer).unwrap();
+            parquet_buffer.write_optional_falliable(column_writer, values)
+        }
+    }
+}
+
+/// Parses the text representation of a time, e.g. `12:34:56.1234567`. Digits beyond nanoseconds
+/// are truncated.
+fn nanoseconds_since_midnight(text: &[u8]) -> Result<i64, Error> {
+    let invalid = || anyhow!("Invalid time: '{}'", String::from_utf8_lossy(text));
+    let text = std::str::from_utf8(text).map_err(|_| invalid())?.trim();
+    let (hms, fraction) = text.split_once('.').unwrap_or((text, ""));
+    let mut parts = hms.split(':').map(|part| part.parse::<i64>());
+    let (Some(Ok(hours)), Some(Ok(minutes)), Some(Ok(seconds)), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(invalid());
+    };
+    if !fraction.bytes().all(|c| c.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let digits = &fraction[..fraction.len().min(9)];
+    let nanoseconds = if digits.is_empty() {
+        0
+    } else {
+        digits.parse::<i64>().unwrap() * 10i64.pow(9 - digits.len() as u32)
+    };
+    Ok(((hours * 60 + minutes) * 60 + seconds) * 1_000_000_000 + nanoseconds)
+}
+
+#[cfg(test)]
+mod test {
+    use super::nanoseconds_since_midnight;
+
+    #[test]
+    fn parse_times() {
+        assert_eq!(0, nanoseconds_since_midnight(b"00:00:00").unwrap());
+        assert_eq!(
+            45_296_123_456_700,
+            nanoseconds_since_midnight(b"12:34:56.1234567").unwrap()
+        );
+        assert_eq!(
+            3_600_500_000_000,
+            nanoseconds_since_midnight(b"01:00:00.5").unwrap()
+        );
+        assert!(nanoseconds_since_midnight(b"12:34").is_err());
+        assert!(nanoseconds_since_midnight(b"12:34:56.x").is_err());
+    }
+}
@@ -1,5 +1,5 @@
-use anyhow::Error;
-use chrono::NaiveDate;
+use anyhow::{anyhow, Error};
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, NaiveDateTime, TimeZone, Timelike, Utc};
 use odbc_api::{
     buffers::{AnyColumnView, BufferDescription, BufferKind},
     sys::Timestamp,
@@ -7,8 +7,7 @@ use odbc_api::{
 use parquet::{
     basic::{LogicalType, Repetition, TimeUnit, Type as PhysicalType},
     column::writer::ColumnWriter,
-    data_type::{DataType, Int64Type},
-    format::{MicroSeconds, MilliSeconds},
+    data_type::{DataType, Int64Type, Int96, Int96Type},
     schema::types::Type,
 };
 
@@ -18,14 +17,18 @@ use super::strategy::ColumnFetchStrategy;
 
 pub struct TimestampToInt {
     repetition: Repetition,
-    precision: u8,
+    unit: TimeUnit,
+    /// Offset of the time zone the timestamps are local to. If set, values are normalized to UTC
+    /// and marked as such. Otherwise they are written as is, without any time zone.
+    utc_offset: Option<FixedOffset>,
 }
 
 impl TimestampToInt {
-    pub fn new(repetition: Repetition, precision: u8) -> Self {
+    pub fn new(repetition: Repetition, unit: TimeUnit, utc_offset: Option<FixedOffset>) -> Self {
         Self {
             repetition,
-            precision,
+            unit,
+            utc_offset,
         }
     }
 }
@@ -33,10 +36,10 @@ impl TimestampToInt {
 impl ColumnFetchStrategy for TimestampToInt {
     fn parquet_type(&self, name: &str) -> Type {
         Type::primitive_type_builder(name, PhysicalType::INT64)
-            .with_logical_type(Some(LogicalType::Timestamp {
-                is_adjusted_to_u_t_c: false,
-                unit: precision_to_time_unit(self.precision),
-            }))
+            .with_logical_type(Some(LogicalType::timestamp(
+                self.utc_offset.is_some(),
+                self.unit,
+            )))
             .with_repetition(self.repetition)
             .build()
             .unwrap()
@@ -55,15 +58,82 @@ impl ColumnFetchStrategy for TimestampToInt {
         column_writer: &mut ColumnWriter,
         column_view: AnyColumnView,
     ) -> Result<(), Error> {
-        write_timestamp_col(parquet_buffer, column_writer, column_view, self.precision)
+        write_timestamp_col(
+            parquet_buffer,
+            column_writer,
+            column_view,
+            self.unit,
+            self.utc_offset,
+        )
     }
 }
 
+/// Writes timestamps using the legacy INT96 physical type, for consumers which do not understand
+/// INT64 timestamps.
+pub struct TimestampToInt96 {
+    repetition: Repetition,
+    /// Offset of the time zone the timestamps are local to. If set, values are normalized to UTC.
+    utc_offset: Option<FixedOffset>,
+}
+
+impl TimestampToInt96 {
+    pub fn new(repetition: Repetition, utc_offset: Option<FixedOffset>) -> Self {
+        Self {
+            repetition,
+            utc_offset,
+        }
+    }
+}
+
+impl ColumnFetchStrategy for TimestampToInt96 {
+    fn parquet_type(&self, name: &str) -> Type {
+        Type::primitive_type_builder(name, PhysicalType::INT96)
+            .with_repetition(self.repetition)
+            .build()
+            .unwrap()
+    }
+
+    fn buffer_description(&self) -> BufferDescription {
+        BufferDescription {
+            kind: BufferKind::Timestamp,
+            nullable: true,
+        }
+    }
+
+    fn copy_odbc_to_parquet(
+        &self,
+        parquet_buffer: &mut ParquetBuffer,
+        column_writer: &mut ColumnWriter,
+        column_view: AnyColumnView,
+    ) -> Result<(), Error> {
+        let from = column_view.as_nullable_slice::<Timestamp>().unwrap();
+        let into = Int96Type::get_column_writer_mut(column_writer).unwrap();
+        let from = from.map(|option| {
+            option.map(|ts| datetime_to_int96(&to_utc(naive_datetime(ts), self.utc_offset)))
+        });
+        parquet_buffer.write_optional(into, from)?;
+        Ok(())
+    }
+}
+
+/// Legacy INT96 representation of a timestamp. The first 8 bytes hold the nanoseconds since
+/// midnight, the last 4 bytes the julian day number. Both little endian.
+pub fn datetime_to_int96(datetime: &NaiveDateTime) -> Int96 {
+    // Julian day number of 0001-01-01 minus one, since `num_days_from_ce` starts counting at one.
+    const JULIAN_DAY_OF_CE: i32 = 1_721_425;
+    let julian_day = (datetime.num_days_from_ce() + JULIAN_DAY_OF_CE) as u32;
+    let nanos =
+        datetime.num_seconds_from_midnight() as u64 * 1_000_000_000 + datetime.nanosecond() as u64;
+    let mut int96 = Int96::new();
+    int96.set_data(nanos as u32, (nanos >> 32) as u32, julian_day);
+    int96
+}
+
 pub fn precision_to_time_unit(precision: u8) -> TimeUnit {
     if precision <= 3 {
-        TimeUnit::MILLIS(MilliSeconds {})
+        TimeUnit::MILLIS
     } else {
-        TimeUnit::MICROS(MicroSeconds {})
+        TimeUnit::MICROS
     }
 }
 
@@ -71,27 +141,122 @@ fn write_timestamp_col(
     pb: &mut ParquetBuffer,
     column_writer: &mut ColumnWriter,
     column_reader: AnyColumnView,
-    precision: u8,
+    unit: TimeUnit,
+    utc_offset: Option<FixedOffset>,
 ) -> Result<(), Error> {
     let from = column_reader.as_nullable_slice::<Timestamp>().unwrap();
     let into = Int64Type::get_column_writer_mut(column_writer).unwrap();
-    let from = from.map(|option| option.map(|ts| timestamp_to_int(ts, precision)));
-    pb.write_optional(into, from)?;
-    Ok(())
+    let from = from.map(|option| {
+        option
+            .map(|ts| datetime_to_int(to_utc(naive_datetime(ts), utc_offset).and_utc(), unit))
+            .transpose()
+    });
+    pb.write_optional_falliable(into, from)
+}
+
+/// Convert a timestamp into the number of `unit`s since the unix epoch.
+pub fn datetime_to_int(datetime: DateTime<Utc>, unit: TimeUnit) -> Result<i64, Error> {
+    match unit {
+        TimeUnit::MILLIS => Ok(datetime.timestamp_millis()),
+        TimeUnit::MICROS => Ok(datetime.timestamp_micros()),
+        TimeUnit::NANOS => datetime.timestamp_nanos_opt().ok_or_else(|| {
+            anyhow!(
+                "Timestamp {datetime} can not be represented with nanosecond precision. Only \
+                timestamps between the years 1677 and 2262 can."
+            )
+        }),
+    }
 }
 
-/// Convert an ODBC timestamp struct into nanoseconds.
-fn timestamp_to_int(ts: &Timestamp, precision: u8) -> i64 {
-    let datetime = NaiveDate::from_ymd(ts.year as i32, ts.month as u32, ts.day as u32)
-        .and_hms_nano(
+/// Interprets `datetime` as local time with the given offset and converts it to UTC. Without an
+/// offset the timestamp is returned unchanged.
+fn to_utc(datetime: NaiveDateTime, utc_offset: Option<FixedOffset>) -> NaiveDateTime {
+    match utc_offset {
+        Some(offset) => offset
+            .from_local_datetime(&datetime)
+            .single()
+            .expect("Local times are unambiguous for fixed offsets")
+            .naive_utc(),
+        None => datetime,
+    }
+}
+
+fn naive_datetime(ts: &Timestamp) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(ts.year as i32, ts.month as u32, ts.day as u32)
+        .unwrap()
+        .and_hms_nano_opt(
             ts.hour as u32,
             ts.minute as u32,
             ts.second as u32,
             ts.fraction as u32,
+        )
+        .unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::NaiveDate;
+
+    use parquet::basic::TimeUnit;
+
+    use super::{datetime_to_int, datetime_to_int96, to_utc};
+
+    #[test]
+    fn int96_representation_of_timestamp() {
+        let datetime = NaiveDate::from_ymd_opt(2022, 9, 7)
+            .unwrap()
+            .and_hms_nano_opt(16, 4, 12, 123_456_789)
+            .unwrap();
+
+        let int96 = datetime_to_int96(&datetime);
+
+        // Julian day of 2022-09-07
+        assert_eq!(2_459_830, int96.data()[2]);
+        assert_eq!(
+            datetime.and_utc().timestamp_nanos_opt().unwrap(),
+            int96.to_nanos()
         );
-    if precision <= 3 {
-        datetime.timestamp_millis()
-    } else {
-        datetime.timestamp_micros()
+    }
+
+    #[test]
+    fn normalize_local_timestamp_to_utc() {
+        let datetime = NaiveDate::from_ymd_opt(2022, 1, 1)
+            .unwrap()
+            .and_hms_opt(1, 30, 0)
+            .unwrap();
+        let berlin = "+01:00".parse().unwrap();
+
+        let utc = to_utc(datetime, Some(berlin));
+
+        assert_eq!("2022-01-01 00:30:00", utc.to_string());
+        assert_eq!(datetime, to_utc(datetime, None));
+    }
+
+    #[test]
+    fn timestamp_in_unit() {
+        let datetime = NaiveDate::from_ymd_opt(2022, 9, 7)
+            .unwrap()
+            .and_hms_nano_opt(16, 4, 12, 123_456_789)
+            .unwrap()
+            .and_utc();
+
+        assert_eq!(
+            1_662_566_652_123,
+            datetime_to_int(datetime, TimeUnit::MILLIS).unwrap()
+        );
+        assert_eq!(
+            1_662_566_652_123_456,
+            datetime_to_int(datetime, TimeUnit::MICROS).unwrap()
+        );
+        assert_eq!(
+            1_662_566_652_123_456_789,
+            datetime_to_int(datetime, TimeUnit::NANOS).unwrap()
+        );
+        let far_future = NaiveDate::from_ymd_opt(9999, 12, 31)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap()
+            .and_utc();
+        assert!(datetime_to_int(far_future, TimeUnit::NANOS).is_err());
     }
 }
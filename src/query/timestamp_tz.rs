@@ -1,20 +1,48 @@
 use anyhow::Error;
-use chrono::{DateTime, Utc};
-use odbc_api::buffers::{AnyColumnView, BufferDescription, BufferKind};
+use chrono::{DateTime, FixedOffset, Utc};
+use odbc_api::{
+    buffers::{AnyColumnView, BufferDescription, BufferKind},
+    sys::SqlDataType,
+    DataType,
+};
 use parquet::{
-    basic::{LogicalType, Repetition, Type as PhysicalType},
+    basic::{ConvertedType, LogicalType, Repetition, TimeUnit, Type as PhysicalType},
     column::writer::{get_typed_column_writer_mut, ColumnWriter},
-    data_type::Int64Type,
+    data_type::{Int32Type, Int64Type, Int96Type},
     schema::types::Type,
 };
 
 use crate::parquet_buffer::ParquetBuffer;
 
-use super::{strategy::ColumnFetchStrategy, timestamp::precision_to_time_unit};
+use super::{
+    strategy::ColumnFetchStrategy,
+    timestamp::{datetime_to_int, datetime_to_int96},
+};
+
+/// `SQL_SS_TIMESTAMPOFFSET`, i.e. `DATETIMEOFFSET` on Microsoft SQL Server.
+pub const SQL_SS_TIMESTAMPOFFSET: SqlDataType = SqlDataType(-155);
+
+/// Precision of the column, if it is a `DATETIMEOFFSET` column of Microsoft SQL Server. These are
+/// fetched with their offset.
+pub fn datetimeoffset_precision(data_type: &DataType, db_name: &str) -> Option<u8> {
+    match data_type {
+        DataType::Other {
+            data_type: SQL_SS_TIMESTAMPOFFSET,
+            decimal_digits,
+            ..
+        } if db_name == "Microsoft SQL Server" => Some((*decimal_digits).try_into().unwrap()),
+        _ => None,
+    }
+}
 
-pub fn timestamp_tz(precision: u8, repetition: Repetition) -> Result<Box<TimestampTz>, Error> {
+pub fn timestamp_tz(
+    precision: u8,
+    unit: TimeUnit,
+    repetition: Repetition,
+    int96: bool,
+) -> Result<Box<TimestampTz>, Error> {
     Ok(Box::new(TimestampTz::with_bytes_length(
-        repetition, precision,
+        repetition, precision, unit, int96,
     )))
 }
 
@@ -22,45 +50,91 @@ pub struct TimestampTz {
     repetition: Repetition,
     // Precision
     precision: u8,
+    /// Unit of the INT64 timestamps written.
+    unit: TimeUnit,
+    /// Write the legacy INT96 representation, rather than INT64.
+    int96: bool,
 }
 
 impl TimestampTz {
-    pub fn with_bytes_length(repetition: Repetition, precision: u8) -> Self {
+    pub fn with_bytes_length(
+        repetition: Repetition,
+        precision: u8,
+        unit: TimeUnit,
+        int96: bool,
+    ) -> Self {
         Self {
             repetition,
             precision,
+            unit,
+            int96,
         }
     }
 }
 
 impl ColumnFetchStrategy for TimestampTz {
     fn parquet_type(&self, name: &str) -> Type {
+        if self.int96 {
+            return Type::primitive_type_builder(name, PhysicalType::INT96)
+                .with_repetition(self.repetition)
+                .build()
+                .unwrap();
+        }
         Type::primitive_type_builder(name, PhysicalType::INT64)
-            .with_logical_type(Some(LogicalType::Timestamp {
-                is_adjusted_to_u_t_c: true,
-                unit: precision_to_time_unit(self.precision),
-            }))
+            .with_logical_type(Some(LogicalType::timestamp(true, self.unit)))
             .with_repetition(self.repetition)
             .build()
             .unwrap()
     }
 
     fn buffer_description(&self) -> BufferDescription {
-        // Text representation looks like e.g. 2022-09-07 16:04:12 +02:00
-        // Text representation looks like e.g. 2022-09-07 16:04:12.123 +02:00
-
-        let max_str_len = 26
-            + if self.precision == 0 {
-                0
-            } else {
-                // Radix character `.` and precision.
-                1 + self.precision as usize
-            };
-        BufferDescription {
-            kind: BufferKind::Text { max_str_len },
-            nullable: true,
+        text_buffer_description(self.precision)
+    }
+
+    fn copy_odbc_to_parquet(
+        &self,
+        parquet_buffer: &mut ParquetBuffer,
+        column_writer: &mut ColumnWriter,
+        column_view: AnyColumnView,
+    ) -> Result<(), Error> {
+        if self.int96 {
+            write_timestamp_tz_int96(parquet_buffer, column_writer, column_view)
+        } else {
+            write_timestamp_tz(parquet_buffer, column_writer, column_view, self.unit)
         }
     }
+}
+
+/// Writes the offsets from UTC of timestamps with time zone in minutes, e.g. `120` for `+02:00`.
+/// Copies from the same fetch buffer as the [`TimestampTz`] column it accompanies, so the offset
+/// is not lost by normalizing the timestamp to UTC.
+pub struct OffsetMinutes {
+    repetition: Repetition,
+    precision: u8,
+}
+
+impl OffsetMinutes {
+    pub fn new(repetition: Repetition, precision: u8) -> Self {
+        Self {
+            repetition,
+            precision,
+        }
+    }
+}
+
+impl ColumnFetchStrategy for OffsetMinutes {
+    fn parquet_type(&self, name: &str) -> Type {
+        // Offsets range from -14:00 to +14:00, so minutes fit into 16 Bit.
+        Type::primitive_type_builder(name, PhysicalType::INT32)
+            .with_converted_type(ConvertedType::INT_16)
+            .with_repetition(self.repetition)
+            .build()
+            .unwrap()
+    }
+
+    fn buffer_description(&self) -> BufferDescription {
+        text_buffer_description(self.precision)
+    }
 
     fn copy_odbc_to_parquet(
         &self,
@@ -68,7 +142,35 @@ impl ColumnFetchStrategy for TimestampTz {
         column_writer: &mut ColumnWriter,
         column_view: AnyColumnView,
     ) -> Result<(), Error> {
-        write_timestamp_tz(parquet_buffer, column_writer, column_view, self.precision)
+        let view = column_view.as_text_view().expect(
+            "Invalid Column view type. This is not supposed to happen. Please open a Bug at \
+            https://github.com/pacman82/odbc2parquet/issues.",
+        );
+        let cw = get_typed_column_writer_mut::<Int32Type>(column_writer);
+        parquet_buffer.write_optional_falliable(
+            cw,
+            view.iter().map(|item| {
+                item.map(|text| Ok(parse_timestamp_tz(text)?.offset().local_minus_utc() / 60))
+                    .transpose()
+            }),
+        )
+    }
+}
+
+fn text_buffer_description(precision: u8) -> BufferDescription {
+    // Text representation looks like e.g. 2022-09-07 16:04:12 +02:00
+    // Text representation looks like e.g. 2022-09-07 16:04:12.123 +02:00
+
+    let max_str_len = 26
+        + if precision == 0 {
+            0
+        } else {
+            // Radix character `.` and precision.
+            1 + precision as usize
+        };
+    BufferDescription {
+        kind: BufferKind::Text { max_str_len },
+        nullable: true,
     }
 }
 
@@ -76,7 +178,7 @@ fn write_timestamp_tz(
     pb: &mut ParquetBuffer,
     column_writer: &mut ColumnWriter,
     column_reader: AnyColumnView,
-    precision: u8,
+    unit: TimeUnit,
 ) -> Result<(), Error> {
     let view = column_reader.as_text_view().expect(
         "Invalid Column view type. This is not supposed to happen. Please open a Bug at \
@@ -85,24 +187,43 @@ fn write_timestamp_tz(
     let cw = get_typed_column_writer_mut::<Int64Type>(column_writer);
     pb.write_optional_falliable(
         cw,
-        view.iter()
-            .map(|item| item.map(|text| to_utc_epoch(text, precision)).transpose()),
+        view.iter().map(|item| {
+            item.map(|text| datetime_to_int(to_utc(text)?, unit))
+                .transpose()
+        }),
+    )?;
+    Ok(())
+}
+
+fn write_timestamp_tz_int96(
+    pb: &mut ParquetBuffer,
+    column_writer: &mut ColumnWriter,
+    column_reader: AnyColumnView,
+) -> Result<(), Error> {
+    let view = column_reader.as_text_view().expect(
+        "Invalid Column view type. This is not supposed to happen. Please open a Bug at \
+        https://github.com/pacman82/odbc2parquet/issues.",
+    );
+    let cw = get_typed_column_writer_mut::<Int96Type>(column_writer);
+    pb.write_optional_falliable(
+        cw,
+        view.iter().map(|item| {
+            item.map(|text| Ok(datetime_to_int96(&to_utc(text)?.naive_utc())))
+                .transpose()
+        }),
     )?;
     Ok(())
 }
 
-fn to_utc_epoch(bytes: &[u8], precision: u8) -> Result<i64, Error> {
+fn to_utc(bytes: &[u8]) -> Result<DateTime<Utc>, Error> {
+    Ok(parse_timestamp_tz(bytes)?.with_timezone(&Utc))
+}
+
+fn parse_timestamp_tz(bytes: &[u8]) -> Result<DateTime<FixedOffset>, Error> {
     // Text representation looks like e.g. 2022-09-07 16:04:12 +02:00
     let utf8 = String::from_utf8_lossy(bytes);
 
     // Parse to datetime
     let date_time = DateTime::parse_from_str(&utf8, "%Y-%m-%d %H:%M:%S%.9f %:z")?;
-    // let utc = date_time.naive_utc();
-    let utc = date_time.with_timezone(&Utc);
-    let integer = if precision <= 3 {
-        utc.timestamp_millis()
-    } else {
-        utc.timestamp_micros()
-    };
-    Ok(integer)
+    Ok(date_time)
 }
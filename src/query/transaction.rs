@@ -0,0 +1,55 @@
+//! Wraps the export in an explicit transaction, so all batches and all executions of the query see
+//! the same consistent state of the database. See `--isolation-level`.
+
+use anyhow::{bail, Context, Error};
+use log::{info, warn};
+use odbc_api::Connection;
+
+use crate::enum_args::IsolationLevel;
+
+/// Invokes `export` within a transaction with the given isolation level. Without an isolation
+/// level, `export` is invoked in auto-commit mode, i.e. each statement is a transaction of its own.
+pub fn in_transaction(
+    conn: &Connection<'_>,
+    db_name: &str,
+    isolation_level: Option<IsolationLevel>,
+    export: impl FnOnce() -> Result<(), Error>,
+) -> Result<(), Error> {
+    let Some(isolation_level) = isolation_level else {
+        return export();
+    };
+    if isolation_level == IsolationLevel::Snapshot && db_name != "Microsoft SQL Server" {
+        bail!("Snapshot isolation is only supported for Microsoft SQL Server.")
+    }
+    conn.set_autocommit(false)?;
+    let result = begin(conn, isolation_level).and_then(|()| export());
+    // The export only reads, so there is nothing to be committed. Ending the transaction releases
+    // the locks held, or the snapshot.
+    let end = match &result {
+        Ok(()) => conn.commit(),
+        Err(_) => conn.rollback(),
+    };
+    let end = end.and_then(|()| conn.set_autocommit(true));
+    match (result, end) {
+        (Ok(()), end) => end.context("Failed to end the transaction."),
+        (Err(error), Err(end_error)) => {
+            warn!("Failed to end the transaction: {end_error}");
+            Err(error)
+        }
+        (Err(error), Ok(())) => Err(error),
+    }
+}
+
+/// Sets the isolation level of the transaction started by the next statement. Executed first, so
+/// it is accepted by data sources (like PostgreSQL or Oracle) which only allow it at the beginning
+/// of a transaction.
+fn begin(conn: &Connection<'_>, isolation_level: IsolationLevel) -> Result<(), Error> {
+    let statement = format!(
+        "SET TRANSACTION ISOLATION LEVEL {}",
+        isolation_level.as_sql()
+    );
+    info!("Starting transaction: {statement}");
+    conn.execute(&statement, ())
+        .with_context(|| format!("Failed to set isolation level with '{statement}'."))?;
+    Ok(())
+}
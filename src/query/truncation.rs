@@ -0,0 +1,283 @@
+//! Handles values the driver truncated while fetching, because they are longer than the fetch buffer
+//! of their column. See `--on-truncation`.
+
+use std::fmt;
+
+use anyhow::{bail, Error};
+use log::warn;
+use odbc_api::{
+    buffers::{AnyColumnView, BufferKind, ColumnarAnyBuffer},
+    handles::{Diagnostics, State},
+};
+use parquet::basic::Type as PhysicalType;
+
+use crate::enum_args::OnTruncation;
+
+use super::ColumnInfo;
+
+/// Each time the result set is fetched again, the buffers of the truncated columns grow by this
+/// factor.
+const GROWTH_FACTOR: usize = 4;
+
+/// `true` if the diagnostics of the last function called on the statement report a truncated value
+/// (SQLSTATE `01004`).
+pub fn has_truncated_values(statement: &impl Diagnostics) -> bool {
+    let mut rec_number = 1;
+    while let Some(record) = statement.diagnostic_record(rec_number, &mut []) {
+        if record.state == State::STRING_DATA_RIGHT_TRUNCATION {
+            return true;
+        }
+        if rec_number == i16::MAX {
+            break;
+        }
+        rec_number += 1;
+    }
+    false
+}
+
+/// A column of the fetch buffer, whose values may be truncated.
+struct TruncatableColumn {
+    buffer_index: usize,
+    name: String,
+    /// Length of the buffer, in `u16` for wide text and in bytes otherwise.
+    length: usize,
+    /// Length the buffer may grow to, given `--max-column-size` and `--max-value-size`.
+    max_length: usize,
+    /// `true` if the buffer is limited by `--max-value-size`. Values filling it are handled by
+    /// `--oversized` instead.
+    oversized: bool,
+    /// `true` once truncated values of this column have been warned about.
+    warned: bool,
+}
+
+/// Inspects the row sets the driver reported truncated values for, and acts on them as told by
+/// `--on-truncation`.
+pub struct TruncationCheck {
+    on_truncation: OnTruncation,
+    columns: Vec<TruncatableColumn>,
+}
+
+impl TruncationCheck {
+    /// `max_column_size` and `max_value_size` are the values of `--max-column-size` and
+    /// `--max-value-size` in bytes. Buffers are not grown beyond either.
+    pub fn new(
+        strategies: &[ColumnInfo],
+        on_truncation: OnTruncation,
+        max_column_size: Option<usize>,
+        max_value_size: Option<usize>,
+    ) -> Self {
+        let max_size = max_column_size.unwrap_or(usize::MAX);
+        let max_value_size = max_value_size.unwrap_or(usize::MAX);
+        let columns = strategies
+            .iter()
+            .enumerate()
+            .filter_map(|(buffer_index, (_, name, strategy))| {
+                let (length, is_wide) = match strategy.buffer_description().kind {
+                    BufferKind::Text { max_str_len } => (max_str_len, false),
+                    BufferKind::WText { max_str_len } => (max_str_len, true),
+                    BufferKind::Binary { length } => (length, false),
+                    _ => return None,
+                };
+                let in_characters = |size: usize| if is_wide { size / 2 } else { size };
+                let value_limit = in_characters(max_value_size).max(1);
+                let max_length = in_characters(max_size).min(value_limit);
+                // Values of fixed length fill their buffer without being truncated.
+                let is_fixed_length = strategy.parquet_type(name).get_physical_type()
+                    == PhysicalType::FIXED_LEN_BYTE_ARRAY;
+                let oversized = length >= value_limit;
+                (!is_fixed_length).then(|| TruncatableColumn {
+                    buffer_index,
+                    name: name.clone(),
+                    length,
+                    max_length: max_length.max(length),
+                    oversized,
+                    warned: false,
+                })
+            })
+            .collect();
+        Self {
+            on_truncation,
+            columns,
+        }
+    }
+
+    /// Invoked for each row set the driver reported truncated values for. The diagnostics do not
+    /// tell the column, so every column with a value filling its buffer is considered truncated.
+    pub fn check(&mut self, buffer: &ColumnarAnyBuffer, num_batch: usize) -> Result<(), Error> {
+        let mut truncated: Vec<_> = self
+            .columns
+            .iter_mut()
+            .filter(|column| fills_buffer(buffer.column(column.buffer_index), column.length))
+            .collect();
+        if truncated.is_empty() {
+            if self.on_truncation == OnTruncation::Warn {
+                warn!("The driver reported truncated values in batch {num_batch}.");
+                return Ok(());
+            }
+            bail!(
+                "The driver reported truncated values in batch {num_batch}, but none of the text \
+                or binary columns has a value filling its buffer."
+            )
+        }
+        truncated.retain(|column| !column.oversized);
+        if truncated.is_empty() {
+            return Ok(());
+        }
+        let names = truncated
+            .iter()
+            .map(|column| format!("'{}'", column.name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        match self.on_truncation {
+            OnTruncation::Warn => {
+                for column in truncated.iter_mut().filter(|column| !column.warned) {
+                    warn!(
+                        "Values of column '{}' are longer than its buffer of length {} and have \
+                        been truncated. Use `--on-truncation refetch` to fetch them completely.",
+                        column.name, column.length
+                    );
+                    column.warned = true;
+                }
+                Ok(())
+            }
+            OnTruncation::Error => bail!(
+                "Values of column(s) {names} in batch {num_batch} are longer than their buffers. \
+                Use `--on-truncation refetch` to fetch them with larger buffers, or \
+                `--on-truncation warn` to write them truncated."
+            ),
+            OnTruncation::Refetch => {
+                // Columns limited by `--max-column-size` are written truncated, with a warning of
+                // their own.
+                let columns: Vec<_> = truncated
+                    .iter()
+                    .filter(|column| column.length < column.max_length)
+                    .map(|column| {
+                        let length = column.length.saturating_mul(GROWTH_FACTOR).max(1);
+                        (column.name.clone(), length.min(column.max_length))
+                    })
+                    .collect();
+                if columns.is_empty() {
+                    return Ok(());
+                }
+                Err(Truncated { columns }.into())
+            }
+        }
+    }
+}
+
+/// `true` if any value of the column is as long as its buffer.
+fn fills_buffer(view: AnyColumnView, length: usize) -> bool {
+    match view {
+        AnyColumnView::Text(view) => view.iter().flatten().any(|value| value.len() >= length),
+        AnyColumnView::WText(view) => view.iter().flatten().any(|value| value.len() >= length),
+        AnyColumnView::Binary(view) => view.iter().flatten().any(|value| value.len() >= length),
+        _ => false,
+    }
+}
+
+/// Fails an export with `--on-truncation refetch`, so [`with_refetches`] can fetch the result set
+/// again, with larger buffers for the truncated columns.
+#[derive(Debug)]
+pub struct Truncated {
+    /// Name of each truncated column, together with the length its buffer grows to.
+    columns: Vec<(String, usize)>,
+}
+
+impl fmt::Display for Truncated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<_> = self
+            .columns
+            .iter()
+            .map(|(name, _)| format!("'{name}'"))
+            .collect();
+        write!(
+            f,
+            "Values of column(s) {} have been truncated.",
+            names.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for Truncated {}
+
+/// Invokes `export` and repeats it as long as it fails, because values have been truncated. Each
+/// time, the buffers of the truncated columns are larger. `export` is passed the length of each
+/// grown column, and whether it fetches the result set again, so it may resume from the checkpoint.
+/// `column_lengths` holds the columns whose buffers are sized up front, e.g. from a sample.
+pub fn with_refetches(
+    mut column_lengths: Vec<(String, usize)>,
+    mut export: impl FnMut(&[(String, usize)], bool) -> Result<(), Error>,
+) -> Result<(), Error> {
+    let mut refetch = false;
+    loop {
+        let error = match export(&column_lengths, refetch) {
+            Ok(()) => return Ok(()),
+            Err(error) => error,
+        };
+        let Some(truncated) = error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<Truncated>())
+        else {
+            return Err(error);
+        };
+        for (name, length) in &truncated.columns {
+            // Guards against fetching again and again, should the buffer not grow.
+            if column_lengths
+                .iter()
+                .any(|(grown, grown_length)| grown == name && grown_length >= length)
+            {
+                bail!("Values of column '{name}' are still truncated with a buffer of length {length}.")
+            }
+            warn!(
+                "Values of column '{name}' have been truncated. Fetching the result set again, \
+                with a buffer of length {length}."
+            );
+            match column_lengths.iter_mut().find(|(grown, _)| grown == name) {
+                Some((_, grown_length)) => *grown_length = *length,
+                None => column_lengths.push((name.clone(), *length)),
+            }
+        }
+        refetch = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Error;
+
+    use super::{with_refetches, Truncated};
+
+    #[test]
+    fn grow_columns_until_nothing_is_truncated() {
+        let mut attempts = Vec::new();
+        let sampled = vec![("b".to_owned(), 2)];
+        with_refetches(sampled, |column_lengths, refetch| {
+            attempts.push((column_lengths.to_vec(), refetch));
+            let columns = match attempts.len() {
+                1 => vec![("a".to_owned(), 40), ("b".to_owned(), 8)],
+                2 => vec![("a".to_owned(), 160)],
+                _ => return Ok(()),
+            };
+            Err(Error::from(Truncated { columns }).context("Failed to write batch."))
+        })
+        .unwrap();
+
+        assert_eq!(3, attempts.len());
+        assert_eq!((vec![("b".to_owned(), 2)], false), attempts[0]);
+        assert_eq!(
+            (vec![("b".to_owned(), 8), ("a".to_owned(), 160)], true),
+            attempts[2]
+        );
+    }
+
+    #[test]
+    fn other_errors_are_not_refetched() {
+        let mut attempts = 0;
+        let result = with_refetches(Vec::new(), |_, _| {
+            attempts += 1;
+            Err(Error::msg("Output already exists."))
+        });
+        assert!(result.is_err());
+        assert_eq!(1, attempts);
+    }
+}
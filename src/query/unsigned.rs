@@ -0,0 +1,126 @@
+//! Unsigned integers, e.g. `INT UNSIGNED` in MySQL. Parquet stores `UINT_32` and `UINT_64` in the
+//! signed physical types of the same width, so values above the signed range are written as their
+//! two's complement bit pattern and readers interpret them as unsigned again.
+
+use anyhow::{anyhow, Error};
+use odbc_api::buffers::{AnyColumnView, BufferDescription, BufferKind};
+use parquet::{
+    basic::{ConvertedType, Repetition, Type as PhysicalType},
+    column::writer::ColumnWriter,
+    data_type::{DataType, Int32Type, Int64Type},
+    schema::types::Type,
+};
+
+use crate::parquet_buffer::ParquetBuffer;
+
+use super::{identical::fetch_identical_with_converted_type, strategy::ColumnFetchStrategy};
+
+/// Strategy for an unsigned integer column with the given width in bits. Integers of up to 16 Bit
+/// fit into a signed 32 Bit integer and are fetched as such. Wider ones are fetched as text, since
+/// ODBC buffers for unsigned integers are not supported.
+pub fn unsigned_fetch_strategy(is_optional: bool, bits: u8) -> Box<dyn ColumnFetchStrategy> {
+    let repetition = if is_optional {
+        Repetition::OPTIONAL
+    } else {
+        Repetition::REQUIRED
+    };
+    match bits {
+        8 => fetch_identical_with_converted_type::<Int32Type>(is_optional, ConvertedType::UINT_8),
+        16 => fetch_identical_with_converted_type::<Int32Type>(is_optional, ConvertedType::UINT_16),
+        32 => Box::new(UnsignedFromText {
+            repetition,
+            is_64_bit: false,
+        }),
+        64 => Box::new(UnsignedFromText {
+            repetition,
+            is_64_bit: true,
+        }),
+        _ => panic!("Unsigned integers must have a width of 8, 16, 32 or 64 Bits"),
+    }
+}
+
+/// Fetches 32 or 64 Bit unsigned integers as text.
+struct UnsignedFromText {
+    repetition: Repetition,
+    /// Write `UINT_64` into `INT64`, rather than `UINT_32` into `INT32`.
+    is_64_bit: bool,
+}
+
+impl ColumnFetchStrategy for UnsignedFromText {
+    fn parquet_type(&self, name: &str) -> Type {
+        let (physical_type, converted_type) = if self.is_64_bit {
+            (PhysicalType::INT64, ConvertedType::UINT_64)
+        } else {
+            (PhysicalType::INT32, ConvertedType::UINT_32)
+        };
+        Type::primitive_type_builder(name, physical_type)
+            .with_converted_type(converted_type)
+            .with_repetition(self.repetition)
+            .build()
+            .unwrap()
+    }
+
+    fn buffer_description(&self) -> BufferDescription {
+        BufferDescription {
+            kind: BufferKind::Text {
+                // Digits of `u64::MAX` or `u32::MAX`
+                max_str_len: if self.is_64_bit { 20 } else { 10 },
+            },
+            nullable: self.repetition == Repetition::OPTIONAL,
+        }
+    }
+
+    fn copy_odbc_to_parquet(
+        &self,
+        parquet_buffer: &mut ParquetBuffer,
+        column_writer: &mut ColumnWriter,
+        column_view: AnyColumnView,
+    ) -> Result<(), Error> {
+        let view = column_view.as_text_view().expect(
+            "Invalid Column view type. This is not supposed to happen. Please open a Bug at \
+            https://github.com/pacman82/odbc2parquet/issues.",
+        );
+        let values = view.iter().map(|text| text.map(parse_unsigned).transpose());
+        if self.is_64_bit {
+            let column_writer = Int64Type::get_column_writer_mut(column_writer).unwrap();
+            parquet_buffer.write_optional_falliable(
+                column_writer,
+                values.map(|value| Ok(value?.map(|value| value as i64))),
+            )
+        } else {
+            let column_writer = Int32Type::get_column_writer_mut(column_writer).unwrap();
+            parquet_buffer.write_optional_falliable(
+                column_writer,
+                values.map(|value| {
+                    value?
+                        .map(|value| {
+                            let value = u32::try_from(value).map_err(|_| {
+                                anyhow!("Unsigned 32 Bit integer out of range: {value}")
+                            })?;
+                            Ok(value as i32)
+                        })
+                        .transpose()
+                }),
+            )
+        }
+    }
+}
+
+fn parse_unsigned(text: &[u8]) -> Result<u64, Error> {
+    let text = String::from_utf8_lossy(text);
+    text.trim()
+        .parse()
+        .map_err(|_| anyhow!("Invalid unsigned integer: '{text}'"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_unsigned;
+
+    #[test]
+    fn parse_unsigned_integers() {
+        assert_eq!(u64::MAX, parse_unsigned(b"18446744073709551615").unwrap());
+        assert_eq!(-1, parse_unsigned(b"4294967295").unwrap() as u32 as i32);
+        assert!(parse_unsigned(b"-1").is_err());
+    }
+}
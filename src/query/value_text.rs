@@ -0,0 +1,85 @@
+//! Text representation of fetched values, e.g. used to name partitions or to write CSV.
+
+use odbc_api::buffers::AnyColumnView;
+
+/// Text representation of the values in a column. `None` represents NULL.
+pub fn column_as_text(view: AnyColumnView) -> Vec<Option<String>> {
+    match view {
+        AnyColumnView::Text(view) => view
+            .iter()
+            .map(|value| value.map(|bytes| String::from_utf8_lossy(bytes).into_owned()))
+            .collect(),
+        AnyColumnView::WText(view) => view
+            .iter()
+            .map(|value| value.map(|chars| String::from_utf16_lossy(chars.as_slice())))
+            .collect(),
+        AnyColumnView::Binary(view) => view
+            .iter()
+            .map(|value| value.map(|bytes| bytes.iter().map(|b| format!("{b:02x}")).collect()))
+            .collect(),
+        AnyColumnView::Date(values) => values.iter().map(|d| Some(format_date(d))).collect(),
+        AnyColumnView::NullableDate(values) => values.map(|d| d.map(format_date)).collect(),
+        AnyColumnView::Time(values) => values.iter().map(|t| Some(format_time(t))).collect(),
+        AnyColumnView::NullableTime(values) => values.map(|t| t.map(format_time)).collect(),
+        AnyColumnView::Timestamp(values) => {
+            values.iter().map(|ts| Some(format_timestamp(ts))).collect()
+        }
+        AnyColumnView::NullableTimestamp(values) => {
+            values.map(|ts| ts.map(format_timestamp)).collect()
+        }
+        AnyColumnView::Bit(values) => values
+            .iter()
+            .map(|bit| Some(bit.as_bool().to_string()))
+            .collect(),
+        AnyColumnView::NullableBit(values) => values
+            .map(|bit| bit.map(|bit| bit.as_bool().to_string()))
+            .collect(),
+        AnyColumnView::F64(values) => to_strings(values),
+        AnyColumnView::F32(values) => to_strings(values),
+        AnyColumnView::I8(values) => to_strings(values),
+        AnyColumnView::I16(values) => to_strings(values),
+        AnyColumnView::I32(values) => to_strings(values),
+        AnyColumnView::I64(values) => to_strings(values),
+        AnyColumnView::U8(values) => to_strings(values),
+        AnyColumnView::NullableF64(values) => nullable_to_strings(values),
+        AnyColumnView::NullableF32(values) => nullable_to_strings(values),
+        AnyColumnView::NullableI8(values) => nullable_to_strings(values),
+        AnyColumnView::NullableI16(values) => nullable_to_strings(values),
+        AnyColumnView::NullableI32(values) => nullable_to_strings(values),
+        AnyColumnView::NullableI64(values) => nullable_to_strings(values),
+        AnyColumnView::NullableU8(values) => nullable_to_strings(values),
+    }
+}
+
+fn to_strings<T: ToString>(values: &[T]) -> Vec<Option<String>> {
+    values.iter().map(|value| Some(value.to_string())).collect()
+}
+
+fn nullable_to_strings<'a, T: ToString + 'a>(
+    values: impl Iterator<Item = Option<&'a T>>,
+) -> Vec<Option<String>> {
+    values.map(|value| value.map(T::to_string)).collect()
+}
+
+fn format_date(date: &odbc_api::sys::Date) -> String {
+    format!("{:04}-{:02}-{:02}", date.year, date.month, date.day)
+}
+
+fn format_time(time: &odbc_api::sys::Time) -> String {
+    format!("{:02}:{:02}:{:02}", time.hour, time.minute, time.second)
+}
+
+/// Fractional seconds are only rendered, if they are not zero. E.g. `2022-10-15 14:30:00` or
+/// `2022-10-15 14:30:00.123`.
+fn format_timestamp(ts: &odbc_api::sys::Timestamp) -> String {
+    let mut text = format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        ts.year, ts.month, ts.day, ts.hour, ts.minute, ts.second
+    );
+    if ts.fraction != 0 {
+        let fraction = format!("{:09}", ts.fraction);
+        text.push('.');
+        text.push_str(fraction.trim_end_matches('0'));
+    }
+    text
+}
@@ -0,0 +1,185 @@
+//! Geometries as Well Known Binary (WKB). Drivers report spatial columns without a length, so we
+//! fetch them into binary buffers of a configurable maximum length. Spatial types like `geometry`
+//! in Microsoft SQL Server must be converted to WKB by the database, which we can do by wrapping
+//! the query.
+
+use anyhow::{bail, Error};
+use odbc_api::{
+    buffers::{AnyColumnView, BufferDescription, BufferKind},
+    sys::SqlDataType,
+};
+use parquet::{
+    basic::{Repetition, Type as PhysicalType},
+    column::writer::ColumnWriter,
+    data_type::{ByteArray, ByteArrayType, DataType},
+    schema::types::Type,
+};
+
+use crate::parquet_buffer::ParquetBuffer;
+
+use super::strategy::ColumnFetchStrategy;
+
+/// `SQL_SS_UDT` reported by Microsoft SQL Server drivers for CLR types, like `geometry`,
+/// `geography` or `hierarchyid`.
+pub const SQL_SS_UDT: SqlDataType = SqlDataType(-151);
+
+/// Rewrites a name or expression into the SQL dialect of the database.
+type SqlRewrite = fn(&str) -> String;
+
+/// Wraps `query`, so the database converts the `geometry_columns` to WKB. `column_names` are the
+/// names of all columns in the result set of `query`.
+pub fn query_with_geometries_as_wkb(
+    query: &str,
+    column_names: &[String],
+    geometry_columns: &[String],
+    db_name: &str,
+) -> Result<String, Error> {
+    for name in geometry_columns {
+        if !column_names.contains(name) {
+            bail!("Geometry column '{name}' is not part of the result set.")
+        }
+    }
+    let (quote, as_wkb): (SqlRewrite, SqlRewrite) = match db_name {
+        "Microsoft SQL Server" => (
+            |name| format!("[{}]", name.replace(']', "]]")),
+            |column| format!("{column}.STAsBinary()"),
+        ),
+        "PostgreSQL" => (
+            |name| format!("\"{}\"", name.replace('"', "\"\"")),
+            |column| format!("ST_AsBinary({column})"),
+        ),
+        _ => bail!(
+            "Converting geometries to WKB is not supported for {db_name}. Select them as WKB in \
+            the query instead."
+        ),
+    };
+    let select_list: Vec<String> = column_names
+        .iter()
+        .map(|name| {
+            let column = format!("q.{}", quote(name));
+            if geometry_columns.contains(name) {
+                format!("{} AS {}", as_wkb(&column), quote(name))
+            } else {
+                column
+            }
+        })
+        .collect();
+    let query = query.trim_end().trim_end_matches(';');
+    Ok(format!(
+        "SELECT {} FROM ({query}) AS q",
+        select_list.join(", ")
+    ))
+}
+
+/// Writes geometries as WKB into byte arrays without annotation. Fails rather than writing
+/// truncated geometries.
+pub struct Wkb {
+    repetition: Repetition,
+    /// Maximum length of a geometry in bytes.
+    max_len: usize,
+}
+
+impl Wkb {
+    pub fn new(repetition: Repetition, max_len: usize) -> Self {
+        Self {
+            repetition,
+            max_len,
+        }
+    }
+}
+
+impl ColumnFetchStrategy for Wkb {
+    fn parquet_type(&self, name: &str) -> Type {
+        Type::primitive_type_builder(name, PhysicalType::BYTE_ARRAY)
+            .with_repetition(self.repetition)
+            .build()
+            .unwrap()
+    }
+
+    fn buffer_description(&self) -> BufferDescription {
+        BufferDescription {
+            kind: BufferKind::Binary {
+                length: self.max_len,
+            },
+            nullable: true,
+        }
+    }
+
+    fn copy_odbc_to_parquet(
+        &self,
+        parquet_buffer: &mut ParquetBuffer,
+        column_writer: &mut ColumnWriter,
+        column_view: AnyColumnView,
+    ) -> Result<(), Error> {
+        let AnyColumnView::Binary(view) = column_view else {
+            panic!(
+                "Invalid Column view type. This is not supposed to happen. Please open a Bug at \
+                https://github.com/pacman82/odbc2parquet/issues."
+            )
+        };
+        let column_writer = ByteArrayType::get_column_writer_mut(column_writer).unwrap();
+        parquet_buffer.write_optional_falliable(
+            column_writer,
+            view.iter().map(|item| {
+                item.map(|bytes| {
+                    if bytes.len() >= self.max_len {
+                        bail!(
+                            "Found a geometry with a length of at least {} bytes, which is likely \
+                            truncated. Raise `--wkb-max-length` to fetch larger geometries.",
+                            self.max_len
+                        )
+                    }
+                    let byte_array: ByteArray = bytes.to_owned().into();
+                    Ok(byte_array)
+                })
+                .transpose()
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::query_with_geometries_as_wkb;
+
+    #[test]
+    fn wrap_query_converting_geometries() {
+        let columns = ["id".to_owned(), "geom".to_owned()];
+        let geometries = ["geom".to_owned()];
+
+        assert_eq!(
+            "SELECT q.[id], q.[geom].STAsBinary() AS [geom] FROM (SELECT * FROM Stores) AS q",
+            query_with_geometries_as_wkb(
+                "SELECT * FROM Stores;",
+                &columns,
+                &geometries,
+                "Microsoft SQL Server"
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            "SELECT q.\"id\", ST_AsBinary(q.\"geom\") AS \"geom\" FROM (SELECT * FROM stores) AS q",
+            query_with_geometries_as_wkb(
+                "SELECT * FROM stores",
+                &columns,
+                &geometries,
+                "PostgreSQL"
+            )
+            .unwrap()
+        );
+        assert!(query_with_geometries_as_wkb(
+            "SELECT * FROM stores",
+            &columns,
+            &geometries,
+            "SQLite"
+        )
+        .is_err());
+        assert!(query_with_geometries_as_wkb(
+            "SELECT id FROM stores",
+            &columns[..1],
+            &geometries,
+            "PostgreSQL"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,194 @@
+use std::io::Write;
+
+use anyhow::{bail, Error};
+use log::info;
+use odbc_api::{buffers::AnyColumnView, Cursor};
+use parquet::basic::ConvertedType;
+use rust_xlsxwriter::{Format, Workbook, Worksheet};
+
+use crate::destination::Destination;
+
+use super::{
+    batch_size_limit::BatchSizeLimit, fetch_buffer, give_hint_about_flag_for_oracle_users,
+    make_schema, strategy::MappingOptions, value_text::column_as_text,
+};
+
+/// Maximum number of rows in an Excel worksheet, without the header row.
+pub const XLSX_MAX_DATA_ROWS: usize = 1_048_575;
+
+/// Writes the result set into the first worksheet of an Excel workbook. The first row contains the
+/// column names. The export fails, if the result set has more than `max_rows` rows.
+///
+/// The whole workbook is kept in memory until it is written, so this is intended for small result
+/// sets only.
+pub fn cursor_to_xlsx(
+    mut cursor: impl Cursor,
+    output: Destination,
+    batch_size: BatchSizeLimit,
+    mapping_options: MappingOptions,
+    max_rows: usize,
+) -> Result<(), Error> {
+    let strategies = make_schema(&mut cursor, mapping_options, false)?;
+
+    if strategies.is_empty() {
+        bail!("Resulting worksheet would not have any columns!")
+    }
+
+    // Decimals are fetched as text, but should be numbers in the spreadsheet.
+    let is_decimal: Vec<_> = strategies
+        .iter()
+        .map(|(_index, name, strategy)| {
+            strategy
+                .parquet_type(name)
+                .get_basic_info()
+                .converted_type()
+                == ConvertedType::DECIMAL
+        })
+        .collect();
+
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    let header_format = Format::new().set_bold();
+    for (col_index, (_index, name, _strategy)) in strategies.iter().enumerate() {
+        worksheet.write_string_with_format(0, col_index as u16, name, &header_format)?;
+    }
+    worksheet.set_freeze_panes(1, 0)?;
+
+    let (mut odbc_buffer, _batch_size_row) = fetch_buffer(&strategies, batch_size, 0)?;
+    let mut row_set_cursor = cursor.bind_buffer(&mut odbc_buffer)?;
+
+    let mut num_batch = 0;
+    let mut num_rows_total = 0;
+    while let Some(buffer) = row_set_cursor
+        .fetch()
+        .map_err(give_hint_about_flag_for_oracle_users)?
+    {
+        num_batch += 1;
+        let num_rows = buffer.num_rows();
+        info!("Fetched batch {} with {} rows.", num_batch, num_rows);
+        if num_rows_total + num_rows > max_rows {
+            bail!(
+                "The result set has more than {max_rows} rows, which is the limit for xlsx output. \
+                Restrict the query, raise the limit with `--xlsx-max-rows` (at most \
+                {XLSX_MAX_DATA_ROWS}), or export into a format without limit, like parquet or csv."
+            )
+        }
+        for (col_index, &is_decimal) in is_decimal.iter().enumerate() {
+            write_column(
+                worksheet,
+                buffer.column(col_index),
+                num_rows_total + 1,
+                col_index as u16,
+                is_decimal,
+            )?;
+        }
+        num_rows_total += num_rows;
+    }
+
+    let bytes = workbook.save_to_buffer()?;
+    let mut writer = output.create()?;
+    writer.write_all(&bytes)?;
+    writer.finish()?;
+
+    Ok(())
+}
+
+/// Writes the values of a fetched column into the worksheet, starting at `first_row`. Numbers and
+/// booleans are written as such, everything else as text. NULL leaves the cell empty.
+fn write_column(
+    worksheet: &mut Worksheet,
+    view: AnyColumnView,
+    first_row: usize,
+    col: u16,
+    is_decimal: bool,
+) -> Result<(), Error> {
+    fn numbers<T: Copy + Into<f64>>(
+        worksheet: &mut Worksheet,
+        values: impl Iterator<Item = Option<T>>,
+        first_row: usize,
+        col: u16,
+    ) -> Result<(), Error> {
+        for (row, value) in (first_row..).zip(values) {
+            if let Some(value) = value {
+                worksheet.write_number(row as u32, col, value)?;
+            }
+        }
+        Ok(())
+    }
+
+    match view {
+        AnyColumnView::I8(values) => {
+            numbers(worksheet, values.iter().copied().map(Some), first_row, col)
+        }
+        AnyColumnView::I16(values) => {
+            numbers(worksheet, values.iter().copied().map(Some), first_row, col)
+        }
+        AnyColumnView::I32(values) => {
+            numbers(worksheet, values.iter().copied().map(Some), first_row, col)
+        }
+        // Excel stores all numbers as double precision floating points.
+        AnyColumnView::I64(values) => numbers(
+            worksheet,
+            values.iter().map(|&v| Some(v as f64)),
+            first_row,
+            col,
+        ),
+        AnyColumnView::U8(values) => {
+            numbers(worksheet, values.iter().copied().map(Some), first_row, col)
+        }
+        AnyColumnView::F32(values) => {
+            numbers(worksheet, values.iter().copied().map(Some), first_row, col)
+        }
+        AnyColumnView::F64(values) => {
+            numbers(worksheet, values.iter().copied().map(Some), first_row, col)
+        }
+        AnyColumnView::NullableI8(values) => {
+            numbers(worksheet, values.map(Option::<&_>::copied), first_row, col)
+        }
+        AnyColumnView::NullableI16(values) => {
+            numbers(worksheet, values.map(Option::<&_>::copied), first_row, col)
+        }
+        AnyColumnView::NullableI32(values) => {
+            numbers(worksheet, values.map(Option::<&_>::copied), first_row, col)
+        }
+        AnyColumnView::NullableI64(values) => numbers(
+            worksheet,
+            values.map(|v| v.map(|&v| v as f64)),
+            first_row,
+            col,
+        ),
+        AnyColumnView::NullableU8(values) => {
+            numbers(worksheet, values.map(Option::<&_>::copied), first_row, col)
+        }
+        AnyColumnView::NullableF32(values) => {
+            numbers(worksheet, values.map(Option::<&_>::copied), first_row, col)
+        }
+        AnyColumnView::NullableF64(values) => {
+            numbers(worksheet, values.map(Option::<&_>::copied), first_row, col)
+        }
+        AnyColumnView::Bit(values) => {
+            for (row, bit) in (first_row..).zip(values) {
+                worksheet.write_boolean(row as u32, col, bit.as_bool())?;
+            }
+            Ok(())
+        }
+        AnyColumnView::NullableBit(values) => {
+            for (row, bit) in (first_row..).zip(values) {
+                if let Some(bit) = bit {
+                    worksheet.write_boolean(row as u32, col, bit.as_bool())?;
+                }
+            }
+            Ok(())
+        }
+        other => {
+            for (row, text) in (first_row..).zip(column_as_text(other)) {
+                let Some(text) = text else { continue };
+                match text.parse::<f64>() {
+                    Ok(number) if is_decimal => worksheet.write_number(row as u32, col, number)?,
+                    _ => worksheet.write_string(row as u32, col, text)?,
+                };
+            }
+            Ok(())
+        }
+    }
+}
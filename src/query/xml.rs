@@ -0,0 +1,155 @@
+//! XML columns, like `XML` in Microsoft SQL Server or IBM DB2. Drivers report these with a
+//! vendor specific type and without a length, so we fetch them as text of a configurable maximum
+//! length.
+
+use anyhow::{bail, Error};
+use odbc_api::{
+    buffers::{AnyColumnView, BufferDescription, BufferKind},
+    sys::SqlDataType,
+    DataType,
+};
+use parquet::{
+    basic::{ConvertedType, Repetition, Type as PhysicalType},
+    column::writer::ColumnWriter,
+    data_type::{ByteArray, ByteArrayType, DataType as _},
+    file::metadata::KeyValue,
+    schema::types::Type,
+};
+use serde_json::json;
+
+use crate::parquet_buffer::ParquetBuffer;
+
+use super::{strategy::ColumnFetchStrategy, text::utf8_bytes_to_byte_array};
+
+/// `SQL_SS_XML` reported by Microsoft SQL Server drivers.
+const SQL_SS_XML: SqlDataType = SqlDataType(-152);
+/// `SQL_XML` reported by IBM DB2 drivers.
+const SQL_XML: SqlDataType = SqlDataType(-370);
+
+/// `true` if the driver reports the column to hold XML documents.
+pub fn is_xml(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Other {
+            data_type: SQL_SS_XML | SQL_XML,
+            ..
+        }
+    )
+}
+
+/// Key value metadata marking `columns` as XML, so readers can tell them apart from other text.
+pub fn xml_columns_metadata(columns: &[&str]) -> KeyValue {
+    KeyValue::new("xml_columns".to_owned(), json!(columns).to_string())
+}
+
+/// Writes XML documents as UTF8 byte arrays. Fails rather than writing truncated documents.
+pub struct Xml {
+    repetition: Repetition,
+    /// Maximum length of a document in characters of the fetch buffer (bytes for narrow, `u16` for
+    /// wide buffers).
+    max_len: usize,
+    use_utf16: bool,
+}
+
+impl Xml {
+    pub fn new(repetition: Repetition, max_len: usize, use_utf16: bool) -> Self {
+        Self {
+            repetition,
+            max_len,
+            use_utf16,
+        }
+    }
+
+    fn check_length(&self, len: usize) -> Result<(), Error> {
+        if len >= self.max_len {
+            bail!(
+                "Found an XML document with a length of at least {} characters, which is likely \
+                truncated. Raise `--xml-max-length` to fetch longer documents.",
+                self.max_len
+            )
+        }
+        Ok(())
+    }
+}
+
+impl ColumnFetchStrategy for Xml {
+    fn parquet_type(&self, name: &str) -> Type {
+        Type::primitive_type_builder(name, PhysicalType::BYTE_ARRAY)
+            .with_converted_type(ConvertedType::UTF8)
+            .with_repetition(self.repetition)
+            .build()
+            .unwrap()
+    }
+
+    fn buffer_description(&self) -> BufferDescription {
+        let kind = if self.use_utf16 {
+            BufferKind::WText {
+                max_str_len: self.max_len,
+            }
+        } else {
+            BufferKind::Text {
+                max_str_len: self.max_len,
+            }
+        };
+        BufferDescription {
+            kind,
+            nullable: true,
+        }
+    }
+
+    fn copy_odbc_to_parquet(
+        &self,
+        parquet_buffer: &mut ParquetBuffer,
+        column_writer: &mut ColumnWriter,
+        column_view: AnyColumnView,
+    ) -> Result<(), Error> {
+        let column_writer = ByteArrayType::get_column_writer_mut(column_writer).unwrap();
+        match column_view {
+            AnyColumnView::Text(view) => parquet_buffer.write_optional_falliable(
+                column_writer,
+                view.iter().map(|item| {
+                    item.map(|bytes| {
+                        self.check_length(bytes.len())?;
+                        Ok(utf8_bytes_to_byte_array(bytes))
+                    })
+                    .transpose()
+                }),
+            ),
+            AnyColumnView::WText(view) => parquet_buffer.write_optional_falliable(
+                column_writer,
+                view.iter().map(|item| {
+                    item.map(|ustr| {
+                        self.check_length(ustr.len())?;
+                        let byte_array: ByteArray = ustr.to_string()?.into_bytes().into();
+                        Ok(byte_array)
+                    })
+                    .transpose()
+                }),
+            ),
+            _ => panic!(
+                "Invalid Column view type. This is not supposed to happen. Please open a Bug at \
+                https://github.com/pacman82/odbc2parquet/issues."
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use odbc_api::{sys::SqlDataType, DataType};
+
+    use super::is_xml;
+
+    #[test]
+    fn detect_xml_columns() {
+        let xml = |code| DataType::Other {
+            data_type: SqlDataType(code),
+            column_size: 0,
+            decimal_digits: 0,
+        };
+        assert!(is_xml(&xml(-152)));
+        assert!(is_xml(&xml(-370)));
+        assert!(!is_xml(&xml(-155)));
+        assert!(!is_xml(&DataType::LongVarchar { length: 0 }));
+    }
+}
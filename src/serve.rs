@@ -0,0 +1,263 @@
+//! Arrow Flight endpoint. Clients submit SQL, which is executed via ODBC and the result set is
+//! streamed back as Arrow record batches.
+
+use std::{sync::Arc, thread};
+
+use anyhow::{anyhow, bail, Error};
+use arrow_array::RecordBatch;
+use arrow_flight::{
+    encode::FlightDataEncoderBuilder,
+    error::FlightError,
+    flight_service_server::{FlightService, FlightServiceServer},
+    Action, ActionType, Criteria, Empty, FlightData, FlightDescriptor, FlightEndpoint, FlightInfo,
+    HandshakeRequest, HandshakeResponse, PollInfo, PutResult, SchemaResult, Ticket,
+};
+use arrow_schema::SchemaRef;
+use futures::{
+    channel::{mpsc, oneshot},
+    executor::block_on,
+    stream::BoxStream,
+    SinkExt, StreamExt, TryStreamExt,
+};
+use log::{info, warn};
+use odbc_api::Environment;
+use tonic::{transport::Server, Request, Response, Status, Streaming};
+
+use crate::{
+    open_connection,
+    query::record_batch::{cursor_to_record_batches, BatchSizeLimit, MappingOptions},
+    ServeOpt,
+};
+
+/// Serves the Arrow Flight endpoint until the process is terminated.
+pub fn serve(environment: &'static Environment, opt: ServeOpt) -> Result<(), Error> {
+    let address = opt.address;
+    let gateway = FlightGateway {
+        environment,
+        opt: Arc::new(opt),
+    };
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    info!("Serving Arrow Flight endpoint at {address}.");
+    runtime.block_on(
+        Server::builder()
+            .add_service(FlightServiceServer::new(gateway))
+            .serve(address),
+    )?;
+    Ok(())
+}
+
+/// Executes the query contained in the ticket of `DoGet` requests. `GetFlightInfo` returns an
+/// endpoint with the command of the descriptor as ticket, so clients may use either.
+struct FlightGateway {
+    environment: &'static Environment,
+    opt: Arc<ServeOpt>,
+}
+
+#[tonic::async_trait]
+impl FlightService for FlightGateway {
+    type HandshakeStream = BoxStream<'static, Result<HandshakeResponse, Status>>;
+    type ListFlightsStream = BoxStream<'static, Result<FlightInfo, Status>>;
+    type DoGetStream = BoxStream<'static, Result<FlightData, Status>>;
+    type DoPutStream = BoxStream<'static, Result<PutResult, Status>>;
+    type DoExchangeStream = BoxStream<'static, Result<FlightData, Status>>;
+    type DoActionStream = BoxStream<'static, Result<arrow_flight::Result, Status>>;
+    type ListActionsStream = BoxStream<'static, Result<ActionType, Status>>;
+
+    async fn handshake(
+        &self,
+        _request: Request<Streaming<HandshakeRequest>>,
+    ) -> Result<Response<Self::HandshakeStream>, Status> {
+        Err(Status::unimplemented("Handshake is not required."))
+    }
+
+    async fn list_flights(
+        &self,
+        _request: Request<Criteria>,
+    ) -> Result<Response<Self::ListFlightsStream>, Status> {
+        Err(Status::unimplemented(
+            "Flights are created on demand from queries.",
+        ))
+    }
+
+    async fn get_flight_info(
+        &self,
+        request: Request<FlightDescriptor>,
+    ) -> Result<Response<FlightInfo>, Status> {
+        let descriptor = request.into_inner();
+        let ticket = Ticket::new(descriptor.cmd.clone());
+        let info = FlightInfo::new()
+            .with_descriptor(descriptor)
+            .with_endpoint(FlightEndpoint::new().with_ticket(ticket));
+        Ok(Response::new(info))
+    }
+
+    async fn poll_flight_info(
+        &self,
+        _request: Request<FlightDescriptor>,
+    ) -> Result<Response<PollInfo>, Status> {
+        Err(Status::unimplemented("Use GetFlightInfo instead."))
+    }
+
+    async fn get_schema(
+        &self,
+        _request: Request<FlightDescriptor>,
+    ) -> Result<Response<SchemaResult>, Status> {
+        Err(Status::unimplemented(
+            "The schema is sent as part of the DoGet response.",
+        ))
+    }
+
+    async fn do_get(
+        &self,
+        request: Request<Ticket>,
+    ) -> Result<Response<Self::DoGetStream>, Status> {
+        let query = String::from_utf8(request.into_inner().ticket.to_vec()).map_err(|_| {
+            Status::invalid_argument("Ticket must contain the query as UTF-8 text.")
+        })?;
+        info!("Executing query: {query}");
+
+        let (schema_sender, schema_receiver) = oneshot::channel();
+        // Fetching is blocking, so we do it on a dedicated thread. The channel has only room for
+        // one batch, so fetching is paused if the client is slower than the database.
+        let (batch_sender, batch_receiver) = mpsc::channel(1);
+        let environment = self.environment;
+        let opt = self.opt.clone();
+        thread::spawn(move || {
+            let mut schema_sender = Some(schema_sender);
+            let mut batch_sender = batch_sender;
+            let result = fetch(
+                environment,
+                &opt,
+                &query,
+                &mut schema_sender,
+                &mut batch_sender,
+            );
+            if let Err(error) = result {
+                warn!("Query failed: {error:#}");
+                // Errors before the schema is known are reported as the status of the response,
+                // later ones as part of the stream.
+                if let Some(schema_sender) = schema_sender {
+                    let _ = schema_sender.send(Err(error));
+                } else {
+                    let _ =
+                        block_on(batch_sender.send(Err(FlightError::ExternalError(error.into()))));
+                }
+            }
+        });
+
+        let schema = schema_receiver
+            .await
+            .map_err(|_| Status::internal("Query has been aborted."))?
+            .map_err(|error| Status::internal(format!("{error:#}")))?;
+        let stream = FlightDataEncoderBuilder::new()
+            .with_schema(schema)
+            .build(batch_receiver)
+            .map_err(Status::from)
+            .boxed();
+        Ok(Response::new(stream))
+    }
+
+    async fn do_put(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoPutStream>, Status> {
+        Err(Status::unimplemented("Uploading data is not supported."))
+    }
+
+    async fn do_exchange(
+        &self,
+        _request: Request<Streaming<FlightData>>,
+    ) -> Result<Response<Self::DoExchangeStream>, Status> {
+        Err(Status::unimplemented("Exchanging data is not supported."))
+    }
+
+    async fn do_action(
+        &self,
+        _request: Request<Action>,
+    ) -> Result<Response<Self::DoActionStream>, Status> {
+        Err(Status::unimplemented("No actions are supported."))
+    }
+
+    async fn list_actions(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<Self::ListActionsStream>, Status> {
+        Ok(Response::new(futures::stream::empty().boxed()))
+    }
+}
+
+/// Executes the query on a new connection and sends the schema and the fetched batches to the
+/// receiving ends of the channels.
+fn fetch(
+    environment: &Environment,
+    opt: &ServeOpt,
+    query: &str,
+    schema_sender: &mut Option<oneshot::Sender<Result<SchemaRef, Error>>>,
+    batch_sender: &mut mpsc::Sender<Result<RecordBatch, FlightError>>,
+) -> Result<(), Error> {
+    let conn = open_connection(environment, &opt.connect_opts)?;
+    let db_name = conn.database_management_system_name()?;
+    let mapping_options = MappingOptions {
+        db_name: &db_name,
+        use_utf16: opt.encoding.use_utf16(),
+        prefer_varbinary: opt.prefer_varbinary,
+        driver_does_support_i64: !opt.driver_does_not_support_64bit_integers,
+        prefer_int_over_decimal: opt.prefer_int_over_decimal,
+        plain_narrow_integers: opt.plain_narrow_integers,
+        real_as_float16: false,
+        decimal_as_double: opt.decimal_as_double,
+        decimal_as_text: opt.decimal_as_text,
+        default_number_as: opt.default_number_as,
+        decfloat_as: opt.decfloat_as,
+        timestamps_as_int96: false,
+        timestamps_utc_offset: None,
+        timestamp_units: &[],
+        time_unit: None,
+        time_as_text: false,
+        guid_as_uuid: false,
+        xml_max_length: opt.xml_max_length,
+        intervals_as_iso8601: true,
+        geometry_columns: &[],
+        wkb_max_length: 0,
+        max_column_size: opt
+            .max_column_size
+            .map(|size| size.as_u64().try_into().unwrap()),
+        column_lengths: &[],
+        unknown_types_as_text: opt.unknown_types_as_text,
+        trim_char: false,
+        trim_char_columns: &[],
+        column_casts: &[],
+        column_order: &[],
+        renames: &[],
+        columns: &[],
+        exclude_columns: &[],
+        sanitize_column_names: None,
+        null_values: &[],
+        null_defaults: &[],
+        masks: &[],
+        max_value_size: None,
+        oversized: &[],
+        not_null_columns: &[],
+        nullable_columns: &[],
+    };
+    let batch_size = BatchSizeLimit::new(opt.batch_size_row, opt.batch_size_memory);
+
+    let Some(cursor) = conn.execute(query, ())? else {
+        bail!("Query came back empty (not even a schema has been returned).")
+    };
+    cursor_to_record_batches(
+        cursor,
+        batch_size,
+        mapping_options,
+        |schema| {
+            schema_sender
+                .take()
+                .expect("Schema must only be sent once.")
+                .send(Ok(schema))
+                .map_err(|_| anyhow!("Client disconnected."))
+        },
+        |batch| block_on(batch_sender.send(Ok(batch))).map_err(|_| anyhow!("Client disconnected.")),
+    )
+}
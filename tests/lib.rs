@@ -1,15 +1,20 @@
 use std::{fs::File, io::Write, path::Path, sync::Arc};
 
 use assert_cmd::{assert::Assert, Command};
+use calamine::{open_workbook, Data, Reader, Xlsx};
 use lazy_static::lazy_static;
 use odbc_api::{
     buffers::{BufferDescription, TextRowSet},
     Connection, Cursor, Environment, IntoParameter,
 };
 use parquet::{
+    basic::Compression,
     column::writer::ColumnWriter,
     data_type::{ByteArray, FixedLenByteArray},
-    file::{properties::WriterProperties, writer::SerializedFileWriter},
+    file::{
+        metadata::SortingColumn, properties::WriterProperties, reader::FileReader,
+        serialized_reader::SerializedFileReader, writer::SerializedFileWriter,
+    },
     schema::parser::parse_message_type,
 };
 use predicates::{ord::eq, str::contains};
@@ -253,6 +258,7 @@ fn query_sales() {
             out_str,
             "--connection-string",
             MSSQL,
+            "--time-as-text",
             &query,
         ])
         .assert()
@@ -482,28 +488,12 @@ fn query_numeric_33_3() {
 }
 
 #[test]
-fn query_timestamp_with_timezone_mssql() {
-    // Setup table for test
-    let table_name = "QueryTimestampWithTimezone";
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    // ODBC data type: SqlDataType(-155), column_size: 34, decimal_digits: 7
-    setup_empty_table_mssql(&conn, table_name, &["DATETIMEOFFSET"]).unwrap();
-    let insert = format!(
-        "INSERT INTO {}
-        (a)
-        VALUES
-        ('2022-09-07 16:04:12 +02:00');",
-        table_name
-    );
-    conn.execute(&insert, ()).unwrap();
+fn query_decimal_as_double() {
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
     let out_path = out_dir.path().join("out.par");
     // We need to pass the output path as a string argument.
     let out_str = out_path.to_str().expect("Temporary file path must be utf8");
-    let query = format!("SELECT a FROM {table_name};");
 
     Command::cargo_bin("odbc2parquet")
         .unwrap()
@@ -513,39 +503,25 @@ fn query_timestamp_with_timezone_mssql() {
             out_str,
             "--connection-string",
             MSSQL,
-            &query,
+            "--decimal-as-double",
+            "SELECT CAST(1.25 AS DECIMAL(5,2)) AS a, CAST(-12345.5 AS NUMERIC(38,1)) AS b",
         ])
         .assert()
         .success();
 
-    let expected_values = "{a: 2022-09-07 14:04:12 +00:00}\n";
-    parquet_read_out(out_str).stdout(eq(expected_values));
-
-    parquet_schema_out(out_str).stdout(contains("OPTIONAL INT64 a (TIMESTAMP(MICROS,true));"));
+    parquet_read_out(out_str).stdout(eq("{a: 1.25, b: -12345.5}\n"));
+    parquet_schema_out(out_str)
+        .stdout(contains("DOUBLE a;"))
+        .stdout(contains("DOUBLE b;"));
 }
 
 #[test]
-fn query_timestamp_mssql() {
-    // Setup table for test
-    let table_name = "QueryTimestamp";
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["DATETIME2"]).unwrap();
-    let insert = format!(
-        "INSERT INTO {}
-        (a)
-        VALUES
-        ('2022-09-07 16:04:12');",
-        table_name
-    );
-    conn.execute(&insert, ()).unwrap();
+fn query_decimal_as_text() {
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
     let out_path = out_dir.path().join("out.par");
     // We need to pass the output path as a string argument.
     let out_str = out_path.to_str().expect("Temporary file path must be utf8");
-    let query = format!("SELECT a FROM {table_name};");
 
     Command::cargo_bin("odbc2parquet")
         .unwrap()
@@ -555,41 +531,43 @@ fn query_timestamp_mssql() {
             out_str,
             "--connection-string",
             MSSQL,
-            &query,
+            "--decimal-as-text",
+            "SELECT CAST(-123.450 AS DECIMAL(6,3)) AS a",
         ])
         .assert()
         .success();
 
-    let expected_values = "{a: 2022-09-07 16:04:12 +00:00}\n";
-    parquet_read_out(out_str).stdout(eq(expected_values));
+    parquet_read_out(out_str).stdout(eq("{a: \"-123.450\"}\n"));
+    parquet_schema_out(out_str).stdout(contains("BYTE_ARRAY a (UTF8);"));
+}
 
-    parquet_schema_out(out_str).stdout(contains("OPTIONAL INT64 a (TIMESTAMP(MICROS,false));"));
+#[test]
+fn invalid_default_number_as() {
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            "out.par",
+            "--connection-string",
+            MSSQL,
+            "--default-number-as",
+            "decimal(5,6)",
+            "SELECT 1 AS a",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("scale between 0 and its precision"));
 }
 
 #[test]
-fn query_timestamp_ms_with_timezone_mssql() {
-    // Setup table for test
-    let table_name = "QueryTimestampMsWithTimezone";
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    // ODBC data type: SqlDataType(-155), decimal_digits: 3
-    setup_empty_table_mssql(&conn, table_name, &["DATETIMEOFFSET(3)"]).unwrap();
-    let insert = format!(
-        "INSERT INTO {}
-        (a)
-        VALUES
-        ('2022-09-07 16:04:12 +02:00');",
-        table_name
-    );
-    conn.execute(&insert, ()).unwrap();
+fn query_unknown_type_as_text() {
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
     let out_path = out_dir.path().join("out.par");
     // We need to pass the output path as a string argument.
     let out_str = out_path.to_str().expect("Temporary file path must be utf8");
-    let query = format!("SELECT a FROM {table_name};");
 
+    // `sql_variant` is reported as `SQL_SS_VARIANT` (-150), which odbc2parquet does not know.
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
@@ -598,39 +576,23 @@ fn query_timestamp_ms_with_timezone_mssql() {
             out_str,
             "--connection-string",
             MSSQL,
-            &query,
+            "--unknown-types-as-text",
+            "SELECT CAST(42 AS sql_variant) AS a, 1 AS b",
         ])
         .assert()
         .success();
 
-    let expected_values = "{a: 2022-09-07 14:04:12 +00:00}\n";
-    parquet_read_out(out_str).stdout(eq(expected_values));
-
-    parquet_schema_out(out_str).stdout(contains("OPTIONAL INT64 a (TIMESTAMP(MILLIS,true));"));
+    parquet_read_out(out_str).stdout(eq("{a: \"42\", b: 1}\n"));
+    parquet_schema_out(out_str).stdout(contains("BYTE_ARRAY a (UTF8);"));
 }
 
 #[test]
-fn query_timestamp_with_timezone_postgres() {
-    // Setup table for test
-    let table_name = "QueryTimestampWithTimezone";
-    let conn = ENV.connect_with_connection_string(POSTGRES).unwrap();
-    setup_empty_table_pg(&conn, table_name, &["TIMESTAMPTZ"]).unwrap();
-    let insert = format!(
-        "INSERT INTO {}
-        (a)
-        VALUES
-        ('2022-09-07 16:04:12 +02:00');",
-        table_name
-    );
-    conn.execute(&insert, ()).unwrap();
+fn query_time_with_fractional_seconds() {
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
     let out_path = out_dir.path().join("out.par");
     // We need to pass the output path as a string argument.
     let out_str = out_path.to_str().expect("Temporary file path must be utf8");
-    let query = format!("SELECT a FROM {table_name};");
 
     Command::cargo_bin("odbc2parquet")
         .unwrap()
@@ -639,40 +601,26 @@ fn query_timestamp_with_timezone_postgres() {
             "query",
             out_str,
             "--connection-string",
-            POSTGRES,
-            &query,
+            MSSQL,
+            "SELECT CAST('12:34:56.123' AS TIME(3)) AS a, \
+            CAST('12:34:56.1234567' AS TIME(7)) AS b",
         ])
         .assert()
         .success();
 
-    let expected_values = "{a: 2022-09-07 14:04:12 +00:00}\n";
-    parquet_read_out(out_str).stdout(eq(expected_values));
-
-    parquet_schema_out(out_str).stdout(contains("OPTIONAL INT64 a (TIMESTAMP(MICROS,false));"));
+    parquet_read_out(out_str).stdout(eq("{a: 12:34:56.123, b: 12:34:56.123456}\n"));
+    parquet_schema_out(out_str)
+        .stdout(contains("INT32 a (TIME(MILLIS,false));"))
+        .stdout(contains("INT64 b (TIME(MICROS,false));"));
 }
 
 #[test]
-fn query_timestamp_postgres() {
-    // Setup table for test
-    let table_name = "QueryTimestamp";
-    let conn = ENV.connect_with_connection_string(POSTGRES).unwrap();
-    setup_empty_table_pg(&conn, table_name, &["TIMESTAMP"]).unwrap();
-    let insert = format!(
-        "INSERT INTO {}
-        (a)
-        VALUES
-        ('2022-09-07 16:04:12');",
-        table_name
-    );
-    conn.execute(&insert, ()).unwrap();
+fn query_time_with_nanoseconds() {
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
     let out_path = out_dir.path().join("out.par");
     // We need to pass the output path as a string argument.
     let out_str = out_path.to_str().expect("Temporary file path must be utf8");
-    let query = format!("SELECT a FROM {table_name};");
 
     Command::cargo_bin("odbc2parquet")
         .unwrap()
@@ -681,76 +629,27 @@ fn query_timestamp_postgres() {
             "query",
             out_str,
             "--connection-string",
-            POSTGRES,
-            &query,
+            MSSQL,
+            "--time-unit",
+            "nanos",
+            "SELECT CAST('12:34:56.1234567' AS TIME(7)) AS a",
         ])
         .assert()
         .success();
 
-    let expected_values = "{a: 2022-09-07 16:04:12 +00:00}\n";
-    parquet_read_out(out_str).stdout(eq(expected_values));
-
-    parquet_schema_out(out_str).stdout(contains("OPTIONAL INT64 a (TIMESTAMP(MICROS,false));"));
+    parquet_read_out(out_str).stdout(eq("{a: 45296123456700}\n"));
+    parquet_schema_out(out_str).stdout(contains("INT64 a (TIME(NANOS,false));"));
 }
 
+/// Microsoft SQL Server reports `TINYINT` to be unsigned.
 #[test]
-fn query_all_the_types() {
-    // Setup table for test
-    let table_name = "AllTheTypes";
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(
-        &conn,
-        table_name,
-        &[
-            "CHAR(5) NOT NULL",
-            "NUMERIC(3,2) NOT NULL",
-            "DECIMAL(3,2) NOT NULL",
-            "INTEGER NOT NULL",
-            "SMALLINT NOT NULL",
-            "FLOAT(3) NOT NULL",
-            "REAL NOT NULL",
-            "DOUBLE PRECISION NOT NULL",
-            "VARCHAR(100) NOT NULL",
-            "DATE NOT NULL",
-            "TIME NOT NULL",
-            "DATETIME NOT NULL",
-        ],
-    )
-    .unwrap();
-    let insert = format!(
-        "INSERT INTO {}
-        (a,b,c,d,e,f,g,h,i,j,k,l)
-        VALUES
-        ('abcde', 1.23, 1.23, 42, 42, 1.23, 1.23, 1.23, 'Hello, World!', '2020-09-16', '03:54:12', '2020-09-16 03:54:12');",
-        table_name
-    );
-    conn.execute(&insert, ()).unwrap();
-
-    let expected_values = "{\
-        a: \"abcde\", \
-        b: 1.23, \
-        c: 1.23, \
-        d: 42, \
-        e: 42, \
-        f: 1.23, \
-        g: 1.23, \
-        h: 1.23, \
-        i: \"Hello, World!\", \
-        j: 2020-09-16 +00:00, \
-        k: \"03:54:12.0000000\", \
-        l: 2020-09-16 03:54:12 +00:00\
-    }\n";
-
+fn query_unsigned_tiny_int() {
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
     let out_path = out_dir.path().join("out.par");
     // We need to pass the output path as a string argument.
     let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    let query = format!("SELECT a,b,c,d,e,f,g,h,i,j,k,l FROM {};", table_name);
-
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
@@ -759,47 +658,23 @@ fn query_all_the_types() {
             out_str,
             "--connection-string",
             MSSQL,
-            &query,
+            "SELECT CAST(200 AS TINYINT) AS a",
         ])
         .assert()
         .success();
 
-    parquet_read_out(out_str).stdout(eq(expected_values));
+    parquet_read_out(out_str).stdout(eq("{a: 200}\n"));
+    parquet_schema_out(out_str).stdout(contains("INT32 a (UINT_8);"));
 }
 
 #[test]
-fn query_bits() {
-    // Setup table for test
-    let table_name = "QueryBits";
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["BIT"]).unwrap();
-    let insert = format!(
-        "INSERT INTO {}
-        (a)
-        VALUES
-        (0), (1), (NULL), (1), (0);",
-        table_name
-    );
-    conn.execute(&insert, ()).unwrap();
-
-    let expected_values = "\
-        {a: false}\n\
-        {a: true}\n\
-        {a: null}\n\
-        {a: true}\n\
-        {a: false}\n\
-    ";
-
+fn query_plain_narrow_integers() {
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
     let out_path = out_dir.path().join("out.par");
     // We need to pass the output path as a string argument.
     let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    let query = format!("SELECT a FROM {};", table_name);
-
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
@@ -808,46 +683,52 @@ fn query_bits() {
             out_str,
             "--connection-string",
             MSSQL,
-            &query,
+            "--plain-narrow-integers",
+            "SELECT CAST(-5 AS SMALLINT) AS a, CAST(200 AS TINYINT) AS b",
         ])
         .assert()
         .success();
 
-    parquet_read_out(out_str).stdout(eq(expected_values));
+    parquet_read_out(out_str).stdout(eq("{a: -5, b: 200}\n"));
+    parquet_schema_out(out_str)
+        .stdout(contains("INT32 a;"))
+        .stdout(contains("INT32 b;"));
 }
 
 #[test]
-fn query_doubles() {
-    // Setup table for test
-    let table_name = "QueryDoubles";
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["DOUBLE PRECISION NOT NULL"]).unwrap();
-    let insert = format!(
-        "INSERT INTO {}
-        (a)
-        VALUES
-        (0.1), (2.3);",
-        table_name
-    );
-    conn.execute(&insert, ()).unwrap();
+fn query_trim_char() {
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    let expected_values = "\
-        {a: 0.1}\n\
-        {a: 2.3}\n\
-    ";
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--trim-char",
+            "SELECT CAST('a' AS CHAR(3)) AS a, CAST('b  ' AS VARCHAR(3)) AS b",
+        ])
+        .assert()
+        .success();
 
-    let expected_schema = "REQUIRED DOUBLE a;";
+    // Only fixed length columns are trimmed.
+    parquet_read_out(out_str).stdout(eq("{a: \"a\", b: \"b  \"}\n"));
+}
 
+#[test]
+fn query_trim_char_column() {
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
     let out_path = out_dir.path().join("out.par");
     // We need to pass the output path as a string argument.
     let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    let query = format!("SELECT a FROM {};", table_name);
-
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
@@ -856,80 +737,52 @@ fn query_doubles() {
             out_str,
             "--connection-string",
             MSSQL,
-            &query,
+            "--trim-char-column",
+            "b",
+            "SELECT CAST('a' AS CHAR(3)) AS a, CAST('b  ' AS VARCHAR(3)) AS b",
         ])
         .assert()
         .success();
 
-    parquet_read_out(out_str).stdout(eq(expected_values));
-
-    // Also verify schema to ensure f64 is choosen and not f32
-    parquet_schema_out(out_str).stdout(contains(expected_schema));
+    parquet_read_out(out_str).stdout(eq("{a: \"a  \", b: \"b\"}\n"));
 }
 
-/// Should read query from standard input if "-" is provided as query text.
 #[test]
-fn read_query_from_stdin() {
-    // Setup table for test
-    let table_name = "ReadQueryFromStdin";
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["INT"]).unwrap();
-    let insert = format!(
-        "INSERT INTO {}
-        (a)
-        VALUES
-        (54),
-        (54),
-        (34),
-        (12);",
-        table_name
-    );
-    conn.execute(&insert, ()).unwrap();
-
-    let expected_values = "\
-        {a: 54}\n\
-        {a: 54}\n\
-        {a: 34}\n\
-        {a: 12}\n\
-    ";
-    let query = format!("SELECT a FROM {} ORDER BY id", table_name);
+fn query_null_values() {
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
     let out_path = out_dir.path().join("out.par");
     // We need to pass the output path as a string argument.
-    let out_str = out_path.to_str().expect("Tempfile path must be utf8");
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
     Command::cargo_bin("odbc2parquet")
         .unwrap()
-        .args(&["-vvvv", "query", out_str, "--connection-string", MSSQL, "-"])
-        .write_stdin(query)
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--null-value",
+            "a=-9999",
+            "--null-value",
+            "b=",
+            "SELECT a, b FROM (VALUES (-9999, ''), (42, 'x')) AS t (a, b)",
+        ])
         .assert()
         .success();
 
-    parquet_read_out(out_str).stdout(eq(expected_values));
+    parquet_read_out(out_str).stdout(eq("{a: null, b: null}\n{a: 42, b: \"x\"}\n"));
 }
 
 #[test]
-fn split_files_on_num_row_groups() {
-    // Setup table for test
-    let table_name = "SplitFilesOnNumRowGroups";
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
-    let insert = format!("INSERT INTO {} (A) VALUES(1),(2),(3)", table_name);
-    conn.execute(&insert, ()).unwrap();
-
+fn query_masked_columns() {
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
     let out_path = out_dir.path().join("out.par");
     // We need to pass the output path as a string argument.
     let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    let query = format!("SELECT a FROM {}", table_name);
-
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
@@ -938,41 +791,28 @@ fn split_files_on_num_row_groups() {
             out_str,
             "--connection-string",
             MSSQL,
-            "--batch-size-row",
-            "1",
-            "--row-groups-per-file",
-            "1",
-            &query,
+            "--mask",
+            "a:sha256",
+            "--mask",
+            "b:redact",
+            "SELECT 'hello' AS a, 42 AS b",
         ])
         .assert()
         .success();
 
-    // Expect one file per row in table (3)
-
-    parquet_read_out(out_dir.path().join("out_01.par").to_str().unwrap());
-    parquet_read_out(out_dir.path().join("out_02.par").to_str().unwrap());
-    parquet_read_out(out_dir.path().join("out_03.par").to_str().unwrap());
+    parquet_read_out(out_str).stdout(eq(
+        "{a: \"2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824\", b: null}\n",
+    ));
 }
 
 #[test]
-fn split_files_on_size_limit() {
-    // Setup table for test
-    let table_name = "SplitFilesOnSizeLimit";
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
-    let insert = format!("INSERT INTO {} (A) VALUES(1),(2),(3)", table_name);
-    conn.execute(&insert, ()).unwrap();
-
+fn override_nullability() {
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
     let out_path = out_dir.path().join("out.par");
     // We need to pass the output path as a string argument.
     let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    let query = format!("SELECT a FROM {}", table_name);
-
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
@@ -981,41 +821,28 @@ fn split_files_on_size_limit() {
             out_str,
             "--connection-string",
             MSSQL,
-            "--batch-size-row",
-            "1",
-            "--file-size-threshold",
-            "1B",
-            &query,
+            "--force-nullable",
+            "a",
+            "--assert-not-null",
+            "b",
+            // Literals are reported as not nullable, the result of `NULLIF` as nullable.
+            "SELECT 1 AS a, NULLIF(2, 3) AS b",
         ])
         .assert()
         .success();
 
-    // Expect one file per row in table (3)
-
-    parquet_read_out(out_dir.path().join("out_01.par").to_str().unwrap());
-    parquet_read_out(out_dir.path().join("out_02.par").to_str().unwrap());
-    parquet_read_out(out_dir.path().join("out_03.par").to_str().unwrap());
+    parquet_read_out(out_str).stdout(eq("{a: 1, b: 2}\n"));
+    parquet_schema_out(out_str).stdout(contains("{\n  OPTIONAL INT32 a;\n  REQUIRED INT32 b;\n}"));
 }
 
 #[test]
-fn configurable_suffix_length() {
-    // Setup table for test
-    let table_name = "ConfigurableSuffixLength";
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
-    let insert = format!("INSERT INTO {} (A) VALUES(1)", table_name);
-    conn.execute(&insert, ()).unwrap();
-
+fn assert_not_null_fails_on_null() {
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
     let out_path = out_dir.path().join("out.par");
     // We need to pass the output path as a string argument.
     let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    let query = format!("SELECT a FROM {}", table_name);
-
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
@@ -1024,46 +851,23 @@ fn configurable_suffix_length() {
             out_str,
             "--connection-string",
             MSSQL,
-            "--batch-size-row",
-            "1",
-            "--file-size-threshold",
-            "1B",
-            "--suffix-length",
-            "4",
-            &query,
+            "--assert-not-null",
+            "a",
+            "SELECT NULLIF(2, 2) AS a",
         ])
         .assert()
-        .success();
-
-    // Expect one file per row in table (3)
-
-    parquet_read_out(out_dir.path().join("out_0001.par").to_str().unwrap());
+        .failure()
+        .stderr(contains("Column 'a' is asserted not to contain NULL"));
 }
 
 #[test]
-fn varbinary_column() {
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-
-    setup_empty_table_mssql(&conn, "VarbinaryColumn", &["VARBINARY(10)"]).unwrap();
-    conn.execute(
-        "INSERT INTO VarbinaryColumn (a) Values \
-        (CONVERT(Binary(5), 'Hello')),\
-        (CONVERT(Binary(5), 'World')),\
-        (NULL)",
-        (),
-    )
-    .unwrap();
-
+fn query_null_defaults() {
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
     let out_path = out_dir.path().join("out.par");
     // We need to pass the output path as a string argument.
     let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    let query = "SELECT a FROM VarbinaryColumn;";
-
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
@@ -1072,123 +876,93 @@ fn varbinary_column() {
             out_str,
             "--connection-string",
             MSSQL,
-            query,
+            "--null-default",
+            "a=0",
+            "--null-default",
+            "b=",
+            "SELECT CAST(NULL AS INT) AS a, CAST(NULL AS VARCHAR(10)) AS b",
         ])
         .assert()
         .success();
 
-    let expected = "{a: [72, 101, 108, 108, 111]}\n{a: [87, 111, 114, 108, 100]}\n{a: null}\n";
-
-    parquet_read_out(out_str).stdout(eq(expected));
+    parquet_read_out(out_str).stdout(eq("{a: 0, b: \"\"}\n"));
+    parquet_schema_out(out_str).stdout(contains("REQUIRED INT32 a;"));
 }
 
 #[test]
-fn query_varchar_max() {
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    let table_name = "QueryVarcharMax";
-
-    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(MAX)"]).unwrap();
-    conn.execute(
-        &format!(
-            "INSERT INTO {} (a) Values ('Hello'), ('World');",
-            table_name
-        ),
-        (),
-    )
-    .unwrap();
-
+fn query_added_columns() {
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
     let out_path = out_dir.path().join("out.par");
     // We need to pass the output path as a string argument.
     let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    let query = format!("SELECT a FROM {};", table_name);
-
-    // VARCHAR(max) has size 0. => Column is ignored and file would be empty and schemaless
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
             "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            out_str,
-            &query,
+            "--add-column",
+            "batch=42",
+            "--add-column",
+            "_source",
+            "--add-column",
+            "_extracted_at",
+            "SELECT 1 AS a",
         ])
         .assert()
-        .failure();
+        .success();
+
+    parquet_read_out(out_str).stdout(contains("{a: 1, batch: \"42\", _source: \"localhost\""));
+    parquet_schema_out(out_str).stdout(contains(
+        "REQUIRED INT64 _extracted_at (TIMESTAMP(MICROS,true));",
+    ));
 }
 
-/// Introduced after discovering a bug, that columns were not ignored on windows.
 #[test]
-fn query_varchar_max_utf16() {
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    let table_name = "QueryVarcharMaxUtf16";
-
-    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(MAX)"]).unwrap();
-    conn.execute(
-        &format!(
-            "INSERT INTO {} (a) Values ('Hello'), ('World');",
-            table_name
-        ),
-        (),
-    )
-    .unwrap();
-
+fn query_from_file() {
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
     let out_path = out_dir.path().join("out.par");
     // We need to pass the output path as a string argument.
     let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+    let query_path = out_dir.path().join("query.sql");
+    std::fs::write(
+        &query_path,
+        "-- Comments and formatting are passed on to the data source\n\
+        SELECT\n    CAST(? AS INT) AS a\n",
+    )
+    .unwrap();
 
-    let query = format!("SELECT a FROM {};", table_name);
-
-    // VARCHAR(max) has size 0. => Column is ignored and file would be empty and schemaless
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
             "query",
-            "--encoding",
-            "Utf16",
             "--connection-string",
             MSSQL,
+            "--query-file",
+            query_path.to_str().unwrap(),
             out_str,
-            &query,
+            "42",
         ])
         .assert()
-        .failure();
+        .success();
+
+    parquet_read_out(out_str).stdout(eq("{a: 42}\n"));
 }
 
 #[test]
-fn binary_column() {
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-
-    setup_empty_table_mssql(&conn, "BinaryColumn", &["BINARY(5)"]).unwrap();
-    conn.execute(
-        "INSERT INTO BinaryColumn (a) Values \
-        (CONVERT(Binary(5), 'Hello')),\
-        (CONVERT(Binary(5), 'World')),\
-        (NULL)",
-        (),
-    )
-    .unwrap();
-
+fn substitute_variables_in_query() {
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
     let out_path = out_dir.path().join("out.par");
     // We need to pass the output path as a string argument.
     let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    let query = "SELECT a FROM BinaryColumn;";
-
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
@@ -1197,2152 +971,6411 @@ fn binary_column() {
             out_str,
             "--connection-string",
             MSSQL,
-            query,
+            "--var",
+            "column=a",
+            "--var",
+            "value=42",
+            "SELECT ${value} AS ${column}",
         ])
         .assert()
         .success();
 
-    let expected = "{a: [72, 101, 108, 108, 111]}\n{a: [87, 111, 114, 108, 100]}\n{a: null}\n";
-    parquet_read_out(out_str).stdout(eq(expected));
+    parquet_read_out(out_str).stdout(eq("{a: 42}\n"));
 }
 
-/// The prefer-varbinary flag must enforce mapping of binary colmuns to BYTE_ARRAY instead of
-/// FIXED_LEN_BYTE_ARRAY.
 #[test]
-fn prefer_varbinary() {
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-
-    let table_name = "PreferVarbinary";
-
-    setup_empty_table_mssql(&conn, table_name, &["BINARY(5)"]).unwrap();
-    conn.execute(
-        &format!(
-            "INSERT INTO {} (a) Values \
-        (CONVERT(Binary(5), 'Hello')),\
-        (CONVERT(Binary(5), 'World')),\
-        (NULL)",
-            table_name
-        ),
-        (),
-    )
-    .unwrap();
-
+fn query_once_per_parameter_set() {
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let out_path = out_dir.path().join("out.par");
+    let out_path = out_dir.path().join("out_{tenant}.par");
     // We need to pass the output path as a string argument.
     let out_str = out_path.to_str().expect("Temporary file path must be utf8");
-
-    let query = format!("SELECT a FROM {};", table_name);
+    let parameter_path = out_dir.path().join("parameters.csv");
+    std::fs::write(&parameter_path, "tenant,id\nacme,1\nglobex,2\n").unwrap();
 
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
             "query",
-            out_str,
-            "--prefer-varbinary",
             "--connection-string",
             MSSQL,
-            &query,
+            "--parameter-file",
+            parameter_path.to_str().unwrap(),
+            out_str,
+            "SELECT CAST(? AS VARCHAR(10)) AS tenant, CAST(? AS INT) AS id",
         ])
         .assert()
         .success();
 
-    let expected = "{a: [72, 101, 108, 108, 111]}\n{a: [87, 111, 114, 108, 100]}\n{a: null}\n";
-    parquet_read_out(out_str).stdout(eq(expected));
-
-    parquet_schema_out(out_str).stdout(contains("OPTIONAL BYTE_ARRAY a;"));
+    let acme = out_dir.path().join("out_acme.par");
+    parquet_read_out(acme.to_str().unwrap()).stdout(eq("{tenant: \"acme\", id: 1}\n"));
+    let globex = out_dir.path().join("out_globex.par");
+    parquet_read_out(globex.to_str().unwrap()).stdout(eq("{tenant: \"globex\", id: 2}\n"));
 }
 
-/// Strings with interior nuls should be written into parquet file as they are.
 #[test]
-fn interior_nul_in_varchar() {
+fn export_source_table() {
+    // Setup table for test
+    let table_name = "ExportSourceTable";
     let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, "InteriorNul", &["VARCHAR(10)"]).unwrap();
-
-    conn.execute(
-        "INSERT INTO InteriorNul (a) VALUES (?);",
-        &"a\0b".into_parameter(),
-    )
-    .unwrap();
-
+    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(10)", "INTEGER"]).unwrap();
+    let insert = format!("INSERT INTO {table_name} (a, b) VALUES ('Hello', 42);");
+    conn.execute(&insert, ()).unwrap();
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
     let out_path = out_dir.path().join("out.par");
     // We need to pass the output path as a string argument.
     let out_str = out_path.to_str().expect("Temporary file path must be utf8");
-
-    let query = "SELECT a FROM InteriorNul;";
+    let source_table = format!("dbo.{table_name}");
 
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
             "query",
-            out_str,
             "--connection-string",
             MSSQL,
-            query,
+            "--source-table",
+            &source_table,
+            out_str,
         ])
         .assert()
         .success();
 
-    let expected = "{a: \"a\0b\"}\n";
-
-    parquet_read_out(out_str).stdout(eq(expected));
+    parquet_read_out(out_str).stdout(eq("{id: 1, a: \"Hello\", b: 42}\n"));
 }
 
-/// Fixed size NCHAR column on database is not truncated then value is passed into a narrow buffer.
 #[test]
-#[cfg(not(target_os = "windows"))] // Windows does not use UTF-8 as default system encoding
-fn nchar_not_truncated() {
+fn resume_export_from_checkpoint() {
+    // Setup table for test
+    let table_name = "ResumeExportFromCheckpoint";
     let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    let table_name = "NCharNotTruncated";
-    setup_empty_table_mssql(&conn, table_name, &["NCHAR(1)"]).unwrap();
-
-    conn.execute(
-        &format!("INSERT INTO {} (a) VALUES (?);", table_name),
-        &"Ü".into_parameter(),
-    )
-    .unwrap();
-
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
+    let insert = format!("INSERT INTO {table_name} (a) VALUES (1), (2), (3);");
+    conn.execute(&insert, ()).unwrap();
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
     let out_path = out_dir.path().join("out.par");
     // We need to pass the output path as a string argument.
     let out_str = out_path.to_str().expect("Temporary file path must be utf8");
-
-    let query = &format!("SELECT a FROM {};", table_name);
+    let query = format!("SELECT a FROM {table_name} ORDER BY a");
+    let resume_query = format!("SELECT a FROM {table_name} WHERE a > ? ORDER BY a");
+    // Pretend a previous run has been killed after writing the first two files.
+    let checkpoint_path = out_dir.path().join("out.par.checkpoint");
+    let checkpoint = format!(r#"{{"query": "{query}", "files": 2, "rows": 2, "key": "2"}}"#);
+    std::fs::write(&checkpoint_path, checkpoint).unwrap();
 
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
             "query",
-            "--encoding",
-            "system",
             "--connection-string",
             MSSQL,
+            "--batch-size-row",
+            "1",
+            "--row-groups-per-file",
+            "1",
+            "--sorted-by",
+            "a",
+            "--checkpoint",
+            "--resume",
+            "--resume-query",
+            &resume_query,
             out_str,
-            query,
+            &query,
         ])
         .assert()
         .success();
 
-    let expected = "{a: \"Ü\"}\n";
-
-    parquet_read_out(out_str).stdout(eq(expected));
+    // Only the remaining row has been written, into the third file.
+    assert!(!out_dir.path().join("out_01.par").exists());
+    parquet_read_out(out_dir.path().join("out_03.par").to_str().unwrap()).stdout(eq("{a: 3}\n"));
+    // The checkpoint is removed once the export is complete.
+    assert!(!checkpoint_path.exists());
 }
 
-/// Test non ASCII character with system encoding
 #[test]
-#[cfg(not(target_os = "windows"))] // Windows does not use UTF-8 as default system encoding
-fn system_encoding() {
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    let table_name = "SystemEncoding";
-    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(10)"]).unwrap();
-
-    conn.execute(
-        &format!("INSERT INTO {} (a) VALUES (?);", table_name),
-        &"Ü".into_parameter(),
-    )
-    .unwrap();
-
+fn existing_output_policy() {
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
     let out_path = out_dir.path().join("out.par");
     // We need to pass the output path as a string argument.
     let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+    std::fs::write(&out_path, "existing").unwrap();
 
-    let query = &format!("SELECT a FROM {};", table_name);
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            "--connection-string",
+            MSSQL,
+            "--if-exists",
+            "error",
+            out_str,
+            "SELECT 42 AS a",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("already exists"));
 
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
-            "-vvvv",
             "query",
-            "--encoding",
-            "system",
             "--connection-string",
             MSSQL,
+            "--if-exists",
+            "skip",
             out_str,
-            query,
+            "SELECT 42 AS a",
         ])
         .assert()
         .success();
 
-    let expected = "{a: \"Ü\"}\n";
-
-    parquet_read_out(out_str).stdout(eq(expected));
+    // Neither execution touched the existing file.
+    assert_eq!("existing", std::fs::read_to_string(&out_path).unwrap());
 }
 
-/// Test non ASCII character with utf16 encoding
 #[test]
-fn utf_16_encoding() {
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    let table_name = "Utf16Encoding";
-    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(10)"]).unwrap();
-
-    conn.execute(
-        &format!("INSERT INTO {} (a) VALUES (?);", table_name),
-        &"Ü".into_parameter(),
-    )
-    .unwrap();
-
+fn do_not_retry_permanent_failures() {
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
     let out_path = out_dir.path().join("out.par");
     // We need to pass the output path as a string argument.
     let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    let query = &format!("SELECT a FROM {};", table_name);
-
+    // A missing table is not transient, so the test fails at once, rather than waiting an hour.
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
-            "-vvvv",
             "query",
-            "--encoding",
-            "utf16",
             "--connection-string",
             MSSQL,
+            "--retries",
+            "3",
+            "--retry-backoff",
+            "1h",
             out_str,
-            query,
+            "SELECT * FROM TableWhichDoesNotExist",
         ])
+        .timeout(std::time::Duration::from_secs(60))
         .assert()
-        .success();
-
-    let expected = "{a: \"Ü\"}\n";
-
-    parquet_read_out(out_str).stdout(eq(expected));
+        .failure()
+        .stderr(contains("42S02"));
 }
 
-/// Test non ASCII character with automatic codec detection
 #[test]
-fn auto_encoding() {
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    let table_name = "AutoEncoding";
-    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(1)"]).unwrap();
-
-    conn.execute(
-        &format!("INSERT INTO {} (a) VALUES (?);", table_name),
-        &"Ü".into_parameter(),
-    )
-    .unwrap();
-
+fn export_within_transaction() {
     // A temporary directory, to be removed at the end of the test.
     let out_dir = tempdir().unwrap();
-    // The name of the output parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
     let out_path = out_dir.path().join("out.par");
     // We need to pass the output path as a string argument.
     let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    let query = &format!("SELECT a FROM {};", table_name);
-
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
             "query",
-            "--encoding",
-            "auto",
             "--connection-string",
             MSSQL,
+            "--isolation-level",
+            "serializable",
             out_str,
-            query,
+            "SELECT 42 AS a",
         ])
         .assert()
-        .success();
-
-    let expected = "{a: \"Ü\"}\n";
+        .success()
+        .stderr(contains("SET TRANSACTION ISOLATION LEVEL SERIALIZABLE"));
 
-    parquet_read_out(out_str).stdout(eq(expected));
+    parquet_read_out(out_str).stdout(eq("{a: 42}\n"));
 }
 
 #[test]
-pub fn insert_32_bit_integer() {
-    let table_name = "Insert32BitInteger";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
-
-    // Prepare file
-
+fn execute_query_asynchronously() {
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            REQUIRED INT32 a;
-        }
-    ";
-
-    write_values_to_file(message_type, &input_path, &[42i32, 5, 1], None);
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--async-execution",
+            out_str,
+            "SELECT CAST(? AS INT) AS a",
+            "42",
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!("42\n5\n1", actual);
+    parquet_read_out(out_str).stdout(eq("{a: 42}\n"));
 }
 
 #[test]
-pub fn insert_optional_32_bit_integer() {
-    let table_name = "InsertOptional32BitInteger";
-    // Prepare table
+fn append_to_file_sequence() {
+    // Setup table for test
+    let table_name = "AppendToFileSequence";
     let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
     setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
-
-    // Prepare file
-
+    let insert = format!("INSERT INTO {table_name} (a) VALUES (1), (2);");
+    conn.execute(&insert, ()).unwrap();
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+    let query = format!("SELECT a FROM {table_name} ORDER BY a");
 
-    let message_type = "
-        message schema {
-            OPTIONAL INT32 a;
-        }
-    ";
+    // Export twice. The second export continues the numbering of the first one.
+    for _ in 0..2 {
+        Command::cargo_bin("odbc2parquet")
+            .unwrap()
+            .args(&[
+                "-vvvv",
+                "query",
+                "--connection-string",
+                MSSQL,
+                "--batch-size-row",
+                "1",
+                "--row-groups-per-file",
+                "1",
+                "--append",
+                out_str,
+                &query,
+            ])
+            .assert()
+            .success();
+    }
 
-    write_values_to_file(message_type, &input_path, &[42i32, 1], Some(&[1, 0, 1]));
+    parquet_read_out(out_dir.path().join("out_01.par").to_str().unwrap()).stdout(eq("{a: 1}\n"));
+    parquet_read_out(out_dir.path().join("out_04.par").to_str().unwrap()).stdout(eq("{a: 2}\n"));
+    assert!(!out_dir.path().join("out_05.par").exists());
+}
+
+#[test]
+fn export_schema() {
+    // Setup tables for test
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    for (table_name, value) in [("ExportSchemaA", 1), ("ExportSchemaB", 2)] {
+        setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
+        let insert = format!("INSERT INTO {table_name} (a) VALUES ({value});");
+        conn.execute(&insert, ()).unwrap();
+    }
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("{schema}_{table}.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "export-schema",
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--include",
+            "dbo.ExportSchema?",
+            "--parallel",
+            "2",
+            out_str,
+            "--",
+            "--column-compression-default",
+            "zstd",
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!("42\nNULL\n1", actual);
+    let a = out_dir.path().join("dbo_ExportSchemaA.par");
+    parquet_read_out(a.to_str().unwrap()).stdout(eq("{id: 1, a: 1}\n"));
+    let b = out_dir.path().join("dbo_ExportSchemaB.par");
+    parquet_read_out(b.to_str().unwrap()).stdout(eq("{id: 1, a: 2}\n"));
 }
 
 #[test]
-pub fn insert_64_bit_integer() {
-    let table_name = "Insert64BitInteger";
-    // Prepare table
+fn write_catalog_metadata() {
+    // Setup table for test
+    let table_name = "WriteCatalogMetadata";
     let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
-
-    // Prepare file
-
+    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(10)"]).unwrap();
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            REQUIRED INT64 a;
-        }
-    ";
-
-    write_values_to_file(message_type, &input_path, &[-42i64, 5, 1], None);
+    let out_dir = tempdir().unwrap();
+    // We need to pass the output path as a string argument.
+    let out_str = out_dir
+        .path()
+        .to_str()
+        .expect("Temporary file path must be utf8");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "catalog",
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
+            "--table",
             table_name,
+            out_str,
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!("-42\n5\n1", actual);
+    let tables = out_dir.path().join("tables.par");
+    parquet_read_out(tables.to_str().unwrap())
+        .stdout(contains("TABLE_NAME: \"WriteCatalogMetadata\""));
+    let columns = out_dir.path().join("columns.par");
+    parquet_read_out(columns.to_str().unwrap())
+        .stdout(contains("COLUMN_NAME: \"id\""))
+        .stdout(contains("COLUMN_NAME: \"a\""));
 }
 
 #[test]
-pub fn insert_optional_64_bit_integer() {
-    let table_name = "InsertOptional64BitInteger";
-    // Prepare table
+fn capture_output_parameters() {
     let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["BIGINT"]).unwrap();
-
-    // Prepare file
-
+    conn.execute(
+        "CREATE OR ALTER PROCEDURE CaptureOutputParameters @factor INT, @total INT OUTPUT AS \
+        BEGIN SELECT 1 AS a; SET @total = 21 * @factor; RETURN 7; END",
+        (),
+    )
+    .unwrap();
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            OPTIONAL INT64 a;
-        }
-    ";
-
-    write_values_to_file(message_type, &input_path, &[-42i64, 1], Some(&[1, 0, 1]));
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+    let parameters_path = out_dir.path().join("parameters.json");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--output-parameter",
+            "1=return_code",
+            "--output-parameter",
+            "3=total",
+            "--output-parameters-file",
+            parameters_path.to_str().unwrap(),
+            out_str,
+            "{? = call CaptureOutputParameters(?, ?)}",
+            "2",
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!("-42\nNULL\n1", actual);
+    parquet_read_out(out_str).stdout(eq("{a: 1}\n"));
+    let parameters = std::fs::read_to_string(&parameters_path).unwrap();
+    let parameters: serde_json::Value = serde_json::from_str(&parameters).unwrap();
+    assert_eq!(
+        serde_json::json!({"return_code": "7", "total": "42"}),
+        parameters
+    );
 }
 
 #[test]
-pub fn insert_utf8() {
-    let table_name = "InsertUtf8";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(50)"]).unwrap();
-
-    // Prepare file
-
+fn reorder_columns() {
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            REQUIRED BYTE_ARRAY a (UTF8);
-        }
-    ";
-
-    let text: ByteArray = "Hello, World!".into();
-    write_values_to_file(
-        message_type,
-        &input_path,
-        &[text, "Hallo, Welt!".into(), "Bonjour, Monde!".into()],
-        None,
-    );
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--column-order",
+            "c,a",
+            "SELECT 1 AS a, 2 AS b, 3 AS c",
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!("Hello, World!\nHallo, Welt!\nBonjour, Monde!", actual);
+    parquet_read_out(out_str).stdout(eq("{c: 3, a: 1, b: 2}\n"));
 }
 
 #[test]
-pub fn insert_optional_utf8() {
-    let table_name = "InsertOptionalUtf8";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(50)"]).unwrap();
-
-    // Prepare file
-
+fn null_value_column_must_be_part_of_result_set() {
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            OPTIONAL BYTE_ARRAY a (UTF8);
-        }
-    ";
-
-    let text: ByteArray = "Hello, World!".into();
-    write_values_to_file(
-        message_type,
-        &input_path,
-        &[text, "Hallo, Welt!".into()],
-        Some(&[1, 0, 1]),
-    );
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--null-value",
+            "c=-9999",
+            "SELECT 42 AS a",
         ])
         .assert()
-        .success();
-
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!("Hello, World!\nNULL\nHallo, Welt!", actual);
+        .failure()
+        .stderr(contains(
+            "Null value column 'c' is not part of the result set.",
+        ));
 }
 
 #[test]
-pub fn insert_utf16() {
-    let table_name = "InsertUtf16";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(50)"]).unwrap();
-
-    // Prepare file
-
+fn query_real_as_float16() {
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            REQUIRED BYTE_ARRAY a (UTF8);
-        }
-    ";
-
-    let text: ByteArray = "Hello, World!".into();
-    write_values_to_file(
-        message_type,
-        &input_path,
-        &[text, "Hallo, Welt!".into(), "Bonjour, Monde!".into()],
-        None,
-    );
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            "--encoding",
-            "Utf16",
-            input_path.to_str().unwrap(),
-            table_name,
+            "--real-as-float16",
+            "SELECT CAST(1.5 AS REAL) AS a, CAST(NULL AS REAL) AS b",
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!("Hello, World!\nHallo, Welt!\nBonjour, Monde!", actual);
+    parquet_read_out(out_str).stdout(eq("{a: 1.5, b: null}\n"));
+    parquet_schema_out(out_str)
+        .stdout(contains("FIXED_LEN_BYTE_ARRAY (2) a (FLOAT16);"))
+        .stdout(contains("FIXED_LEN_BYTE_ARRAY (2) b (FLOAT16);"));
 }
 
 #[test]
-pub fn insert_optional_utf16() {
-    let table_name = "InsertOptionalUtf16";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(50)"]).unwrap();
-
-    // Prepare file
-
+fn query_guid_as_uuid() {
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            OPTIONAL BYTE_ARRAY a (UTF8);
-        }
-    ";
-
-    let text: ByteArray = "Hello, World!".into();
-    write_values_to_file(
-        message_type,
-        &input_path,
-        &[text, "Hallo, Welt!".into()],
-        Some(&[1, 0, 1]),
-    );
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            "--encoding",
-            "Utf16",
-            input_path.to_str().unwrap(),
-            table_name,
+            "SELECT CAST('6F9619FF-8B86-D011-B42D-00C04FC964FF' AS UNIQUEIDENTIFIER) AS a",
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!("Hello, World!\nNULL\nHallo, Welt!", actual);
+    parquet_schema_out(out_str).stdout(contains("FIXED_LEN_BYTE_ARRAY (16) a (UUID);"));
 }
 
 #[test]
-pub fn insert_bool() {
-    let table_name = "InsertBool";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["BIT"]).unwrap();
-
-    // Prepare file
-
+fn query_guid_as_text() {
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            REQUIRED BOOLEAN a;
-        }
-    ";
-
-    write_values_to_file(message_type, &input_path, &[true, false, false], None);
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--guid-as-text",
+            "SELECT CAST('6F9619FF-8B86-D011-B42D-00C04FC964FF' AS UNIQUEIDENTIFIER) AS a",
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!("1\n0\n0", actual);
+    parquet_read_out(out_str).stdout(eq("{a: \"6F9619FF-8B86-D011-B42D-00C04FC964FF\"}\n"));
 }
 
 #[test]
-pub fn insert_optional_bool() {
-    let table_name = "InsertOptionalBool";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["BIT"]).unwrap();
-
-    // Prepare file
-
+fn query_interval_postgres() {
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            OPTIONAL BOOLEAN a;
-        }
-    ";
-
-    write_values_to_file(message_type, &input_path, &[true, false], Some(&[1, 0, 1]));
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+    // Render intervals in the format of SQL intervals, rather than e.g. `3 days 04:05:06.5`.
+    let connection_string = format!("{POSTGRES}ConnSettings=SET intervalstyle TO sql_standard;");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
-            MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            &connection_string,
+            "SELECT CAST('3 04:05:06.5' AS INTERVAL DAY TO SECOND) AS a",
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!("1\nNULL\n0", actual);
+    parquet_schema_out(out_str).stdout(contains("FIXED_LEN_BYTE_ARRAY (12) a (INTERVAL);"));
 }
 
 #[test]
-pub fn insert_f32() {
-    let table_name = "InsertF32";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["FLOAT"]).unwrap();
-
-    // Prepare file
-
+fn query_interval_as_iso8601_postgres() {
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            REQUIRED FLOAT a;
-        }
-    ";
-
-    write_values_to_file(message_type, &input_path, &[1.2f32, 3.4, 5.6], None);
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+    // Render intervals in the format of SQL intervals, rather than e.g. `3 days 04:05:06.5`.
+    let connection_string = format!("{POSTGRES}ConnSettings=SET intervalstyle TO sql_standard;");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
-            MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            &connection_string,
+            "--intervals-as",
+            "iso8601",
+            "SELECT CAST('-3 04:05:06.5' AS INTERVAL DAY TO SECOND) AS a",
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!(
-        "1.2000000476837158\n3.4000000953674316\n5.5999999046325684",
-        actual
-    );
+    parquet_read_out(out_str).stdout(eq("{a: \"-P3DT4H5M6.5S\"}\n"));
 }
 
 #[test]
-pub fn insert_optional_f32() {
-    let table_name = "InsertOptionalF32";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["FLOAT"]).unwrap();
-
-    // Prepare file
-
+fn query_numeric_50_5_postgres() {
+    // Setup table for test
+    let table_name = "QueryNumeric50_5";
+    let conn = ENV.connect_with_connection_string(POSTGRES).unwrap();
+    setup_empty_table_pg(&conn, table_name, &["NUMERIC(50,5) NOT NULL"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {table_name}
+        (a)
+        VALUES
+        (-123456789012345678901234567890123456789012345.12345);"
+    );
+    conn.execute(&insert, ()).unwrap();
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            OPTIONAL FLOAT a;
-        }
-    ";
-
-    write_values_to_file(message_type, &input_path, &[1.2f32, 3.4], Some(&[1, 0, 1]));
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+    let query = format!("SELECT a FROM {table_name};");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
-            MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            POSTGRES,
+            &query,
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
+    let expected_values = "{a: -123456789012345678901234567890123456789012345.12345}\n";
+    parquet_read_out(out_str).stdout(eq(expected_values));
 
-    assert_eq!("1.2000000476837158\nNULL\n3.4000000953674316", actual);
+    parquet_schema_out(out_str).stdout(contains(
+        "{\n  REQUIRED FIXED_LEN_BYTE_ARRAY (21) a (DECIMAL(50,5));\n}",
+    ));
 }
 
 #[test]
-pub fn insert_f64() {
-    let table_name = "InsertF64";
-    // Prepare table
+fn query_timestamp_with_timezone_mssql() {
+    // Setup table for test
+    let table_name = "QueryTimestampWithTimezone";
     let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["FLOAT(53)"]).unwrap();
-
-    // Prepare file
-
+    // ODBC data type: SqlDataType(-155), column_size: 34, decimal_digits: 7
+    setup_empty_table_mssql(&conn, table_name, &["DATETIMEOFFSET"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {}
+        (a)
+        VALUES
+        ('2022-09-07 16:04:12 +02:00');",
+        table_name
+    );
+    conn.execute(&insert, ()).unwrap();
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
     // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            REQUIRED DOUBLE a;
-        }
-    ";
-
-    write_values_to_file(message_type, &input_path, &[1.2f64, 3.4, 5.6], None);
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+    let query = format!("SELECT a FROM {table_name};");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            &query,
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
+    let expected_values = "{a: 2022-09-07 14:04:12 +00:00}\n";
+    parquet_read_out(out_str).stdout(eq(expected_values));
 
-    assert_eq!("1.2\n3.3999999999999999\n5.5999999999999996", actual);
+    parquet_schema_out(out_str).stdout(contains("OPTIONAL INT64 a (TIMESTAMP(MICROS,true));"));
 }
 
 #[test]
-pub fn insert_optional_f64() {
-    let table_name = "InsertOptionalF64";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["FLOAT(53)"]).unwrap();
-
-    // Prepare file
+fn query_timestamp_as_int96_mssql() {
+    use parquet::{column::reader::get_typed_column_reader, data_type::Int96Type};
 
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            OPTIONAL DOUBLE a;
-        }
-    ";
-
-    write_values_to_file(message_type, &input_path, &[1.2f64, 3.4], Some(&[1, 0, 1]));
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--timestamps-as",
+            "int96",
+            "SELECT CAST('2022-09-07 16:04:12.1234567' AS DATETIME2) AS a",
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!("1.2\nNULL\n3.3999999999999999", actual);
+    parquet_schema_out(out_str).stdout(contains("INT96 a;"));
+    let reader = SerializedFileReader::new(File::open(&out_path).unwrap()).unwrap();
+    let column_reader = reader
+        .get_row_group(0)
+        .unwrap()
+        .get_column_reader(0)
+        .unwrap();
+    let mut values = Vec::new();
+    get_typed_column_reader::<Int96Type>(column_reader)
+        .read_records(1, None, None, &mut values)
+        .unwrap();
+    assert_eq!(1_662_566_652_123_456_700, values[0].to_nanos());
 }
 
 #[test]
-pub fn insert_date() {
-    let table_name = "InsertDate";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["DATE"]).unwrap();
-
-    // Prepare file
-
+fn query_datetimeoffset_offset_minutes() {
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            REQUIRED INT32 a (DATE);
-        }
-    ";
-
-    write_values_to_file(message_type, &input_path, &[0i32, 365, 18695], None);
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
-            "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--datetimeoffset-offset-minutes",
+            "SELECT CAST('2022-09-07 16:04:12 +02:00' AS DATETIMEOFFSET(0)) AS a, 42 AS b \
+            UNION ALL SELECT CAST('2022-09-07 16:04:12 -05:30' AS DATETIMEOFFSET(0)), 43",
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!("1970-01-01\n1971-01-01\n2021-03-09", actual);
+    parquet_schema_out(out_str).stdout(contains("INT32 a_offset_minutes (INT_16);"));
+    let expected_values = "\
+        {a: 2022-09-07 14:04:12 +00:00, a_offset_minutes: 120, b: 42}\n\
+        {a: 2022-09-07 21:34:12 +00:00, a_offset_minutes: -330, b: 43}\n\
+    ";
+    parquet_read_out(out_str).stdout(eq(expected_values));
 }
 
 #[test]
-pub fn insert_optional_date() {
-    let table_name = "InsertOptionalDate";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["Date"]).unwrap();
-
-    // Prepare file
-
+fn query_timestamp_precision_override() {
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            OPTIONAL INT32 a (DATE);
-        }
-    ";
-
-    write_values_to_file(message_type, &input_path, &[0i32, 18695], Some(&[1, 0, 1]));
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
-            "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--timestamp-precision",
+            "a:millis",
+            "--timestamp-precision",
+            "b:nanos",
+            "SELECT CAST('2022-09-07 16:04:12.1234567' AS DATETIME2(7)) AS a, \
+            CAST('2022-09-07 16:04:12.1234567' AS DATETIME2(7)) AS b, \
+            CAST('2022-09-07 16:04:12.1234567' AS DATETIME2(7)) AS c",
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!("1970-01-01\nNULL\n2021-03-09", actual);
+    parquet_schema_out(out_str)
+        .stdout(contains("INT64 a (TIMESTAMP(MILLIS,false));"))
+        .stdout(contains("INT64 b (TIMESTAMP(NANOS,false));"))
+        .stdout(contains("INT64 c (TIMESTAMP(MICROS,false));"));
 }
 
 #[test]
-pub fn insert_time_ms() {
-    let table_name = "InsertTimeMs";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["TIME(3)"]).unwrap();
-
-    // Prepare file
+fn timestamp_precision_of_unknown_column() {
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            "out.par",
+            "--connection-string",
+            MSSQL,
+            "--timestamp-precision",
+            "created_at:millis",
+            "SELECT CAST('2022-09-07 16:04:12' AS DATETIME2) AS a",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Timestamp precision column 'created_at' is not part of the result set.",
+        ));
+}
 
+#[test]
+fn query_timestamp_tz_utc() {
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            REQUIRED INT32 a (TIME_MILLIS);
-        }
-    ";
-
-    // Total number of milli seconds since midnight
-    write_values_to_file(
-        message_type,
-        &input_path,
-        &[0i32, 3_600_000, 82_800_000],
-        None,
-    );
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
-            "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--timestamp-tz",
+            "utc",
+            "SELECT CAST('2022-09-07 16:04:12' AS DATETIME2(3)) AS a",
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!("00:00:00.000\n01:00:00.000\n23:00:00.000", actual);
+    parquet_schema_out(out_str).stdout(contains("INT64 a (TIMESTAMP(MILLIS,true));"));
+    parquet_read_out(out_str).stdout(eq("{a: 2022-09-07 16:04:12 +00:00}\n"));
 }
 
 #[test]
-pub fn insert_optional_time_ms() {
-    let table_name = "InsertOptionalTimeMs";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["TIME(3)"]).unwrap();
-
-    // Prepare file
-
+fn query_timestamp_tz_session() {
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            OPTIONAL INT32 a (TIME_MILLIS);
-        }
-    ";
-
-    // Total number of milli seconds since midnight
-    write_values_to_file(
-        message_type,
-        &input_path,
-        &[0i32, 82_800_000],
-        Some(&[1, 0, 1]),
-    );
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
-            "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--timestamp-tz",
+            "session",
+            "--session-time-zone",
+            "+02:00",
+            "SELECT CAST('2022-09-07 16:04:12' AS DATETIME2(3)) AS a",
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!("00:00:00.000\nNULL\n23:00:00.000", actual);
+    parquet_schema_out(out_str).stdout(contains("INT64 a (TIMESTAMP(MILLIS,true));"));
+    parquet_read_out(out_str).stdout(eq("{a: 2022-09-07 14:04:12 +00:00}\n"));
 }
 
 #[test]
-pub fn insert_time_us() {
-    let table_name = "InsertTimeUs";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["TIME(6)"]).unwrap();
+fn session_time_zone_requires_timestamp_tz_session() {
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            "out.par",
+            "--connection-string",
+            MSSQL,
+            "--session-time-zone",
+            "+02:00",
+            "SELECT CAST('2022-09-07 16:04:12' AS DATETIME2(3)) AS a",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "session-time-zone requires `--timestamp-tz session`.",
+        ));
+}
 
-    // Prepare file
+#[test]
+fn converted_types_only() {
+    use parquet::basic::ConvertedType;
 
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            REQUIRED INT64 a (TIME_MICROS);
-        }
-    ";
-
-    // Total number of milli seconds since midnight
-    write_values_to_file(
-        message_type,
-        &input_path,
-        &[0i64, 3_600_000_000, 82_800_000_000],
-        None,
-    );
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--converted-types-only",
+            "SELECT CAST('2022-09-07 16:04:12.123' AS DATETIME2(3)) AS a, \
+            CAST('Hello' AS VARCHAR(10)) AS b, CAST(1.23 AS DECIMAL(9,2)) AS c",
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!("00:00:00.000000\n01:00:00.000000\n23:00:00.000000", actual);
+    let reader = SerializedFileReader::new(File::open(&out_path).unwrap()).unwrap();
+    let schema = reader.metadata().file_metadata().schema_descr();
+    let converted_types: Vec<_> = schema
+        .columns()
+        .iter()
+        .map(|column| {
+            assert_eq!(None, column.logical_type_ref());
+            column.converted_type()
+        })
+        .collect();
+    assert_eq!(
+        vec![
+            ConvertedType::TIMESTAMP_MILLIS,
+            ConvertedType::UTF8,
+            ConvertedType::DECIMAL
+        ],
+        converted_types
+    );
 }
 
 #[test]
-pub fn insert_optional_time_us() {
-    let table_name = "InsertOptionalTimeUs";
-    // Prepare table
+fn query_timestamp_mssql() {
+    // Setup table for test
+    let table_name = "QueryTimestamp";
     let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["TIME(6)"]).unwrap();
-
-    // Prepare file
-
+    setup_empty_table_mssql(&conn, table_name, &["DATETIME2"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {}
+        (a)
+        VALUES
+        ('2022-09-07 16:04:12');",
+        table_name
+    );
+    conn.execute(&insert, ()).unwrap();
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
     // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            OPTIONAL INT64 a (TIME_MICROS);
-        }
-    ";
-
-    // Total number of milli seconds since midnight
-    write_values_to_file(
-        message_type,
-        &input_path,
-        &[0i64, 82_800_000_000],
-        Some(&[1, 0, 1]),
-    );
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+    let query = format!("SELECT a FROM {table_name};");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            &query,
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
+    let expected_values = "{a: 2022-09-07 16:04:12 +00:00}\n";
+    parquet_read_out(out_str).stdout(eq(expected_values));
 
-    assert_eq!("00:00:00.000000\nNULL\n23:00:00.000000", actual);
+    parquet_schema_out(out_str).stdout(contains("OPTIONAL INT64 a (TIMESTAMP(MICROS,false));"));
 }
 
 #[test]
-pub fn insert_decimal_from_i32() {
-    let table_name = "InsertDecimalFromI32";
-    // Prepare table
+fn query_timestamp_ms_with_timezone_mssql() {
+    // Setup table for test
+    let table_name = "QueryTimestampMsWithTimezone";
     let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["DECIMAL(9,2)"]).unwrap();
-
-    // Prepare file
-
+    // ODBC data type: SqlDataType(-155), decimal_digits: 3
+    setup_empty_table_mssql(&conn, table_name, &["DATETIMEOFFSET(3)"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {}
+        (a)
+        VALUES
+        ('2022-09-07 16:04:12 +02:00');",
+        table_name
+    );
+    conn.execute(&insert, ()).unwrap();
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
     // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            REQUIRED INT32 a (DECIMAL(9,2));
-        }
-    ";
-
-    // Total number of milli seconds since midnight
-    write_values_to_file(message_type, &input_path, &[0i32, 123456789, -42], None);
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+    let query = format!("SELECT a FROM {table_name};");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            &query,
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
+    let expected_values = "{a: 2022-09-07 14:04:12 +00:00}\n";
+    parquet_read_out(out_str).stdout(eq(expected_values));
 
-    assert_eq!(".00\n1234567.89\n-.42", actual);
+    parquet_schema_out(out_str).stdout(contains("OPTIONAL INT64 a (TIMESTAMP(MILLIS,true));"));
 }
 
 #[test]
-pub fn insert_decimal_from_i32_optional() {
-    let table_name = "InsertDecimalFromI32Optional";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["DECIMAL(9,2)"]).unwrap();
-
-    // Prepare file
-
+fn query_timestamp_with_timezone_postgres() {
+    // Setup table for test
+    let table_name = "QueryTimestampWithTimezone";
+    let conn = ENV.connect_with_connection_string(POSTGRES).unwrap();
+    setup_empty_table_pg(&conn, table_name, &["TIMESTAMPTZ"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {}
+        (a)
+        VALUES
+        ('2022-09-07 16:04:12 +02:00');",
+        table_name
+    );
+    conn.execute(&insert, ()).unwrap();
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
     // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            OPTIONAL INT32 a (DECIMAL(9,2));
-        }
-    ";
-
-    // Total number of milli seconds since midnight
-    write_values_to_file(message_type, &input_path, &[0i32, -42], Some(&[1, 0, 1]));
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+    let query = format!("SELECT a FROM {table_name};");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
-            MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            POSTGRES,
+            &query,
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
+    let expected_values = "{a: 2022-09-07 14:04:12 +00:00}\n";
+    parquet_read_out(out_str).stdout(eq(expected_values));
 
-    assert_eq!(".00\nNULL\n-.42", actual);
+    parquet_schema_out(out_str).stdout(contains("OPTIONAL INT64 a (TIMESTAMP(MICROS,false));"));
 }
 
 #[test]
-pub fn insert_decimal_from_i64() {
-    let table_name = "InsertDecimalFromI64";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["DECIMAL(9,2)"]).unwrap();
-
-    // Prepare file
-
+fn query_timestamp_postgres() {
+    // Setup table for test
+    let table_name = "QueryTimestamp";
+    let conn = ENV.connect_with_connection_string(POSTGRES).unwrap();
+    setup_empty_table_pg(&conn, table_name, &["TIMESTAMP"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {}
+        (a)
+        VALUES
+        ('2022-09-07 16:04:12');",
+        table_name
+    );
+    conn.execute(&insert, ()).unwrap();
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
     // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            REQUIRED INT64 a (DECIMAL(9,2));
-        }
-    ";
-
-    // Total number of milli seconds since midnight
-    write_values_to_file(message_type, &input_path, &[0i64, 123456789, -42], None);
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+    let query = format!("SELECT a FROM {table_name};");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
-            MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            POSTGRES,
+            &query,
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
+    let expected_values = "{a: 2022-09-07 16:04:12 +00:00}\n";
+    parquet_read_out(out_str).stdout(eq(expected_values));
 
-    assert_eq!(".00\n1234567.89\n-.42", actual);
+    parquet_schema_out(out_str).stdout(contains("OPTIONAL INT64 a (TIMESTAMP(MICROS,false));"));
 }
 
 #[test]
-pub fn insert_decimal_from_i64_optional() {
-    let table_name = "InsertDecimalFromI64Optional";
-    // Prepare table
+fn query_all_the_types() {
+    // Setup table for test
+    let table_name = "AllTheTypes";
     let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["DECIMAL(9,2)"]).unwrap();
+    setup_empty_table_mssql(
+        &conn,
+        table_name,
+        &[
+            "CHAR(5) NOT NULL",
+            "NUMERIC(3,2) NOT NULL",
+            "DECIMAL(3,2) NOT NULL",
+            "INTEGER NOT NULL",
+            "SMALLINT NOT NULL",
+            "FLOAT(3) NOT NULL",
+            "REAL NOT NULL",
+            "DOUBLE PRECISION NOT NULL",
+            "VARCHAR(100) NOT NULL",
+            "DATE NOT NULL",
+            "TIME NOT NULL",
+            "DATETIME NOT NULL",
+        ],
+    )
+    .unwrap();
+    let insert = format!(
+        "INSERT INTO {}
+        (a,b,c,d,e,f,g,h,i,j,k,l)
+        VALUES
+        ('abcde', 1.23, 1.23, 42, 42, 1.23, 1.23, 1.23, 'Hello, World!', '2020-09-16', '03:54:12', '2020-09-16 03:54:12');",
+        table_name
+    );
+    conn.execute(&insert, ()).unwrap();
 
-    // Prepare file
+    let expected_values = "{\
+        a: \"abcde\", \
+        b: 1.23, \
+        c: 1.23, \
+        d: 42, \
+        e: 42, \
+        f: 1.23, \
+        g: 1.23, \
+        h: 1.23, \
+        i: \"Hello, World!\", \
+        j: 2020-09-16 +00:00, \
+        k: \"03:54:12.0000000\", \
+        l: 2020-09-16 03:54:12 +00:00\
+    }\n";
 
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
     // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            OPTIONAL INT64 a (DECIMAL(9,2));
-        }
-    ";
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Total number of milli seconds since midnight
-    write_values_to_file(message_type, &input_path, &[0i64, -42], Some(&[1, 0, 1]));
+    let query = format!("SELECT a,b,c,d,e,f,g,h,i,j,k,l FROM {};", table_name);
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--time-as-text",
+            &query,
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!(".00\nNULL\n-.42", actual);
+    parquet_read_out(out_str).stdout(eq(expected_values));
 }
 
 #[test]
-pub fn insert_timestamp_ms() {
-    let table_name = "InsertTimestampMs";
-    // Prepare table
+fn query_bits() {
+    // Setup table for test
+    let table_name = "QueryBits";
     let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["DATETIME2"]).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["BIT"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {}
+        (a)
+        VALUES
+        (0), (1), (NULL), (1), (0);",
+        table_name
+    );
+    conn.execute(&insert, ()).unwrap();
 
-    // Prepare file
+    let expected_values = "\
+        {a: false}\n\
+        {a: true}\n\
+        {a: null}\n\
+        {a: true}\n\
+        {a: false}\n\
+    ";
 
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
     // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            REQUIRED INT64 a (TIMESTAMP_MILLIS);
-        }
-    ";
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Total number of milli seconds since unix epoch
-    write_values_to_file(message_type, &input_path, &[0i64, 1, 1616367053000], None);
+    let query = format!("SELECT a FROM {};", table_name);
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            &query,
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!(
-        "1970-01-01 00:00:00.0000000\n1970-01-01 00:00:00.0010000\n2021-03-21 22:50:53.0000000",
-        actual
-    );
-}
+    parquet_read_out(out_str).stdout(eq(expected_values));
+}
 
 #[test]
-pub fn insert_timestamp_ms_optional() {
-    let table_name = "InsertTimestampMsOptional";
-    // Prepare table
+fn query_doubles() {
+    // Setup table for test
+    let table_name = "QueryDoubles";
     let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["DATETIME2"]).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["DOUBLE PRECISION NOT NULL"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {}
+        (a)
+        VALUES
+        (0.1), (2.3);",
+        table_name
+    );
+    conn.execute(&insert, ()).unwrap();
 
-    // Prepare file
+    let expected_values = "\
+        {a: 0.1}\n\
+        {a: 2.3}\n\
+    ";
+
+    let expected_schema = "REQUIRED DOUBLE a;";
 
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
     // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            OPTIONAL INT64 a (TIMESTAMP_MILLIS);
-        }
-    ";
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Total number of milli seconds since unix epoch
-    write_values_to_file(
-        message_type,
-        &input_path,
-        &[0i64, 1616367053000],
-        Some(&[1, 0, 1]),
-    );
+    let query = format!("SELECT a FROM {};", table_name);
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            &query,
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
+    parquet_read_out(out_str).stdout(eq(expected_values));
 
-    assert_eq!(
-        "1970-01-01 00:00:00.0000000\nNULL\n2021-03-21 22:50:53.0000000",
-        actual
-    );
+    // Also verify schema to ensure f64 is choosen and not f32
+    parquet_schema_out(out_str).stdout(contains(expected_schema));
 }
 
+/// Should read query from standard input if "-" is provided as query text.
 #[test]
-pub fn insert_timestamp_us() {
-    let table_name = "InsertTimestampUs";
-    // Prepare table
+fn read_query_from_stdin() {
+    // Setup table for test
+    let table_name = "ReadQueryFromStdin";
     let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["DATETIME2"]).unwrap();
-
-    // Prepare file
+    setup_empty_table_mssql(&conn, table_name, &["INT"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {}
+        (a)
+        VALUES
+        (54),
+        (54),
+        (34),
+        (12);",
+        table_name
+    );
+    conn.execute(&insert, ()).unwrap();
 
+    let expected_values = "\
+        {a: 54}\n\
+        {a: 54}\n\
+        {a: 34}\n\
+        {a: 12}\n\
+    ";
+    let query = format!("SELECT a FROM {} ORDER BY id", table_name);
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
     // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            REQUIRED INT64 a (TIMESTAMP_MICROS);
-        }
-    ";
-
-    // Total number of milli seconds since unix epoch
-    write_values_to_file(
-        message_type,
-        &input_path,
-        &[0i64, 1, 1616367053000000],
-        None,
-    );
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Tempfile path must be utf8");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
-        .args(&[
-            "-vvvv",
-            "insert",
-            "--connection-string",
-            MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
-        ])
+        .args(&["-vvvv", "query", out_str, "--connection-string", MSSQL, "-"])
+        .write_stdin(query)
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!(
-        "1970-01-01 00:00:00.0000000\n1970-01-01 00:00:00.0000010\n2021-03-21 22:50:53.0000000",
-        actual
-    );
+    parquet_read_out(out_str).stdout(eq(expected_values));
 }
 
 #[test]
-pub fn insert_timestamp_us_optional() {
-    let table_name = "InsertTimestampUsOptional";
-    // Prepare table
+fn split_files_on_num_row_groups() {
+    // Setup table for test
+    let table_name = "SplitFilesOnNumRowGroups";
     let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["DATETIME2"]).unwrap();
-
-    // Prepare file
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
+    let insert = format!("INSERT INTO {} (A) VALUES(1),(2),(3)", table_name);
+    conn.execute(&insert, ()).unwrap();
 
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
     // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            OPTIONAL INT64 a (TIMESTAMP_MICROS);
-        }
-    ";
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Total number of milli seconds since unix epoch
-    write_values_to_file(
-        message_type,
-        &input_path,
-        &[0i64, 1616367053000000],
-        Some(&[1, 0, 1]),
-    );
+    let query = format!("SELECT a FROM {}", table_name);
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--batch-size-row",
+            "1",
+            "--row-groups-per-file",
+            "1",
+            &query,
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
+    // Expect one file per row in table (3)
 
-    assert_eq!(
-        "1970-01-01 00:00:00.0000000\nNULL\n2021-03-21 22:50:53.0000000",
-        actual
-    );
+    parquet_read_out(out_dir.path().join("out_01.par").to_str().unwrap());
+    parquet_read_out(out_dir.path().join("out_02.par").to_str().unwrap());
+    parquet_read_out(out_dir.path().join("out_03.par").to_str().unwrap());
 }
 
 #[test]
-pub fn insert_binary() {
-    let table_name = "InsertBinary";
-    // Prepare table
+fn fetch_concurrently() {
+    // Setup table for test
+    let table_name = "FetchConcurrently";
     let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["VARBINARY(50)"]).unwrap();
-
-    // Prepare file
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER", "VARCHAR(10)"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {} (a,b) VALUES (1,'one'),(2,'two'),(3,'three'),(4,'four'),(5,NULL)",
+        table_name
+    );
+    conn.execute(&insert, ()).unwrap();
 
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            REQUIRED BYTE_ARRAY a;
-        }
-    ";
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    let text: ByteArray = "Hello, World!".into();
-    write_values_to_file(
-        message_type,
-        &input_path,
-        &[text, "Hallo, Welt!".into(), "Bonjour, Monde!".into()],
-        None,
-    );
+    let query = format!("SELECT a,b FROM {} ORDER BY id", table_name);
 
-    // Insert file into table
+    // More batches than buffers, so each buffer is filled several times.
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--batch-size-row",
+            "2",
+            "--fetch-buffers",
+            "2",
+            &query,
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!(
-        "48656C6C6F2C20576F726C6421\n48616C6C6F2C2057656C7421\n426F6E6A6F75722C204D6F6E646521",
-        actual
-    );
+    parquet_read_out(out_str).stdout(eq("\
+        {a: 1, b: \"one\"}\n\
+        {a: 2, b: \"two\"}\n\
+        {a: 3, b: \"three\"}\n\
+        {a: 4, b: \"four\"}\n\
+        {a: 5, b: null}\n\
+    "));
 }
 
 #[test]
-pub fn insert_binary_optional() {
-    let table_name = "InsertBinaryOptional";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["VARBINARY(50)"]).unwrap();
+fn adaptive_batch_size() {
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Prepare file
+    let query = "SELECT TOP 3000 ROW_NUMBER() OVER (ORDER BY (SELECT NULL)) AS a \
+        FROM sys.all_objects AS x CROSS JOIN sys.all_objects AS y";
 
-    // A temporary directory, to be removed at the end of the test.
+    // The first batch is full, so the buffer grows for the second one.
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--adaptive-batch-size",
+            query,
+        ])
+        .assert()
+        .success()
+        .stderr(contains("Growing batch size to 2048 rows."));
+
+    let reader = SerializedFileReader::new(File::open(&out_path).unwrap()).unwrap();
+    let num_rows: Vec<i64> = reader
+        .metadata()
+        .row_groups()
+        .iter()
+        .map(|row_group| row_group.num_rows())
+        .collect();
+    assert_eq!(vec![1024, 1976], num_rows);
+}
+
+#[test]
+fn spill_columns_larger_than_batch() {
+    // Setup table for test
+    let table_name = "SpillColumnsLargerThanBatch";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(
+        &conn,
+        table_name,
+        &["INTEGER", "VARCHAR(4000)", "VARBINARY(4000)"],
+    )
+    .unwrap();
+    let insert = format!(
+        "INSERT INTO {} (a,b,c) VALUES (1,'one',0x01),(2,NULL,NULL),(3,'three',0x0203)",
+        table_name
+    );
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a,b,c FROM {table_name} ORDER BY id");
+
+    // A single row takes more than 8KiB in the fetch buffer
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--batch-size-memory",
+            "4KiB",
+            &query,
+        ])
+        .assert()
+        .success()
+        .stderr(contains(
+            "Spilling the values of column 'b' into a temporary file.",
+        ))
+        .stderr(contains(
+            "Spilling the values of column 'c' into a temporary file.",
+        ));
+
+    let expected = "{a: 1, b: \"one\", c: [1]}\n\
+        {a: 2, b: null, c: null}\n\
+        {a: 3, b: \"three\", c: [2, 3]}\n";
+    parquet_read_out(out_str).stdout(eq(expected));
+}
+
+#[test]
+fn bench_settings() {
+    // Setup table for test
+    let table_name = "BenchSettings";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER", "VARCHAR(10)"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {} (a,b) VALUES (1,'one'),(2,NULL),(3,'three')",
+        table_name
+    );
+    conn.execute(&insert, ()).unwrap();
+
+    let query = format!("SELECT a,b FROM {table_name}");
+
+    // Two batch sizes times two numbers of fetch buffers, each writing three rows
+    let output = Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "bench",
+            "--connection-string",
+            MSSQL,
+            "--batch-size-row",
+            "1,1024",
+            "--fetch-buffers",
+            "1,2",
+            "--encoding-threads",
+            "1",
+            &query,
+        ])
+        .assert()
+        .success()
+        .stdout(contains("rows/sec"))
+        .get_output()
+        .stdout
+        .clone();
+    let output = String::from_utf8(output).unwrap();
+    let rows: Vec<_> = output
+        .lines()
+        .skip(1)
+        .map(|line| line.split_whitespace().nth(4).unwrap())
+        .collect();
+    assert_eq!(vec!["3", "3", "3", "3"], rows);
+}
+
+#[test]
+fn encode_columns_concurrently() {
+    // Setup table for test
+    let table_name = "EncodeColumnsConcurrently";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER", "VARCHAR(10)", "REAL"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {} (a,b,c) VALUES (1,'one',1.5),(2,NULL,2.5),(3,'three',NULL)",
+        table_name
+    );
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a,b,c FROM {} ORDER BY id", table_name);
+
+    // More threads than columns, and several row groups.
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--batch-size-row",
+            "2",
+            "--encoding-threads",
+            "4",
+            "--column-compression-default",
+            "zstd",
+            &query,
+        ])
+        .assert()
+        .success();
+
+    parquet_read_out(out_str).stdout(eq("\
+        {a: 1, b: \"one\", c: 1.5}\n\
+        {a: 2, b: null, c: 2.5}\n\
+        {a: 3, b: \"three\", c: null}\n\
+    "));
+}
+
+#[test]
+fn export_partitions_in_parallel() {
+    // Setup table for test
+    let table_name = "ExportPartitionsInParallel";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER", "VARCHAR(10)"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {} (a,b) VALUES (1,'one'),(10,'ten')",
+        table_name
+    );
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a,b FROM {}", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--partition-on",
+            "a",
+            "--concurrency",
+            "2",
+            &query,
+        ])
+        .assert()
+        .success();
+
+    // Values from 1 to 10 are split into the ranges 1 to 5 and 6 to 10.
+    let first = out_dir.path().join("out_01.par");
+    parquet_read_out(first.to_str().unwrap()).stdout(eq("{a: 1, b: \"one\"}\n"));
+    let second = out_dir.path().join("out_02.par");
+    parquet_read_out(second.to_str().unwrap()).stdout(eq("{a: 10, b: \"ten\"}\n"));
+}
+
+/// The query is used as a subquery, so its terminating semicolon must be stripped.
+#[test]
+fn export_partitions_of_terminated_query_in_parallel() {
+    // Setup table for test
+    let table_name = "ExportPartitionsOfTerminatedQueryInParallel";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
+    let insert = format!("INSERT INTO {} (a) VALUES (1),(10)", table_name);
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a FROM {};", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--partition-on",
+            "a",
+            "--concurrency",
+            "2",
+            &query,
+        ])
+        .assert()
+        .success();
+
+    let first = out_dir.path().join("out_01.par");
+    parquet_read_out(first.to_str().unwrap()).stdout(eq("{a: 1}\n"));
+    let second = out_dir.path().join("out_02.par");
+    parquet_read_out(second.to_str().unwrap()).stdout(eq("{a: 10}\n"));
+}
+
+#[test]
+fn write_each_result_set_into_its_own_file() {
+    // Setup table for test
+    let table_name = "WriteEachResultSetIntoItsOwnFile";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER", "VARCHAR(10)"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {} (a,b) VALUES (1,'one'),(2,'two')",
+        table_name
+    );
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    // The row count of the update in between is not a result set of its own.
+    let query = format!(
+        "SELECT a FROM {table_name} ORDER BY id; \
+        UPDATE {table_name} SET b = 'zwei' WHERE a = 2; \
+        SELECT b FROM {table_name} ORDER BY id;"
+    );
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&["query", out_str, "--connection-string", MSSQL, &query])
+        .assert()
+        .success();
+
+    parquet_read_out(out_str).stdout(eq("{a: 1}\n{a: 2}\n"));
+    let second = out_dir.path().join("out_02.par");
+    parquet_read_out(second.to_str().unwrap()).stdout(eq("{b: \"one\"}\n{b: \"zwei\"}\n"));
+}
+
+#[test]
+fn split_files_on_size_limit() {
+    // Setup table for test
+    let table_name = "SplitFilesOnSizeLimit";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
+    let insert = format!("INSERT INTO {} (A) VALUES(1),(2),(3)", table_name);
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a FROM {}", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--batch-size-row",
+            "1",
+            "--file-size-threshold",
+            "1B",
+            &query,
+        ])
+        .assert()
+        .success();
+
+    // Expect one file per row in table (3)
+
+    parquet_read_out(out_dir.path().join("out_01.par").to_str().unwrap());
+    parquet_read_out(out_dir.path().join("out_02.par").to_str().unwrap());
+    parquet_read_out(out_dir.path().join("out_03.par").to_str().unwrap());
+}
+
+#[test]
+fn success_marker_and_manifest() {
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--batch-size-row",
+            "2",
+            "--row-groups-per-file",
+            "1",
+            "--success-marker",
+            "--manifest",
+            "SELECT a FROM (VALUES (1), (2), (3)) AS t(a)",
+        ])
+        .assert()
+        .success();
+
+    assert!(out_dir.path().join("_SUCCESS").exists());
+    let manifest = std::fs::read_to_string(out_dir.path().join("_manifest.json")).unwrap();
+    let manifest: serde_json::Value = serde_json::from_str(&manifest).unwrap();
+    let size = |name: &str| std::fs::metadata(out_dir.path().join(name)).unwrap().len();
+    assert_eq!(
+        serde_json::json!([
+            {"path": "out_01.par", "num_rows": 2, "size": size("out_01.par")},
+            {"path": "out_02.par", "num_rows": 1, "size": size("out_02.par")}
+        ]),
+        manifest["files"]
+    );
+    assert_eq!(3, manifest["num_rows"]);
+    assert!(manifest["schema"].as_str().unwrap().contains("INT32 a;"));
+}
+
+#[test]
+fn success_marker_conflicts_with_stdout() {
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            "-",
+            "--connection-string",
+            MSSQL,
+            "--success-marker",
+            "SELECT 42",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "success-marker and manifest conflict with specifying stdout ('-') as output.",
+        ));
+}
+
+/// `--file-size-bytes` is an alias for `--file-size-threshold`. Plain numbers are bytes.
+#[test]
+fn split_files_on_size_limit_in_bytes() {
+    // Setup table for test
+    let table_name = "SplitFilesOnSizeLimitInBytes";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
+    let insert = format!("INSERT INTO {} (A) VALUES(1),(2)", table_name);
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a FROM {}", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--batch-size-row",
+            "1",
+            "--file-size-bytes",
+            "1",
+            &query,
+        ])
+        .assert()
+        .success();
+
+    // Expect one file per row in table (2)
+    parquet_read_out(out_dir.path().join("out_01.par").to_str().unwrap());
+    parquet_read_out(out_dir.path().join("out_02.par").to_str().unwrap());
+}
+
+#[test]
+fn sequence_placeholder_in_output_file_name() {
+    // Setup table for test
+    let table_name = "SequencePlaceholderInOutputFileName";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
+    let insert = format!("INSERT INTO {} (A) VALUES(1),(2)", table_name);
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let out_path = out_dir.path().join("part-{seq}-of-export.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a FROM {}", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--batch-size-row",
+            "1",
+            "--row-groups-per-file",
+            "1",
+            &query,
+        ])
+        .assert()
+        .success();
+
+    // Expect one file per row in table (2)
+    parquet_read_out(
+        out_dir
+            .path()
+            .join("part-01-of-export.par")
+            .to_str()
+            .unwrap(),
+    );
+    parquet_read_out(
+        out_dir
+            .path()
+            .join("part-02-of-export.par")
+            .to_str()
+            .unwrap(),
+    );
+}
+
+/// Write identical files to several destinations with a single query
+#[test]
+fn tee_output_to_multiple_destinations() {
+    // Setup table for test
+    let table_name = "TeeOutputToMultipleDestinations";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
+    let insert = format!("INSERT INTO {} (A) VALUES(1),(2)", table_name);
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    let tee_path = out_dir.path().join("copy/out.par");
+    // We need to pass the output paths as string arguments.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+    let tee_str = tee_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a FROM {}", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--tee",
+            tee_str,
+            &query,
+        ])
+        .assert()
+        .success();
+
+    let expected = "{a: 1}\n{a: 2}\n";
+    parquet_read_out(out_str).stdout(eq(expected));
+    assert_eq!(
+        std::fs::read(&out_path).unwrap(),
+        std::fs::read(&tee_path).unwrap()
+    );
+}
+
+#[test]
+fn encrypt_parquet_output() {
+    // Setup table for test
+    let table_name = "EncryptParquetOutput";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
+    let insert = format!("INSERT INTO {} (A) VALUES(1),(2)", table_name);
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a FROM {}", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--encryption-key-command",
+            "echo 00112233445566778899aabbccddeeff",
+            &query,
+        ])
+        .assert()
+        .success();
+
+    // Files with an encrypted footer start and end with the magic bytes `PARE` rather than `PAR1`.
+    let bytes = std::fs::read(&out_path).unwrap();
+    assert_eq!(b"PARE", &bytes[..4]);
+    assert_eq!(b"PARE", &bytes[bytes.len() - 4..]);
+}
+
+#[test]
+fn bloom_filter_for_selected_column() {
+    // Setup table for test
+    let table_name = "BloomFilterForSelectedColumn";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER", "INTEGER"]).unwrap();
+    let insert = format!("INSERT INTO {} (a,b) VALUES (1,2),(3,4)", table_name);
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a, b FROM {}", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--bloom-filter",
+            "a:0.01",
+            &query,
+        ])
+        .assert()
+        .success();
+
+    let reader = SerializedFileReader::new(File::open(&out_path).unwrap()).unwrap();
+    let row_group = reader.metadata().row_group(0);
+    assert!(row_group.column(0).bloom_filter_offset().is_some());
+    assert!(row_group.column(1).bloom_filter_offset().is_none());
+}
+
+#[test]
+fn bloom_filter_for_renamed_column() {
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--rename",
+            "a=id",
+            "--bloom-filter",
+            "a",
+            "SELECT 1 AS a, 2 AS b",
+        ])
+        .assert()
+        .success();
+
+    let reader = SerializedFileReader::new(File::open(&out_path).unwrap()).unwrap();
+    let row_group = reader.metadata().row_group(0);
+    assert!(row_group.column(0).bloom_filter_offset().is_some());
+    assert!(row_group.column(1).bloom_filter_offset().is_none());
+}
+
+#[test]
+fn bloom_filter_column_must_be_part_of_result_set() {
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--bloom-filter",
+            "c",
+            "SELECT 1 AS a",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Bloom filter column 'c' is not part of the result set.",
+        ));
+}
+
+#[test]
+fn record_sorting_columns() {
+    // Setup table for test
+    let table_name = "RecordSortingColumns";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER", "VARCHAR(10)"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {} (a,b) VALUES (1,'b'),(1,'a'),(2,'c')",
+        table_name
+    );
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a, b FROM {} ORDER BY a, b DESC", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--sorted-by",
+            "a,b DESC",
+            "--verify-sort-order",
+            &query,
+        ])
+        .assert()
+        .success();
+
+    let reader = SerializedFileReader::new(File::open(&out_path).unwrap()).unwrap();
+    let expected = vec![
+        SortingColumn {
+            column_idx: 0,
+            descending: false,
+            nulls_first: true,
+        },
+        SortingColumn {
+            column_idx: 1,
+            descending: true,
+            nulls_first: false,
+        },
+    ];
+    assert_eq!(
+        Some(&expected),
+        reader.metadata().row_group(0).sorting_columns()
+    );
+}
+
+#[test]
+fn verify_sort_order_fails_for_unsorted_rows() {
+    // Setup table for test
+    let table_name = "VerifySortOrderFailsForUnsortedRows";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
+    let insert = format!("INSERT INTO {} (a) VALUES (1),(3),(2)", table_name);
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a FROM {} ORDER BY a DESC", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--sorted-by",
+            "a",
+            "--verify-sort-order",
+            &query,
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("Row 2 of the result set violates the sort order"));
+}
+
+#[test]
+fn write_parquet_format_version_2() {
+    // Setup table for test
+    let table_name = "WriteParquetFormatVersion2";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
+    let insert = format!("INSERT INTO {} (a) VALUES (1),(2),(3)", table_name);
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a FROM {}", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--writer-version",
+            "2",
+            &query,
+        ])
+        .assert()
+        .success();
+
+    let reader = SerializedFileReader::new(File::open(&out_path).unwrap()).unwrap();
+    assert_eq!(2, reader.metadata().file_metadata().version());
+    parquet_read_out(out_str).stdout(eq("{a: 1}\n{a: 2}\n{a: 3}\n"));
+}
+
+#[test]
+fn compression_per_column() {
+    // Setup table for test
+    let table_name = "CompressionPerColumn";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER", "VARBINARY(10)"]).unwrap();
+    let insert = format!("INSERT INTO {} (a,b) VALUES (1, 0x0102)", table_name);
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a, b FROM {}", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--column-compression-default",
+            "zstd",
+            "--column-compression",
+            "b:uncompressed",
+            &query,
+        ])
+        .assert()
+        .success();
+
+    let reader = SerializedFileReader::new(File::open(&out_path).unwrap()).unwrap();
+    let row_group = reader.metadata().row_group(0);
+    assert!(matches!(
+        row_group.column(0).compression(),
+        Compression::ZSTD(_)
+    ));
+    assert_eq!(Compression::UNCOMPRESSED, row_group.column(1).compression());
+}
+
+#[test]
+fn compression_column_must_be_part_of_result_set() {
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--column-compression",
+            "c:uncompressed",
+            "SELECT 1 AS a",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Compression column 'c' is not part of the result set.",
+        ));
+}
+
+#[test]
+fn compression_level() {
+    // Setup table for test
+    let table_name = "CompressionLevel";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER", "VARCHAR(10)"]).unwrap();
+    let insert = format!("INSERT INTO {} (a,b) VALUES (1, 'one')", table_name);
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a, b FROM {}", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--column-compression-default",
+            "zstd:9",
+            "--column-compression",
+            "b:gzip:4",
+            &query,
+        ])
+        .assert()
+        .success();
+
+    let reader = SerializedFileReader::new(File::open(&out_path).unwrap()).unwrap();
+    let row_group = reader.metadata().row_group(0);
+    assert!(matches!(
+        row_group.column(0).compression(),
+        Compression::ZSTD(_)
+    ));
+    assert!(matches!(
+        row_group.column(1).compression(),
+        Compression::GZIP(_)
+    ));
+    parquet_read_out(out_str).stdout(eq("{a: 1, b: \"one\"}\n"));
+}
+
+#[test]
+fn compression_level_not_supported_by_codec() {
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            "out.par",
+            "--connection-string",
+            MSSQL,
+            "--column-compression-default",
+            "lz4",
+            "--compression-level",
+            "3",
+            "SELECT 42",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("does not support a compression level"));
+}
+
+#[test]
+fn dictionary_per_column() {
+    // Setup table for test
+    let table_name = "DictionaryPerColumn";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(10)", "VARCHAR(10)"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {} (a,b) VALUES ('x','one'),('x','two')",
+        table_name
+    );
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a, b FROM {}", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--dictionary",
+            "off",
+            "--column-dictionary",
+            "a:on",
+            &query,
+        ])
+        .assert()
+        .success();
+
+    let reader = SerializedFileReader::new(File::open(&out_path).unwrap()).unwrap();
+    let row_group = reader.metadata().row_group(0);
+    assert!(row_group.column(0).dictionary_page_offset().is_some());
+    assert!(row_group.column(1).dictionary_page_offset().is_none());
+}
+
+#[test]
+fn dictionary_column_must_be_part_of_result_set() {
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--column-dictionary",
+            "c:off",
+            "SELECT 1 AS a",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Dictionary column 'c' is not part of the result set.",
+        ));
+}
+
+#[test]
+fn statistics_per_column() {
+    // Setup table for test
+    let table_name = "StatisticsPerColumn";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER", "VARCHAR(50)"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {} (a,b) VALUES (1,'Free text'),(2,'More free text')",
+        table_name
+    );
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a, b FROM {}", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--statistics",
+            "none",
+            "--column-statistics",
+            "a:page",
+            &query,
+        ])
+        .assert()
+        .success();
+
+    let reader = SerializedFileReader::new(File::open(&out_path).unwrap()).unwrap();
+    let row_group = reader.metadata().row_group(0);
+    assert!(row_group.column(0).statistics().is_some());
+    assert!(row_group.column(0).column_index_offset().is_some());
+    assert!(row_group.column(1).statistics().is_none());
+}
+
+#[test]
+fn statistics_column_must_be_part_of_result_set() {
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--column-statistics",
+            "c:none",
+            "SELECT 1 AS a",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Statistics column 'c' is not part of the result set.",
+        ));
+}
+
+#[test]
+fn truncate_statistics_of_long_text() {
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--statistics-truncate-length",
+            "16",
+            "SELECT REPLICATE('a', 1000) AS a",
+        ])
+        .assert()
+        .success();
+
+    let reader = SerializedFileReader::new(File::open(&out_path).unwrap()).unwrap();
+    let statistics = reader
+        .metadata()
+        .row_group(0)
+        .column(0)
+        .statistics()
+        .unwrap();
+    assert_eq!(16, statistics.min_bytes_opt().unwrap().len());
+    assert!(!statistics.min_is_exact());
+}
+
+#[test]
+fn omit_column_and_offset_index() {
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--column-index",
+            "off",
+            "--offset-index",
+            "off",
+            "SELECT 42 AS a",
+        ])
+        .assert()
+        .success();
+
+    let reader = SerializedFileReader::new(File::open(&out_path).unwrap()).unwrap();
+    let column = reader.metadata().row_group(0).column(0);
+    assert_eq!(None, column.column_index_offset());
+    assert_eq!(None, column.offset_index_offset());
+    // Chunk level statistics are still written
+    assert!(column.statistics().is_some());
+}
+
+#[test]
+fn offset_index_requires_column_index_off() {
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            "out.par",
+            "--connection-string",
+            MSSQL,
+            "--offset-index",
+            "off",
+            "SELECT 42",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "offset-index can only be turned off together with the column index",
+        ));
+}
+
+#[test]
+fn data_page_size_limit() {
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    // 10000 distinct integers, too many to fit into a single data page of 1KiB.
+    let query =
+        "SELECT TOP 10000 CAST(ROW_NUMBER() OVER (ORDER BY (SELECT NULL)) AS INTEGER) AS a \
+        FROM sys.all_objects AS x CROSS JOIN sys.all_objects AS y";
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--dictionary",
+            "off",
+            "--data-page-size",
+            "1KiB",
+            query,
+        ])
+        .assert()
+        .success();
+
+    let reader = SerializedFileReader::new(File::open(&out_path).unwrap()).unwrap();
+    let num_pages = reader
+        .get_row_group(0)
+        .unwrap()
+        .get_column_page_reader(0)
+        .unwrap()
+        .count();
+    assert!(num_pages > 1);
+}
+
+#[test]
+fn row_group_size_in_bytes() {
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    // 10000 non nullable integers of 4 bytes each.
+    let query =
+        "SELECT TOP 10000 CAST(ROW_NUMBER() OVER (ORDER BY (SELECT NULL)) AS INTEGER) AS a \
+        FROM sys.all_objects AS x CROSS JOIN sys.all_objects AS y";
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--batch-size-row",
+            "3000",
+            "--row-group-size-bytes",
+            "10000",
+            query,
+        ])
+        .assert()
+        .success();
+
+    // Row groups are independent of the batches of 3000 rows.
+    let reader = SerializedFileReader::new(File::open(&out_path).unwrap()).unwrap();
+    let num_rows: Vec<_> = reader
+        .metadata()
+        .row_groups()
+        .iter()
+        .map(|row_group| row_group.num_rows())
+        .collect();
+    assert_eq!(vec![2500, 2500, 2500, 2500], num_rows);
+}
+
+#[test]
+fn row_group_size_in_bytes_requires_parquet() {
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            "out.csv",
+            "--connection-string",
+            MSSQL,
+            "--format",
+            "csv",
+            "--row-group-size-bytes",
+            "128MiB",
+            "SELECT 42",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "row-group-size-bytes is only supported for parquet.",
+        ));
+}
+
+#[test]
+fn page_checksums() {
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--page-checksums",
+            "--column-compression-default",
+            "uncompressed",
+            "SELECT 'Hello, World!' AS a",
+        ])
+        .assert()
+        .success();
+
+    // Checksums are verified while reading
+    let read_rows = |path: &Path| -> Result<Vec<String>, parquet::errors::ParquetError> {
+        SerializedFileReader::new(File::open(path).unwrap())?
+            .get_row_iter(None)?
+            .map(|row| row.map(|row| row.to_string()))
+            .collect()
+    };
+    assert_eq!(
+        vec!["{a: \"Hello, World!\"}"],
+        read_rows(&out_path).unwrap()
+    );
+
+    // Corrupt the page data
+    let mut bytes = std::fs::read(&out_path).unwrap();
+    let pos = bytes.windows(5).position(|w| w == b"World").unwrap();
+    bytes[pos] = b'w';
+    std::fs::write(&out_path, bytes).unwrap();
+    assert!(read_rows(&out_path).is_err());
+}
+
+#[test]
+fn field_ids_from_name_mapping() {
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+    let mapping_path = out_dir.path().join("mapping.json");
+    std::fs::write(
+        &mapping_path,
+        r#"[{"field-id": 7, "names": ["a"]}, {"field-id": 3, "names": ["b", "c"]}]"#,
+    )
+    .unwrap();
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--field-ids",
+            mapping_path.to_str().unwrap(),
+            "SELECT 1 AS a, 2 AS b",
+        ])
+        .assert()
+        .success();
+
+    let reader = SerializedFileReader::new(File::open(&out_path).unwrap()).unwrap();
+    let schema = reader.metadata().file_metadata().schema_descr();
+    let ids: Vec<_> = schema
+        .columns()
+        .iter()
+        .map(|column| column.self_type().get_basic_info().id())
+        .collect();
+    assert_eq!(vec![7, 3], ids);
+}
+
+#[test]
+fn field_ids_must_cover_all_columns() {
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+    let mapping_path = out_dir.path().join("mapping.json");
+    std::fs::write(&mapping_path, r#"[{"field-id": 1, "names": ["a"]}]"#).unwrap();
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--field-ids",
+            mapping_path.to_str().unwrap(),
+            "SELECT 1 AS a, 2 AS b",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("Column 'b' is missing in the name mapping file"));
+}
+
+#[test]
+fn cast_columns_from_file() {
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+    let cast_path = out_dir.path().join("casts.yaml");
+    std::fs::write(&cast_path, "flags: int32\namount: decimal(10,2)\n").unwrap();
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--cast-file",
+            cast_path.to_str().unwrap(),
+            "SELECT '42' AS flags, CAST(1.5 AS FLOAT) AS amount",
+        ])
+        .assert()
+        .success();
+
+    parquet_read_out(out_str).stdout(eq("{flags: 42, amount: 1.50}\n"));
+    parquet_schema_out(out_str)
+        .stdout(contains("INT32 flags (INT_32);"))
+        .stdout(contains("INT64 amount (DECIMAL(10,2));"));
+}
+
+#[test]
+fn cast_column_must_be_part_of_result_set() {
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+    let cast_path = out_dir.path().join("casts.yaml");
+    std::fs::write(&cast_path, "b: int64\n").unwrap();
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--cast-file",
+            cast_path.to_str().unwrap(),
+            "SELECT 1 AS a",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("Cast column 'b' is not part of the result set."));
+}
+
+#[test]
+fn rename_columns() {
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--rename",
+            "order id=order_id",
+            "--json-column",
+            "order_id",
+            "SELECT '1' AS [order id], 2 AS b",
+        ])
+        .assert()
+        .success();
+
+    parquet_schema_out(out_str)
+        .stdout(contains("BYTE_ARRAY order_id (JSON);"))
+        .stdout(contains("INT32 b"));
+}
+
+#[test]
+fn renamed_column_must_be_part_of_result_set() {
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--rename",
+            "c=d",
+            "SELECT 1 AS a",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Renamed column 'c' is not part of the result set.",
+        ));
+}
+
+#[test]
+fn include_and_exclude_columns() {
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--columns",
+            "id,customer_*",
+            "--exclude-columns",
+            "*_secret",
+            "SELECT 1 AS id, 'a' AS customer_name, 'b' AS customer_secret, 'c' AS other",
+        ])
+        .assert()
+        .success();
+
+    parquet_read_out(out_str).stdout(eq("{id: 1, customer_name: \"a\"}\n"));
+}
+
+#[test]
+fn sanitize_column_names() {
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--sanitize-column-names",
+            "snake_case",
+            "SELECT 1 AS [Order Id], 2 AS OrderID, 3 AS [Unit Price (EUR)]",
+        ])
+        .assert()
+        .success();
+
+    parquet_read_out(out_str).stdout(eq("{order_id: 1, order_id_2: 2, unit_price_eur: 3}\n"));
+}
+
+#[test]
+fn configurable_suffix_length() {
+    // Setup table for test
+    let table_name = "ConfigurableSuffixLength";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
+    let insert = format!("INSERT INTO {} (A) VALUES(1)", table_name);
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a FROM {}", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--batch-size-row",
+            "1",
+            "--file-size-threshold",
+            "1B",
+            "--suffix-length",
+            "4",
+            &query,
+        ])
+        .assert()
+        .success();
+
+    // Expect one file per row in table (3)
+
+    parquet_read_out(out_dir.path().join("out_0001.par").to_str().unwrap());
+}
+
+#[test]
+fn partition_by_column() {
+    // Setup table for test
+    let table_name = "PartitionByColumn";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(10)", "INTEGER"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {} (a,b) VALUES ('DE', 1), ('FR', 2), ('DE', 3), (NULL, 4)",
+        table_name
+    );
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    // The partitioned dataset is written into a subdirectory of the temporary directory.
+    let out_path = out_dir.path().join("out");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a,b FROM {} ORDER BY id", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--partition-by",
+            "a",
+            &query,
+        ])
+        .assert()
+        .success();
+
+    // Partition column is part of the path, not of the file.
+    parquet_read_out(out_path.join("a=DE/part.par").to_str().unwrap()).stdout(eq("\
+        {b: 1}\n\
+        {b: 3}\n\
+    "));
+    parquet_read_out(out_path.join("a=FR/part.par").to_str().unwrap()).stdout(eq("{b: 2}\n"));
+    parquet_read_out(
+        out_path
+            .join("a=__HIVE_DEFAULT_PARTITION__/part.par")
+            .to_str()
+            .unwrap(),
+    )
+    .stdout(eq("{b: 4}\n"));
+}
+
+#[test]
+fn overwrite_partitions() {
+    // Setup table for test
+    let table_name = "OverwritePartitions";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(10)", "INTEGER"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {} (a,b) VALUES ('DE', 1), ('FR', 2)",
+        table_name
+    );
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out");
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+    // Left over from a previous run, which wrote more files into the partition.
+    std::fs::create_dir_all(out_path.join("a=DE")).unwrap();
+    std::fs::write(out_path.join("a=DE/part-00001.par"), b"stale").unwrap();
+    // Written by another export, not touched by this one.
+    std::fs::create_dir_all(out_path.join("a=IT")).unwrap();
+    std::fs::write(out_path.join("a=IT/part.par"), b"other").unwrap();
+
+    let query = format!("SELECT a,b FROM {} ORDER BY id", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--partition-by",
+            "a",
+            "--overwrite-partitions",
+            &query,
+        ])
+        .assert()
+        .success();
+
+    parquet_read_out(out_path.join("a=DE/part.par").to_str().unwrap()).stdout(eq("{b: 1}\n"));
+    assert!(!out_path.join("a=DE/part-00001.par").exists());
+    assert!(out_path.join("a=IT/part.par").exists());
+}
+
+#[test]
+fn append_to_delta_table() {
+    // Setup table for test
+    let table_name = "AppendToDeltaTable";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER", "VARCHAR(10)"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {} (a,b) VALUES (1, 'one'), (2, 'two')",
+        table_name
+    );
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    // The Delta Lake table is created in a subdirectory of the temporary directory.
+    let table_path = out_dir.path().join("table");
+    // We need to pass the output path as a string argument.
+    let table_str = table_path
+        .to_str()
+        .expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a,b FROM {} ORDER BY id", table_name);
+
+    // Run twice, so the second run appends to the table created by the first one.
+    for _ in 0..2 {
+        Command::cargo_bin("odbc2parquet")
+            .unwrap()
+            .args(&[
+                "-vvvv",
+                "query",
+                table_str,
+                "--connection-string",
+                MSSQL,
+                "--delta-table",
+                &query,
+            ])
+            .assert()
+            .success();
+    }
+
+    let first_commit =
+        std::fs::read_to_string(table_path.join("_delta_log/00000000000000000000.json")).unwrap();
+    assert!(first_commit.contains("\"metaData\""));
+    assert!(first_commit.contains("\"numRecords\\\":2"));
+    let second_commit =
+        std::fs::read_to_string(table_path.join("_delta_log/00000000000000000001.json")).unwrap();
+    assert!(!second_commit.contains("\"metaData\""));
+
+    let data_files: Vec<_> = std::fs::read_dir(&table_path)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "parquet"))
+        .collect();
+    assert_eq!(2, data_files.len());
+    for data_file in data_files {
+        parquet_read_out(data_file.to_str().unwrap()).stdout(eq("\
+            {a: 1, b: \"one\"}\n\
+            {a: 2, b: \"two\"}\n\
+        "));
+    }
+}
+
+#[test]
+fn varbinary_column() {
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+
+    setup_empty_table_mssql(&conn, "VarbinaryColumn", &["VARBINARY(10)"]).unwrap();
+    conn.execute(
+        "INSERT INTO VarbinaryColumn (a) Values \
+        (CONVERT(Binary(5), 'Hello')),\
+        (CONVERT(Binary(5), 'World')),\
+        (NULL)",
+        (),
+    )
+    .unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = "SELECT a FROM VarbinaryColumn;";
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            query,
+        ])
+        .assert()
+        .success();
+
+    let expected = "{a: [72, 101, 108, 108, 111]}\n{a: [87, 111, 114, 108, 100]}\n{a: null}\n";
+
+    parquet_read_out(out_str).stdout(eq(expected));
+}
+
+#[test]
+fn required_text_and_binary_columns() {
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+
+    setup_empty_table_mssql(
+        &conn,
+        "RequiredTextAndBinaryColumns",
+        &["VARCHAR(10) NOT NULL", "VARBINARY(10) NOT NULL"],
+    )
+    .unwrap();
+    conn.execute(
+        "INSERT INTO RequiredTextAndBinaryColumns (a, b) Values \
+        ('Hello', CONVERT(Binary(2), 'Hi')),\
+        ('', CONVERT(VarBinary(1), '')),\
+        ('World', CONVERT(Binary(3), 'Yo!'))",
+        (),
+    )
+    .unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = "SELECT a, b FROM RequiredTextAndBinaryColumns ORDER BY id;";
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            query,
+        ])
+        .assert()
+        .success();
+
+    let expected = "{a: \"Hello\", b: [72, 105]}\n\
+        {a: \"\", b: []}\n\
+        {a: \"World\", b: [89, 111, 33]}\n";
+    parquet_read_out(out_str).stdout(eq(expected));
+}
+
+#[test]
+fn query_varchar_max() {
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    let table_name = "QueryVarcharMax";
+
+    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(MAX)"]).unwrap();
+    conn.execute(
+        &format!(
+            "INSERT INTO {} (a) Values ('Hello'), ('World');",
+            table_name
+        ),
+        (),
+    )
+    .unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a FROM {};", table_name);
+
+    // VARCHAR(max) has size 0. => Values are fetched in parts
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            "--connection-string",
+            MSSQL,
+            out_str,
+            &query,
+        ])
+        .assert()
+        .success()
+        .stderr(contains(
+            "Column 'a' has no maximum length. Fetching its values in parts.",
+        ));
+
+    let expected = "{a: \"Hello\"}\n{a: \"World\"}\n";
+    parquet_read_out(out_str).stdout(eq(expected));
+}
+
+/// Introduced after discovering a bug, that columns were not ignored on windows.
+#[test]
+fn query_varchar_max_utf16() {
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    let table_name = "QueryVarcharMaxUtf16";
+
+    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(MAX)"]).unwrap();
+    conn.execute(
+        &format!(
+            "INSERT INTO {} (a) Values ('Hello'), ('World');",
+            table_name
+        ),
+        (),
+    )
+    .unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a FROM {};", table_name);
+
+    // VARCHAR(max) has size 0. => Values are fetched in parts, as UTF-16
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            "--encoding",
+            "Utf16",
+            "--connection-string",
+            MSSQL,
+            out_str,
+            &query,
+        ])
+        .assert()
+        .success();
+
+    let expected = "{a: \"Hello\"}\n{a: \"World\"}\n";
+    parquet_read_out(out_str).stdout(eq(expected));
+}
+
+#[test]
+fn query_varchar_max_longer_than_part() {
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    let table_name = "QueryVarcharMaxLongerThanPart";
+
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER", "VARCHAR(MAX)"]).unwrap();
+    // Larger than a single part of 64KiB
+    conn.execute(
+        &format!(
+            "INSERT INTO {} (a,b) Values (1, REPLICATE(CAST('x' AS VARCHAR(MAX)), 100000)), \
+            (2, NULL), (3, 'short');",
+            table_name
+        ),
+        (),
+    )
+    .unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a, b FROM {table_name} ORDER BY id;");
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&["query", "--connection-string", MSSQL, out_str, &query])
+        .assert()
+        .success();
+
+    let rows: Vec<String> = SerializedFileReader::new(File::open(&out_path).unwrap())
+        .unwrap()
+        .get_row_iter(None)
+        .unwrap()
+        .map(|row| row.unwrap().to_string())
+        .collect();
+    let long = format!("{{a: 1, b: \"{}\"}}", "x".repeat(100000));
+    assert_eq!(
+        vec![long.as_str(), "{a: 2, b: null}", "{a: 3, b: \"short\"}"],
+        rows
+    );
+}
+
+#[test]
+fn max_column_size() {
+    // Setup table for test
+    let table_name = "MaxColumnSize";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(100)"]).unwrap();
+    let insert = format!("INSERT INTO {} (a) VALUES ('one'),('three')", table_name);
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a FROM {table_name} ORDER BY id");
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--encoding",
+            "System",
+            "--max-column-size",
+            "4",
+            &query,
+        ])
+        .assert()
+        .success()
+        .stderr(contains(
+            "Column 'a' holds values of at least 4 bytes, which are likely truncated.",
+        ));
+
+    let expected = "{a: \"one\"}\n{a: \"thre\"}\n";
+    parquet_read_out(out_str).stdout(eq(expected));
+}
+
+#[test]
+fn on_truncation_error() {
+    // Setup table for test
+    let table_name = "OnTruncationError";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(100)"]).unwrap();
+    let insert = format!("INSERT INTO {} (a) VALUES ('one'),('three')", table_name);
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a FROM {table_name} ORDER BY id");
+
+    // The buffer is too small for 'three', so the driver truncates it.
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--encoding",
+            "System",
+            "--max-column-size",
+            "4",
+            "--on-truncation",
+            "error",
+            &query,
+        ])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Values of column(s) 'a' in batch 1 are longer than their buffers.",
+        ));
+
+    assert!(!out_path.exists());
+}
+
+#[test]
+fn sample_lengths() {
+    // Setup table for test
+    let table_name = "SampleLengths";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["NVARCHAR(4000)"]).unwrap();
+    let insert = format!("INSERT INTO {} (a) VALUES ('one'),('three')", table_name);
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a FROM {table_name} ORDER BY id");
+
+    // The sample only holds 'one', so 'three' is truncated and fetched again with a larger buffer.
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--sample-lengths",
+            "1",
+            "--on-truncation",
+            "refetch",
+            &query,
+        ])
+        .assert()
+        .success()
+        .stderr(contains(
+            "Values of column 'a' have been truncated. Fetching the result set again",
+        ));
+
+    let expected = "{a: \"one\"}\n{a: \"three\"}\n";
+    parquet_read_out(out_str).stdout(eq(expected));
+}
+
+#[test]
+fn oversized_values_as_null() {
+    // Setup table for test
+    let table_name = "OversizedValuesAsNull";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(MAX)"]).unwrap();
+    let insert = format!("INSERT INTO {} (a) VALUES ('one'),('three')", table_name);
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT id, a FROM {table_name} ORDER BY id");
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--max-value-size",
+            "4",
+            "--oversized",
+            "a:null",
+            "--sorted-by",
+            "id",
+            &query,
+        ])
+        .assert()
+        .success()
+        .stderr(contains(
+            "Writing the value of column 'a' in row 2 (key '2') as NULL, since it is larger than \
+            4 bytes.",
+        ));
+
+    let expected = "{id: 1, a: \"one\"}\n{id: 2, a: null}\n";
+    parquet_read_out(out_str).stdout(eq(expected));
+}
+
+#[test]
+fn oversized_values_fail_by_default() {
+    // Setup table for test
+    let table_name = "OversizedValuesFailByDefault";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(100)"]).unwrap();
+    let insert = format!("INSERT INTO {} (a) VALUES ('one'),('three')", table_name);
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a FROM {table_name} ORDER BY id");
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--encoding",
+            "System",
+            "--max-value-size",
+            "4",
+            &query,
+        ])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "The value of column 'a' in row 2 is larger than 4 bytes.",
+        ));
+
+    assert!(!out_path.exists());
+}
+
+#[test]
+fn oversized_values_into_side_files() {
+    // Setup table for test
+    let table_name = "OversizedValuesIntoSideFiles";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(MAX)"]).unwrap();
+    let insert = format!("INSERT INTO {} (a) VALUES ('one'),('three')", table_name);
+    conn.execute(&insert, ()).unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT id, a FROM {table_name} ORDER BY id");
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--max-value-size",
+            "4",
+            "--oversized",
+            "a:file",
+            "--side-file-names",
+            "key",
+            "--sorted-by",
+            "id",
+            &query,
+        ])
+        .assert()
+        .success();
+
+    let expected = "{id: 1, a: \"one\", a_file: null}\n\
+        {id: 2, a: null, a_file: \"side_files/a/2\"}\n";
+    parquet_read_out(out_str).stdout(eq(expected));
+    let side_file = std::fs::read_to_string(out_dir.path().join("side_files/a/2")).unwrap();
+    assert_eq!("three", side_file);
+}
+
+#[test]
+fn query_varbinary_max() {
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    let table_name = "QueryVarbinaryMax";
+
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER", "VARBINARY(MAX)"]).unwrap();
+    // Larger than a single part of 64KiB
+    conn.execute(
+        &format!(
+            "INSERT INTO {} (a,b) Values \
+            (1, CAST(REPLICATE(CAST('x' AS VARCHAR(MAX)), 100000) AS VARBINARY(MAX))), \
+            (2, NULL), (3, 0x0102);",
+            table_name
+        ),
+        (),
+    )
+    .unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a, b FROM {table_name} ORDER BY id;");
+
+    // VARBINARY(max) has size 0. => Values are fetched in parts
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            "--connection-string",
+            MSSQL,
+            out_str,
+            &query,
+        ])
+        .assert()
+        .success()
+        .stderr(contains(
+            "Column 'b' has no maximum length. Fetching its values in parts.",
+        ));
+
+    let rows: Vec<String> = SerializedFileReader::new(File::open(&out_path).unwrap())
+        .unwrap()
+        .get_row_iter(None)
+        .unwrap()
+        .map(|row| row.unwrap().to_string())
+        .collect();
+    let long = format!("{{a: 1, b: [{}]}}", vec!["120"; 100000].join(", "));
+    assert_eq!(
+        vec![long.as_str(), "{a: 2, b: null}", "{a: 3, b: [1, 2]}"],
+        rows
+    );
+}
+
+#[test]
+fn binary_column() {
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+
+    setup_empty_table_mssql(&conn, "BinaryColumn", &["BINARY(5)"]).unwrap();
+    conn.execute(
+        "INSERT INTO BinaryColumn (a) Values \
+        (CONVERT(Binary(5), 'Hello')),\
+        (CONVERT(Binary(5), 'World')),\
+        (NULL)",
+        (),
+    )
+    .unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = "SELECT a FROM BinaryColumn;";
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            query,
+        ])
+        .assert()
+        .success();
+
+    let expected = "{a: [72, 101, 108, 108, 111]}\n{a: [87, 111, 114, 108, 100]}\n{a: null}\n";
+    parquet_read_out(out_str).stdout(eq(expected));
+}
+
+/// Binary columns with a fixed length keep their width, with shorter values padded with zeros.
+#[test]
+fn binary_column_with_fixed_length() {
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "SELECT CAST(0x0102 AS BINARY(4)) AS a",
+        ])
+        .assert()
+        .success();
+
+    parquet_schema_out(out_str).stdout(contains("FIXED_LEN_BYTE_ARRAY (4) a;"));
+    parquet_read_out(out_str).stdout(eq("{a: [1, 2, 0, 0]}\n"));
+}
+
+/// The prefer-varbinary flag must enforce mapping of binary colmuns to BYTE_ARRAY instead of
+/// FIXED_LEN_BYTE_ARRAY.
+#[test]
+fn prefer_varbinary() {
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+
+    let table_name = "PreferVarbinary";
+
+    setup_empty_table_mssql(&conn, table_name, &["BINARY(5)"]).unwrap();
+    conn.execute(
+        &format!(
+            "INSERT INTO {} (a) Values \
+        (CONVERT(Binary(5), 'Hello')),\
+        (CONVERT(Binary(5), 'World')),\
+        (NULL)",
+            table_name
+        ),
+        (),
+    )
+    .unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = format!("SELECT a FROM {};", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--prefer-varbinary",
+            "--connection-string",
+            MSSQL,
+            &query,
+        ])
+        .assert()
+        .success();
+
+    let expected = "{a: [72, 101, 108, 108, 111]}\n{a: [87, 111, 114, 108, 100]}\n{a: null}\n";
+    parquet_read_out(out_str).stdout(eq(expected));
+
+    parquet_schema_out(out_str).stdout(contains("OPTIONAL BYTE_ARRAY a;"));
+}
+
+/// Strings with interior nuls should be written into parquet file as they are.
+#[test]
+fn interior_nul_in_varchar() {
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, "InteriorNul", &["VARCHAR(10)"]).unwrap();
+
+    conn.execute(
+        "INSERT INTO InteriorNul (a) VALUES (?);",
+        &"a\0b".into_parameter(),
+    )
+    .unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = "SELECT a FROM InteriorNul;";
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            query,
+        ])
+        .assert()
+        .success();
+
+    let expected = "{a: \"a\0b\"}\n";
+
+    parquet_read_out(out_str).stdout(eq(expected));
+}
+
+/// Fixed size NCHAR column on database is not truncated then value is passed into a narrow buffer.
+#[test]
+#[cfg(not(target_os = "windows"))] // Windows does not use UTF-8 as default system encoding
+fn nchar_not_truncated() {
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    let table_name = "NCharNotTruncated";
+    setup_empty_table_mssql(&conn, table_name, &["NCHAR(1)"]).unwrap();
+
+    conn.execute(
+        &format!("INSERT INTO {} (a) VALUES (?);", table_name),
+        &"Ü".into_parameter(),
+    )
+    .unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = &format!("SELECT a FROM {};", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            "--encoding",
+            "system",
+            "--connection-string",
+            MSSQL,
+            out_str,
+            query,
+        ])
+        .assert()
+        .success();
+
+    let expected = "{a: \"Ü\"}\n";
+
+    parquet_read_out(out_str).stdout(eq(expected));
+}
+
+/// Test non ASCII character with system encoding
+#[test]
+#[cfg(not(target_os = "windows"))] // Windows does not use UTF-8 as default system encoding
+fn system_encoding() {
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    let table_name = "SystemEncoding";
+    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(10)"]).unwrap();
+
+    conn.execute(
+        &format!("INSERT INTO {} (a) VALUES (?);", table_name),
+        &"Ü".into_parameter(),
+    )
+    .unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = &format!("SELECT a FROM {};", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            "--encoding",
+            "system",
+            "--connection-string",
+            MSSQL,
+            out_str,
+            query,
+        ])
+        .assert()
+        .success();
+
+    let expected = "{a: \"Ü\"}\n";
+
+    parquet_read_out(out_str).stdout(eq(expected));
+}
+
+/// Test non ASCII character with utf16 encoding
+#[test]
+fn utf_16_encoding() {
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    let table_name = "Utf16Encoding";
+    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(10)"]).unwrap();
+
+    conn.execute(
+        &format!("INSERT INTO {} (a) VALUES (?);", table_name),
+        &"Ü".into_parameter(),
+    )
+    .unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = &format!("SELECT a FROM {};", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            "--encoding",
+            "utf16",
+            "--connection-string",
+            MSSQL,
+            out_str,
+            query,
+        ])
+        .assert()
+        .success();
+
+    let expected = "{a: \"Ü\"}\n";
+
+    parquet_read_out(out_str).stdout(eq(expected));
+}
+
+/// Test non ASCII character with automatic codec detection
+#[test]
+fn auto_encoding() {
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    let table_name = "AutoEncoding";
+    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(1)"]).unwrap();
+
+    conn.execute(
+        &format!("INSERT INTO {} (a) VALUES (?);", table_name),
+        &"Ü".into_parameter(),
+    )
+    .unwrap();
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    // The name of the output parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    let query = &format!("SELECT a FROM {};", table_name);
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            "--encoding",
+            "auto",
+            "--connection-string",
+            MSSQL,
+            out_str,
+            query,
+        ])
+        .assert()
+        .success();
+
+    let expected = "{a: \"Ü\"}\n";
+
+    parquet_read_out(out_str).stdout(eq(expected));
+}
+
+#[test]
+pub fn insert_32_bit_integer() {
+    let table_name = "Insert32BitInteger";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            REQUIRED INT32 a;
+        }
+    ";
+
+    write_values_to_file(message_type, &input_path, &[42i32, 5, 1], None);
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("42\n5\n1", actual);
+}
+
+#[test]
+pub fn insert_optional_32_bit_integer() {
+    let table_name = "InsertOptional32BitInteger";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            OPTIONAL INT32 a;
+        }
+    ";
+
+    write_values_to_file(message_type, &input_path, &[42i32, 1], Some(&[1, 0, 1]));
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("42\nNULL\n1", actual);
+}
+
+#[test]
+pub fn insert_64_bit_integer() {
+    let table_name = "Insert64BitInteger";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            REQUIRED INT64 a;
+        }
+    ";
+
+    write_values_to_file(message_type, &input_path, &[-42i64, 5, 1], None);
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("-42\n5\n1", actual);
+}
+
+#[test]
+pub fn insert_optional_64_bit_integer() {
+    let table_name = "InsertOptional64BitInteger";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["BIGINT"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            OPTIONAL INT64 a;
+        }
+    ";
+
+    write_values_to_file(message_type, &input_path, &[-42i64, 1], Some(&[1, 0, 1]));
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("-42\nNULL\n1", actual);
+}
+
+#[test]
+pub fn insert_utf8() {
+    let table_name = "InsertUtf8";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(50)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            REQUIRED BYTE_ARRAY a (UTF8);
+        }
+    ";
+
+    let text: ByteArray = "Hello, World!".into();
+    write_values_to_file(
+        message_type,
+        &input_path,
+        &[text, "Hallo, Welt!".into(), "Bonjour, Monde!".into()],
+        None,
+    );
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("Hello, World!\nHallo, Welt!\nBonjour, Monde!", actual);
+}
+
+#[test]
+pub fn insert_optional_utf8() {
+    let table_name = "InsertOptionalUtf8";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(50)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            OPTIONAL BYTE_ARRAY a (UTF8);
+        }
+    ";
+
+    let text: ByteArray = "Hello, World!".into();
+    write_values_to_file(
+        message_type,
+        &input_path,
+        &[text, "Hallo, Welt!".into()],
+        Some(&[1, 0, 1]),
+    );
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("Hello, World!\nNULL\nHallo, Welt!", actual);
+}
+
+#[test]
+pub fn insert_utf16() {
+    let table_name = "InsertUtf16";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(50)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            REQUIRED BYTE_ARRAY a (UTF8);
+        }
+    ";
+
+    let text: ByteArray = "Hello, World!".into();
+    write_values_to_file(
+        message_type,
+        &input_path,
+        &[text, "Hallo, Welt!".into(), "Bonjour, Monde!".into()],
+        None,
+    );
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            "--encoding",
+            "Utf16",
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("Hello, World!\nHallo, Welt!\nBonjour, Monde!", actual);
+}
+
+#[test]
+pub fn insert_optional_utf16() {
+    let table_name = "InsertOptionalUtf16";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["VARCHAR(50)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            OPTIONAL BYTE_ARRAY a (UTF8);
+        }
+    ";
+
+    let text: ByteArray = "Hello, World!".into();
+    write_values_to_file(
+        message_type,
+        &input_path,
+        &[text, "Hallo, Welt!".into()],
+        Some(&[1, 0, 1]),
+    );
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            "--encoding",
+            "Utf16",
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("Hello, World!\nNULL\nHallo, Welt!", actual);
+}
+
+#[test]
+pub fn insert_bool() {
+    let table_name = "InsertBool";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["BIT"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            REQUIRED BOOLEAN a;
+        }
+    ";
+
+    write_values_to_file(message_type, &input_path, &[true, false, false], None);
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("1\n0\n0", actual);
+}
+
+#[test]
+pub fn insert_optional_bool() {
+    let table_name = "InsertOptionalBool";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["BIT"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            OPTIONAL BOOLEAN a;
+        }
+    ";
+
+    write_values_to_file(message_type, &input_path, &[true, false], Some(&[1, 0, 1]));
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("1\nNULL\n0", actual);
+}
+
+#[test]
+pub fn insert_f32() {
+    let table_name = "InsertF32";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["FLOAT"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            REQUIRED FLOAT a;
+        }
+    ";
+
+    write_values_to_file(message_type, &input_path, &[1.2f32, 3.4, 5.6], None);
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!(
+        "1.2000000476837158\n3.4000000953674316\n5.5999999046325684",
+        actual
+    );
+}
+
+#[test]
+pub fn insert_optional_f32() {
+    let table_name = "InsertOptionalF32";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["FLOAT"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            OPTIONAL FLOAT a;
+        }
+    ";
+
+    write_values_to_file(message_type, &input_path, &[1.2f32, 3.4], Some(&[1, 0, 1]));
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("1.2000000476837158\nNULL\n3.4000000953674316", actual);
+}
+
+#[test]
+pub fn insert_f64() {
+    let table_name = "InsertF64";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["FLOAT(53)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            REQUIRED DOUBLE a;
+        }
+    ";
+
+    write_values_to_file(message_type, &input_path, &[1.2f64, 3.4, 5.6], None);
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("1.2\n3.3999999999999999\n5.5999999999999996", actual);
+}
+
+#[test]
+pub fn insert_optional_f64() {
+    let table_name = "InsertOptionalF64";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["FLOAT(53)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            OPTIONAL DOUBLE a;
+        }
+    ";
+
+    write_values_to_file(message_type, &input_path, &[1.2f64, 3.4], Some(&[1, 0, 1]));
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("1.2\nNULL\n3.3999999999999999", actual);
+}
+
+#[test]
+pub fn insert_date() {
+    let table_name = "InsertDate";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["DATE"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            REQUIRED INT32 a (DATE);
+        }
+    ";
+
+    write_values_to_file(message_type, &input_path, &[0i32, 365, 18695], None);
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("1970-01-01\n1971-01-01\n2021-03-09", actual);
+}
+
+#[test]
+pub fn insert_optional_date() {
+    let table_name = "InsertOptionalDate";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["Date"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            OPTIONAL INT32 a (DATE);
+        }
+    ";
+
+    write_values_to_file(message_type, &input_path, &[0i32, 18695], Some(&[1, 0, 1]));
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("1970-01-01\nNULL\n2021-03-09", actual);
+}
+
+#[test]
+pub fn insert_time_ms() {
+    let table_name = "InsertTimeMs";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["TIME(3)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            REQUIRED INT32 a (TIME_MILLIS);
+        }
+    ";
+
+    // Total number of milli seconds since midnight
+    write_values_to_file(
+        message_type,
+        &input_path,
+        &[0i32, 3_600_000, 82_800_000],
+        None,
+    );
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("00:00:00.000\n01:00:00.000\n23:00:00.000", actual);
+}
+
+#[test]
+pub fn insert_optional_time_ms() {
+    let table_name = "InsertOptionalTimeMs";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["TIME(3)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            OPTIONAL INT32 a (TIME_MILLIS);
+        }
+    ";
+
+    // Total number of milli seconds since midnight
+    write_values_to_file(
+        message_type,
+        &input_path,
+        &[0i32, 82_800_000],
+        Some(&[1, 0, 1]),
+    );
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("00:00:00.000\nNULL\n23:00:00.000", actual);
+}
+
+#[test]
+pub fn insert_time_us() {
+    let table_name = "InsertTimeUs";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["TIME(6)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            REQUIRED INT64 a (TIME_MICROS);
+        }
+    ";
+
+    // Total number of milli seconds since midnight
+    write_values_to_file(
+        message_type,
+        &input_path,
+        &[0i64, 3_600_000_000, 82_800_000_000],
+        None,
+    );
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("00:00:00.000000\n01:00:00.000000\n23:00:00.000000", actual);
+}
+
+#[test]
+pub fn insert_optional_time_us() {
+    let table_name = "InsertOptionalTimeUs";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["TIME(6)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            OPTIONAL INT64 a (TIME_MICROS);
+        }
+    ";
+
+    // Total number of milli seconds since midnight
+    write_values_to_file(
+        message_type,
+        &input_path,
+        &[0i64, 82_800_000_000],
+        Some(&[1, 0, 1]),
+    );
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!("00:00:00.000000\nNULL\n23:00:00.000000", actual);
+}
+
+#[test]
+pub fn insert_decimal_from_i32() {
+    let table_name = "InsertDecimalFromI32";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["DECIMAL(9,2)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            REQUIRED INT32 a (DECIMAL(9,2));
+        }
+    ";
+
+    // Total number of milli seconds since midnight
+    write_values_to_file(message_type, &input_path, &[0i32, 123456789, -42], None);
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!(".00\n1234567.89\n-.42", actual);
+}
+
+#[test]
+pub fn insert_decimal_from_i32_optional() {
+    let table_name = "InsertDecimalFromI32Optional";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["DECIMAL(9,2)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            OPTIONAL INT32 a (DECIMAL(9,2));
+        }
+    ";
+
+    // Total number of milli seconds since midnight
+    write_values_to_file(message_type, &input_path, &[0i32, -42], Some(&[1, 0, 1]));
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!(".00\nNULL\n-.42", actual);
+}
+
+#[test]
+pub fn insert_decimal_from_i64() {
+    let table_name = "InsertDecimalFromI64";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["DECIMAL(9,2)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            REQUIRED INT64 a (DECIMAL(9,2));
+        }
+    ";
+
+    // Total number of milli seconds since midnight
+    write_values_to_file(message_type, &input_path, &[0i64, 123456789, -42], None);
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!(".00\n1234567.89\n-.42", actual);
+}
+
+#[test]
+pub fn insert_decimal_from_i64_optional() {
+    let table_name = "InsertDecimalFromI64Optional";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["DECIMAL(9,2)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            OPTIONAL INT64 a (DECIMAL(9,2));
+        }
+    ";
+
+    // Total number of milli seconds since midnight
+    write_values_to_file(message_type, &input_path, &[0i64, -42], Some(&[1, 0, 1]));
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!(".00\nNULL\n-.42", actual);
+}
+
+#[test]
+pub fn insert_timestamp_ms() {
+    let table_name = "InsertTimestampMs";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["DATETIME2"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            REQUIRED INT64 a (TIMESTAMP_MILLIS);
+        }
+    ";
+
+    // Total number of milli seconds since unix epoch
+    write_values_to_file(message_type, &input_path, &[0i64, 1, 1616367053000], None);
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!(
+        "1970-01-01 00:00:00.0000000\n1970-01-01 00:00:00.0010000\n2021-03-21 22:50:53.0000000",
+        actual
+    );
+}
+
+#[test]
+pub fn insert_timestamp_ms_optional() {
+    let table_name = "InsertTimestampMsOptional";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["DATETIME2"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            OPTIONAL INT64 a (TIMESTAMP_MILLIS);
+        }
+    ";
+
+    // Total number of milli seconds since unix epoch
+    write_values_to_file(
+        message_type,
+        &input_path,
+        &[0i64, 1616367053000],
+        Some(&[1, 0, 1]),
+    );
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!(
+        "1970-01-01 00:00:00.0000000\nNULL\n2021-03-21 22:50:53.0000000",
+        actual
+    );
+}
+
+#[test]
+pub fn insert_timestamp_us() {
+    let table_name = "InsertTimestampUs";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["DATETIME2"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            REQUIRED INT64 a (TIMESTAMP_MICROS);
+        }
+    ";
+
+    // Total number of milli seconds since unix epoch
+    write_values_to_file(
+        message_type,
+        &input_path,
+        &[0i64, 1, 1616367053000000],
+        None,
+    );
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!(
+        "1970-01-01 00:00:00.0000000\n1970-01-01 00:00:00.0000010\n2021-03-21 22:50:53.0000000",
+        actual
+    );
+}
+
+#[test]
+pub fn insert_timestamp_us_optional() {
+    let table_name = "InsertTimestampUsOptional";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["DATETIME2"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            OPTIONAL INT64 a (TIMESTAMP_MICROS);
+        }
+    ";
+
+    // Total number of milli seconds since unix epoch
+    write_values_to_file(
+        message_type,
+        &input_path,
+        &[0i64, 1616367053000000],
+        Some(&[1, 0, 1]),
+    );
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!(
+        "1970-01-01 00:00:00.0000000\nNULL\n2021-03-21 22:50:53.0000000",
+        actual
+    );
+}
+
+#[test]
+pub fn insert_binary() {
+    let table_name = "InsertBinary";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["VARBINARY(50)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            REQUIRED BYTE_ARRAY a;
+        }
+    ";
+
+    let text: ByteArray = "Hello, World!".into();
+    write_values_to_file(
+        message_type,
+        &input_path,
+        &[text, "Hallo, Welt!".into(), "Bonjour, Monde!".into()],
+        None,
+    );
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!(
+        "48656C6C6F2C20576F726C6421\n48616C6C6F2C2057656C7421\n426F6E6A6F75722C204D6F6E646521",
+        actual
+    );
+}
+
+#[test]
+pub fn insert_binary_optional() {
+    let table_name = "InsertBinaryOptional";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["VARBINARY(50)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            OPTIONAL BYTE_ARRAY a;
+        }
+    ";
+
+    let text: ByteArray = "Hello, World!".into();
+    write_values_to_file(
+        message_type,
+        &input_path,
+        &[text, "Hallo, Welt!".into()],
+        Some(&[1, 0, 1]),
+    );
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!(
+        "48656C6C6F2C20576F726C6421\nNULL\n48616C6C6F2C2057656C7421",
+        actual
+    );
+}
+
+#[test]
+pub fn insert_fixed_len_binary() {
+    let table_name = "InsertFixedLenBinary";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["BINARY(13)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            REQUIRED FIXED_LEN_BYTE_ARRAY(13) a;
+        }
+    ";
+
+    let to_fixed_len_byte_array = |input: &str| -> FixedLenByteArray {
+        let ba: ByteArray = input.into();
+        ba.into()
+    };
+
+    let text: FixedLenByteArray = to_fixed_len_byte_array("Hello, World!");
+    write_values_to_file(
+        message_type,
+        &input_path,
+        &[
+            text,
+            to_fixed_len_byte_array("Hallo, Welt!!"),
+            to_fixed_len_byte_array("0123456789012"),
+        ],
+        None,
+    );
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!(
+        "48656C6C6F2C20576F726C6421\n48616C6C6F2C2057656C742121\n30313233343536373839303132",
+        actual
+    );
+}
+
+#[test]
+pub fn insert_fixed_len_binary_optional() {
+    let table_name = "InsertFixedLenBinaryOptional";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["BINARY(13)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            OPTIONAL FIXED_LEN_BYTE_ARRAY(13) a;
+        }
+    ";
+
+    let to_fixed_len_byte_array = |input: &str| -> FixedLenByteArray {
+        let ba: ByteArray = input.into();
+        ba.into()
+    };
+
+    let text: FixedLenByteArray = to_fixed_len_byte_array("Hello, World!");
+    write_values_to_file(
+        message_type,
+        &input_path,
+        &[text, to_fixed_len_byte_array("0123456789012")],
+        Some(&[1, 0, 1]),
+    );
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!(
+        "48656C6C6F2C20576F726C6421\nNULL\n30313233343536373839303132",
+        actual
+    );
+}
+
+#[test]
+pub fn insert_decimal_from_binary() {
+    let table_name = "InsertDecimalFromBinary";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["DECIMAL(9,2)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            REQUIRED BYTE_ARRAY a (DECIMAL(9,2));
+        }
+    ";
+
+    let zero: ByteArray = vec![0u8].into();
+    write_values_to_file(
+        message_type,
+        &input_path,
+        &[
+            zero,
+            vec![1].into(),
+            vec![1, 0, 0].into(),
+            vec![255, 255, 255].into(),
+            vec![255].into(),
+        ],
+        None,
+    );
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!(".00\n.01\n655.36\n-.01\n-.01", actual);
+}
+
+#[test]
+pub fn insert_decimal_from_binary_optional() {
+    let table_name = "InsertDecimalFromBinaryOptional";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["DECIMAL(9,2)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            OPTIONAL BYTE_ARRAY a (DECIMAL(9,2));
+        }
+    ";
+
+    let zero: ByteArray = vec![0u8].into();
+    write_values_to_file(
+        message_type,
+        &input_path,
+        &[zero, vec![1].into()],
+        Some(&[1, 0, 1]),
+    );
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!(".00\nNULL\n.01", actual);
+}
+
+#[test]
+pub fn insert_decimal_from_fixed_binary() {
+    let table_name = "InsertDecimalFromFixedBinary";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["DECIMAL(5,2)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
+    let tmp_dir = tempdir().unwrap();
+    // The name of the input parquet file we are going to write. Since it is in a temporary
+    // directory it will not outlive the end of the test.
+    let input_path = tmp_dir.path().join("input.par");
+
+    let message_type = "
+        message schema {
+            REQUIRED FIXED_LEN_BYTE_ARRAY(3) a (DECIMAL(5,2));
+        }
+    ";
+
+    let to_fixed_len_byte_array = |input: Vec<u8>| -> FixedLenByteArray {
+        let ba: ByteArray = input.into();
+        ba.into()
+    };
+
+    let zero: FixedLenByteArray = to_fixed_len_byte_array(vec![0u8, 0, 0]);
+    write_values_to_file(
+        message_type,
+        &input_path,
+        &[
+            zero,
+            to_fixed_len_byte_array(vec![0, 0, 1]),
+            to_fixed_len_byte_array(vec![1, 0, 0]),
+            to_fixed_len_byte_array(vec![255, 255, 255]),
+        ],
+        None,
+    );
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!(".00\n.01\n655.36\n-.01", actual);
+}
+
+#[test]
+pub fn insert_decimal_from_fixed_binary_optional() {
+    let table_name = "InsertDecimalFromFixedBinaryOptional";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["DECIMAL(5,2)"]).unwrap();
+
+    // Prepare file
+
+    // A temporary directory, to be removed at the end of the test.
     let tmp_dir = tempdir().unwrap();
     // The name of the input parquet file we are going to write. Since it is in a temporary
     // directory it will not outlive the end of the test.
     let input_path = tmp_dir.path().join("input.par");
 
-    let message_type = "
-        message schema {
-            OPTIONAL BYTE_ARRAY a;
-        }
+    let message_type = "
+        message schema {
+            OPTIONAL FIXED_LEN_BYTE_ARRAY(3) a (DECIMAL(5,2));
+        }
+    ";
+
+    let to_fixed_len_byte_array = |input: Vec<u8>| -> FixedLenByteArray {
+        let ba: ByteArray = input.into();
+        ba.into()
+    };
+
+    write_values_to_file(
+        message_type,
+        &input_path,
+        &[
+            to_fixed_len_byte_array(vec![0, 0, 1]),
+            to_fixed_len_byte_array(vec![255, 255, 255]),
+        ],
+        Some(&[1, 0, 1]),
+    );
+
+    // Insert file into table
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "insert",
+            "--connection-string",
+            MSSQL,
+            input_path.to_str().unwrap(),
+            table_name,
+        ])
+        .assert()
+        .success();
+
+    // Query table and check for expected result
+    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
+    let cursor = conn.execute(&query, ()).unwrap().unwrap();
+    let actual = cursor_to_string(cursor);
+
+    assert_eq!(".01\nNULL\n-.01", actual);
+}
+
+/// Write query output to stdout
+#[test]
+pub fn write_query_result_to_stdout() {
+    // Given
+    let table_name = "WriteQueryResultToStdout";
+    // Prepare table
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
+    conn.execute(
+        &format!("INSERT INTO {table_name} (a) VALUES (?)"),
+        [42i32, 5, 64].as_slice(),
+    )
+    .unwrap();
+
+    // When
+
+    // Query table and write contents to stdout
+    let query = format!("SELECT a FROM {table_name} ORDER BY id");
+    let command = Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            "--connection-string",
+            MSSQL,
+            "-", // Use `-` to explicitly write to stdout
+            &query,
+        ])
+        .assert()
+        .success();
+
+    // Then
+    let output = &command.get_output().stdout;
+    assert!(!output.is_empty());
+
+    // Write captured contents of stdout to temporary file so we can inspect it with read parquet
+    let mut output_file = NamedTempFile::new().unwrap();
+    output_file.write_all(output).unwrap();
+
+    let expected = "{a: 42}\n";
+
+    let output_path = output_file.path().to_str().unwrap();
+    parquet_read_out(output_path).stdout(eq(expected));
+}
+
+/// Write query output to stdout
+#[test]
+pub fn reject_writing_to_stdout_and_file_size_limit() {
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            "--connection-string",
+            "FakeConnectionString",
+            "--file-size-threshold",
+            "1GiB",
+            "-", // Use `-` to explicitly write to stdout
+            "SELECT a FROM FakeTableName ORDER BY id",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "file-size-threshold conflicts with specifying stdout ('-') as output.",
+        ));
+}
+
+/// Write query output as CSV to stdout
+#[test]
+pub fn write_query_result_as_csv() {
+    // Given
+    let table_name = "WriteQueryResultAsCsv";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(
+        &conn,
+        table_name,
+        &["INTEGER", "VARCHAR(20)", "DATETIME2(3)"],
+    )
+    .unwrap();
+    let insert = format!(
+        "INSERT INTO {table_name} (a,b,c) VALUES
+        (42, 'Hello; World', '2022-10-15 14:30:00.123'),
+        (NULL, 'Say \"Hi\"', NULL)"
+    );
+    conn.execute(&insert, ()).unwrap();
+
+    // When
+    let query = format!("SELECT a,b,c FROM {table_name} ORDER BY id");
+    let command = Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            "--connection-string",
+            MSSQL,
+            "--format",
+            "csv",
+            "--csv-delimiter",
+            ";",
+            "-",
+            &query,
+        ])
+        .assert()
+        .success();
+
+    // Then
+    let expected = "\
+        a;b;c\n\
+        42;\"Hello; World\";2022-10-15 14:30:00.123\n\
+        ;\"Say \"\"Hi\"\"\";\n\
     ";
+    let output = String::from_utf8(command.get_output().stdout.clone()).unwrap();
+    assert_eq!(expected, output);
+}
 
-    let text: ByteArray = "Hello, World!".into();
-    write_values_to_file(
-        message_type,
-        &input_path,
-        &[text, "Hallo, Welt!".into()],
-        Some(&[1, 0, 1]),
+/// Write query output into an Excel workbook
+#[test]
+pub fn write_query_result_as_xlsx() {
+    // Given
+    let table_name = "WriteQueryResultAsXlsx";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(
+        &conn,
+        table_name,
+        &["INTEGER", "VARCHAR(20)", "DECIMAL(5,2)"],
+    )
+    .unwrap();
+    let insert = format!(
+        "INSERT INTO {table_name} (a,b,c) VALUES (42, 'Hello', 123.45), (NULL, NULL, NULL)"
     );
+    conn.execute(&insert, ()).unwrap();
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.xlsx");
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Insert file into table
+    // When
+    let query = format!("SELECT a,b,c FROM {table_name} ORDER BY id");
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--format",
+            "xlsx",
+            out_str,
+            &query,
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
+    // Then
+    let mut workbook: Xlsx<_> = open_workbook(&out_path).unwrap();
+    let range = workbook.worksheet_range_at(0).unwrap().unwrap();
+    let rows: Vec<_> = range.rows().map(|row| row.to_vec()).collect();
     assert_eq!(
-        "48656C6C6F2C20576F726C6421\nNULL\n48616C6C6F2C2057656C7421",
-        actual
+        vec![
+            vec![
+                Data::String("a".to_owned()),
+                Data::String("b".to_owned()),
+                Data::String("c".to_owned())
+            ],
+            vec![
+                Data::Float(42.),
+                Data::String("Hello".to_owned()),
+                Data::Float(123.45)
+            ],
+            vec![Data::Empty, Data::Empty, Data::Empty],
+        ],
+        rows
     );
 }
 
+/// Rather than writing a truncated spreadsheet, the export fails if the result set has more rows
+/// than allowed.
 #[test]
-pub fn insert_fixed_len_binary() {
-    let table_name = "InsertFixedLenBinary";
-    // Prepare table
+pub fn xlsx_row_limit_exceeded() {
+    // Given
+    let table_name = "XlsxRowLimitExceeded";
     let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["BINARY(13)"]).unwrap();
-
-    // Prepare file
-
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
+    let insert = format!("INSERT INTO {table_name} (a) VALUES (1), (2), (3)");
+    conn.execute(&insert, ()).unwrap();
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.xlsx");
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    let message_type = "
-        message schema {
-            REQUIRED FIXED_LEN_BYTE_ARRAY(13) a;
-        }
-    ";
+    // When
+    let query = format!("SELECT a FROM {table_name}");
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "-vvvv",
+            "query",
+            "--connection-string",
+            MSSQL,
+            "--format",
+            "xlsx",
+            "--xlsx-max-rows",
+            "2",
+            out_str,
+            &query,
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("more than 2 rows"));
 
-    let to_fixed_len_byte_array = |input: &str| -> FixedLenByteArray {
-        let ba: ByteArray = input.into();
-        ba.into()
-    };
+    // Then
+    assert!(!out_path.exists());
+}
 
-    let text: FixedLenByteArray = to_fixed_len_byte_array("Hello, World!");
-    write_values_to_file(
-        message_type,
-        &input_path,
-        &[
-            text,
-            to_fixed_len_byte_array("Hallo, Welt!!"),
-            to_fixed_len_byte_array("0123456789012"),
-        ],
-        None,
-    );
+/// Embed Arrow schema with dictionary and time zone hints
+#[test]
+#[cfg(feature = "arrow")]
+fn embed_arrow_schema() {
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--arrow-schema",
+            "--arrow-dictionary",
+            "status",
+            "--arrow-time-zone",
+            "Europe/Berlin",
+            "SELECT 'open' AS status, \
+            CAST('2024-01-01 12:00:00 +02:00' AS DATETIMEOFFSET) AS created",
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(&out_path).unwrap()).unwrap();
+    let schema = builder.schema();
     assert_eq!(
-        "48656C6C6F2C20576F726C6421\n48616C6C6F2C2057656C742121\n30313233343536373839303132",
-        actual
+        "Dictionary(Int32, Utf8)",
+        format!("{:?}", schema.field(0).data_type())
+    );
+    assert_eq!(
+        "Timestamp(Microsecond, Some(\"Europe/Berlin\"))",
+        format!("{:?}", schema.field(1).data_type())
     );
 }
 
 #[test]
-pub fn insert_fixed_len_binary_optional() {
-    let table_name = "InsertFixedLenBinaryOptional";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["BINARY(13)"]).unwrap();
-
-    // Prepare file
-
+fn geo_parquet_metadata() {
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            OPTIONAL FIXED_LEN_BYTE_ARRAY(13) a;
-        }
-    ";
-
-    let to_fixed_len_byte_array = |input: &str| -> FixedLenByteArray {
-        let ba: ByteArray = input.into();
-        ba.into()
-    };
-
-    let text: FixedLenByteArray = to_fixed_len_byte_array("Hello, World!");
-    write_values_to_file(
-        message_type,
-        &input_path,
-        &[text, to_fixed_len_byte_array("0123456789012")],
-        Some(&[1, 0, 1]),
-    );
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+    let crs_path = out_dir.path().join("crs.json");
+    std::fs::write(&crs_path, r#"{"id": {"authority": "EPSG", "code": 4326}}"#).unwrap();
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--geometry-column",
+            "geom",
+            "--geometry-crs",
+            crs_path.to_str().unwrap(),
+            "SELECT geometry::STGeomFromText('POINT(1 2)', 4326).STAsBinary() AS geom",
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
+    let reader = SerializedFileReader::new(File::open(&out_path).unwrap()).unwrap();
+    let key_value_metadata = reader
+        .metadata()
+        .file_metadata()
+        .key_value_metadata()
+        .unwrap();
+    let geo = key_value_metadata
+        .iter()
+        .find(|kv| kv.key == "geo")
+        .and_then(|kv| kv.value.as_deref())
+        .unwrap();
+    let geo: serde_json::Value = serde_json::from_str(geo).unwrap();
     assert_eq!(
-        "48656C6C6F2C20576F726C6421\nNULL\n30313233343536373839303132",
-        actual
+        serde_json::json!({
+            "version": "1.1.0",
+            "primary_column": "geom",
+            "columns": {
+                "geom": {
+                    "encoding": "WKB",
+                    "geometry_types": [],
+                    "crs": {"id": {"authority": "EPSG", "code": 4326}}
+                }
+            }
+        }),
+        geo
     );
 }
 
 #[test]
-pub fn insert_decimal_from_binary() {
-    let table_name = "InsertDecimalFromBinary";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["DECIMAL(9,2)"]).unwrap();
+fn cast_geometry_to_wkb() {
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Prepare file
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--geometry-column",
+            "geom",
+            "--cast-geometry-to-wkb",
+            "SELECT 42 AS id, geometry::STGeomFromText('POINT(1 2)', 0) AS geom",
+        ])
+        .assert()
+        .success();
 
-    // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
+    parquet_schema_out(out_str).stdout(contains("BYTE_ARRAY geom;"));
+    // Little endian WKB of `POINT(1 2)`
+    let expected =
+        "{id: 42, geom: [1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 240, 63, 0, 0, 0, 0, 0, 0, 0, 64]}\n";
+    parquet_read_out(out_str).stdout(eq(expected));
+}
 
-    let message_type = "
-        message schema {
-            REQUIRED BYTE_ARRAY a (DECIMAL(9,2));
-        }
-    ";
+#[test]
+fn spatial_type_as_geometry_column_requires_cast() {
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            "out.par",
+            "--connection-string",
+            MSSQL,
+            "--geometry-column",
+            "geom",
+            "SELECT geometry::STGeomFromText('POINT(1 2)', 0) AS geom",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains("pass `--cast-geometry-to-wkb`"));
+}
 
-    let zero: ByteArray = vec![0u8].into();
-    write_values_to_file(
-        message_type,
-        &input_path,
-        &[
-            zero,
-            vec![1].into(),
-            vec![1, 0, 0].into(),
-            vec![255, 255, 255].into(),
-            vec![255].into(),
-        ],
-        None,
-    );
+#[test]
+fn geometry_column_must_be_binary() {
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    Command::cargo_bin("odbc2parquet")
+        .unwrap()
+        .args(&[
+            "query",
+            out_str,
+            "--connection-string",
+            MSSQL,
+            "--geometry-column",
+            "geom",
+            "SELECT geometry::STGeomFromText('POINT(1 2)', 4326).STAsText() AS geom",
+        ])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "Geometry column 'geom' must be binary and hold Well Known Binary (WKB).",
+        ));
+}
+
+#[test]
+fn json_column() {
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--json-column",
+            "doc",
+            "SELECT CAST('{\"a\": 1}' AS NVARCHAR(50)) AS doc",
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!(".00\n.01\n655.36\n-.01\n-.01", actual);
+    parquet_schema_out(out_str).stdout(contains("BYTE_ARRAY doc (JSON);"));
 }
 
 #[test]
-pub fn insert_decimal_from_binary_optional() {
-    let table_name = "InsertDecimalFromBinaryOptional";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["DECIMAL(9,2)"]).unwrap();
-
-    // Prepare file
-
+fn json_column_must_be_text() {
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            OPTIONAL BYTE_ARRAY a (DECIMAL(9,2));
-        }
-    ";
-
-    let zero: ByteArray = vec![0u8].into();
-    write_values_to_file(
-        message_type,
-        &input_path,
-        &[zero, vec![1].into()],
-        Some(&[1, 0, 1]),
-    );
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
-            "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--json-column",
+            "doc",
+            "SELECT 42 AS doc",
         ])
         .assert()
-        .success();
-
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!(".00\nNULL\n.01", actual);
+        .failure()
+        .stderr(contains("JSON column 'doc' must hold text."));
 }
 
 #[test]
-pub fn insert_decimal_from_fixed_binary() {
-    let table_name = "InsertDecimalFromFixedBinary";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["DECIMAL(5,2)"]).unwrap();
-
-    // Prepare file
-
+fn xml_column() {
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            REQUIRED FIXED_LEN_BYTE_ARRAY(3) a (DECIMAL(5,2));
-        }
-    ";
-
-    let to_fixed_len_byte_array = |input: Vec<u8>| -> FixedLenByteArray {
-        let ba: ByteArray = input.into();
-        ba.into()
-    };
-
-    let zero: FixedLenByteArray = to_fixed_len_byte_array(vec![0u8, 0, 0]);
-    write_values_to_file(
-        message_type,
-        &input_path,
-        &[
-            zero,
-            to_fixed_len_byte_array(vec![0, 0, 1]),
-            to_fixed_len_byte_array(vec![1, 0, 0]),
-            to_fixed_len_byte_array(vec![255, 255, 255]),
-        ],
-        None,
-    );
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--xml-metadata",
+            "SELECT CAST('<a>1</a>' AS XML) AS doc",
         ])
         .assert()
         .success();
 
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!(".00\n.01\n655.36\n-.01", actual);
+    parquet_read_out(out_str).stdout(eq("{doc: \"<a>1</a>\"}\n"));
+    let reader = SerializedFileReader::new(File::open(&out_path).unwrap()).unwrap();
+    let key_value_metadata = reader
+        .metadata()
+        .file_metadata()
+        .key_value_metadata()
+        .unwrap();
+    let xml_columns = key_value_metadata
+        .iter()
+        .find(|kv| kv.key == "xml_columns")
+        .and_then(|kv| kv.value.as_deref())
+        .unwrap();
+    assert_eq!(r#"["doc"]"#, xml_columns);
 }
 
 #[test]
-pub fn insert_decimal_from_fixed_binary_optional() {
-    let table_name = "InsertDecimalFromFixedBinaryOptional";
-    // Prepare table
-    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["DECIMAL(5,2)"]).unwrap();
-
-    // Prepare file
-
+fn xml_document_exceeding_max_length() {
     // A temporary directory, to be removed at the end of the test.
-    let tmp_dir = tempdir().unwrap();
-    // The name of the input parquet file we are going to write. Since it is in a temporary
-    // directory it will not outlive the end of the test.
-    let input_path = tmp_dir.path().join("input.par");
-
-    let message_type = "
-        message schema {
-            OPTIONAL FIXED_LEN_BYTE_ARRAY(3) a (DECIMAL(5,2));
-        }
-    ";
-
-    let to_fixed_len_byte_array = |input: Vec<u8>| -> FixedLenByteArray {
-        let ba: ByteArray = input.into();
-        ba.into()
-    };
-
-    write_values_to_file(
-        message_type,
-        &input_path,
-        &[
-            to_fixed_len_byte_array(vec![0, 0, 1]),
-            to_fixed_len_byte_array(vec![255, 255, 255]),
-        ],
-        Some(&[1, 0, 1]),
-    );
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.par");
+    // We need to pass the output path as a string argument.
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
-    // Insert file into table
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
-            "-vvvv",
-            "insert",
+            "query",
+            out_str,
             "--connection-string",
             MSSQL,
-            input_path.to_str().unwrap(),
-            table_name,
+            "--xml-max-length",
+            "5",
+            "SELECT CAST('<a>12345</a>' AS XML) AS doc",
         ])
         .assert()
-        .success();
-
-    // Query table and check for expected result
-    let query = format!("SELECT a FROM {} ORDER BY Id", table_name);
-    let cursor = conn.execute(&query, ()).unwrap().unwrap();
-    let actual = cursor_to_string(cursor);
-
-    assert_eq!(".01\nNULL\n-.01", actual);
+        .failure()
+        .stderr(contains(
+            "Raise `--xml-max-length` to fetch longer documents.",
+        ));
 }
 
-/// Write query output to stdout
+/// Write query output into a table of a DuckDB database file
 #[test]
-pub fn write_query_result_to_stdout() {
+#[cfg(feature = "duckdb")]
+pub fn write_query_result_to_duckdb() {
     // Given
-    let table_name = "WriteQueryResultToStdout";
-    // Prepare table
+    let table_name = "WriteQueryResultToDuckdb";
     let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
-    setup_empty_table_mssql(&conn, table_name, &["INTEGER"]).unwrap();
-    conn.execute(
-        &format!("INSERT INTO {table_name} (a) VALUES (?)"),
-        [42i32, 5, 64].as_slice(),
-    )
-    .unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER", "VARCHAR(20)", "DATE"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {table_name} (a,b,c) VALUES (42, 'Hello', '2022-10-15'), (NULL, NULL, NULL)"
+    );
+    conn.execute(&insert, ()).unwrap();
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.duckdb");
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
 
     // When
-
-    // Query table and write contents to stdout
-    let query = format!("SELECT a FROM {table_name} ORDER BY id");
-    let command = Command::cargo_bin("odbc2parquet")
+    let query = format!("SELECT a,b,c FROM {table_name} ORDER BY id");
+    Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
             "query",
             "--connection-string",
             MSSQL,
-            "-", // Use `-` to explicitly write to stdout
+            "--format",
+            "duckdb",
+            "--table",
+            "results",
+            out_str,
             &query,
         ])
         .assert()
         .success();
 
     // Then
-    let output = &command.get_output().stdout;
-    assert!(!output.is_empty());
-
-    // Write captured contents of stdout to temporary file so we can inspect it with read parquet
-    let mut output_file = NamedTempFile::new().unwrap();
-    output_file.write_all(output).unwrap();
-
-    let expected = "{a: 42}\n";
-
-    let output_path = output_file.path().to_str().unwrap();
-    parquet_read_out(output_path).stdout(eq(expected));
+    let duckdb = duckdb::Connection::open(&out_path).unwrap();
+    let rows: Vec<(Option<i32>, Option<String>, Option<String>)> = duckdb
+        .prepare("SELECT a, b, CAST(c AS VARCHAR) FROM results")
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        vec![
+            (
+                Some(42),
+                Some("Hello".to_owned()),
+                Some("2022-10-15".to_owned())
+            ),
+            (None, None, None)
+        ],
+        rows
+    );
 }
 
-/// Write query output to stdout
+/// Write query output into a table of a SQLite database file
 #[test]
-pub fn reject_writing_to_stdout_and_file_size_limit() {
+pub fn write_query_result_to_sqlite() {
+    // Given
+    let table_name = "WriteQueryResultToSqlite";
+    let conn = ENV.connect_with_connection_string(MSSQL).unwrap();
+    setup_empty_table_mssql(&conn, table_name, &["INTEGER", "VARCHAR(20)", "DATE"]).unwrap();
+    let insert = format!(
+        "INSERT INTO {table_name} (a,b,c) VALUES (42, 'Hello', '2022-10-15'), (NULL, NULL, NULL)"
+    );
+    conn.execute(&insert, ()).unwrap();
+    // A temporary directory, to be removed at the end of the test.
+    let out_dir = tempdir().unwrap();
+    let out_path = out_dir.path().join("out.sqlite");
+    let out_str = out_path.to_str().expect("Temporary file path must be utf8");
+
+    // When
+    let query = format!("SELECT a,b,c FROM {table_name} ORDER BY id");
     Command::cargo_bin("odbc2parquet")
         .unwrap()
         .args(&[
             "-vvvv",
             "query",
             "--connection-string",
-            "FakeConnectionString",
-            "--file-size-threshold",
-            "1GiB",
-            "-", // Use `-` to explicitly write to stdout
-            "SELECT a FROM FakeTableName ORDER BY id",
+            MSSQL,
+            "--format",
+            "sqlite",
+            "--table",
+            "results",
+            out_str,
+            &query,
         ])
         .assert()
-        .failure()
-        .stderr(contains(
-            "file-size-threshold conflicts with specifying stdout ('-') as output.",
-        ));
+        .success();
+
+    // Then
+    let sqlite = rusqlite::Connection::open(&out_path).unwrap();
+    let rows: Vec<(Option<i64>, Option<String>, Option<String>)> = sqlite
+        .prepare("SELECT a, b, c FROM results")
+        .unwrap()
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        vec![
+            (
+                Some(42),
+                Some("Hello".to_owned()),
+                Some("2022-10-15".to_owned())
+            ),
+            (None, None, None)
+        ],
+        rows
+    );
 }
 
 /// This did not work in earlier versions there we set the batch write size of the parquet writer to